@@ -0,0 +1,233 @@
+use crate::parse::{Attributes, Data, Field, Fields, GenericParamKind, Generics, Item, Variant};
+
+const READ_FROM: &str = "::binary_file_reader::read_from::ReadFrom";
+const READER: &str = "::binary_file_reader::BinaryFileReader";
+const ERROR: &str = "::binary_file_reader::error::BinaryFileReaderError";
+const ENDIAN: &str = "::binary_file_reader::endian::Endian";
+
+pub fn expand(item: &Item) -> Result<String, String> {
+    let body = match &item.data {
+        Data::Struct(fields) => {
+            if item.attributes.tag_type.is_some() {
+                return Err("`#[tag(...)]` can only be used on enums".to_string());
+            }
+            expand_struct(&item.attributes, fields)
+        }
+        Data::Enum(variants) => expand_enum(&item.name, &item.attributes, variants)?,
+    };
+
+    let min_size = match &item.data {
+        Data::Struct(Fields::Named(fields) | Fields::Unnamed(fields)) => fields
+            .iter()
+            .filter(|field| field.attributes.count.is_none())
+            .map(|field| format!(" + <{} as {READ_FROM}>::MIN_SIZE", field.ty))
+            .collect::<String>(),
+        Data::Struct(Fields::Unit) => String::new(),
+        Data::Enum(_) => format!(
+            " + <{} as {READ_FROM}>::MIN_SIZE",
+            item.attributes.tag_type.as_deref().unwrap_or_default()
+        ),
+    };
+
+    Ok(format!(
+        "impl{impl_generics} {READ_FROM} for {name}{ty_generics} {where_clause} {{
+            const MIN_SIZE: usize = 0{min_size};
+
+            fn read_from(__reader: &mut {READER}<'_>) -> ::core::result::Result<Self, {ERROR}> {{
+                {body}
+            }}
+        }}",
+        impl_generics = impl_generics(&item.generics),
+        name = item.name,
+        ty_generics = ty_generics(&item.generics),
+        where_clause = where_clause(&item.generics),
+    ))
+}
+
+fn expand_struct(attributes: &Attributes, fields: &Fields) -> String {
+    let (reads, construct) = expand_fields(fields, "Self");
+
+    let body = format!(
+        "let __start = __reader.current_offset();
+        {magic}
+        {reads}
+        {pad}
+        ::core::result::Result::Ok({construct})",
+        magic = expect_magic(attributes),
+        pad = skip_padding(attributes),
+    );
+
+    with_endian(attributes, "Self", &body)
+}
+
+fn expand_enum(
+    name: &str,
+    attributes: &Attributes,
+    variants: &[Variant],
+) -> Result<String, String> {
+    let tag_type = attributes.tag_type.as_deref().ok_or_else(|| {
+        "deriving BinaryRead for an enum requires `#[tag(u8)]`-style attribute".to_string()
+    })?;
+
+    let mut arms = String::new();
+    let mut previous: Option<String> = None;
+    for variant in variants {
+        let value = match (&variant.attributes.tag, &variant.discriminant, &previous) {
+            (Some(tag), _, _) => tag.clone(),
+            (None, Some(discriminant), _) => discriminant.clone(),
+            (None, None, Some(previous)) => format!("({previous}) + 1"),
+            (None, None, None) => "0".to_string(),
+        };
+
+        let (reads, construct) = expand_fields(&variant.fields, &format!("Self::{}", variant.name));
+        arms.push_str(&format!(
+            "if __tag == ({value}) as {tag_type} {{
+                *__reader = __peek;
+                let __start = __reader.current_offset();
+                {magic}
+                {reads}
+                {pad}
+                return ::core::result::Result::Ok({construct});
+            }}",
+            magic = expect_magic(&variant.attributes),
+            pad = skip_padding(&variant.attributes),
+        ));
+        previous = Some(value);
+    }
+
+    let body = format!(
+        "{magic}
+        let __tag_offset = __reader.current_offset();
+        let mut __peek = ::core::clone::Clone::clone(__reader);
+        let __tag = <{tag_type} as {READ_FROM}>::read_from(&mut __peek)?;
+        {arms}
+        ::core::result::Result::Err({ERROR}::InvalidDiscriminant {{
+            type_name: {name:?},
+            value: __tag as u64,
+            current_offset: __tag_offset,
+        }})",
+        magic = expect_magic(attributes),
+    );
+
+    Ok(with_endian(attributes, "Self", &body))
+}
+
+fn expand_fields(fields: &Fields, path: &str) -> (String, String) {
+    match fields {
+        Fields::Named(fields) => {
+            let mut reads = String::new();
+            let mut names = Vec::new();
+            for field in fields {
+                let name = field.name.clone().unwrap_or_default();
+                reads.push_str(&read_field(&name, field));
+                names.push(name);
+            }
+            (reads, format!("{path} {{ {} }}", names.join(", ")))
+        }
+        Fields::Unnamed(fields) => {
+            let mut reads = String::new();
+            let mut names = Vec::new();
+            for (index, field) in fields.iter().enumerate() {
+                let name = format!("__field{}", index);
+                reads.push_str(&read_field(&name, field));
+                names.push(name);
+            }
+            (reads, format!("{path}({})", names.join(", ")))
+        }
+        Fields::Unit => (String::new(), path.to_string()),
+    }
+}
+
+fn read_field(name: &str, field: &Field) -> String {
+    let read = match &field.attributes.count {
+        Some(count) => format!("__reader.read_vec_of(({count}) as usize)"),
+        None => format!("<{ty} as {READ_FROM}>::read_from(__reader)", ty = field.ty),
+    };
+
+    format!(
+        "{magic}
+        let {name}: {ty} = {{ {read} }}?;
+        {pad}",
+        magic = expect_magic(&field.attributes),
+        ty = field.ty,
+        read = with_endian(&field.attributes, &field.ty, &read),
+        pad = skip_padding(&field.attributes),
+    )
+}
+
+fn expect_magic(attributes: &Attributes) -> String {
+    match &attributes.magic {
+        Some(magic) => {
+            format!("__reader.expect(::core::convert::AsRef::<[u8]>::as_ref({magic}))?;")
+        }
+        None => String::new(),
+    }
+}
+
+fn skip_padding(attributes: &Attributes) -> String {
+    match attributes.pad {
+        Some(pad) => format!(
+            "let __misalignment = (__reader.current_offset() - __start) % {pad};
+            if __misalignment != 0 {{
+                __reader.read_slice({pad} - __misalignment)?;
+            }}"
+        ),
+        None => String::new(),
+    }
+}
+
+fn with_endian(attributes: &Attributes, ty: &str, body: &str) -> String {
+    match attributes.endian {
+        Some(endian) => format!(
+            "let __saved_endian = __reader.endian();
+            __reader.set_endian({ENDIAN}::{endian});
+            let __result = (|| -> ::core::result::Result<{ty}, {ERROR}> {{ {body} }})();
+            __reader.set_endian(__saved_endian);
+            __result"
+        ),
+        None => body.to_string(),
+    }
+}
+
+fn impl_generics(generics: &Generics) -> String {
+    if generics.params.is_empty() {
+        return String::new();
+    }
+
+    let params = generics
+        .params
+        .iter()
+        .map(|param| match param.kind {
+            GenericParamKind::Const => format!("const {} {}", param.name, param.bounds),
+            _ => format!("{} {}", param.name, param.bounds),
+        })
+        .collect::<Vec<_>>();
+    format!("<{}>", params.join(", "))
+}
+
+fn ty_generics(generics: &Generics) -> String {
+    if generics.params.is_empty() {
+        return String::new();
+    }
+
+    let names = generics
+        .params
+        .iter()
+        .map(|param| param.name.clone())
+        .collect::<Vec<_>>();
+    format!("<{}>", names.join(", "))
+}
+
+fn where_clause(generics: &Generics) -> String {
+    let mut predicates = generics.where_predicates.clone();
+    for param in &generics.params {
+        if param.kind == GenericParamKind::Type {
+            predicates.push(format!("{}: {READ_FROM}", param.name));
+        }
+    }
+
+    if predicates.is_empty() {
+        return String::new();
+    }
+    format!("where {}", predicates.join(", "))
+}
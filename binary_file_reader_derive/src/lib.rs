@@ -0,0 +1,28 @@
+use proc_macro::TokenStream;
+
+mod expand;
+mod parse;
+
+/// Field and struct attributes:
+///
+/// - `#[magic = b"..."]` expects the bytes before the struct or field.
+/// - `#[pad = N]` skips bytes after the struct or field until its offset from the
+///   start of the struct is a multiple of `N`.
+/// - `#[count = "expr"]` reads `expr` elements into a `Vec` field; earlier fields
+///   are in scope by name.
+/// - `#[endian = "little"]` / `#[endian = "big"]` reads the struct or field with
+///   the given byte order and restores the reader's byte order afterwards.
+/// - `#[tag(u8)]` on an enum reads a discriminant of the given type and reads the
+///   fields of the matching variant. Variants match their `#[tag = N]` attribute,
+///   their explicit discriminant, or the previous value plus one.
+#[proc_macro_derive(BinaryRead, attributes(magic, pad, count, endian, tag))]
+pub fn derive_binary_read(input: TokenStream) -> TokenStream {
+    let result = parse::Item::parse(input).and_then(|item| expand::expand(&item));
+
+    let code = match result {
+        Ok(code) => code,
+        Err(message) => format!("::core::compile_error!({:?});", message),
+    };
+
+    code.parse().expect("generated code must be valid tokens")
+}
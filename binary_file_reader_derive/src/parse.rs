@@ -0,0 +1,493 @@
+use proc_macro::{Delimiter, TokenStream, TokenTree};
+
+pub struct Item {
+    pub name: String,
+    pub attributes: Attributes,
+    pub generics: Generics,
+    pub data: Data,
+}
+
+#[derive(Default)]
+pub struct Attributes {
+    pub magic: Option<String>,
+    pub pad: Option<usize>,
+    pub count: Option<String>,
+    pub endian: Option<&'static str>,
+    pub tag_type: Option<String>,
+    pub tag: Option<String>,
+}
+
+#[derive(Default)]
+pub struct Generics {
+    pub params: Vec<GenericParam>,
+    pub where_predicates: Vec<String>,
+}
+
+pub struct GenericParam {
+    pub kind: GenericParamKind,
+    pub name: String,
+    pub bounds: String,
+}
+
+#[derive(PartialEq)]
+pub enum GenericParamKind {
+    Lifetime,
+    Type,
+    Const,
+}
+
+pub enum Data {
+    Struct(Fields),
+    Enum(Vec<Variant>),
+}
+
+pub struct Variant {
+    pub name: String,
+    pub attributes: Attributes,
+    pub fields: Fields,
+    pub discriminant: Option<String>,
+}
+
+pub enum Fields {
+    Named(Vec<Field>),
+    Unnamed(Vec<Field>),
+    Unit,
+}
+
+pub struct Field {
+    pub name: Option<String>,
+    pub attributes: Attributes,
+    pub ty: String,
+}
+
+impl Item {
+    pub fn parse(input: TokenStream) -> Result<Self, String> {
+        let tokens = input.into_iter().collect::<Vec<_>>();
+        let mut cursor = Cursor::new(&tokens);
+
+        let attributes = cursor.parse_attributes()?;
+        cursor.skip_visibility();
+
+        if attributes.count.is_some() {
+            return Err("`count` can only be used on fields".to_string());
+        }
+        if attributes.tag.is_some() {
+            return Err("`#[tag = ...]` can only be used on enum variants".to_string());
+        }
+
+        let keyword = cursor.expect_ident()?;
+        let name = cursor.expect_ident()?;
+        let mut generics = Generics::parse(&mut cursor)?;
+
+        let data = match keyword.as_str() {
+            "struct" => Data::Struct(parse_struct_body(&mut cursor, &mut generics)?),
+            "enum" => Data::Enum(parse_enum_body(&mut cursor, &mut generics)?),
+            "union" => return Err("BinaryRead cannot be derived for unions".to_string()),
+            other => return Err(format!("unexpected item kind `{}`", other)),
+        };
+
+        Ok(Self {
+            name,
+            attributes,
+            generics,
+            data,
+        })
+    }
+}
+
+impl Attributes {
+    fn parse_attribute(&mut self, tokens: &[TokenTree]) -> Result<(), String> {
+        let name = match tokens.first() {
+            Some(TokenTree::Ident(name)) => name.to_string(),
+            _ => return Ok(()),
+        };
+
+        if !matches!(name.as_str(), "magic" | "pad" | "count" | "endian" | "tag") {
+            return Ok(());
+        }
+
+        if let [_, TokenTree::Group(group)] = tokens {
+            if name == "tag" && group.delimiter() == Delimiter::Parenthesis {
+                let ty = group.stream().to_string();
+                if !matches!(ty.as_str(), "u8" | "u16" | "u32" | "u64") {
+                    return Err(format!("unsupported tag type `{}`", ty));
+                }
+                self.tag_type = Some(ty);
+                return Ok(());
+            }
+        }
+
+        let value = match tokens {
+            [_, TokenTree::Punct(eq), TokenTree::Literal(value)] if eq.as_char() == '=' => {
+                value.to_string()
+            }
+            _ => return Err(format!("expected `#[{} = ...]`", name)),
+        };
+
+        match name.as_str() {
+            "magic" => self.magic = Some(value),
+            "pad" => {
+                let pad = value
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|pad| *pad != 0)
+                    .ok_or_else(|| {
+                        format!("`pad` must be a positive integer, found `{}`", value)
+                    })?;
+                self.pad = Some(pad);
+            }
+            "count" => self.count = Some(unquote(&value)?),
+            "tag" => self.tag = Some(value),
+            "endian" => {
+                let endian = match unquote(&value)?.as_str() {
+                    "big" => "Big",
+                    "little" => "Little",
+                    _ => {
+                        return Err(format!(
+                            "`endian` must be \"big\" or \"little\", found `{}`",
+                            value
+                        ))
+                    }
+                };
+                self.endian = Some(endian);
+            }
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    }
+}
+
+impl Generics {
+    fn parse(cursor: &mut Cursor) -> Result<Self, String> {
+        if !cursor.peek_punct('<') {
+            return Ok(Self::default());
+        }
+        cursor.next();
+
+        let mut depth = 0;
+        let mut param_tokens = Vec::new();
+        let mut params = Vec::new();
+
+        loop {
+            let token = cursor
+                .next()
+                .ok_or_else(|| "unterminated generic parameter list".to_string())?;
+
+            match &token {
+                TokenTree::Punct(p) if p.as_char() == '<' => depth += 1,
+                TokenTree::Punct(p) if p.as_char() == '>' && depth == 0 => {
+                    if !param_tokens.is_empty() {
+                        params.push(GenericParam::parse(&param_tokens)?);
+                    }
+                    break;
+                }
+                TokenTree::Punct(p) if p.as_char() == '>' => depth -= 1,
+                TokenTree::Punct(p) if p.as_char() == ',' && depth == 0 => {
+                    params.push(GenericParam::parse(&param_tokens)?);
+                    param_tokens.clear();
+                    continue;
+                }
+                _ => {}
+            }
+
+            param_tokens.push(token.clone());
+        }
+
+        Ok(Self {
+            params,
+            where_predicates: Vec::new(),
+        })
+    }
+
+    fn parse_where_clause(&mut self, tokens: &[TokenTree]) {
+        for predicate in split_top_level(tokens, ',') {
+            if !predicate.is_empty() {
+                self.where_predicates.push(to_string(&predicate));
+            }
+        }
+    }
+}
+
+impl GenericParam {
+    fn parse(tokens: &[TokenTree]) -> Result<Self, String> {
+        let tokens = strip_default(tokens);
+
+        match tokens {
+            [TokenTree::Punct(p), TokenTree::Ident(name), rest @ ..] if p.as_char() == '\'' => {
+                Ok(Self {
+                    kind: GenericParamKind::Lifetime,
+                    name: format!("'{}", name),
+                    bounds: to_string(rest),
+                })
+            }
+            [TokenTree::Ident(keyword), TokenTree::Ident(name), rest @ ..]
+                if keyword.to_string() == "const" =>
+            {
+                Ok(Self {
+                    kind: GenericParamKind::Const,
+                    name: name.to_string(),
+                    bounds: to_string(rest),
+                })
+            }
+            [TokenTree::Ident(name), rest @ ..] => Ok(Self {
+                kind: GenericParamKind::Type,
+                name: name.to_string(),
+                bounds: to_string(rest),
+            }),
+            _ => Err("unsupported generic parameter".to_string()),
+        }
+    }
+}
+
+fn parse_struct_body(cursor: &mut Cursor, generics: &mut Generics) -> Result<Fields, String> {
+    let where_tokens = cursor.take_where_clause();
+    generics.parse_where_clause(&where_tokens);
+
+    match cursor.next() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
+            Ok(Fields::Named(parse_fields(group.stream(), true)?))
+        }
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => {
+            let fields = parse_fields(group.stream(), false)?;
+            let where_tokens = cursor.take_where_clause();
+            generics.parse_where_clause(&where_tokens);
+            Ok(Fields::Unnamed(fields))
+        }
+        Some(TokenTree::Punct(p)) if p.as_char() == ';' => Ok(Fields::Unit),
+        _ => Err("unexpected struct body".to_string()),
+    }
+}
+
+fn parse_enum_body(cursor: &mut Cursor, generics: &mut Generics) -> Result<Vec<Variant>, String> {
+    let where_tokens = cursor.take_where_clause();
+    generics.parse_where_clause(&where_tokens);
+
+    let stream = match cursor.next() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => group.stream(),
+        _ => return Err("unexpected enum body".to_string()),
+    };
+
+    let tokens = stream.into_iter().collect::<Vec<_>>();
+    let mut variants = Vec::new();
+
+    for variant_tokens in split_top_level(&tokens, ',') {
+        if variant_tokens.is_empty() {
+            continue;
+        }
+
+        let mut cursor = Cursor::new(&variant_tokens);
+        let attributes = cursor.parse_attributes()?;
+        let name = cursor.expect_ident()?;
+
+        let fields = match cursor.peek() {
+            Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
+                cursor.next();
+                Fields::Named(parse_fields(group.stream(), true)?)
+            }
+            Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => {
+                cursor.next();
+                Fields::Unnamed(parse_fields(group.stream(), false)?)
+            }
+            _ => Fields::Unit,
+        };
+
+        let discriminant = if cursor.peek_punct('=') {
+            cursor.next();
+            Some(to_string(cursor.rest()))
+        } else {
+            None
+        };
+
+        variants.push(Variant {
+            name,
+            attributes,
+            fields,
+            discriminant,
+        });
+    }
+
+    Ok(variants)
+}
+
+fn parse_fields(stream: TokenStream, named: bool) -> Result<Vec<Field>, String> {
+    let tokens = stream.into_iter().collect::<Vec<_>>();
+    let mut fields = Vec::new();
+
+    for field_tokens in split_top_level(&tokens, ',') {
+        if field_tokens.is_empty() {
+            continue;
+        }
+
+        let mut cursor = Cursor::new(&field_tokens);
+        let attributes = cursor.parse_attributes()?;
+        cursor.skip_visibility();
+
+        let name = if named {
+            let name = cursor.expect_ident()?;
+            if !cursor.peek_punct(':') {
+                return Err(format!("expected `:` after field `{}`", name));
+            }
+            cursor.next();
+            Some(name)
+        } else {
+            None
+        };
+
+        fields.push(Field {
+            name,
+            attributes,
+            ty: to_string(cursor.rest()),
+        });
+    }
+
+    Ok(fields)
+}
+
+struct Cursor<'t> {
+    tokens: &'t [TokenTree],
+    position: usize,
+}
+
+impl<'t> Cursor<'t> {
+    fn new(tokens: &'t [TokenTree]) -> Self {
+        Self {
+            tokens,
+            position: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<&'t TokenTree> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<&'t TokenTree> {
+        let token = self.tokens.get(self.position)?;
+        self.position += 1;
+        Some(token)
+    }
+
+    fn rest(&self) -> &'t [TokenTree] {
+        &self.tokens[self.position..]
+    }
+
+    fn peek_punct(&self, c: char) -> bool {
+        matches!(self.peek(), Some(TokenTree::Punct(p)) if p.as_char() == c)
+    }
+
+    fn peek_ident(&self, ident: &str) -> bool {
+        matches!(self.peek(), Some(TokenTree::Ident(i)) if i.to_string() == ident)
+    }
+
+    fn expect_ident(&mut self) -> Result<String, String> {
+        match self.next() {
+            Some(TokenTree::Ident(ident)) => Ok(ident.to_string()),
+            Some(other) => Err(format!("expected identifier, found `{}`", other)),
+            None => Err("expected identifier, found end of input".to_string()),
+        }
+    }
+
+    fn parse_attributes(&mut self) -> Result<Attributes, String> {
+        let mut attributes = Attributes::default();
+
+        while self.peek_punct('#') {
+            self.next();
+            match self.next() {
+                Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Bracket => {
+                    let tokens = group.stream().into_iter().collect::<Vec<_>>();
+                    attributes.parse_attribute(&tokens)?;
+                }
+                _ => return Err("malformed attribute".to_string()),
+            }
+        }
+
+        Ok(attributes)
+    }
+
+    fn skip_visibility(&mut self) {
+        if !self.peek_ident("pub") {
+            return;
+        }
+        self.next();
+
+        if let Some(TokenTree::Group(group)) = self.peek() {
+            if group.delimiter() == Delimiter::Parenthesis {
+                self.next();
+            }
+        }
+    }
+
+    fn take_where_clause(&mut self) -> Vec<TokenTree> {
+        if !self.peek_ident("where") {
+            return Vec::new();
+        }
+        self.next();
+
+        let mut tokens = Vec::new();
+        while let Some(token) = self.peek() {
+            match token {
+                TokenTree::Group(group) if group.delimiter() == Delimiter::Brace => break,
+                TokenTree::Punct(p) if p.as_char() == ';' => break,
+                _ => tokens.push(token.clone()),
+            }
+            self.next();
+        }
+        tokens
+    }
+}
+
+fn split_top_level(tokens: &[TokenTree], separator: char) -> Vec<Vec<TokenTree>> {
+    let mut parts = vec![Vec::new()];
+    let mut depth = 0i32;
+
+    for (index, token) in tokens.iter().enumerate() {
+        if let TokenTree::Punct(p) = token {
+            match p.as_char() {
+                '<' => depth += 1,
+                '>' if !is_arrow(tokens, index) => depth -= 1,
+                c if c == separator && depth == 0 => {
+                    parts.push(Vec::new());
+                    continue;
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(part) = parts.last_mut() {
+            part.push(token.clone());
+        }
+    }
+
+    parts
+}
+
+fn is_arrow(tokens: &[TokenTree], index: usize) -> bool {
+    index > 0 && matches!(&tokens[index - 1], TokenTree::Punct(p) if p.as_char() == '-')
+}
+
+fn strip_default(tokens: &[TokenTree]) -> &[TokenTree] {
+    let mut depth = 0i32;
+    for (index, token) in tokens.iter().enumerate() {
+        if let TokenTree::Punct(p) = token {
+            match p.as_char() {
+                '<' => depth += 1,
+                '>' => depth -= 1,
+                '=' if depth == 0 => return &tokens[..index],
+                _ => {}
+            }
+        }
+    }
+    tokens
+}
+
+fn unquote(literal: &str) -> Result<String, String> {
+    literal
+        .strip_prefix('"')
+        .and_then(|literal| literal.strip_suffix('"'))
+        .map(|literal| literal.to_string())
+        .ok_or_else(|| format!("expected a string literal, found `{}`", literal))
+}
+
+pub fn to_string(tokens: &[TokenTree]) -> String {
+    tokens.iter().cloned().collect::<TokenStream>().to_string()
+}
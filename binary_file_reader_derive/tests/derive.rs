@@ -0,0 +1,271 @@
+use binary_file_reader::{
+    error::BinaryFileReaderError,
+    read_from::{ReadFrom, Spanned},
+    BinaryFileReader, BinaryRead,
+};
+
+#[derive(Debug, PartialEq, BinaryRead)]
+struct Ihdr {
+    width: u32,
+    height: u32,
+    bit_depth: u8,
+    color_type: u8,
+    compression_method: u8,
+    filter_method: u8,
+    interlace_method: u8,
+}
+
+#[derive(Debug, PartialEq, BinaryRead)]
+pub struct Point(pub u16, pub u16);
+
+#[derive(Debug, PartialEq, BinaryRead)]
+struct Marker;
+
+#[derive(Debug, PartialEq, BinaryRead)]
+struct Pair<T, const N: usize>
+where
+    T: Copy,
+{
+    tag: [u8; N],
+    values: (T, T),
+}
+
+#[derive(Debug, PartialEq, BinaryRead)]
+struct Nested {
+    origin: Point,
+    marker: Marker,
+    pairs: [Pair<u8, 2>; 2],
+}
+
+#[test]
+fn test_named_struct() -> Result<(), BinaryFileReaderError> {
+    let buffer = vec![0, 0, 0, 100, 0, 0, 0, 50, 8, 2, 0, 0, 1];
+    let mut reader = BinaryFileReader::new(&buffer);
+    assert_eq!(
+        reader.read_struct::<Ihdr>()?,
+        Ihdr {
+            width: 100,
+            height: 50,
+            bit_depth: 8,
+            color_type: 2,
+            compression_method: 0,
+            filter_method: 0,
+            interlace_method: 1,
+        }
+    );
+    assert_eq!(reader.available_bytes(), 0);
+    assert_eq!(Ihdr::MIN_SIZE, 13);
+
+    let mut reader = BinaryFileReader::new(&buffer[..10]);
+    assert!(matches!(
+        reader.read_struct::<Ihdr>(),
+        Err(BinaryFileReaderError::BufferUnderflow {
+            requested_bytes: 1,
+            current_offset: 10,
+            available_bytes: 0
+        })
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_tuple_unit_and_generic_structs() -> Result<(), BinaryFileReaderError> {
+    let buffer = vec![0, 1, 0, 2, b'a', b'b', 3, 4, b'c', b'd', 5, 6];
+    let mut reader = BinaryFileReader::new(&buffer);
+    assert_eq!(
+        reader.read_struct::<Nested>()?,
+        Nested {
+            origin: Point(1, 2),
+            marker: Marker,
+            pairs: [
+                Pair {
+                    tag: *b"ab",
+                    values: (3, 4)
+                },
+                Pair {
+                    tag: *b"cd",
+                    values: (5, 6)
+                }
+            ],
+        }
+    );
+    assert_eq!(reader.available_bytes(), 0);
+
+    Ok(())
+}
+
+#[derive(Debug, PartialEq, BinaryRead)]
+#[magic = b"\x89PNG\r\n\x1a\n"]
+struct Signature;
+
+#[derive(Debug, PartialEq, BinaryRead)]
+#[magic = "RIFF"]
+#[endian = "little"]
+struct RiffHeader {
+    size: u32,
+    #[magic = "WAVE"]
+    #[endian = "big"]
+    format_tag: u16,
+    channels: u16,
+}
+
+#[derive(Debug, PartialEq, BinaryRead)]
+#[pad = 4]
+struct Table {
+    #[pad = 2]
+    num_entries: u8,
+    #[count = "num_entries"]
+    #[endian = "little"]
+    entries: Vec<u16>,
+    flags: u8,
+}
+
+#[test]
+fn test_magic_and_endian() -> Result<(), BinaryFileReaderError> {
+    let buffer = b"\x89PNG\r\n\x1a\nRIFF\x24\x08\x00\x00WAVE\x00\x01\x02\x00\x12\x34".to_vec();
+    let mut reader = BinaryFileReader::new(&buffer);
+    assert_eq!(reader.read_struct::<Signature>()?, Signature);
+    assert_eq!(
+        reader.read_struct::<RiffHeader>()?,
+        RiffHeader {
+            size: 0x0824,
+            format_tag: 0x0001,
+            channels: 2,
+        }
+    );
+    assert_eq!(reader.read_u16()?, 0x1234);
+
+    let mut reader = BinaryFileReader::new(b"RIFF\x00\x00\x00\x00AVI \x00\x01\x02\x00");
+    assert!(matches!(
+        reader.read_struct::<RiffHeader>(),
+        Err(BinaryFileReaderError::Expect {
+            current_offset: 8,
+            ..
+        })
+    ));
+    assert_eq!(reader.endian(), binary_file_reader::endian::Endian::Big);
+
+    Ok(())
+}
+
+#[test]
+fn test_count_and_pad() -> Result<(), BinaryFileReaderError> {
+    let buffer = vec![3, 0xff, 1, 0, 2, 0, 3, 0, 0x80, 0xff, 0xff, 0xff, 0x42];
+    let mut reader = BinaryFileReader::new(&buffer);
+    assert_eq!(
+        reader.read_struct::<Table>()?,
+        Table {
+            num_entries: 3,
+            entries: vec![1, 2, 3],
+            flags: 0x80,
+        }
+    );
+    assert_eq!(reader.current_offset(), 12);
+    assert_eq!(reader.read_u8()?, 0x42);
+
+    assert_eq!(Table::MIN_SIZE, 2);
+    assert_eq!(Nested::MIN_SIZE, 12);
+    assert_eq!(Record::MIN_SIZE, 1);
+
+    let buffer = vec![0, 0, 0x01, 0];
+    let mut reader = BinaryFileReader::new(&buffer);
+    assert_eq!(
+        reader.read_struct::<Table>()?,
+        Table {
+            num_entries: 0,
+            entries: vec![],
+            flags: 0x01,
+        }
+    );
+    assert_eq!(reader.available_bytes(), 0);
+
+    Ok(())
+}
+
+#[derive(Debug, PartialEq, BinaryRead)]
+#[tag(u8)]
+enum Record {
+    Empty,
+    Point(u16, u16),
+    #[tag = 0x10]
+    Named {
+        id: u8,
+        #[count = "id"]
+        data: Vec<u8>,
+    },
+    Next,
+}
+
+#[derive(Debug, PartialEq, BinaryRead)]
+#[tag(u16)]
+#[endian = "little"]
+enum Command {
+    Start = 0x0100,
+    Stop,
+}
+
+#[test]
+fn test_tagged_enum() -> Result<(), BinaryFileReaderError> {
+    let buffer = vec![0, 1, 0, 1, 0, 2, 0x10, 2, 0xaa, 0xbb, 0x11, 0x12];
+    let mut reader = BinaryFileReader::new(&buffer);
+    assert_eq!(reader.read_struct::<Record>()?, Record::Empty);
+    assert_eq!(reader.read_struct::<Record>()?, Record::Point(1, 2));
+    assert_eq!(
+        reader.read_struct::<Record>()?,
+        Record::Named {
+            id: 2,
+            data: vec![0xaa, 0xbb],
+        }
+    );
+    assert_eq!(reader.read_struct::<Record>()?, Record::Next);
+    assert!(matches!(
+        reader.read_struct::<Record>(),
+        Err(BinaryFileReaderError::InvalidDiscriminant {
+            type_name: "Record",
+            value: 0x12,
+            current_offset: 11,
+        })
+    ));
+    assert_eq!(reader.current_offset(), 11);
+
+    let buffer = vec![0x00, 0x01, 0x01, 0x01, 0x02, 0x01];
+    let mut reader = BinaryFileReader::new(&buffer);
+    assert_eq!(reader.read_struct::<Command>()?, Command::Start);
+    assert_eq!(reader.read_struct::<Command>()?, Command::Stop);
+    assert!(matches!(
+        reader.read_struct::<Command>(),
+        Err(BinaryFileReaderError::InvalidDiscriminant { value: 0x0102, .. })
+    ));
+
+    Ok(())
+}
+
+#[derive(Debug, PartialEq, BinaryRead)]
+struct Entry {
+    #[endian = "little"]
+    length: Spanned<u32>,
+    #[magic = "ID"]
+    id: Spanned<u16>,
+    flags: u8,
+}
+
+#[test]
+fn test_spanned_fields() -> Result<(), BinaryFileReaderError> {
+    let buffer = vec![0xff, 0x02, 0x00, 0x00, 0x00, b'I', b'D', 0x00, 0x07, 0x01];
+    let mut reader = BinaryFileReader::new(&buffer);
+    reader.read_u8()?;
+    let entry = reader.read_struct::<Entry>()?;
+    assert_eq!(
+        entry.length,
+        Spanned {
+            value: 2,
+            span: 1..5
+        }
+    );
+    assert_eq!((*entry.id, entry.id.span), (7, 7..9));
+    assert_eq!(entry.flags, 1);
+    assert_eq!(<Entry as ReadFrom>::MIN_SIZE, 7);
+
+    Ok(())
+}
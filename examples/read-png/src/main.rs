@@ -1,9 +1,8 @@
 use binary_file_reader::BinaryFileReader;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let buffer = std::fs::read("./sample-files/1.png").unwrap();
-
-    let mut reader = BinaryFileReader::new(&buffer);
+    let mut file = BinaryFileReader::from_path("./sample-files/1.png")?;
+    let mut reader = file.reader();
 
     // Read PNG signature
     reader.expect(&[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A])?;
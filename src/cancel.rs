@@ -0,0 +1,49 @@
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// A flag shared between a reader and the code that may abort it, such as a
+/// GUI thread. Once [`cancel`](Self::cancel) is called, the looping methods of
+/// a reader holding the token, like
+/// [`repeat`](crate::BinaryFileReader::repeat) and
+/// [`iter_of`](crate::BinaryFileReader::iter_of), fail with
+/// [`Cancelled`](crate::error::BinaryFileReaderError::Cancelled) before their
+/// next item.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::BinaryFileReader;
+/// # use binary_file_reader::cancel::CancelToken;
+/// # use binary_file_reader::error::BinaryFileReaderError;
+/// let buffer = vec![0; 1024];
+/// let mut reader = BinaryFileReader::new(&buffer);
+/// let token = CancelToken::new();
+/// reader.set_cancel_token(token.clone());
+///
+/// let result = reader.repeat(|r| {
+///     if r.current_offset() == 8 {
+///         token.cancel();
+///     }
+///     r.read_u32()
+/// });
+/// assert!(matches!(
+///     result,
+///     Err(BinaryFileReaderError::Cancelled { current_offset: 12 })
+/// ));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,289 @@
+use crate::{endian::Endian, error::BinaryFileReaderError, source::DataSource};
+use alloc::{borrow::Cow, vec, vec::Vec};
+#[cfg(feature = "std")]
+use std::io::IoSlice;
+
+/// A reader over several buffers read back to back, such as the segments of
+/// a ring buffer. Offsets, including those in errors, count from the start of
+/// the first buffer. A read that spans a segment boundary is stitched
+/// together in a scratch buffer; other reads borrow the segments directly.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::BinaryFileReader;
+/// # use binary_file_reader::error::BinaryFileReaderError;
+/// # use binary_file_reader::source::DataSource;
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// let head = [0x00, 0x01, 0x02];
+/// let tail = [0x03, 0x04];
+/// let mut reader = BinaryFileReader::chain(&[&head, &tail]);
+///
+/// assert_eq!(reader.read_u8()?, 0x00);
+/// assert_eq!(reader.read_u32()?, 0x01020304);
+/// assert!(matches!(
+///     reader.read_u8(),
+///     Err(BinaryFileReaderError::BufferUnderflow { current_offset: 5, .. })
+/// ));
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ChainedReader<'a> {
+    segments: Vec<&'a [u8]>,
+    // The segment holding the cursor and the cursor's offset inside it.
+    segment: usize,
+    position: usize,
+    offset: usize,
+    length: usize,
+    scratch: Vec<u8>,
+    endian: Endian,
+}
+
+impl<'a> ChainedReader<'a> {
+    pub fn new(segments: &[&'a [u8]]) -> Self {
+        Self::from(segments.to_vec())
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::chain::ChainedReader;
+    /// # use binary_file_reader::source::DataSource;
+    /// # use std::io::IoSlice;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let received = [IoSlice::new(&[0x00, 0x00]), IoSlice::new(&[0x01, 0x00])];
+    /// let mut reader = ChainedReader::from_io_slices(&received);
+    /// assert_eq!(reader.read_u32()?, 256);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn from_io_slices(slices: &'a [IoSlice<'a>]) -> Self {
+        Self::from(slices.iter().map(|slice| &**slice).collect::<Vec<_>>())
+    }
+
+    #[inline]
+    pub fn current_offset(&self) -> usize {
+        self.offset
+    }
+
+    #[inline]
+    pub fn available_bytes(&self) -> usize {
+        self.length - self.offset
+    }
+
+    #[inline]
+    pub fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    #[inline]
+    pub fn set_endian(&mut self, endian: Endian) {
+        self.endian = endian;
+    }
+
+    /// Reads `length` bytes, borrowing them when they lie in one segment and
+    /// copying them otherwise.
+    ///
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # use std::borrow::Cow;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut reader = BinaryFileReader::chain(&[b"abc", b"def"]);
+    /// assert!(matches!(reader.read_slice(2)?, Cow::Borrowed(b"ab")));
+    /// assert!(matches!(reader.read_slice(2)?, Cow::Owned(bytes) if bytes == b"cd"));
+    /// assert!(reader.read_slice(3).is_err());
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn read_slice(&mut self, length: usize) -> Result<Cow<'a, [u8]>, BinaryFileReaderError> {
+        let current = self.current();
+        if current.len() >= length {
+            self.consume(length);
+            return Ok(Cow::Borrowed(&current[..length]));
+        }
+
+        if length > self.available_bytes() {
+            return Err(BinaryFileReaderError::BufferUnderflow {
+                requested_bytes: length,
+                current_offset: self.offset,
+                available_bytes: self.available_bytes(),
+            });
+        }
+
+        let mut bytes = vec![0; length];
+        self.read_bytes(&mut bytes)?;
+        Ok(Cow::Owned(bytes))
+    }
+
+    /// The rest of the segment holding the cursor.
+    fn current(&self) -> &'a [u8] {
+        match self.segments.get(self.segment) {
+            Some(segment) => &segment[self.position..],
+            None => &[],
+        }
+    }
+
+    fn skip_exhausted(&mut self) {
+        while self
+            .segments
+            .get(self.segment)
+            .is_some_and(|segment| segment.len() == self.position)
+        {
+            self.segment += 1;
+            self.position = 0;
+        }
+    }
+}
+
+impl<'a> From<Vec<&'a [u8]>> for ChainedReader<'a> {
+    fn from(segments: Vec<&'a [u8]>) -> Self {
+        let mut reader = Self {
+            length: segments.iter().map(|segment| segment.len()).sum(),
+            segments,
+            segment: 0,
+            position: 0,
+            offset: 0,
+            scratch: Vec::new(),
+            endian: Endian::Big,
+        };
+        reader.skip_exhausted();
+        reader
+    }
+}
+
+impl DataSource for ChainedReader<'_> {
+    fn fill(&mut self, length: usize) -> Result<&[u8], BinaryFileReaderError> {
+        let current = self.current();
+        if current.len() >= length {
+            return Ok(current);
+        }
+
+        self.scratch.clear();
+        self.scratch.extend_from_slice(current);
+        for segment in self.segments.iter().skip(self.segment + 1) {
+            let needed = length - self.scratch.len();
+            if needed == 0 {
+                break;
+            }
+            self.scratch
+                .extend_from_slice(&segment[..needed.min(segment.len())]);
+        }
+
+        Ok(&self.scratch)
+    }
+
+    fn consume(&mut self, length: usize) {
+        debug_assert!(length <= self.available_bytes());
+        let mut remaining = length;
+        while remaining > 0 {
+            let step = remaining.min(self.current().len());
+            self.position += step;
+            remaining -= step;
+            self.skip_exhausted();
+        }
+        self.offset += length;
+    }
+
+    #[inline]
+    fn current_offset(&self) -> usize {
+        self.offset
+    }
+
+    #[inline]
+    fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    fn skip(&mut self, length: usize) -> Result<(), BinaryFileReaderError> {
+        if length > self.available_bytes() {
+            return Err(BinaryFileReaderError::BufferUnderflow {
+                requested_bytes: length,
+                current_offset: self.offset,
+                available_bytes: self.available_bytes(),
+            });
+        }
+
+        self.consume(length);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChainedReader;
+    use crate::{endian::Endian, error::BinaryFileReaderError, source::DataSource};
+
+    #[test]
+    fn test_chained_reader() -> Result<(), BinaryFileReaderError> {
+        let segments: [&[u8]; 5] = [&[], &[0xca], &[], &[0xfe, 0x01, 0x02], &[0x03, 0x04, 0x05]];
+        let mut reader = ChainedReader::new(&segments);
+        assert_eq!(reader.available_bytes(), 7);
+
+        reader.expect(&[0xca, 0xfe])?;
+        assert_eq!(reader.peek_slice(2)?, &[0x01, 0x02]);
+        reader.set_endian(Endian::Little);
+        assert_eq!(reader.read_u32()?, 0x04030201);
+        assert!(matches!(
+            reader.read_u16(),
+            Err(BinaryFileReaderError::BufferUnderflow {
+                requested_bytes: 2,
+                current_offset: 6,
+                available_bytes: 1
+            })
+        ));
+        assert!(matches!(
+            reader.skip(2),
+            Err(BinaryFileReaderError::BufferUnderflow {
+                current_offset: 6,
+                ..
+            })
+        ));
+        reader.skip(1)?;
+        assert!(reader.is_at_end()?);
+
+        let mut reader = ChainedReader::new(&segments);
+        let mut all = [0; 7];
+        reader.read_bytes(&mut all)?;
+        assert_eq!(all, [0xca, 0xfe, 0x01, 0x02, 0x03, 0x04, 0x05]);
+        assert!(ChainedReader::new(&[]).is_at_end()?);
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_io_slices() -> Result<(), BinaryFileReaderError> {
+        use std::{borrow::Cow, io::IoSlice};
+
+        let data = (0..16).collect::<Vec<u8>>();
+        let slices = data.chunks(1).map(IoSlice::new).collect::<Vec<_>>();
+        let mut reader = ChainedReader::from_io_slices(&slices);
+
+        assert_eq!(reader.read_u64()?, 0x0001020304050607);
+        assert_eq!(reader.read_slice(1)?, Cow::Borrowed(&[8][..]));
+        assert!(matches!(reader.read_slice(3)?, Cow::Owned(bytes) if bytes == [9, 10, 11]));
+        assert!(matches!(
+            reader.read_slice(5),
+            Err(BinaryFileReaderError::BufferUnderflow {
+                current_offset: 12,
+                ..
+            })
+        ));
+        assert_eq!(reader.available_bytes(), 4);
+
+        Ok(())
+    }
+}
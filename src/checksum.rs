@@ -0,0 +1,977 @@
+/// A checksum or digest computed incrementally over bytes, such as the bytes
+/// consumed inside [`with_checksum`](crate::BinaryFileReader::with_checksum).
+/// Implement it for a wrapper around another hashing crate to use that crate
+/// while parsing.
+pub trait Checksum {
+    type Output;
+
+    fn update(&mut self, bytes: &[u8]);
+
+    /// The checksum of the bytes so far. More bytes can still be added.
+    fn finish(&self) -> Self::Output;
+}
+
+const CRC32_TABLE: [u32; 256] = crc32_table();
+
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut index = 0;
+    while index < 256 {
+        let mut crc = index as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[index] = crc;
+        index += 1;
+    }
+    table
+}
+
+/// An incremental CRC-32 (IEEE 802.3), as used by PNG, ZIP and gzip.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::checksum::{crc32, Crc32};
+/// let mut crc = Crc32::new();
+/// crc.update(b"1234");
+/// crc.update(b"56789");
+/// assert_eq!(crc.finish(), 0xcbf4_3926);
+/// assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crc32 {
+    state: u32,
+}
+
+impl Crc32 {
+    pub fn new() -> Self {
+        Self { state: !0 }
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.state =
+                CRC32_TABLE[((self.state ^ byte as u32) & 0xff) as usize] ^ (self.state >> 8);
+        }
+    }
+
+    /// The CRC of the bytes so far. More bytes can still be added.
+    pub fn finish(&self) -> u32 {
+        !self.state
+    }
+}
+
+impl Checksum for Crc32 {
+    type Output = u32;
+
+    fn update(&mut self, bytes: &[u8]) {
+        Crc32::update(self, bytes);
+    }
+
+    fn finish(&self) -> u32 {
+        Crc32::finish(self)
+    }
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The CRC-32 of `bytes`.
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(bytes);
+    crc.finish()
+}
+
+/// The CRC-16 parameter sets supported by [`Crc16`], named as in the
+/// catalogue of parametrised CRC algorithms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Crc16Variant {
+    /// CRC-16/X-25, the frame check sequence of HDLC, X.25 and PPP.
+    X25,
+    /// CRC-16/MODBUS, the trailer of Modbus RTU frames. It is sent low byte
+    /// first.
+    Modbus,
+    /// CRC-16/XMODEM, also used by ZMODEM and many serial protocols.
+    Xmodem,
+    /// CRC-16/IBM-3740, often called CCITT-FALSE.
+    CcittFalse,
+    /// CRC-16/KERMIT, often called CCITT.
+    Kermit,
+}
+
+impl Crc16Variant {
+    // (polynomial, initial value, reflected, final XOR)
+    const fn parameters(self) -> (u16, u16, bool, u16) {
+        match self {
+            Self::X25 => (0x1021, 0xffff, true, 0xffff),
+            Self::Modbus => (0x8005, 0xffff, true, 0x0000),
+            Self::Xmodem => (0x1021, 0x0000, false, 0x0000),
+            Self::CcittFalse => (0x1021, 0xffff, false, 0x0000),
+            Self::Kermit => (0x1021, 0x0000, true, 0x0000),
+        }
+    }
+}
+
+/// An incremental CRC-16 with the parameters of a [`Crc16Variant`].
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::BinaryFileReader;
+/// # use binary_file_reader::checksum::{Crc16, Crc16Variant};
+/// # use binary_file_reader::endian::Endian;
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// // Modbus "read holding registers" request with its CRC.
+/// let buffer = vec![0x01, 0x03, 0x00, 0x00, 0x00, 0x0a, 0xc5, 0xcd];
+/// let mut reader = BinaryFileReader::new(&buffer);
+///
+/// let crc = Crc16::new(Crc16Variant::Modbus);
+/// let ((), crc) = reader.with_checksum(crc, |r| r.read_slice(6).map(drop))?;
+/// reader.set_endian(Endian::Little);
+/// assert_eq!(crc, reader.read_u16()?);
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crc16 {
+    variant: Crc16Variant,
+    state: u16,
+}
+
+impl Crc16 {
+    pub fn new(variant: Crc16Variant) -> Self {
+        let (_, init, reflected, _) = variant.parameters();
+        Self {
+            variant,
+            // Reflected variants are computed on the bit-reversed register.
+            state: if reflected { init.reverse_bits() } else { init },
+        }
+    }
+
+    pub fn variant(&self) -> Crc16Variant {
+        self.variant
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        let (poly, _, reflected, _) = self.variant.parameters();
+        if reflected {
+            let poly = poly.reverse_bits();
+            for &byte in bytes {
+                self.state ^= byte as u16;
+                for _ in 0..8 {
+                    self.state = if self.state & 1 == 1 {
+                        (self.state >> 1) ^ poly
+                    } else {
+                        self.state >> 1
+                    };
+                }
+            }
+        } else {
+            for &byte in bytes {
+                self.state ^= (byte as u16) << 8;
+                for _ in 0..8 {
+                    self.state = if self.state & 0x8000 != 0 {
+                        (self.state << 1) ^ poly
+                    } else {
+                        self.state << 1
+                    };
+                }
+            }
+        }
+    }
+
+    /// The CRC of the bytes so far. More bytes can still be added.
+    pub fn finish(&self) -> u16 {
+        let (_, _, _, xor_out) = self.variant.parameters();
+        self.state ^ xor_out
+    }
+}
+
+impl Checksum for Crc16 {
+    type Output = u16;
+
+    fn update(&mut self, bytes: &[u8]) {
+        Crc16::update(self, bytes);
+    }
+
+    fn finish(&self) -> u16 {
+        Crc16::finish(self)
+    }
+}
+
+/// The CRC-16 of `bytes` with the parameters of `variant`.
+pub fn crc16(variant: Crc16Variant, bytes: &[u8]) -> u16 {
+    let mut crc = Crc16::new(variant);
+    crc.update(bytes);
+    crc.finish()
+}
+
+/// An incremental Adler-32, as used by zlib streams.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::BinaryFileReader;
+/// # use binary_file_reader::checksum::{adler32, Adler32};
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+///
+/// let buffer = b"Wikipedia\x11\xe6\x03\x98".to_vec();
+/// let mut reader = BinaryFileReader::new(&buffer);
+/// let ((), adler) = reader.with_checksum(Adler32::new(), |r| r.read_slice(9).map(drop))?;
+/// assert_eq!(adler, reader.read_u32()?);
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Adler32 {
+    a: u32,
+    b: u32,
+}
+
+impl Adler32 {
+    const MODULUS: u32 = 65521;
+    // The most bytes that can be summed before `b` could overflow.
+    const CHUNK: usize = 5552;
+
+    pub fn new() -> Self {
+        Self { a: 1, b: 0 }
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        for chunk in bytes.chunks(Self::CHUNK) {
+            for &byte in chunk {
+                self.a += byte as u32;
+                self.b += self.a;
+            }
+            self.a %= Self::MODULUS;
+            self.b %= Self::MODULUS;
+        }
+    }
+
+    /// The checksum of the bytes so far. More bytes can still be added.
+    pub fn finish(&self) -> u32 {
+        (self.b << 16) | self.a
+    }
+}
+
+impl Checksum for Adler32 {
+    type Output = u32;
+
+    fn update(&mut self, bytes: &[u8]) {
+        Adler32::update(self, bytes);
+    }
+
+    fn finish(&self) -> u32 {
+        Adler32::finish(self)
+    }
+}
+
+impl Default for Adler32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The Adler-32 of `bytes`.
+pub fn adler32(bytes: &[u8]) -> u32 {
+    let mut adler = Adler32::new();
+    adler.update(bytes);
+    adler.finish()
+}
+
+#[cfg(feature = "xxhash")]
+mod xxh64 {
+    pub(super) const P1: u64 = 0x9e37_79b1_85eb_ca87;
+    pub(super) const P2: u64 = 0xc2b2_ae3d_27d4_eb4f;
+    pub(super) const P3: u64 = 0x1656_67b1_9e37_79f9;
+    pub(super) const P4: u64 = 0x85eb_ca77_c2b2_ae63;
+    pub(super) const P5: u64 = 0x27d4_eb2f_1656_67c5;
+
+    pub(super) fn round(acc: u64, lane: u64) -> u64 {
+        acc.wrapping_add(lane.wrapping_mul(P2))
+            .rotate_left(31)
+            .wrapping_mul(P1)
+    }
+
+    pub(super) fn merge(acc: u64, value: u64) -> u64 {
+        (acc ^ round(0, value)).wrapping_mul(P1).wrapping_add(P4)
+    }
+
+    pub(super) fn lane(bytes: &[u8]) -> u64 {
+        let mut lane = [0; 8];
+        lane.copy_from_slice(&bytes[..8]);
+        u64::from_le_bytes(lane)
+    }
+}
+
+/// An incremental XXH64, a fast non-cryptographic hash for deduplicating or
+/// caching regions. It is not suitable for integrity checks against tampering.
+///
+/// Only available with the `xxhash` feature.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::checksum::{xxh64, Checksum, Xxh64};
+/// let mut hash = Xxh64::new(0);
+/// hash.update(b"Nobody inspects");
+/// hash.update(b" the spammish repetition");
+/// assert_eq!(hash.finish(), 0xfbce_a83c_8a37_8bf1);
+/// assert_eq!(xxh64(b"abc", 0), 0x44bc_2cf5_ad77_0999);
+/// ```
+#[cfg(feature = "xxhash")]
+#[derive(Debug, Clone)]
+pub struct Xxh64 {
+    seed: u64,
+    accumulators: [u64; 4],
+    pending: [u8; 32],
+    pending_len: usize,
+    total_len: u64,
+}
+
+#[cfg(feature = "xxhash")]
+impl Xxh64 {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            accumulators: [
+                seed.wrapping_add(xxh64::P1).wrapping_add(xxh64::P2),
+                seed.wrapping_add(xxh64::P2),
+                seed,
+                seed.wrapping_sub(xxh64::P1),
+            ],
+            pending: [0; 32],
+            pending_len: 0,
+            total_len: 0,
+        }
+    }
+
+    fn stripe(&mut self, stripe: &[u8]) {
+        for (acc, lane) in self.accumulators.iter_mut().zip(stripe.chunks_exact(8)) {
+            *acc = xxh64::round(*acc, xxh64::lane(lane));
+        }
+    }
+}
+
+#[cfg(feature = "xxhash")]
+impl Checksum for Xxh64 {
+    type Output = u64;
+
+    fn update(&mut self, mut bytes: &[u8]) {
+        self.total_len = self.total_len.wrapping_add(bytes.len() as u64);
+        if self.pending_len > 0 {
+            let take = (32 - self.pending_len).min(bytes.len());
+            self.pending[self.pending_len..self.pending_len + take].copy_from_slice(&bytes[..take]);
+            self.pending_len += take;
+            bytes = &bytes[take..];
+            if self.pending_len < 32 {
+                return;
+            }
+            let pending = self.pending;
+            self.stripe(&pending);
+            self.pending_len = 0;
+        }
+        let mut stripes = bytes.chunks_exact(32);
+        for stripe in &mut stripes {
+            self.stripe(stripe);
+        }
+        let rest = stripes.remainder();
+        self.pending[..rest.len()].copy_from_slice(rest);
+        self.pending_len = rest.len();
+    }
+
+    fn finish(&self) -> u64 {
+        use xxh64::{P1, P2, P3, P4, P5};
+
+        let mut hash = if self.total_len >= 32 {
+            let [v1, v2, v3, v4] = self.accumulators;
+            let hash = v1
+                .rotate_left(1)
+                .wrapping_add(v2.rotate_left(7))
+                .wrapping_add(v3.rotate_left(12))
+                .wrapping_add(v4.rotate_left(18));
+            self.accumulators
+                .iter()
+                .fold(hash, |hash, &v| xxh64::merge(hash, v))
+        } else {
+            self.seed.wrapping_add(P5)
+        };
+        hash = hash.wrapping_add(self.total_len);
+
+        let mut rest = &self.pending[..self.pending_len];
+        while rest.len() >= 8 {
+            hash ^= xxh64::round(0, xxh64::lane(rest));
+            hash = hash.rotate_left(27).wrapping_mul(P1).wrapping_add(P4);
+            rest = &rest[8..];
+        }
+        if rest.len() >= 4 {
+            let word = u32::from_le_bytes([rest[0], rest[1], rest[2], rest[3]]) as u64;
+            hash ^= word.wrapping_mul(P1);
+            hash = hash.rotate_left(23).wrapping_mul(P2).wrapping_add(P3);
+            rest = &rest[4..];
+        }
+        for &byte in rest {
+            hash ^= (byte as u64).wrapping_mul(P5);
+            hash = hash.rotate_left(11).wrapping_mul(P1);
+        }
+
+        hash ^= hash >> 33;
+        hash = hash.wrapping_mul(P2);
+        hash ^= hash >> 29;
+        hash = hash.wrapping_mul(P3);
+        hash ^ (hash >> 32)
+    }
+}
+
+/// The XXH64 of `bytes` with `seed`.
+///
+/// Only available with the `xxhash` feature.
+#[cfg(feature = "xxhash")]
+pub fn xxh64(bytes: &[u8], seed: u64) -> u64 {
+    let mut hash = Xxh64::new(seed);
+    hash.update(bytes);
+    hash.finish()
+}
+
+/// The XXH32 of `bytes` with `seed`, as used by the LZ4 frame format.
+///
+/// Only available with the `xxhash` or `lz4` feature.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::checksum::xxh32;
+/// assert_eq!(xxh32(b"abc", 0), 0x32d1_53ff);
+/// ```
+#[cfg(any(feature = "xxhash", feature = "lz4"))]
+pub fn xxh32(bytes: &[u8], seed: u32) -> u32 {
+    const P1: u32 = 0x9e37_79b1;
+    const P2: u32 = 0x85eb_ca77;
+    const P3: u32 = 0xc2b2_ae3d;
+    const P4: u32 = 0x27d4_eb2f;
+    const P5: u32 = 0x1656_67b1;
+    let word = |bytes: &[u8]| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+
+    let mut stripes = bytes.chunks_exact(16);
+    let mut hash = if bytes.len() >= 16 {
+        let mut accumulators = [
+            seed.wrapping_add(P1).wrapping_add(P2),
+            seed.wrapping_add(P2),
+            seed,
+            seed.wrapping_sub(P1),
+        ];
+        for stripe in &mut stripes {
+            for (acc, lane) in accumulators.iter_mut().zip(stripe.chunks_exact(4)) {
+                *acc = acc
+                    .wrapping_add(word(lane).wrapping_mul(P2))
+                    .rotate_left(13)
+                    .wrapping_mul(P1);
+            }
+        }
+        let [v1, v2, v3, v4] = accumulators;
+        v1.rotate_left(1)
+            .wrapping_add(v2.rotate_left(7))
+            .wrapping_add(v3.rotate_left(12))
+            .wrapping_add(v4.rotate_left(18))
+    } else {
+        seed.wrapping_add(P5)
+    };
+    hash = hash.wrapping_add(bytes.len() as u32);
+
+    let mut words = stripes.remainder().chunks_exact(4);
+    for lane in &mut words {
+        hash = hash
+            .wrapping_add(word(lane).wrapping_mul(P3))
+            .rotate_left(17)
+            .wrapping_mul(P4);
+    }
+    for &byte in words.remainder() {
+        hash = hash
+            .wrapping_add((byte as u32).wrapping_mul(P5))
+            .rotate_left(11)
+            .wrapping_mul(P1);
+    }
+
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(P2);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(P3);
+    hash ^ (hash >> 16)
+}
+
+/// Buffers input into the 64-byte blocks of SHA-1 and SHA-256, and appends
+/// their padding and bit length.
+#[cfg(feature = "sha")]
+#[derive(Debug, Clone)]
+struct Blocks {
+    pending: [u8; 64],
+    pending_len: usize,
+    total_len: u64,
+}
+
+#[cfg(feature = "sha")]
+impl Blocks {
+    const fn new() -> Self {
+        Self {
+            pending: [0; 64],
+            pending_len: 0,
+            total_len: 0,
+        }
+    }
+
+    fn update(&mut self, mut bytes: &[u8], mut compress: impl FnMut(&[u8; 64])) {
+        self.total_len = self.total_len.wrapping_add(bytes.len() as u64);
+        while !bytes.is_empty() {
+            let take = (64 - self.pending_len).min(bytes.len());
+            self.pending[self.pending_len..self.pending_len + take].copy_from_slice(&bytes[..take]);
+            self.pending_len += take;
+            bytes = &bytes[take..];
+            if self.pending_len == 64 {
+                compress(&self.pending);
+                self.pending_len = 0;
+            }
+        }
+    }
+
+    fn finish(mut self, mut compress: impl FnMut(&[u8; 64])) {
+        let bits = self.total_len.wrapping_mul(8);
+        self.update(&[0x80], &mut compress);
+        while self.pending_len != 56 {
+            self.update(&[0], &mut compress);
+        }
+        self.update(&bits.to_be_bytes(), compress);
+    }
+}
+
+/// An incremental SHA-1.
+///
+/// Only available with the `sha` feature.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::checksum::{Checksum, Sha1};
+/// let mut sha = Sha1::new();
+/// sha.update(b"abc");
+/// assert_eq!(
+///     sha.finish(),
+///     *b"\xa9\x99\x3e\x36\x47\x06\x81\x6a\xba\x3e\x25\x71\x78\x50\xc2\x6c\x9c\xd0\xd8\x9d"
+/// );
+/// ```
+#[cfg(feature = "sha")]
+#[derive(Debug, Clone)]
+pub struct Sha1 {
+    state: [u32; 5],
+    blocks: Blocks,
+}
+
+#[cfg(feature = "sha")]
+impl Sha1 {
+    pub const fn new() -> Self {
+        Self {
+            state: [
+                0x6745_2301,
+                0xefcd_ab89,
+                0x98ba_dcfe,
+                0x1032_5476,
+                0xc3d2_e1f0,
+            ],
+            blocks: Blocks::new(),
+        }
+    }
+
+    fn compress(state: &mut [u32; 5], block: &[u8; 64]) {
+        let mut w = [0u32; 80];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = *state;
+        for (i, &word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5a82_7999),
+                20..=39 => (b ^ c ^ d, 0x6ed9_eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1b_bcdc),
+                _ => (b ^ c ^ d, 0xca62_c1d6),
+            };
+            let t = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = t;
+        }
+        for (s, v) in state.iter_mut().zip([a, b, c, d, e]) {
+            *s = s.wrapping_add(v);
+        }
+    }
+}
+
+#[cfg(feature = "sha")]
+impl Default for Sha1 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "sha")]
+impl Checksum for Sha1 {
+    type Output = [u8; 20];
+
+    fn update(&mut self, bytes: &[u8]) {
+        let state = &mut self.state;
+        self.blocks
+            .update(bytes, |block| Self::compress(state, block));
+    }
+
+    fn finish(&self) -> [u8; 20] {
+        let mut state = self.state;
+        self.blocks
+            .clone()
+            .finish(|block| Self::compress(&mut state, block));
+        let mut out = [0; 20];
+        for (chunk, word) in out.chunks_exact_mut(4).zip(state) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        out
+    }
+}
+
+#[cfg(feature = "sha")]
+const SHA256_K: [u32; 64] = [
+    0x428a_2f98,
+    0x7137_4491,
+    0xb5c0_fbcf,
+    0xe9b5_dba5,
+    0x3956_c25b,
+    0x59f1_11f1,
+    0x923f_82a4,
+    0xab1c_5ed5,
+    0xd807_aa98,
+    0x1283_5b01,
+    0x2431_85be,
+    0x550c_7dc3,
+    0x72be_5d74,
+    0x80de_b1fe,
+    0x9bdc_06a7,
+    0xc19b_f174,
+    0xe49b_69c1,
+    0xefbe_4786,
+    0x0fc1_9dc6,
+    0x240c_a1cc,
+    0x2de9_2c6f,
+    0x4a74_84aa,
+    0x5cb0_a9dc,
+    0x76f9_88da,
+    0x983e_5152,
+    0xa831_c66d,
+    0xb003_27c8,
+    0xbf59_7fc7,
+    0xc6e0_0bf3,
+    0xd5a7_9147,
+    0x06ca_6351,
+    0x1429_2967,
+    0x27b7_0a85,
+    0x2e1b_2138,
+    0x4d2c_6dfc,
+    0x5338_0d13,
+    0x650a_7354,
+    0x766a_0abb,
+    0x81c2_c92e,
+    0x9272_2c85,
+    0xa2bf_e8a1,
+    0xa81a_664b,
+    0xc24b_8b70,
+    0xc76c_51a3,
+    0xd192_e819,
+    0xd699_0624,
+    0xf40e_3585,
+    0x106a_a070,
+    0x19a4_c116,
+    0x1e37_6c08,
+    0x2748_774c,
+    0x34b0_bcb5,
+    0x391c_0cb3,
+    0x4ed8_aa4a,
+    0x5b9c_ca4f,
+    0x682e_6ff3,
+    0x748f_82ee,
+    0x78a5_636f,
+    0x84c8_7814,
+    0x8cc7_0208,
+    0x90be_fffa,
+    0xa450_6ceb,
+    0xbef9_a3f7,
+    0xc671_78f2,
+];
+
+/// An incremental SHA-256.
+///
+/// Only available with the `sha` feature.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::BinaryFileReader;
+/// # use binary_file_reader::checksum::Sha256;
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// let buffer = b"\x00\x03abc".to_vec();
+/// let mut reader = BinaryFileReader::new(&buffer);
+///
+/// let length = reader.read_u16()? as usize;
+/// let ((), digest) = reader.with_checksum(Sha256::new(), |r| r.read_slice(length).map(drop))?;
+/// assert_eq!(digest[..4], [0xba, 0x78, 0x16, 0xbf]);
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+#[cfg(feature = "sha")]
+#[derive(Debug, Clone)]
+pub struct Sha256 {
+    state: [u32; 8],
+    blocks: Blocks,
+}
+
+#[cfg(feature = "sha")]
+impl Sha256 {
+    pub const fn new() -> Self {
+        Self {
+            state: [
+                0x6a09_e667,
+                0xbb67_ae85,
+                0x3c6e_f372,
+                0xa54f_f53a,
+                0x510e_527f,
+                0x9b05_688c,
+                0x1f83_d9ab,
+                0x5be0_cd19,
+            ],
+            blocks: Blocks::new(),
+        }
+    }
+
+    fn compress(state: &mut [u32; 8], block: &[u8; 64]) {
+        let mut w = [0u32; 64];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+        for (&k, &word) in SHA256_K.iter().zip(&w) {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(k)
+                .wrapping_add(word);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (s, v) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *s = s.wrapping_add(v);
+        }
+    }
+}
+
+#[cfg(feature = "sha")]
+impl Default for Sha256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "sha")]
+impl Checksum for Sha256 {
+    type Output = [u8; 32];
+
+    fn update(&mut self, bytes: &[u8]) {
+        let state = &mut self.state;
+        self.blocks
+            .update(bytes, |block| Self::compress(state, block));
+    }
+
+    fn finish(&self) -> [u8; 32] {
+        let mut state = self.state;
+        self.blocks
+            .clone()
+            .finish(|block| Self::compress(&mut state, block));
+        let mut out = [0; 32];
+        for (chunk, word) in out.chunks_exact_mut(4).zip(state) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{adler32, crc16, crc32, Adler32, Crc16, Crc16Variant, Crc32};
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(
+            crc32(b"The quick brown fox jumps over the lazy dog"),
+            0x414f_a339
+        );
+
+        let mut crc = Crc32::default();
+        crc.update(b"IE");
+        assert_ne!(crc.finish(), crc32(b"IEND"));
+        crc.update(b"ND");
+        assert_eq!(crc.finish(), crc32(b"IEND"));
+    }
+
+    #[test]
+    fn test_crc16() {
+        for (variant, check) in [
+            (Crc16Variant::X25, 0x906e),
+            (Crc16Variant::Modbus, 0x4b37),
+            (Crc16Variant::Xmodem, 0x31c3),
+            (Crc16Variant::CcittFalse, 0x29b1),
+            (Crc16Variant::Kermit, 0x2189),
+        ] {
+            assert_eq!(crc16(variant, b"123456789"), check, "{:?}", variant);
+
+            let mut crc = Crc16::new(variant);
+            crc.update(b"1234");
+            crc.update(b"56789");
+            assert_eq!(crc.finish(), check);
+            assert_eq!(crc.variant(), variant);
+        }
+        assert_eq!(crc16(Crc16Variant::X25, b""), 0x0000);
+        assert_eq!(crc16(Crc16Variant::CcittFalse, b""), 0xffff);
+    }
+
+    #[test]
+    fn test_adler32() {
+        assert_eq!(adler32(b""), 1);
+        assert_eq!(adler32(&[0xff; 6000]), 0xa497_59ea);
+
+        let mut adler = Adler32::default();
+        for piece in [0xffu8; 6000].chunks(5553) {
+            adler.update(piece);
+        }
+        assert_eq!(adler.finish(), adler32(&[0xff; 6000]));
+    }
+
+    #[cfg(any(feature = "xxhash", feature = "lz4"))]
+    #[test]
+    fn test_xxh32() {
+        use super::xxh32;
+
+        assert_eq!(xxh32(b"", 0), 0x02cc_5d05);
+        assert_eq!(
+            xxh32(b"Nobody inspects the spammish repetition", 0),
+            0xe229_3b2f
+        );
+        assert_eq!(xxh32(&[0x64, 0x40], 0) >> 8 & 0xff, 0xa7);
+    }
+
+    #[cfg(feature = "xxhash")]
+    #[test]
+    fn test_xxh64() {
+        use super::{xxh64, Checksum, Xxh64};
+
+        let bytes = (0..100).collect::<alloc::vec::Vec<u8>>();
+        assert_eq!(xxh64(b"", 0), 0xef46_db37_51d8_e999);
+        assert_eq!(xxh64(&bytes, 0), 0x6ac1_e580_3216_6597);
+        assert_eq!(xxh64(&bytes, 0x1234), 0x9c53_95b5_da7d_2126);
+
+        for piece in [1, 5, 31, 32, 33] {
+            let mut hash = Xxh64::new(0x1234);
+            bytes.chunks(piece).for_each(|piece| hash.update(piece));
+            assert_eq!(hash.finish(), 0x9c53_95b5_da7d_2126, "{}", piece);
+        }
+    }
+
+    #[cfg(feature = "sha")]
+    #[test]
+    fn test_sha() {
+        use super::{Checksum, Sha1, Sha256};
+        use alloc::{string::String, vec};
+        use core::fmt::Write;
+
+        fn hex(bytes: &[u8]) -> String {
+            let mut out = String::new();
+            for byte in bytes {
+                let _ = write!(out, "{:02x}", byte);
+            }
+            out
+        }
+        fn digests(input: &[u8]) -> (String, String) {
+            let mut sha1 = Sha1::new();
+            let mut sha256 = Sha256::new();
+            // Uneven pieces cross the block boundaries.
+            for piece in input.chunks(7) {
+                sha1.update(piece);
+                sha256.update(piece);
+            }
+            (hex(&sha1.finish()), hex(&sha256.finish()))
+        }
+
+        assert_eq!(
+            digests(b""),
+            (
+                "da39a3ee5e6b4b0d3255bfef95601890afd80709".into(),
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855".into()
+            )
+        );
+        assert_eq!(
+            digests(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+            (
+                "84983e441c3bd26ebaae4aa1f95129e5e54670f1".into(),
+                "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1".into()
+            )
+        );
+        let (sha1, sha256) = digests(&vec![b'a'; 1_000_000]);
+        assert_eq!(sha1, "34aa973cd4c4daa4f61eeb2bdbad27316534016f");
+        assert_eq!(
+            sha256,
+            "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+        );
+    }
+}
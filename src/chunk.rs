@@ -0,0 +1,265 @@
+use crate::{error::BinaryFileReaderError, fourcc::FourCC, BinaryFileReader};
+use alloc::{boxed::Box, vec::Vec};
+use core::fmt;
+
+type Handler<'a, 'h, T> =
+    Box<dyn FnMut(&mut BinaryFileReader<'a>) -> Result<T, BinaryFileReaderError> + 'h>;
+type UnknownHandler<'a, 'h, T> =
+    Box<dyn FnMut(FourCC, &mut BinaryFileReader<'a>) -> Result<T, BinaryFileReaderError> + 'h>;
+
+/// The order of the `u32` length and the four-byte tag in a chunk header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkLayout {
+    /// Length first, as in PNG.
+    LengthTag,
+    /// Tag first, as in RIFF and IFF.
+    TagLength,
+}
+
+/// Reads tagged chunks and hands each body to the handler registered for its
+/// tag. The length is read with the reader's endianness.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::BinaryFileReader;
+/// # use binary_file_reader::chunk::{ChunkDispatcher, ChunkLayout};
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// let buffer = vec![
+///     0, 0, 0, 1, b'a', b'b', b'c', b'd', 0x2a, 0xde, 0xad, 0xbe, 0xef,
+///     0, 0, 0, 2, b'z', b'z', b'z', b'z', 0x01, 0x02, 0xde, 0xad, 0xbe, 0xef,
+///     0, 0, 0, 0, b'e', b'n', b'd', b'!', 0xde, 0xad, 0xbe, 0xef,
+/// ];
+/// let mut reader = BinaryFileReader::new(&buffer);
+///
+/// let mut dispatcher = ChunkDispatcher::new(ChunkLayout::LengthTag)
+///     .trailer(4)
+///     .on(b"abcd", |sub| Ok(sub.read_u8()? as u16))
+///     .on(b"end!", |_| Ok(0xffff));
+///
+/// assert_eq!(dispatcher.dispatch(&mut reader)?, Some(0x2a));
+/// assert_eq!(dispatcher.dispatch(&mut reader)?, Some(0xffff));
+/// assert_eq!(dispatcher.dispatch(&mut reader)?, None);
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+pub struct ChunkDispatcher<'a, 'h, T> {
+    layout: ChunkLayout,
+    trailer: usize,
+    align: usize,
+    handlers: Vec<(FourCC, Handler<'a, 'h, T>)>,
+    unknown: Option<UnknownHandler<'a, 'h, T>>,
+}
+
+impl<'a, 'h, T> ChunkDispatcher<'a, 'h, T> {
+    pub fn new(layout: ChunkLayout) -> Self {
+        Self {
+            layout,
+            trailer: 0,
+            align: 1,
+            handlers: Vec::new(),
+            unknown: None,
+        }
+    }
+
+    /// Skips `size` bytes after every chunk body, such as the CRC of a PNG
+    /// chunk.
+    pub fn trailer(mut self, size: usize) -> Self {
+        self.trailer = size;
+        self
+    }
+
+    /// Skips padding after every chunk body so the next chunk starts at a
+    /// multiple of `align` bytes relative to the body length, as in RIFF. A
+    /// missing pad at the end of the data is tolerated.
+    ///
+    /// # Panics
+    /// Panics if `align` is 0.
+    pub fn align(mut self, align: usize) -> Self {
+        assert!(align != 0, "alignment must be non-zero");
+        self.align = align;
+        self
+    }
+
+    /// Registers the handler for chunks tagged `tag`. A later registration for
+    /// the same tag replaces the earlier one.
+    pub fn on<F>(mut self, tag: impl Into<FourCC>, handler: F) -> Self
+    where
+        F: FnMut(&mut BinaryFileReader<'a>) -> Result<T, BinaryFileReaderError> + 'h,
+    {
+        let tag = tag.into();
+        self.handlers.retain(|(registered, _)| *registered != tag);
+        self.handlers.push((tag, Box::new(handler)));
+        self
+    }
+
+    /// Registers the handler for chunks without a handler of their own.
+    /// Without one, such chunks are skipped.
+    pub fn on_unknown<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(FourCC, &mut BinaryFileReader<'a>) -> Result<T, BinaryFileReaderError> + 'h,
+    {
+        self.unknown = Some(Box::new(handler));
+        self
+    }
+
+    /// Reads chunks until one has a handler and returns its result, or `None`
+    /// once the reader is exhausted. A cancelled reader fails before each
+    /// chunk. On failure the reader is left at the start of the failing
+    /// chunk.
+    pub fn dispatch(
+        &mut self,
+        reader: &mut BinaryFileReader<'a>,
+    ) -> Result<Option<T>, BinaryFileReaderError> {
+        while reader.available_bytes() > 0 {
+            reader.check_cancelled()?;
+            let start = reader.current_offset;
+            match self.dispatch_one(reader) {
+                Ok(Some(value)) => return Ok(Some(value)),
+                Ok(None) => {}
+                Err(err) => {
+                    reader.current_offset = start;
+                    return Err(err);
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn dispatch_one(
+        &mut self,
+        reader: &mut BinaryFileReader<'a>,
+    ) -> Result<Option<T>, BinaryFileReaderError> {
+        let (length, tag) = match self.layout {
+            ChunkLayout::LengthTag => {
+                let length = reader.read_u32()?;
+                (length, reader.read_fourcc()?)
+            }
+            ChunkLayout::TagLength => {
+                let tag = reader.read_fourcc()?;
+                (reader.read_u32()?, tag)
+            }
+        };
+        let length = usize::try_from(length).unwrap_or(usize::MAX);
+
+        let mut sub = reader.split_off_front(length)?;
+        let pad = (self.align - length % self.align) % self.align;
+        reader.read_slice(pad.min(reader.available_bytes()))?;
+        reader.read_slice(self.trailer)?;
+
+        if let Some((_, handler)) = self.handlers.iter_mut().find(|(t, _)| *t == tag) {
+            return tag_section(&mut sub, tag, |sub| handler(sub)).map(Some);
+        }
+        match &mut self.unknown {
+            Some(handler) => tag_section(&mut sub, tag, |sub| handler(tag, sub)).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Runs `f` as a section named after `tag`. The name is only built when
+/// there is an event sink to emit it to.
+fn tag_section<'a, T, F>(
+    sub: &mut BinaryFileReader<'a>,
+    tag: FourCC,
+    f: F,
+) -> Result<T, BinaryFileReaderError>
+where
+    F: FnOnce(&mut BinaryFileReader<'a>) -> Result<T, BinaryFileReaderError>,
+{
+    #[cfg(feature = "std")]
+    if sub.events.is_some() {
+        use alloc::string::ToString;
+
+        return sub.section(&tag.to_string(), f);
+    }
+
+    #[cfg(not(feature = "std"))]
+    let _ = tag;
+    f(sub)
+}
+
+impl<T> fmt::Debug for ChunkDispatcher<'_, '_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChunkDispatcher")
+            .field("layout", &self.layout)
+            .field("trailer", &self.trailer)
+            .field("align", &self.align)
+            .field(
+                "handlers",
+                &self.handlers.iter().map(|(tag, _)| tag).collect::<Vec<_>>(),
+            )
+            .field("unknown", &self.unknown.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChunkDispatcher, ChunkLayout};
+    use crate::{endian::Endian, error::BinaryFileReaderError, fourcc::FourCC, BinaryFileReader};
+    use alloc::{vec, vec::Vec};
+
+    #[test]
+    fn test_riff_layout() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![
+            b'f', b'm', b't', b' ', 3, 0, 0, 0, 1, 2, 3, 0, //
+            b'L', b'I', b'S', b'T', 1, 0, 0, 0, 9, 0, //
+            b'd', b'a', b't', b'a', 1, 0, 0, 0, 7,
+        ];
+        let mut reader = BinaryFileReader::new(&buffer);
+        reader.set_endian(Endian::Little);
+
+        let mut unknown = Vec::new();
+        let mut dispatcher = ChunkDispatcher::new(ChunkLayout::TagLength)
+            .align(2)
+            .on(b"fmt ", |sub| Ok(sub.read_slice(3)?.to_vec()))
+            .on(b"data", |sub| Ok(vec![sub.read_u8()?]))
+            .on_unknown(|tag, _| {
+                unknown.push(tag);
+                Ok(Vec::new())
+            });
+
+        assert_eq!(dispatcher.dispatch(&mut reader)?, Some(vec![1, 2, 3]));
+        assert_eq!(dispatcher.dispatch(&mut reader)?, Some(vec![]));
+        assert_eq!(dispatcher.dispatch(&mut reader)?, Some(vec![7]));
+        assert_eq!(dispatcher.dispatch(&mut reader)?, None);
+        drop(dispatcher);
+        assert_eq!(unknown, vec![FourCC::from(b"LIST")]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dispatch_errors() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0, 0, 0, 1, b'a', b'b', b'c', b'd', 0x01, 0, 0, 0, 9];
+        let mut dispatcher =
+            ChunkDispatcher::new(ChunkLayout::LengthTag).on(b"abcd", |sub| sub.read_u16());
+
+        let mut reader = BinaryFileReader::new(&buffer);
+        assert!(matches!(
+            dispatcher.dispatch(&mut reader),
+            Err(BinaryFileReaderError::BufferUnderflow {
+                current_offset: 8,
+                ..
+            })
+        ));
+        assert_eq!(reader.current_offset(), 0);
+
+        let mut reader = BinaryFileReader::new(&buffer[9..]);
+        assert!(matches!(
+            dispatcher.dispatch(&mut reader),
+            Err(BinaryFileReaderError::BufferUnderflow {
+                requested_bytes: 4,
+                ..
+            })
+        ));
+        assert_eq!(reader.current_offset(), 0);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+use core::ops::Range;
+use std::sync::{Arc, Mutex};
+
+/// The byte ranges read by a reader and its sub-readers since
+/// [`enable_coverage`](crate::BinaryFileReader::enable_coverage).
+#[derive(Debug, Default)]
+pub(crate) struct Coverage {
+    region: Range<usize>,
+    // Read ranges in the order they were read. Back-to-back reads are merged.
+    reads: Vec<Range<usize>>,
+}
+
+pub(crate) type SharedCoverage = Arc<Mutex<Coverage>>;
+
+impl Coverage {
+    pub(crate) fn shared(region: Range<usize>) -> SharedCoverage {
+        Arc::new(Mutex::new(Self {
+            region,
+            reads: Vec::new(),
+        }))
+    }
+
+    pub(crate) fn record(&mut self, read: Range<usize>) {
+        if read.is_empty() {
+            return;
+        }
+        match self.reads.last_mut() {
+            Some(last) if last.end == read.start => last.end = read.end,
+            _ => self.reads.push(read),
+        }
+    }
+
+    pub(crate) fn report(&self) -> CoverageReport {
+        // Sweeps over the read boundaries, tracking how many reads cover the
+        // current byte.
+        let mut events = self
+            .reads
+            .iter()
+            .flat_map(|read| [(read.start, 1isize), (read.end, -1)])
+            .collect::<Vec<_>>();
+        events.sort_unstable();
+
+        let mut report = CoverageReport::default();
+        let mut depth = 0;
+        let mut position = self.region.start;
+        for (offset, change) in events.into_iter().chain([(self.region.end, 0)]) {
+            if offset > position {
+                let range = position..offset;
+                match depth {
+                    0 => push_range(&mut report.gaps, range),
+                    1 => {}
+                    _ => push_range(&mut report.overlaps, range),
+                }
+                position = offset;
+            }
+            depth += change;
+        }
+
+        report
+    }
+}
+
+fn push_range(ranges: &mut Vec<Range<usize>>, range: Range<usize>) {
+    match ranges.last_mut() {
+        Some(last) if last.end == range.start => last.end = range.end,
+        _ => ranges.push(range),
+    }
+}
+
+/// Which parts of a buffer were read, returned by
+/// [`coverage_report`](crate::BinaryFileReader::coverage_report). Ranges are
+/// absolute offsets, in order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoverageReport {
+    /// Ranges no read touched.
+    pub gaps: Vec<Range<usize>>,
+    /// Ranges read more than once.
+    pub overlaps: Vec<Range<usize>>,
+}
+
+impl CoverageReport {
+    /// Returns `true` if every byte was read exactly once.
+    pub fn is_exact(&self) -> bool {
+        self.gaps.is_empty() && self.overlaps.is_empty()
+    }
+
+    /// The number of bytes no read touched.
+    pub fn unread_bytes(&self) -> usize {
+        self.gaps.iter().map(|gap| gap.len()).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Coverage, CoverageReport};
+
+    #[test]
+    fn test_coverage_report() {
+        let mut coverage = Coverage {
+            region: 0..20,
+            reads: Vec::new(),
+        };
+        coverage.record(2..4);
+        coverage.record(4..6);
+        coverage.record(5..8);
+        coverage.record(9..9);
+        coverage.record(12..16);
+        coverage.record(12..14);
+        coverage.record(13..14);
+        assert_eq!(coverage.reads, vec![2..6, 5..8, 12..16, 12..14, 13..14]);
+
+        let report = coverage.report();
+        assert_eq!(
+            report,
+            CoverageReport {
+                gaps: vec![0..2, 8..12, 16..20],
+                overlaps: vec![5..6, 12..14],
+            }
+        );
+        assert_eq!(report.unread_bytes(), 10);
+        assert!(!report.is_exact());
+
+        let mut coverage = Coverage {
+            region: 4..8,
+            reads: Vec::new(),
+        };
+        coverage.record(4..8);
+        assert!(coverage.report().is_exact());
+    }
+}
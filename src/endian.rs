@@ -0,0 +1,41 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Endian {
+    #[default]
+    Big,
+    Little,
+}
+
+impl Endian {
+    #[cfg(target_endian = "big")]
+    pub const NATIVE: Endian = Endian::Big;
+    #[cfg(target_endian = "little")]
+    pub const NATIVE: Endian = Endian::Little;
+
+    pub(crate) fn u16_from_bytes(self, bytes: [u8; 2]) -> u16 {
+        match self {
+            Endian::Big => u16::from_be_bytes(bytes),
+            Endian::Little => u16::from_le_bytes(bytes),
+        }
+    }
+
+    pub(crate) fn u32_from_bytes(self, bytes: [u8; 4]) -> u32 {
+        match self {
+            Endian::Big => u32::from_be_bytes(bytes),
+            Endian::Little => u32::from_le_bytes(bytes),
+        }
+    }
+
+    pub(crate) fn u64_from_bytes(self, bytes: [u8; 8]) -> u64 {
+        match self {
+            Endian::Big => u64::from_be_bytes(bytes),
+            Endian::Little => u64::from_le_bytes(bytes),
+        }
+    }
+
+    pub(crate) fn u128_from_bytes(self, bytes: [u8; 16]) -> u128 {
+        match self {
+            Endian::Big => u128::from_be_bytes(bytes),
+            Endian::Little => u128::from_le_bytes(bytes),
+        }
+    }
+}
@@ -1,8 +1,12 @@
-use core::fmt;
-use std::{io, str::Utf8Error};
+use alloc::{borrow::Cow, boxed::Box, sync::Arc, vec::Vec};
+use core::{fmt, str::Utf8Error};
+#[cfg(feature = "std")]
+use std::io;
 
 #[derive(Debug)]
 pub enum BinaryFileReaderError {
+    /// Only available with the `std` feature.
+    #[cfg(feature = "std")]
     IO(io::Error),
     Utf8Error(Utf8Error),
 
@@ -13,43 +17,736 @@ pub enum BinaryFileReaderError {
     },
 
     ExpectInsufficientBytes {
-        require: Vec<u8>,
+        require: ErrorBytes,
         available_bytes: usize,
         current_offset: usize,
     },
 
     Expect {
-        require: Vec<u8>,
-        got: Vec<u8>,
+        require: ErrorBytes,
+        got: ErrorBytes,
         available_bytes: usize,
         current_offset: usize,
     },
 
+    ExpectMasked {
+        require: ErrorBytes,
+        mask: ErrorBytes,
+        got: ErrorBytes,
+        available_bytes: usize,
+        current_offset: usize,
+    },
+
+    /// Unlike the other `Expect` variants, this one allocates to hold the
+    /// candidates.
+    ExpectOneOf {
+        candidates: Vec<ErrorBytes>,
+        got: ErrorBytes,
+        available_bytes: usize,
+        current_offset: usize,
+    },
+
+    ExpectFilled {
+        require: u8,
+        got: u8,
+        offending_offset: usize,
+        current_offset: usize,
+    },
+
+    TrailingBytes {
+        remaining_bytes: usize,
+        current_offset: usize,
+    },
+
+    InvalidDiscriminant {
+        type_name: &'static str,
+        value: u64,
+        current_offset: usize,
+    },
+
+    Element {
+        index: usize,
+        source: Box<BinaryFileReaderError>,
+    },
+
+    /// An error raised inside [`BinaryFileReader::context`](crate::BinaryFileReader::context).
+    Context {
+        label: Cow<'static, str>,
+        source: Box<BinaryFileReaderError>,
+    },
+
+    /// An error raised inside
+    /// [`BinaryFileReader::split_off_front_labeled`](crate::BinaryFileReader::split_off_front_labeled).
+    /// `start` is the absolute offset of the section.
+    Section {
+        label: Cow<'static, str>,
+        start: usize,
+        length: usize,
+        source: Box<BinaryFileReaderError>,
+    },
+
+    /// An error raised inside [`BinaryFileReader::parse`](crate::BinaryFileReader::parse)
+    /// on a reader with a source name.
+    Named {
+        name: Arc<str>,
+        source: Box<BinaryFileReaderError>,
+    },
+
+    InvalidPresenceMarker {
+        got: u8,
+        absent: u8,
+        present: u8,
+        current_offset: usize,
+    },
+
+    UnsupportedVersion {
+        version: u64,
+        current_offset: usize,
+    },
+
+    /// A structure of a file format breaks the format's rules, such as a
+    /// length too small for the header it includes.
+    Malformed {
+        reason: &'static str,
+        current_offset: usize,
+    },
+
+    DepthExceeded {
+        max_depth: usize,
+        current_offset: usize,
+    },
+
+    AllocationLimit {
+        requested_bytes: usize,
+        max_alloc: usize,
+        current_offset: usize,
+    },
+
+    WindowExceeded {
+        requested_bytes: usize,
+        window_size: usize,
+    },
+
+    NeedMoreData {
+        missing: usize,
+    },
+
+    Cancelled {
+        current_offset: usize,
+    },
+
     OutOfRange {
         buffer_size: usize,
         got: usize,
     },
+
+    /// Compressed data is malformed. `current_offset` is near the byte the
+    /// problem was found in.
+    Decompress {
+        reason: &'static str,
+        current_offset: usize,
+    },
+
+    /// A checksum stored in the input does not match the one computed over
+    /// the bytes it covers. `current_offset` is the offset of the stored
+    /// checksum.
+    ChecksumMismatch {
+        stored: u64,
+        computed: u64,
+        current_offset: usize,
+    },
+
+    /// Text-encoded data, such as hex or base64, is malformed.
+    /// `current_offset` is the offset of the offending character, or the end
+    /// of the text.
+    InvalidEncoding {
+        encoding: &'static str,
+        reason: &'static str,
+        current_offset: usize,
+    },
+}
+
+/// The broad category of a [`BinaryFileReaderError`], returned by
+/// [`BinaryFileReaderError::kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// Only available with the `std` feature.
+    #[cfg(feature = "std")]
+    Io,
+    Utf8,
+    /// The input ended before the requested bytes, as with
+    /// [`BufferUnderflow`](BinaryFileReaderError::BufferUnderflow) and
+    /// [`NeedMoreData`](BinaryFileReaderError::NeedMoreData).
+    UnexpectedEof,
+    /// The bytes did not match an `expect` family call.
+    Mismatch,
+    TrailingBytes,
+    /// A value was read but is not valid, such as an unknown discriminant or
+    /// version.
+    InvalidValue,
+    /// A depth, allocation or window limit was reached.
+    LimitExceeded,
+    Cancelled,
+    OutOfRange,
+    ChecksumMismatch,
+}
+
+/// A stable numeric code for each kind of [`BinaryFileReaderError`], returned
+/// by [`BinaryFileReaderError::code`]. The numbers do not change between
+/// releases, so they can be stored or sent to other services. New codes may
+/// be added.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::BinaryFileReader;
+/// # use binary_file_reader::error::ErrorCode;
+/// let mut reader = BinaryFileReader::new(&[0x00]);
+/// let err = reader.read_u16().unwrap_err();
+/// assert_eq!(err.code(), ErrorCode::BufferUnderflow);
+/// assert_eq!(err.code().as_u16(), 10);
+/// assert_eq!(ErrorCode::from_u16(10), Some(ErrorCode::BufferUnderflow));
+/// assert_eq!(ErrorCode::from_u16(9), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+#[repr(u16)]
+pub enum ErrorCode {
+    Io = 1,
+    Utf8 = 2,
+    BufferUnderflow = 10,
+    ExpectInsufficientBytes = 11,
+    NeedMoreData = 12,
+    Expect = 20,
+    ExpectMasked = 21,
+    ExpectOneOf = 22,
+    ExpectFilled = 23,
+    TrailingBytes = 30,
+    InvalidDiscriminant = 40,
+    InvalidPresenceMarker = 41,
+    UnsupportedVersion = 42,
+    Malformed = 43,
+    DepthExceeded = 50,
+    AllocationLimit = 51,
+    WindowExceeded = 52,
+    Cancelled = 60,
+    OutOfRange = 70,
+    ChecksumMismatch = 80,
+    Decompress = 90,
+    InvalidEncoding = 91,
 }
 
+impl ErrorCode {
+    #[inline]
+    pub fn as_u16(self) -> u16 {
+        self as u16
+    }
+
+    /// Returns `None` for numbers that are not a known code.
+    pub fn from_u16(code: u16) -> Option<Self> {
+        Some(match code {
+            1 => Self::Io,
+            2 => Self::Utf8,
+            10 => Self::BufferUnderflow,
+            11 => Self::ExpectInsufficientBytes,
+            12 => Self::NeedMoreData,
+            20 => Self::Expect,
+            21 => Self::ExpectMasked,
+            22 => Self::ExpectOneOf,
+            23 => Self::ExpectFilled,
+            30 => Self::TrailingBytes,
+            40 => Self::InvalidDiscriminant,
+            41 => Self::InvalidPresenceMarker,
+            42 => Self::UnsupportedVersion,
+            43 => Self::Malformed,
+            50 => Self::DepthExceeded,
+            51 => Self::AllocationLimit,
+            52 => Self::WindowExceeded,
+            60 => Self::Cancelled,
+            70 => Self::OutOfRange,
+            80 => Self::ChecksumMismatch,
+            90 => Self::Decompress,
+            91 => Self::InvalidEncoding,
+            _ => return None,
+        })
+    }
+}
+
+impl BinaryFileReaderError {
+    /// A [`Malformed`](Self::Malformed) error with `reason` at `current_offset`.
+    pub(crate) fn malformed(reason: &'static str, current_offset: usize) -> Self {
+        BinaryFileReaderError::Malformed {
+            reason,
+            current_offset,
+        }
+    }
+
+    /// The error inside any [`Element`](Self::Element),
+    /// [`Context`](Self::Context), [`Section`](Self::Section) and
+    /// [`Named`](Self::Named) wrappers.
+    fn root(&self) -> &Self {
+        match self {
+            BinaryFileReaderError::Element { source, .. }
+            | BinaryFileReaderError::Context { source, .. }
+            | BinaryFileReaderError::Section { source, .. }
+            | BinaryFileReaderError::Named { source, .. } => source.root(),
+            err => err,
+        }
+    }
+
+    /// The category of the error, looking through the
+    /// [`Element`](Self::Element), [`Context`](Self::Context),
+    /// [`Section`](Self::Section) and [`Named`](Self::Named) wrappers.
+    ///
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # use binary_file_reader::error::ErrorKind;
+    /// let buffer = vec![0x00, 0x01];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    ///
+    /// let err = reader.context("header", |r| r.read_u32()).unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    /// assert_eq!(err.offset(), Some(0));
+    /// assert_eq!(err.requested(), Some(4));
+    /// assert_eq!(err.available(), Some(2));
+    ///
+    /// let err = reader.expect(b"\x00\x02").unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::Mismatch);
+    /// assert_eq!(err.requested(), None);
+    /// ```
+    pub fn kind(&self) -> ErrorKind {
+        match self.root() {
+            #[cfg(feature = "std")]
+            BinaryFileReaderError::IO(_) => ErrorKind::Io,
+            BinaryFileReaderError::Utf8Error(_) => ErrorKind::Utf8,
+            BinaryFileReaderError::BufferUnderflow { .. }
+            | BinaryFileReaderError::ExpectInsufficientBytes { .. }
+            | BinaryFileReaderError::NeedMoreData { .. } => ErrorKind::UnexpectedEof,
+            BinaryFileReaderError::Expect { .. }
+            | BinaryFileReaderError::ExpectMasked { .. }
+            | BinaryFileReaderError::ExpectOneOf { .. }
+            | BinaryFileReaderError::ExpectFilled { .. } => ErrorKind::Mismatch,
+            BinaryFileReaderError::TrailingBytes { .. } => ErrorKind::TrailingBytes,
+            BinaryFileReaderError::InvalidDiscriminant { .. }
+            | BinaryFileReaderError::InvalidPresenceMarker { .. }
+            | BinaryFileReaderError::UnsupportedVersion { .. }
+            | BinaryFileReaderError::Malformed { .. }
+            | BinaryFileReaderError::Decompress { .. }
+            | BinaryFileReaderError::InvalidEncoding { .. } => ErrorKind::InvalidValue,
+            BinaryFileReaderError::DepthExceeded { .. }
+            | BinaryFileReaderError::AllocationLimit { .. }
+            | BinaryFileReaderError::WindowExceeded { .. } => ErrorKind::LimitExceeded,
+            BinaryFileReaderError::Cancelled { .. } => ErrorKind::Cancelled,
+            BinaryFileReaderError::OutOfRange { .. } => ErrorKind::OutOfRange,
+            BinaryFileReaderError::ChecksumMismatch { .. } => ErrorKind::ChecksumMismatch,
+            BinaryFileReaderError::Element { .. }
+            | BinaryFileReaderError::Context { .. }
+            | BinaryFileReaderError::Section { .. }
+            | BinaryFileReaderError::Named { .. } => unreachable!("root() looks through wrappers"),
+        }
+    }
+
+    /// The code of the error, looking through the same
+    /// [`Element`](Self::Element), [`Context`](Self::Context),
+    /// [`Section`](Self::Section) and [`Named`](Self::Named) wrappers as
+    /// [`kind`](Self::kind).
+    pub fn code(&self) -> ErrorCode {
+        match self.root() {
+            #[cfg(feature = "std")]
+            BinaryFileReaderError::IO(_) => ErrorCode::Io,
+            BinaryFileReaderError::Utf8Error(_) => ErrorCode::Utf8,
+            BinaryFileReaderError::BufferUnderflow { .. } => ErrorCode::BufferUnderflow,
+            BinaryFileReaderError::ExpectInsufficientBytes { .. } => {
+                ErrorCode::ExpectInsufficientBytes
+            }
+            BinaryFileReaderError::NeedMoreData { .. } => ErrorCode::NeedMoreData,
+            BinaryFileReaderError::Expect { .. } => ErrorCode::Expect,
+            BinaryFileReaderError::ExpectMasked { .. } => ErrorCode::ExpectMasked,
+            BinaryFileReaderError::ExpectOneOf { .. } => ErrorCode::ExpectOneOf,
+            BinaryFileReaderError::ExpectFilled { .. } => ErrorCode::ExpectFilled,
+            BinaryFileReaderError::TrailingBytes { .. } => ErrorCode::TrailingBytes,
+            BinaryFileReaderError::InvalidDiscriminant { .. } => ErrorCode::InvalidDiscriminant,
+            BinaryFileReaderError::InvalidPresenceMarker { .. } => ErrorCode::InvalidPresenceMarker,
+            BinaryFileReaderError::UnsupportedVersion { .. } => ErrorCode::UnsupportedVersion,
+            BinaryFileReaderError::Malformed { .. } => ErrorCode::Malformed,
+            BinaryFileReaderError::DepthExceeded { .. } => ErrorCode::DepthExceeded,
+            BinaryFileReaderError::AllocationLimit { .. } => ErrorCode::AllocationLimit,
+            BinaryFileReaderError::WindowExceeded { .. } => ErrorCode::WindowExceeded,
+            BinaryFileReaderError::Cancelled { .. } => ErrorCode::Cancelled,
+            BinaryFileReaderError::OutOfRange { .. } => ErrorCode::OutOfRange,
+            BinaryFileReaderError::ChecksumMismatch { .. } => ErrorCode::ChecksumMismatch,
+            BinaryFileReaderError::Decompress { .. } => ErrorCode::Decompress,
+            BinaryFileReaderError::InvalidEncoding { .. } => ErrorCode::InvalidEncoding,
+            BinaryFileReaderError::Element { .. }
+            | BinaryFileReaderError::Context { .. }
+            | BinaryFileReaderError::Section { .. }
+            | BinaryFileReaderError::Named { .. } => unreachable!("root() looks through wrappers"),
+        }
+    }
+
+    /// The offset the error was raised at, if it has one. For
+    /// [`ExpectFilled`](Self::ExpectFilled) this is the offending byte.
+    pub fn offset(&self) -> Option<usize> {
+        match self.root() {
+            BinaryFileReaderError::BufferUnderflow { current_offset, .. }
+            | BinaryFileReaderError::ExpectInsufficientBytes { current_offset, .. }
+            | BinaryFileReaderError::Expect { current_offset, .. }
+            | BinaryFileReaderError::ExpectMasked { current_offset, .. }
+            | BinaryFileReaderError::ExpectOneOf { current_offset, .. }
+            | BinaryFileReaderError::TrailingBytes { current_offset, .. }
+            | BinaryFileReaderError::InvalidDiscriminant { current_offset, .. }
+            | BinaryFileReaderError::InvalidPresenceMarker { current_offset, .. }
+            | BinaryFileReaderError::UnsupportedVersion { current_offset, .. }
+            | BinaryFileReaderError::Malformed { current_offset, .. }
+            | BinaryFileReaderError::DepthExceeded { current_offset, .. }
+            | BinaryFileReaderError::AllocationLimit { current_offset, .. }
+            | BinaryFileReaderError::ChecksumMismatch { current_offset, .. }
+            | BinaryFileReaderError::Decompress { current_offset, .. }
+            | BinaryFileReaderError::InvalidEncoding { current_offset, .. }
+            | BinaryFileReaderError::Cancelled { current_offset } => Some(*current_offset),
+            BinaryFileReaderError::ExpectFilled {
+                offending_offset, ..
+            } => Some(*offending_offset),
+            _ => None,
+        }
+    }
+
+    /// The number of bytes the failing call asked for, if it has one.
+    pub fn requested(&self) -> Option<usize> {
+        match self.root() {
+            BinaryFileReaderError::BufferUnderflow {
+                requested_bytes, ..
+            }
+            | BinaryFileReaderError::AllocationLimit {
+                requested_bytes, ..
+            }
+            | BinaryFileReaderError::WindowExceeded {
+                requested_bytes, ..
+            } => Some(*requested_bytes),
+            BinaryFileReaderError::ExpectInsufficientBytes { require, .. } => {
+                Some(require.total_len())
+            }
+            _ => None,
+        }
+    }
+
+    /// The number of bytes that were left when the error was raised, if it
+    /// records them.
+    pub fn available(&self) -> Option<usize> {
+        match self.root() {
+            BinaryFileReaderError::BufferUnderflow {
+                available_bytes, ..
+            }
+            | BinaryFileReaderError::ExpectInsufficientBytes {
+                available_bytes, ..
+            }
+            | BinaryFileReaderError::Expect {
+                available_bytes, ..
+            }
+            | BinaryFileReaderError::ExpectMasked {
+                available_bytes, ..
+            }
+            | BinaryFileReaderError::ExpectOneOf {
+                available_bytes, ..
+            } => Some(*available_bytes),
+            _ => None,
+        }
+    }
+
+    /// The labels added with
+    /// [`BinaryFileReader::context`](crate::BinaryFileReader::context) and
+    /// [`split_off_front_labeled`](crate::BinaryFileReader::split_off_front_labeled),
+    /// outermost first. Labels inside [`Element`](Self::Element) errors are included.
+    ///
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// let buffer = vec![0x00, 0x01, 0x00];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    ///
+    /// let err = reader
+    ///     .context("header", |r| r.context("version", |r| r.read_u32()))
+    ///     .unwrap_err();
+    /// assert_eq!(err.contexts(), ["header", "version"]);
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "header: version: Buffer underflow: requested 4 bytes at offset 0x0 (0), but only 3 bytes are available"
+    /// );
+    /// ```
+    pub fn contexts(&self) -> Vec<&str> {
+        let mut labels = Vec::new();
+        let mut err = self;
+        loop {
+            match err {
+                BinaryFileReaderError::Context { label, source }
+                | BinaryFileReaderError::Section { label, source, .. } => {
+                    labels.push(label.as_ref());
+                    err = source;
+                }
+                BinaryFileReaderError::Element { source, .. }
+                | BinaryFileReaderError::Named { source, .. } => err = source,
+                _ => return labels,
+            }
+        }
+    }
+
+    /// The name of the input the error was raised in, if it was raised inside
+    /// [`BinaryFileReader::parse`](crate::BinaryFileReader::parse) on a named
+    /// reader.
+    pub fn source_name(&self) -> Option<&str> {
+        match self {
+            BinaryFileReaderError::Named { name, .. } => Some(name),
+            BinaryFileReaderError::Element { source, .. }
+            | BinaryFileReaderError::Context { source, .. }
+            | BinaryFileReaderError::Section { source, .. } => source.source_name(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
 impl From<io::Error> for BinaryFileReaderError {
     fn from(value: io::Error) -> Self {
         Self::IO(value)
     }
 }
 
-impl std::error::Error for BinaryFileReaderError {
-    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+/// Maps underflows to [`io::ErrorKind::UnexpectedEof`] and malformed input to
+/// [`io::ErrorKind::InvalidData`], keeping the error as the source. An
+/// [`IO`](BinaryFileReaderError::IO) error is unwrapped back into the original.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::BinaryFileReader;
+/// # use std::io;
+/// fn magic(buffer: &[u8]) -> io::Result<u32> {
+///     let mut reader = BinaryFileReader::new(buffer);
+///     reader.expect(b"MAGC")?;
+///     Ok(reader.read_u32()?)
+/// }
+///
+/// assert_eq!(magic(b"MAGC\x00\x00\x00\x01").unwrap(), 1);
+/// assert_eq!(magic(b"MAGC").unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+/// assert_eq!(magic(b"ELF!").unwrap_err().kind(), io::ErrorKind::InvalidData);
+/// ```
+#[cfg(feature = "std")]
+impl From<BinaryFileReaderError> for io::Error {
+    fn from(value: BinaryFileReaderError) -> Self {
+        let kind = match value.root() {
+            BinaryFileReaderError::IO(err) => err.kind(),
+            _ => match value.kind() {
+                ErrorKind::UnexpectedEof => io::ErrorKind::UnexpectedEof,
+                ErrorKind::OutOfRange => io::ErrorKind::InvalidInput,
+                ErrorKind::Cancelled => io::ErrorKind::Other,
+                _ => io::ErrorKind::InvalidData,
+            },
+        };
+        match value {
+            BinaryFileReaderError::IO(err) => err,
+            value => io::Error::new(kind, value),
+        }
+    }
+}
+
+impl core::error::Error for BinaryFileReaderError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
         match self {
             BinaryFileReaderError::Utf8Error(err) => Some(err),
+            #[cfg(feature = "std")]
             BinaryFileReaderError::IO(err) => Some(err),
             BinaryFileReaderError::BufferUnderflow { .. } => None,
             BinaryFileReaderError::ExpectInsufficientBytes { .. } => None,
             BinaryFileReaderError::Expect { .. } => None,
+            BinaryFileReaderError::ExpectMasked { .. } => None,
+            BinaryFileReaderError::ExpectOneOf { .. } => None,
+            BinaryFileReaderError::ExpectFilled { .. } => None,
+            BinaryFileReaderError::TrailingBytes { .. } => None,
+            BinaryFileReaderError::InvalidDiscriminant { .. } => None,
+            BinaryFileReaderError::Element { source, .. } => Some(source.as_ref()),
+            BinaryFileReaderError::Context { source, .. } => Some(source.as_ref()),
+            BinaryFileReaderError::Section { source, .. } => Some(source.as_ref()),
+            BinaryFileReaderError::Named { source, .. } => Some(source.as_ref()),
+            BinaryFileReaderError::InvalidPresenceMarker { .. } => None,
+            BinaryFileReaderError::UnsupportedVersion { .. } => None,
+            BinaryFileReaderError::Malformed { .. } => None,
+            BinaryFileReaderError::DepthExceeded { .. } => None,
+            BinaryFileReaderError::AllocationLimit { .. } => None,
+            BinaryFileReaderError::WindowExceeded { .. } => None,
+            BinaryFileReaderError::NeedMoreData { .. } => None,
+            BinaryFileReaderError::Cancelled { .. } => None,
             BinaryFileReaderError::OutOfRange { .. } => None,
+            BinaryFileReaderError::ChecksumMismatch { .. } => None,
+            BinaryFileReaderError::Decompress { .. } => None,
+            BinaryFileReaderError::InvalidEncoding { .. } => None,
+        }
+    }
+}
+
+/// Bytes captured in an error. Only the first [`CAPACITY`](Self::CAPACITY)
+/// bytes are kept, inline, so building the error never allocates. Readers can
+/// keep fewer with
+/// [`set_max_error_bytes`](crate::BinaryFileReader::set_max_error_bytes).
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::BinaryFileReader;
+/// # use binary_file_reader::error::{BinaryFileReaderError, ErrorBytes};
+/// let signature = [0xaa; 100];
+/// let mut reader = BinaryFileReader::new(&[0; 100]);
+///
+/// match reader.expect(&signature) {
+///     Err(BinaryFileReaderError::Expect { require, got, .. }) => {
+///         assert_eq!(require.total_len(), 100);
+///         assert_eq!(require.len(), ErrorBytes::CAPACITY);
+///         assert!(require.is_truncated());
+///         assert_eq!(got[0], 0);
+///     }
+///     _ => unreachable!(),
+/// }
+/// assert_eq!(ErrorBytes::new(&[1, 2]), [1, 2]);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ErrorBytes {
+    bytes: [u8; ErrorBytes::CAPACITY],
+    captured: u8,
+    total_len: usize,
+}
+
+impl ErrorBytes {
+    /// The number of bytes kept.
+    pub const CAPACITY: usize = 16;
+
+    pub fn new(bytes: &[u8]) -> Self {
+        Self::with_cap(bytes, Self::CAPACITY)
+    }
+
+    /// Keeps at most `cap` bytes, and never more than
+    /// [`CAPACITY`](Self::CAPACITY).
+    pub fn with_cap(bytes: &[u8], cap: usize) -> Self {
+        let captured = bytes.len().min(cap).min(Self::CAPACITY);
+        let mut inline = [0; Self::CAPACITY];
+        inline[..captured].copy_from_slice(&bytes[..captured]);
+        Self {
+            bytes: inline,
+            captured: captured as u8,
+            total_len: bytes.len(),
+        }
+    }
+
+    /// The kept bytes.
+    #[inline]
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bytes[..self.captured as usize]
+    }
+
+    /// The length of the original bytes.
+    #[inline]
+    pub fn total_len(&self) -> usize {
+        self.total_len
+    }
+
+    /// Returns `true` if some of the original bytes were dropped.
+    #[inline]
+    pub fn is_truncated(&self) -> bool {
+        self.total_len > self.captured as usize
+    }
+}
+
+impl core::ops::Deref for ErrorBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl From<&[u8]> for ErrorBytes {
+    fn from(value: &[u8]) -> Self {
+        Self::new(value)
+    }
+}
+
+impl PartialEq<[u8]> for ErrorBytes {
+    fn eq(&self, other: &[u8]) -> bool {
+        self.total_len == other.len() && self.as_slice() == other
+    }
+}
+
+impl<const N: usize> PartialEq<[u8; N]> for ErrorBytes {
+    fn eq(&self, other: &[u8; N]) -> bool {
+        *self == other[..]
+    }
+}
+
+impl fmt::Debug for ErrorBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_truncated() {
+            f.debug_list()
+                .entries(self.as_slice())
+                .finish_non_exhaustive()?;
+            write!(f, " ({} bytes)", self.total_len)
+        } else {
+            f.debug_list().entries(self.as_slice()).finish()
         }
     }
 }
 
+/// Formats the bytes in hex, the way a hex editor shows them.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::error::ErrorBytes;
+/// assert_eq!(ErrorBytes::new(b"\x89PNG").to_string(), "89 50 4E 47");
+/// assert_eq!(
+///     ErrorBytes::new(&[0xff; 20]).to_string(),
+///     "FF FF FF FF FF FF FF FF FF FF FF FF FF FF FF FF .. (20 bytes)"
+/// );
+/// ```
+impl fmt::Display for ErrorBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_hex(f, self.as_slice())?;
+        if self.is_truncated() {
+            write!(f, " .. ({} bytes)", self.total_len)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_hex(f: &mut fmt::Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+    for (index, byte) in bytes.iter().enumerate() {
+        if index > 0 {
+            f.write_str(" ")?;
+        }
+        write!(f, "{:02X}", byte)?;
+    }
+    Ok(())
+}
+
+/// Displays the candidates of [`BinaryFileReaderError::ExpectOneOf`] in hex.
+struct Candidates<'a>(&'a [ErrorBytes]);
+
+impl fmt::Display for Candidates<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, candidate) in self.0.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}", candidate)?;
+        }
+        Ok(())
+    }
+}
+
+/// Displays an offset in hex with the decimal value alongside.
+pub(crate) struct Offset(pub(crate) usize);
+
+impl fmt::Display for Offset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#x} ({})", self.0, self.0)
+    }
+}
+
 impl From<Utf8Error> for BinaryFileReaderError {
     fn from(value: Utf8Error) -> Self {
         Self::Utf8Error(value)
@@ -57,9 +754,10 @@ impl From<Utf8Error> for BinaryFileReaderError {
 }
 
 impl fmt::Display for BinaryFileReaderError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             BinaryFileReaderError::Utf8Error(err) => write!(f, "Utf8Error: {}", err),
+            #[cfg(feature = "std")]
             BinaryFileReaderError::IO(err) => write!(f, "IO error: {}", err),
             BinaryFileReaderError::BufferUnderflow {
                 requested_bytes,
@@ -68,7 +766,7 @@ impl fmt::Display for BinaryFileReaderError {
             } => write!(
                 f,
                 "Buffer underflow: requested {} bytes at offset {}, but only {} bytes are available",
-                requested_bytes, current_offset, available_bytes
+                requested_bytes, Offset(*current_offset), available_bytes
             ),
             BinaryFileReaderError::ExpectInsufficientBytes {
                 require,
@@ -76,8 +774,8 @@ impl fmt::Display for BinaryFileReaderError {
                 current_offset,
             } => write!(
                 f,
-                "Expectation failed: required {:?}, but only {} bytes are available at offset {}",
-                require, available_bytes, current_offset
+                "Expectation failed: required {}, but only {} bytes are available at offset {}",
+                require, available_bytes, Offset(*current_offset)
             ),
             BinaryFileReaderError::Expect {
                 require,
@@ -86,14 +784,162 @@ impl fmt::Display for BinaryFileReaderError {
                 current_offset,
             } => write!(
                 f,
-                "Expectation failed: required {:?}, got {:?}, available bytes: {}, offset: {}",
-                require, got, available_bytes, current_offset
+                "Expectation failed: required {}, got {}, available bytes: {}, offset: {}",
+                require, got, available_bytes, Offset(*current_offset)
+            ),
+            BinaryFileReaderError::ExpectMasked {
+                require,
+                mask,
+                got,
+                available_bytes,
+                current_offset,
+            } => write!(
+                f,
+                "Expectation failed: required {} with mask {}, got {}, available bytes: {}, offset: {}",
+                require, mask, got, available_bytes, Offset(*current_offset)
+            ),
+            BinaryFileReaderError::ExpectOneOf {
+                candidates,
+                got,
+                available_bytes,
+                current_offset,
+            } => write!(
+                f,
+                "Expectation failed: required one of [{}], got {}, available bytes: {}, offset: {}",
+                Candidates(candidates), got, available_bytes, Offset(*current_offset)
             ),
+            BinaryFileReaderError::ExpectFilled {
+                require,
+                got,
+                offending_offset,
+                current_offset,
+            } => write!(
+                f,
+                "Expectation failed: required bytes filled with {:#04x} from offset {}, got {:#04x} at offset {}",
+                require, Offset(*current_offset), got, Offset(*offending_offset)
+            ),
+            BinaryFileReaderError::TrailingBytes {
+                remaining_bytes,
+                current_offset,
+            } => write!(
+                f,
+                "Trailing bytes: {} bytes were left unread at offset {}",
+                remaining_bytes, Offset(*current_offset)
+            ),
+            BinaryFileReaderError::InvalidDiscriminant {
+                type_name,
+                value,
+                current_offset,
+            } => write!(
+                f,
+                "Invalid discriminant: {} is not a valid {} at offset {}",
+                value, type_name, Offset(*current_offset)
+            ),
+            BinaryFileReaderError::Element { index, source } => {
+                write!(f, "Failed to read element {}: {}", index, source)
+            }
+            BinaryFileReaderError::Context { label, source } => write!(f, "{}: {}", label, source),
+            BinaryFileReaderError::Section {
+                label,
+                start,
+                length,
+                source,
+            } => write!(
+                f,
+                "{} at {}, {} bytes: {}",
+                label,
+                Offset(*start),
+                length,
+                source
+            ),
+            BinaryFileReaderError::Named { name, source } => write!(f, "{}: {}", name, source),
+            BinaryFileReaderError::InvalidPresenceMarker {
+                got,
+                absent,
+                present,
+                current_offset,
+            } => write!(
+                f,
+                "Invalid presence marker: expected {:#04x} or {:#04x}, got {:#04x} at offset {}",
+                absent, present, got, Offset(*current_offset)
+            ),
+            BinaryFileReaderError::UnsupportedVersion {
+                version,
+                current_offset,
+            } => write!(
+                f,
+                "Unsupported version {} at offset {}",
+                version, Offset(*current_offset)
+            ),
+            BinaryFileReaderError::Malformed {
+                reason,
+                current_offset,
+            } => write!(f, "Malformed data: {} at offset {}", reason, Offset(*current_offset)),
+            BinaryFileReaderError::DepthExceeded {
+                max_depth,
+                current_offset,
+            } => write!(
+                f,
+                "Nesting depth exceeded: more than {} levels at offset {}",
+                max_depth, Offset(*current_offset)
+            ),
+            BinaryFileReaderError::AllocationLimit {
+                requested_bytes,
+                max_alloc,
+                current_offset,
+            } => write!(
+                f,
+                "Allocation limit exceeded: {} bytes requested at offset {}, but the limit is {}",
+                requested_bytes, Offset(*current_offset), max_alloc
+            ),
+            BinaryFileReaderError::WindowExceeded {
+                requested_bytes,
+                window_size,
+            } => write!(
+                f,
+                "Window exceeded: cannot peek {} bytes through a window of {} bytes",
+                requested_bytes, window_size
+            ),
+            BinaryFileReaderError::NeedMoreData { missing } => write!(
+                f,
+                "Need more data: at least {} more bytes are required",
+                missing
+            ),
+            BinaryFileReaderError::Cancelled { current_offset } => {
+                write!(f, "Cancelled at offset {}", Offset(*current_offset))
+            }
             BinaryFileReaderError::OutOfRange { buffer_size, got } => write!(
                 f,
                 "Out of range error: attempted to access index {} in a buffer of size {}",
-                got, buffer_size
-            )
+                Offset(*got),
+                Offset(*buffer_size)
+            ),
+            BinaryFileReaderError::ChecksumMismatch {
+                stored,
+                computed,
+                current_offset,
+            } => write!(
+                f,
+                "Checksum mismatch: stored {:#x}, but the data checksums to {:#x}, at offset {}",
+                stored, computed, Offset(*current_offset)
+            ),
+            BinaryFileReaderError::Decompress {
+                reason,
+                current_offset,
+            } => write!(
+                f,
+                "Invalid compressed data: {} at offset {}",
+                reason, Offset(*current_offset)
+            ),
+            BinaryFileReaderError::InvalidEncoding {
+                encoding,
+                reason,
+                current_offset,
+            } => write!(
+                f,
+                "Invalid {} text: {} at offset {}",
+                encoding, reason, Offset(*current_offset)
+            ),
         }
     }
 }
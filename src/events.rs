@@ -0,0 +1,76 @@
+use core::fmt;
+use std::sync::{Arc, Mutex};
+
+pub(crate) type SharedSink = Arc<Mutex<Box<dyn FnMut(&ParseEvent<'_>) + Send>>>;
+
+/// An event emitted by [`section`](crate::BinaryFileReader::section),
+/// [`field`](crate::BinaryFileReader::field) and the
+/// [`ChunkDispatcher`](crate::chunk::ChunkDispatcher) to the sink set with
+/// [`set_event_sink`](crate::BinaryFileReader::set_event_sink). Offsets are
+/// absolute, and every `SectionStart` is matched by a `SectionEnd`, even if
+/// the section failed.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::BinaryFileReader;
+/// # use binary_file_reader::events::ParseEvent;
+/// # use std::sync::mpsc;
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// let buffer = vec![0x00, 0x00, 0x01, 0x00, 0x08];
+/// let mut reader = BinaryFileReader::new(&buffer);
+///
+/// let (sender, receiver) = mpsc::channel();
+/// reader.set_event_sink(move |event| {
+///     let line = match event {
+///         ParseEvent::SectionStart { name, .. } => format!("{} {{", name),
+///         ParseEvent::Field { name, offset, value, .. } => {
+///             format!("  {:#04x} {} = {:?}", offset, name, value)
+///         }
+///         ParseEvent::SectionEnd { .. } => "}".into(),
+///     };
+///     sender.send(line).unwrap();
+/// });
+///
+/// reader.section("IHDR", |r| {
+///     r.field("width", |r| r.read_u32())?;
+///     r.field("bit_depth", |r| r.read_u8())
+/// })?;
+///
+/// let lines = receiver.try_iter().collect::<Vec<_>>();
+/// assert_eq!(lines, ["IHDR {", "  0x00 width = 256", "  0x04 bit_depth = 8", "}"]);
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub enum ParseEvent<'e> {
+    SectionStart {
+        name: &'e str,
+        offset: usize,
+    },
+    /// A value read successfully. Failed reads emit nothing.
+    Field {
+        name: &'e str,
+        offset: usize,
+        length: usize,
+        value: &'e dyn fmt::Debug,
+    },
+    SectionEnd {
+        name: &'e str,
+        offset: usize,
+        /// The number of bytes the section consumed.
+        length: usize,
+        /// `false` if the section failed.
+        ok: bool,
+    },
+}
+
+pub(crate) fn shared<F>(sink: F) -> SharedSink
+where
+    F: FnMut(&ParseEvent<'_>) + Send + 'static,
+{
+    Arc::new(Mutex::new(Box::new(sink)))
+}
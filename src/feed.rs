@@ -0,0 +1,212 @@
+use crate::{endian::Endian, error::BinaryFileReaderError, BinaryFileReader};
+use alloc::vec::Vec;
+
+/// A push-style reader for data that arrives in pieces, such as frames over a
+/// socket. Bytes are appended with [`feed`](Self::feed) and parsed with
+/// [`parse`](Self::parse), which either consumes a whole record or leaves the
+/// buffered bytes untouched so the record can be retried once more data has
+/// arrived.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::error::BinaryFileReaderError;
+/// # use binary_file_reader::feed::PushReader;
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// fn frame(reader: &mut PushReader) -> Result<Vec<u8>, BinaryFileReaderError> {
+///     reader.parse(|r| {
+///         let length = r.read_u16()? as usize;
+///         Ok(r.read_slice(length)?.to_vec())
+///     })
+/// }
+///
+/// let mut reader = PushReader::new();
+/// reader.feed(&[0x00, 0x03, b'a']);
+/// assert!(matches!(
+///     frame(&mut reader),
+///     Err(BinaryFileReaderError::NeedMoreData { missing: 2 })
+/// ));
+///
+/// reader.feed(&[b'b', b'c', 0x00]);
+/// assert_eq!(frame(&mut reader)?, b"abc");
+/// assert_eq!(reader.buffered(), &[0x00]);
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PushReader {
+    buf: Vec<u8>,
+    // The number of bytes at the front of `buf` that are already parsed.
+    start: usize,
+    offset: usize,
+    endian: Endian,
+}
+
+impl PushReader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends newly arrived bytes.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        if self.start > 0 {
+            self.buf.drain(..self.start);
+            self.start = 0;
+        }
+        self.buf.extend_from_slice(bytes);
+    }
+
+    /// The bytes fed but not parsed yet.
+    #[inline]
+    pub fn buffered(&self) -> &[u8] {
+        &self.buf[self.start..]
+    }
+
+    /// The number of bytes parsed so far.
+    #[inline]
+    pub fn current_offset(&self) -> usize {
+        self.offset
+    }
+
+    #[inline]
+    pub fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    #[inline]
+    pub fn set_endian(&mut self, endian: Endian) {
+        self.endian = endian;
+    }
+
+    /// Runs `f` over the buffered bytes and consumes what it read if it
+    /// succeeds. If `f` runs past the end of the buffered bytes, returns
+    /// [`NeedMoreData`](BinaryFileReaderError::NeedMoreData) instead of the
+    /// underflow. On any error nothing is consumed. Offsets seen by `f` are
+    /// relative to the first buffered byte.
+    pub fn parse<T, F>(&mut self, f: F) -> Result<T, BinaryFileReaderError>
+    where
+        F: FnOnce(&mut BinaryFileReader) -> Result<T, BinaryFileReaderError>,
+    {
+        let buffered = &self.buf[self.start..];
+        let mut reader = BinaryFileReader::new(buffered);
+        reader.set_endian(self.endian);
+
+        match f(&mut reader) {
+            Ok(value) => {
+                let read = reader.current_offset();
+                self.start += read;
+                self.offset += read;
+                Ok(value)
+            }
+            Err(err) => Err(match missing(&err, buffered.len()) {
+                Some(missing) => BinaryFileReaderError::NeedMoreData { missing },
+                None => err,
+            }),
+        }
+    }
+}
+
+/// The number of bytes past `buffered` that `err` asked for, if it is a
+/// shortage at the end of the buffered bytes.
+fn missing(err: &BinaryFileReaderError, buffered: usize) -> Option<usize> {
+    let end = match err {
+        BinaryFileReaderError::BufferUnderflow {
+            requested_bytes,
+            current_offset,
+            ..
+        } => current_offset.checked_add(*requested_bytes),
+        BinaryFileReaderError::ExpectInsufficientBytes {
+            require,
+            current_offset,
+            ..
+        } => current_offset.checked_add(require.total_len()),
+        BinaryFileReaderError::Element { source, .. }
+        | BinaryFileReaderError::Context { source, .. }
+        | BinaryFileReaderError::Section { source, .. }
+        | BinaryFileReaderError::Named { source, .. } => return missing(source, buffered),
+        _ => None,
+    }?;
+
+    end.checked_sub(buffered).filter(|&missing| missing > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PushReader;
+    use crate::{
+        endian::Endian,
+        error::{BinaryFileReaderError, ErrorBytes},
+    };
+
+    #[test]
+    fn test_push_reader() -> Result<(), BinaryFileReaderError> {
+        let mut reader = PushReader::new();
+        reader.set_endian(Endian::Little);
+        assert!(matches!(
+            reader.parse(|r| r.read_u32()),
+            Err(BinaryFileReaderError::NeedMoreData { missing: 4 })
+        ));
+
+        for byte in [0x01, 0x00, 0x00] {
+            reader.feed(&[byte]);
+            assert!(matches!(
+                reader.parse(|r| r.read_u32()),
+                Err(BinaryFileReaderError::NeedMoreData { .. })
+            ));
+        }
+        reader.feed(&[0x00, b'O', b'K']);
+        assert_eq!(reader.parse(|r| r.read_u32())?, 1);
+        assert_eq!(reader.current_offset(), 4);
+
+        assert!(matches!(
+            reader.parse(|r| r.expect(b"OK!")),
+            Err(BinaryFileReaderError::NeedMoreData { missing: 1 })
+        ));
+        assert!(matches!(
+            reader.parse(|r| r.expect(b"NO")),
+            Err(BinaryFileReaderError::Expect {
+                current_offset: 0,
+                ..
+            })
+        ));
+
+        // An underflow inside a complete record is a real error.
+        reader.feed(&[0x00]);
+        assert!(matches!(
+            reader.parse(|r| r.split_off_front(1)?.read_u16()),
+            Err(BinaryFileReaderError::BufferUnderflow { .. })
+        ));
+        assert!(matches!(
+            reader.parse(|r| r.repeat(|r| r.read_u16())),
+            Err(BinaryFileReaderError::NeedMoreData { missing: 1 })
+        ));
+        assert_eq!(reader.buffered(), b"OK\x00");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_long_magic() -> Result<(), BinaryFileReaderError> {
+        // Longer than the bytes an error captures.
+        let magic = [0xab; ErrorBytes::CAPACITY + 24];
+        let mut reader = PushReader::new();
+        reader.feed(&magic[..10]);
+        assert!(matches!(
+            reader.parse(|r| r.expect(&magic)),
+            Err(BinaryFileReaderError::NeedMoreData { missing: 30 })
+        ));
+        reader.feed(&magic[10..20]);
+        assert!(matches!(
+            reader.parse(|r| r.expect(&magic)),
+            Err(BinaryFileReaderError::NeedMoreData { missing: 20 })
+        ));
+        reader.feed(&magic[20..]);
+        reader.parse(|r| r.expect(&magic))?;
+        assert_eq!(reader.current_offset(), 40);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,29 @@
+//! Readers for common container formats, each behind a feature of the same
+//! name.
+
+#[cfg(feature = "bmff")]
+pub mod bmff;
+#[cfg(feature = "der")]
+pub mod der;
+#[cfg(feature = "dns")]
+pub mod dns;
+#[cfg(feature = "elf")]
+pub mod elf;
+#[cfg(feature = "mpegts")]
+pub mod mpegts;
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
+#[cfg(feature = "nal")]
+pub mod nal;
+#[cfg(feature = "pcap")]
+pub mod pcap;
+#[cfg(feature = "png")]
+pub mod png;
+#[cfg(feature = "riff")]
+pub mod riff;
+#[cfg(feature = "tar")]
+pub mod tar;
+#[cfg(feature = "tiff")]
+pub mod tiff;
+#[cfg(feature = "zip")]
+pub mod zip;
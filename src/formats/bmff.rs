@@ -0,0 +1,325 @@
+//! ISO base media files, such as MP4, MOV, HEIF and AVIF: a tree of boxes,
+//! each with a big-endian size and a FourCC type.
+//!
+//! Only available with the `bmff` feature.
+//!
+//! # Examples
+//! ```
+//! # use binary_file_reader::BinaryFileReader;
+//! # use binary_file_reader::formats::bmff::{self, BoxIter};
+//! # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut buffer = b"\x00\x00\x00\x10ftypisom\x00\x00\x02\x00".to_vec();
+//! buffer.extend(b"\x00\x00\x00\x18moov");
+//! buffer.extend(b"\x00\x00\x00\x10mvhd\x00\x00\x00\x00\x00\x00\x03\xe8");
+//!
+//! let mut timescale = None;
+//! for entry in BoxIter::new(BinaryFileReader::new(&buffer)).walk() {
+//!     let (depth, mut mp4_box) = entry?;
+//!     if mp4_box.box_type == "mvhd" {
+//!         assert_eq!(depth, 1);
+//!         let (version, _flags) = bmff::read_full_box_header(&mut mp4_box.data)?;
+//!         assert_eq!(version, 0);
+//!         timescale = Some(mp4_box.data.read_u32()?);
+//!     }
+//! }
+//! assert_eq!(timescale, Some(1000));
+//! #
+//! # Ok(())
+//! # }
+//! # fn main() {
+//! #    try_main().unwrap();
+//! # }
+//! ```
+
+use crate::{endian::Endian, error::BinaryFileReaderError, fourcc::FourCC, BinaryFileReader};
+use alloc::{vec, vec::Vec};
+use core::iter::FusedIterator;
+
+/// The box types that hold only other boxes, after the version and flags of
+/// `meta`.
+const CONTAINERS: [&[u8; 4]; 24] = [
+    b"moov", b"trak", b"tref", b"edts", b"mdia", b"minf", b"dinf", b"stbl", b"mvex", b"moof",
+    b"traf", b"mfra", b"udta", b"meta", b"ipro", b"sinf", b"schi", b"iprp", b"ipco", b"grpl",
+    b"trgr", b"strk", b"strd", b"ilst",
+];
+
+/// Whether boxes of type `box_type` hold only other boxes, such as `moov`
+/// and `trak`.
+pub fn is_container_type(box_type: FourCC) -> bool {
+    CONTAINERS.iter().any(|container| box_type == **container)
+}
+
+/// One box of an ISO base media file.
+#[derive(Debug, Clone)]
+pub struct BmffBox<'a> {
+    pub box_type: FourCC,
+    /// The extended type of a `uuid` box.
+    pub user_type: Option<[u8; 16]>,
+    /// The absolute offset of the box header.
+    pub offset: usize,
+    /// The size of the box, including its header.
+    pub size: u64,
+    /// A big-endian reader over the box payload, after the header.
+    pub data: BinaryFileReader<'a>,
+}
+
+impl<'a> BmffBox<'a> {
+    /// Whether the box holds only other boxes. See [`is_container_type`].
+    #[inline]
+    pub fn is_container(&self) -> bool {
+        is_container_type(self.box_type)
+    }
+
+    /// The boxes inside a container box. Leaf boxes have no children.
+    ///
+    /// The payload of `meta` starts with a version and flags in ISO files
+    /// but not in QuickTime files; they are skipped if they are zero.
+    pub fn children(&self) -> BoxIter<'a> {
+        let mut reader = self.data.clone();
+        if self.box_type == *b"meta" {
+            let _ = reader.try_expect(&[0; 4]);
+        }
+        BoxIter {
+            reader,
+            done: !self.is_container(),
+        }
+    }
+}
+
+/// Reads the version and 24-bit flags that start the payload of a full box.
+pub fn read_full_box_header(
+    reader: &mut BinaryFileReader<'_>,
+) -> Result<(u8, u32), BinaryFileReaderError> {
+    let word = reader.read_u32()?;
+    Ok(((word >> 24) as u8, word & 0x00ff_ffff))
+}
+
+fn read_box<'a>(reader: &mut BinaryFileReader<'a>) -> Result<BmffBox<'a>, BinaryFileReaderError> {
+    let offset = reader.current_offset();
+    let result = (|| {
+        let size = reader.read_u32()?;
+        let box_type = reader.read_fourcc()?;
+        let size = match size {
+            0 => (8 + reader.available_bytes()) as u64,
+            1 => reader.read_u64()?,
+            size => size as u64,
+        };
+        let user_type = if box_type == *b"uuid" {
+            Some(reader.read_struct::<[u8; 16]>()?)
+        } else {
+            None
+        };
+
+        let header = (reader.current_offset() - offset) as u64;
+        if size < header {
+            return Err(BinaryFileReaderError::Malformed {
+                reason: "box size is smaller than its header",
+                current_offset: offset,
+            });
+        }
+        let length = usize::try_from(size - header).unwrap_or(usize::MAX);
+        Ok(BmffBox {
+            box_type,
+            user_type,
+            offset,
+            size,
+            data: reader.split_off_front(length)?,
+        })
+    })();
+    if result.is_err() {
+        reader.set_offset(offset)?;
+    }
+    result
+}
+
+/// Iterates over a sequence of boxes. A box of size 0 extends to the end of
+/// the data, and a box of size 1 has a 64-bit size after its type.
+///
+/// A box that does not fit in the data is yielded as an error and ends the
+/// iteration.
+#[derive(Debug, Clone)]
+pub struct BoxIter<'a> {
+    reader: BinaryFileReader<'a>,
+    done: bool,
+}
+
+impl<'a> BoxIter<'a> {
+    /// Iterates over the boxes at the cursor of `reader`, such as a whole
+    /// file.
+    pub fn new(mut reader: BinaryFileReader<'a>) -> Self {
+        reader.set_endian(Endian::Big);
+        Self {
+            reader,
+            done: false,
+        }
+    }
+
+    /// Iterates depth-first over these boxes and the boxes nested in them.
+    pub fn walk(self) -> Walk<'a> {
+        Walk { stack: vec![self] }
+    }
+
+    /// The reader after the boxes yielded so far.
+    pub fn into_inner(self) -> BinaryFileReader<'a> {
+        self.reader
+    }
+}
+
+impl<'a> Iterator for BoxIter<'a> {
+    type Item = Result<BmffBox<'a>, BinaryFileReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.reader.available_bytes() == 0 {
+            return None;
+        }
+
+        let next = read_box(&mut self.reader);
+        self.done = next.is_err();
+        Some(next)
+    }
+}
+
+impl FusedIterator for BoxIter<'_> {}
+
+/// Iterates over boxes and their descendants, parents before their children,
+/// with the number of containers each is nested in. An error ends the walk.
+/// Created with [`BoxIter::walk`].
+///
+/// A container nested deeper than the reader's
+/// [`max_depth`](BinaryFileReader::max_depth) is yielded as
+/// [`BinaryFileReaderError::DepthExceeded`].
+#[derive(Debug, Clone)]
+pub struct Walk<'a> {
+    stack: Vec<BoxIter<'a>>,
+}
+
+impl<'a> Iterator for Walk<'a> {
+    type Item = Result<(usize, BmffBox<'a>), BinaryFileReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let depth = self.stack.len().checked_sub(1)?;
+            match self.stack[depth].next() {
+                Some(Ok(next)) => {
+                    if next.is_container() {
+                        let reader = &self.stack[depth].reader;
+                        if reader.depth() + self.stack.len() >= reader.max_depth() {
+                            let err = BinaryFileReaderError::DepthExceeded {
+                                max_depth: reader.max_depth(),
+                                current_offset: next.offset,
+                            };
+                            self.stack.clear();
+                            return Some(Err(err));
+                        }
+                        self.stack.push(next.children());
+                    }
+                    return Some(Ok((depth, next)));
+                }
+                Some(Err(err)) => {
+                    self.stack.clear();
+                    return Some(Err(err));
+                }
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
+impl FusedIterator for Walk<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::BoxIter;
+    use crate::{
+        error::{BinaryFileReaderError, ErrorCode},
+        BinaryFileReader,
+    };
+
+    #[test]
+    fn test_box_iter() -> Result<(), BinaryFileReaderError> {
+        let mut buffer = b"\x00\x00\x00\x0cmeta\x00\x00\x00\x00".to_vec();
+        buffer.extend(b"\x00\x00\x00\x1cuuid0123456789abcdef\xca\xfe\xba\xbe");
+        buffer.extend(b"\x00\x00\x00\x01mdat\x00\x00\x00\x00\x00\x00\x00\x12\x01\x02");
+        buffer.extend(b"\x00\x00\x00\x00free\xff");
+
+        let boxes = BoxIter::new(BinaryFileReader::new(&buffer))
+            .walk()
+            .collect::<Result<Vec<_>, _>>()?;
+        let types = boxes
+            .iter()
+            .map(|(depth, found)| (*depth, found.box_type.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            [
+                (0, "meta".into()),
+                (0, "uuid".into()),
+                (0, "mdat".into()),
+                (0, "free".into())
+            ]
+        );
+        assert!(boxes[0].1.is_container());
+        assert_eq!(boxes[1].1.user_type, Some(*b"0123456789abcdef"));
+        assert_eq!(boxes[1].1.data.clone().read_u32()?, 0xcafebabe);
+        assert_eq!((boxes[2].1.offset, boxes[2].1.size), (40, 18));
+        assert_eq!(boxes[2].1.data.available_bytes(), 2);
+        assert_eq!(boxes[3].1.size, 9);
+        assert!(boxes[3].1.children().next().is_none());
+
+        // QuickTime `meta` boxes hold their children directly.
+        let buffer = b"\x00\x00\x00\x14meta\x00\x00\x00\x0chdlr\x00\x00\x00\x00".to_vec();
+        let children = BoxIter::new(BinaryFileReader::new(&buffer))
+            .walk()
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(children[1].1.box_type, *b"hdlr");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_boxes() {
+        let mut boxes = BoxIter::new(BinaryFileReader::new(b"\x00\x00\x00\x04free"));
+        let err = boxes.next().unwrap().unwrap_err();
+        assert_eq!(err.code(), ErrorCode::Malformed);
+        assert_eq!(
+            err.to_string(),
+            "Malformed data: box size is smaller than its header at offset 0x0 (0)"
+        );
+        assert!(boxes.next().is_none());
+        assert_eq!(boxes.into_inner().current_offset(), 0);
+
+        let buffer = b"\x00\x00\x00\x10moov\x00\x00\x00\x09trak".to_vec();
+        let mut walk = BoxIter::new(BinaryFileReader::new(&buffer)).walk();
+        assert!(walk.next().unwrap().is_ok());
+        let err = walk.next().unwrap().unwrap_err();
+        assert_eq!(err.code(), ErrorCode::BufferUnderflow);
+        assert_eq!(err.offset(), Some(16));
+        assert!(walk.next().is_none());
+    }
+
+    #[test]
+    fn test_deep_nesting() {
+        let mut buffer = Vec::new();
+        for _ in 0..4 {
+            let mut parent = (buffer.len() as u32 + 8).to_be_bytes().to_vec();
+            parent.extend(b"moov");
+            parent.extend(buffer);
+            buffer = parent;
+        }
+
+        let mut reader = BinaryFileReader::new(&buffer);
+        reader.set_max_depth(4);
+        assert_eq!(BoxIter::new(reader.clone()).walk().count(), 4);
+
+        reader.set_max_depth(3);
+        let mut walk = BoxIter::new(reader).walk();
+        assert_eq!(walk.next().unwrap().unwrap().0, 0);
+        assert_eq!(walk.next().unwrap().unwrap().0, 1);
+        let err = walk.next().unwrap().unwrap_err();
+        assert_eq!(err.code(), ErrorCode::DepthExceeded);
+        assert_eq!(err.offset(), Some(16));
+        assert!(walk.next().is_none());
+    }
+}
@@ -0,0 +1,358 @@
+//! ASN.1 values in BER and DER, as in X.509 certificates and PKCS
+//! structures: identifier octets, definite and indefinite lengths, and
+//! constructed values holding more values.
+//!
+//! Only the structure is decoded; the contents of primitive values are left
+//! to the caller.
+//!
+//! Only available with the `der` feature.
+//!
+//! # Examples
+//! ```
+//! # use binary_file_reader::BinaryFileReader;
+//! # use binary_file_reader::formats::der::{self, Class};
+//! # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+//! // SEQUENCE { INTEGER 5, [0] { BOOLEAN TRUE } }
+//! let buffer = vec![0x30, 0x08, 0x02, 0x01, 0x05, 0xa0, 0x03, 0x01, 0x01, 0xff];
+//! let mut reader = BinaryFileReader::new(&buffer);
+//!
+//! let sequence = der::read_value(&mut reader)?;
+//! assert_eq!(sequence.identifier.number, der::SEQUENCE);
+//!
+//! let mut fields = sequence.children();
+//! let mut version = fields.next().unwrap()?;
+//! assert_eq!(version.identifier.number, der::INTEGER);
+//! assert_eq!(version.contents.read_u8()?, 5);
+//!
+//! let explicit = fields.next().unwrap()?;
+//! assert_eq!(explicit.identifier.class, Class::ContextSpecific);
+//! assert_eq!(explicit.children().count(), 1);
+//! assert!(fields.next().is_none());
+//! #
+//! # Ok(())
+//! # }
+//! # fn main() {
+//! #    try_main().unwrap();
+//! # }
+//! ```
+
+use crate::{error::BinaryFileReaderError, BinaryFileReader};
+use core::iter::FusedIterator;
+
+pub const BOOLEAN: u32 = 1;
+pub const INTEGER: u32 = 2;
+pub const BIT_STRING: u32 = 3;
+pub const OCTET_STRING: u32 = 4;
+pub const NULL: u32 = 5;
+pub const OBJECT_IDENTIFIER: u32 = 6;
+pub const UTF8_STRING: u32 = 12;
+pub const SEQUENCE: u32 = 16;
+pub const SET: u32 = 17;
+pub const PRINTABLE_STRING: u32 = 19;
+pub const IA5_STRING: u32 = 22;
+pub const UTC_TIME: u32 = 23;
+pub const GENERALIZED_TIME: u32 = 24;
+
+/// The class bits of an identifier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Class {
+    Universal,
+    Application,
+    ContextSpecific,
+    Private,
+}
+
+/// The identifier octets of a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Identifier {
+    pub class: Class,
+    /// Whether the contents are more values rather than data.
+    pub constructed: bool,
+    /// The tag number, such as [`SEQUENCE`] for the universal class.
+    pub number: u32,
+}
+
+/// The length octets of a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Length {
+    Definite(usize),
+    /// The contents end with two zero bytes, which BER allows for
+    /// constructed values and DER forbids.
+    Indefinite,
+}
+
+/// Reads identifier octets, including the high tag numbers that continue in
+/// base-128 bytes.
+pub fn read_identifier(
+    reader: &mut BinaryFileReader<'_>,
+) -> Result<Identifier, BinaryFileReaderError> {
+    let offset = reader.current_offset();
+    let first = reader.read_u8()?;
+    let class = match first >> 6 {
+        0 => Class::Universal,
+        1 => Class::Application,
+        2 => Class::ContextSpecific,
+        _ => Class::Private,
+    };
+    let mut number = (first & 0x1f) as u32;
+    if number == 0x1f {
+        number = 0;
+        loop {
+            let byte = reader.read_u8()?;
+            if number == 0 && byte == 0x80 {
+                return Err(BinaryFileReaderError::malformed(
+                    "tag number has a leading zero byte",
+                    offset,
+                ));
+            }
+            if number > u32::MAX >> 7 {
+                return Err(BinaryFileReaderError::malformed(
+                    "tag number is too large",
+                    offset,
+                ));
+            }
+            number = (number << 7) | (byte & 0x7f) as u32;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+    }
+
+    Ok(Identifier {
+        class,
+        constructed: first & 0x20 != 0,
+        number,
+    })
+}
+
+/// Reads length octets in the short, long or indefinite form.
+pub fn read_length(reader: &mut BinaryFileReader<'_>) -> Result<Length, BinaryFileReaderError> {
+    let offset = reader.current_offset();
+    let first = reader.read_u8()?;
+    match first {
+        0x00..=0x7f => Ok(Length::Definite(first as usize)),
+        0x80 => Ok(Length::Indefinite),
+        0xff => Err(BinaryFileReaderError::malformed(
+            "length uses the reserved form",
+            offset,
+        )),
+        _ => {
+            let mut length: usize = 0;
+            for byte in reader.read_slice((first & 0x7f) as usize)? {
+                length = length.checked_mul(256).ok_or_else(|| {
+                    BinaryFileReaderError::malformed("length is too large", offset)
+                })? | *byte as usize;
+            }
+            Ok(Length::Definite(length))
+        }
+    }
+}
+
+/// One value: identifier, length and contents.
+#[derive(Debug, Clone)]
+pub struct Value<'a> {
+    pub identifier: Identifier,
+    /// The absolute offset of the identifier octets.
+    pub offset: usize,
+    /// Whether the length was indefinite.
+    pub indefinite: bool,
+    /// A reader over the contents, without the end-of-contents bytes of an
+    /// indefinite length.
+    pub contents: BinaryFileReader<'a>,
+}
+
+impl<'a> Value<'a> {
+    /// The values inside a constructed value. Primitive values have no
+    /// children.
+    pub fn children(&self) -> ValueIter<'a> {
+        ValueIter {
+            reader: self.contents.clone(),
+            done: !self.identifier.constructed,
+        }
+    }
+}
+
+/// Reads the value at the cursor. On failure the reader is left at the
+/// start of the value.
+///
+/// The end of an indefinite-length value is found by reading the values
+/// nested in it, each counting against the reader's
+/// [`max_depth`](BinaryFileReader::max_depth).
+pub fn read_value<'a>(
+    reader: &mut BinaryFileReader<'a>,
+) -> Result<Value<'a>, BinaryFileReaderError> {
+    let offset = reader.current_offset();
+    let result = (|| {
+        let identifier = read_identifier(reader)?;
+        let length_offset = reader.current_offset();
+        let (length, indefinite) = match read_length(reader)? {
+            Length::Definite(length) => (length, false),
+            Length::Indefinite if !identifier.constructed => {
+                return Err(BinaryFileReaderError::malformed(
+                    "primitive value has an indefinite length",
+                    length_offset,
+                ));
+            }
+            Length::Indefinite => (indefinite_length(reader)?, true),
+        };
+
+        let contents = reader.split_off_front(length)?;
+        if indefinite {
+            reader.advance(2)?;
+        }
+        Ok(Value {
+            identifier,
+            offset,
+            indefinite,
+            contents,
+        })
+    })();
+    if result.is_err() {
+        reader.set_offset(offset)?;
+    }
+    result
+}
+
+/// The length of indefinite-length contents at the cursor, up to their
+/// end-of-contents bytes.
+fn indefinite_length(reader: &BinaryFileReader<'_>) -> Result<usize, BinaryFileReaderError> {
+    let mut probe = reader.clone();
+    probe.nested(|probe| loop {
+        if probe.peek_slice(2)? == [0, 0] {
+            return Ok(probe.current_offset() - reader.current_offset());
+        }
+        read_value(probe)?;
+    })
+}
+
+/// Iterates over a sequence of values, such as the contents of a
+/// constructed value. A value that does not fit is yielded as an error and
+/// ends the iteration.
+#[derive(Debug, Clone)]
+pub struct ValueIter<'a> {
+    reader: BinaryFileReader<'a>,
+    done: bool,
+}
+
+impl<'a> ValueIter<'a> {
+    pub fn new(reader: BinaryFileReader<'a>) -> Self {
+        Self {
+            reader,
+            done: false,
+        }
+    }
+
+    /// The reader after the values yielded so far.
+    pub fn into_inner(self) -> BinaryFileReader<'a> {
+        self.reader
+    }
+}
+
+impl<'a> Iterator for ValueIter<'a> {
+    type Item = Result<Value<'a>, BinaryFileReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.reader.available_bytes() == 0 {
+            return None;
+        }
+
+        let value = read_value(&mut self.reader);
+        self.done = value.is_err();
+        Some(value)
+    }
+}
+
+impl FusedIterator for ValueIter<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_identifier, read_length, read_value, Class, Length, ValueIter, OCTET_STRING};
+    use crate::{
+        error::{BinaryFileReaderError, ErrorCode},
+        BinaryFileReader,
+    };
+
+    #[test]
+    fn test_identifier_and_length() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0x7f, 0x87, 0x68, 0x9f, 0x1f];
+        let mut reader = BinaryFileReader::new(&buffer);
+        let identifier = read_identifier(&mut reader)?;
+        assert_eq!(identifier.class, Class::Application);
+        assert!(identifier.constructed);
+        assert_eq!(identifier.number, 1000);
+        assert_eq!(read_identifier(&mut reader)?.number, 31);
+        assert_eq!(
+            read_identifier(&mut BinaryFileReader::new(&[0x1f, 0x80, 0x01]))
+                .unwrap_err()
+                .code(),
+            ErrorCode::Malformed
+        );
+
+        let buffer = vec![0x05, 0x81, 0xc8, 0x82, 0x01, 0x00, 0x80, 0xff];
+        let mut reader = BinaryFileReader::new(&buffer);
+        assert_eq!(read_length(&mut reader)?, Length::Definite(5));
+        assert_eq!(read_length(&mut reader)?, Length::Definite(200));
+        assert_eq!(read_length(&mut reader)?, Length::Definite(256));
+        assert_eq!(read_length(&mut reader)?, Length::Indefinite);
+        assert_eq!(read_length(&mut reader).unwrap_err().offset(), Some(7));
+
+        let mut buffer = vec![0x89];
+        buffer.extend([0x01; 9]);
+        assert_eq!(
+            read_length(&mut BinaryFileReader::new(&buffer))
+                .unwrap_err()
+                .code(),
+            ErrorCode::Malformed
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_indefinite_values() -> Result<(), BinaryFileReaderError> {
+        // SEQUENCE (indefinite) { OCTET STRING (constructed, indefinite)
+        // { OCTET STRING "ab" }, NULL }, then INTEGER 1.
+        let buffer = vec![
+            0x30, 0x80, 0x24, 0x80, 0x04, 0x02, b'a', b'b', 0x00, 0x00, 0x05, 0x00, 0x00, 0x00,
+            0x02, 0x01, 0x01,
+        ];
+        let values =
+            ValueIter::new(BinaryFileReader::new(&buffer)).collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(values.len(), 2);
+        assert!(values[0].indefinite);
+        assert_eq!(values[0].contents.available_bytes(), 10);
+        assert_eq!(values[1].offset, 14);
+
+        let children = values[0].children().collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(children[0].identifier.number, OCTET_STRING);
+        let mut inner = children[0].children().next().unwrap()?;
+        assert_eq!(inner.contents.read_slice(2)?, b"ab");
+        assert!(children[1].children().next().is_none());
+
+        let mut reader = BinaryFileReader::new(&buffer[..12]);
+        assert_eq!(
+            read_value(&mut reader).unwrap_err().code(),
+            ErrorCode::BufferUnderflow
+        );
+        assert_eq!(reader.current_offset(), 0);
+
+        let mut reader = BinaryFileReader::new(&[0x04, 0x80, 0x00, 0x00]);
+        assert!(matches!(
+            read_value(&mut reader),
+            Err(BinaryFileReaderError::Malformed {
+                current_offset: 1,
+                ..
+            })
+        ));
+
+        let nested = [[0x30, 0x80]; 8].concat();
+        let mut reader = BinaryFileReader::new(&nested);
+        reader.set_max_depth(4);
+        assert_eq!(
+            read_value(&mut reader).unwrap_err().code(),
+            ErrorCode::DepthExceeded
+        );
+
+        Ok(())
+    }
+}
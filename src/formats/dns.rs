@@ -0,0 +1,246 @@
+//! Domain names as DNS messages encode them: length-prefixed labels ending
+//! with an empty label, or with a compression pointer to a name earlier in
+//! the message.
+//!
+//! Pointers hold offsets from the start of the message, which is taken to be
+//! the start of the reader, so a message split off from a larger buffer, such
+//! as a packet payload, is read with a sub-reader over the message.
+//!
+//! Only available with the `dns` feature.
+//!
+//! # Examples
+//! ```
+//! # use binary_file_reader::BinaryFileReader;
+//! # use binary_file_reader::formats::dns;
+//! # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+//! // "example.com", then "www" followed by a pointer to offset 0.
+//! let buffer = b"\x07example\x03com\0\x03www\xc0\x00\x00\x01".to_vec();
+//! let mut reader = BinaryFileReader::new(&buffer);
+//!
+//! assert_eq!(dns::read_name(&mut reader)?.to_string(), "example.com");
+//! let name = dns::read_name(&mut reader)?;
+//! assert_eq!(name.labels, [&b"www"[..], b"example", b"com"]);
+//! assert_eq!(reader.read_u16()?, 1);
+//! #
+//! # Ok(())
+//! # }
+//! # fn main() {
+//! #    try_main().unwrap();
+//! # }
+//! ```
+
+use crate::{error::BinaryFileReaderError, BinaryFileReader};
+use alloc::vec::Vec;
+use core::fmt;
+
+/// The number of compression pointers [`read_name`] follows in one name.
+pub const MAX_POINTERS: usize = 16;
+
+/// The longest name, in its encoded form without pointers.
+const MAX_NAME_LEN: usize = 255;
+
+/// A domain name as a sequence of labels, without the empty root label.
+///
+/// It displays with dots between the labels, and as `.` for the root.
+/// Dots, backslashes and bytes outside printable ASCII in labels are
+/// escaped as in zone files.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Name<'a> {
+    pub labels: Vec<&'a [u8]>,
+}
+
+impl Name<'_> {
+    #[inline]
+    pub fn is_root(&self) -> bool {
+        self.labels.is_empty()
+    }
+}
+
+impl fmt::Display for Name<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_root() {
+            return f.write_str(".");
+        }
+        for (i, label) in self.labels.iter().enumerate() {
+            if i > 0 {
+                f.write_str(".")?;
+            }
+            for &byte in *label {
+                match byte {
+                    b'.' | b'\\' => write!(f, "\\{}", byte as char)?,
+                    0x21..=0x7e => write!(f, "{}", byte as char)?,
+                    _ => write!(f, "\\{:03}", byte)?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads the name at the cursor, following up to [`MAX_POINTERS`]
+/// compression pointers. The reader is left after the name's first pointer
+/// or its terminating empty label, and at the start of the name on failure.
+///
+/// Fails with [`BinaryFileReaderError::Malformed`] for a pointer loop, too
+/// many pointers, a reserved label type or a name longer than 255 bytes.
+pub fn read_name<'a>(reader: &mut BinaryFileReader<'a>) -> Result<Name<'a>, BinaryFileReaderError> {
+    read_name_with_limit(reader, MAX_POINTERS)
+}
+
+/// Like [`read_name`], following up to `max_pointers` compression pointers.
+pub fn read_name_with_limit<'a>(
+    reader: &mut BinaryFileReader<'a>,
+    max_pointers: usize,
+) -> Result<Name<'a>, BinaryFileReaderError> {
+    let start = reader.current_offset();
+    let result = (|| {
+        let mut labels = Vec::new();
+        let mut length = 1;
+        let mut targets: Vec<usize> = Vec::new();
+        // The offset after the name, once a pointer is followed.
+        let mut end = None;
+        let mut cursor = reader.clone();
+
+        loop {
+            let offset = cursor.current_offset();
+            let byte = cursor.read_u8()?;
+            match byte >> 6 {
+                0 if byte == 0 => break,
+                0 => {
+                    length += 1 + byte as usize;
+                    if length > MAX_NAME_LEN {
+                        return Err(BinaryFileReaderError::malformed(
+                            "name is longer than 255 bytes",
+                            offset,
+                        ));
+                    }
+                    labels.push(cursor.read_slice(byte as usize)?);
+                }
+                3 => {
+                    let target = (((byte & 0x3f) as usize) << 8) | cursor.read_u8()? as usize;
+                    if targets.contains(&target) {
+                        return Err(BinaryFileReaderError::malformed(
+                            "name compression pointers loop",
+                            offset,
+                        ));
+                    }
+                    if targets.len() == max_pointers {
+                        return Err(BinaryFileReaderError::malformed(
+                            "name has too many compression pointers",
+                            offset,
+                        ));
+                    }
+                    targets.push(target);
+                    end.get_or_insert(cursor.current_offset());
+                    cursor.set_offset(reader.own_start + target)?;
+                }
+                _ => {
+                    return Err(BinaryFileReaderError::malformed(
+                        "name has a reserved label type",
+                        offset,
+                    ))
+                }
+            }
+        }
+
+        reader.set_offset(end.unwrap_or(cursor.current_offset()))?;
+        Ok(Name { labels })
+    })();
+    if result.is_err() {
+        reader.set_offset(start)?;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_name, read_name_with_limit, Name};
+    use crate::{
+        error::{BinaryFileReaderError, ErrorCode},
+        BinaryFileReader,
+    };
+
+    #[test]
+    fn test_read_name() -> Result<(), BinaryFileReaderError> {
+        let buffer = b"\0\x01a\x02b.\xc0\x00\x03\\\x00 \xc0\x01".to_vec();
+        let mut reader = BinaryFileReader::new(&buffer);
+        let root = read_name(&mut reader)?;
+        assert!(root.is_root());
+        assert_eq!(root.to_string(), ".");
+
+        // A pointer to a name that ends with a pointer.
+        reader.set_offset(8)?;
+        let name = read_name(&mut reader)?;
+        assert_eq!(name.to_string(), "\\\\\\000\\032.a.b\\.");
+        assert_eq!(reader.current_offset(), 14);
+
+        let mut reader = BinaryFileReader::new(&buffer);
+        reader.set_offset(1)?;
+        assert_eq!(
+            read_name_with_limit(&mut reader, 0).unwrap_err().code(),
+            ErrorCode::Malformed
+        );
+        assert_eq!(reader.current_offset(), 1);
+        assert_eq!(read_name_with_limit(&mut reader, 1)?.labels.len(), 2);
+
+        let name = Name {
+            labels: vec![b"xn--bcher-kva", b"example"],
+        };
+        assert_eq!(name.to_string(), "xn--bcher-kva.example");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sub_reader() -> Result<(), BinaryFileReaderError> {
+        // A message after a 4-byte header, with a pointer to its offset 0.
+        let buffer = b"\xaa\xbb\xcc\xdd\x01a\0\x01b\xc0\x00";
+        let mut reader = BinaryFileReader::new(buffer);
+        reader.read_u32()?;
+        let mut message = reader.split_off_front(7)?;
+        message.set_offset(7)?;
+        assert_eq!(read_name(&mut message)?.to_string(), "b.a");
+        assert_eq!(message.current_offset(), 11);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_names() {
+        // A pointer to itself, and two pointers to each other.
+        for buffer in [&b"\xc0\x00"[..], b"\x01a\xc0\x04\xc0\x00"] {
+            let err = read_name(&mut BinaryFileReader::new(buffer)).unwrap_err();
+            assert!(err.to_string().contains("loop"));
+        }
+
+        let mut buffer = Vec::new();
+        for _ in 0..5 {
+            buffer.push(63);
+            buffer.extend([b'a'; 63]);
+        }
+        buffer.push(0);
+        let mut reader = BinaryFileReader::new(&buffer);
+        let err = read_name(&mut reader).unwrap_err();
+        assert_eq!(err.offset(), Some(192));
+        assert_eq!(reader.current_offset(), 0);
+
+        assert_eq!(
+            read_name(&mut BinaryFileReader::new(b"\x80"))
+                .unwrap_err()
+                .code(),
+            ErrorCode::Malformed
+        );
+        assert_eq!(
+            read_name(&mut BinaryFileReader::new(b"\x01a\xc0\x10"))
+                .unwrap_err()
+                .code(),
+            ErrorCode::OutOfRange
+        );
+        assert_eq!(
+            read_name(&mut BinaryFileReader::new(b"\x03ab"))
+                .unwrap_err()
+                .code(),
+            ErrorCode::BufferUnderflow
+        );
+    }
+}
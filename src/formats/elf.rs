@@ -0,0 +1,624 @@
+//! ELF executables, shared objects and object files: the ident bytes that
+//! give the class and byte order, the file header, and the tables of
+//! program and section headers it points to.
+//!
+//! Both classes and byte orders are read, including the extended section
+//! numbering of files with more than 65279 sections.
+//!
+//! Only available with the `elf` feature.
+//!
+//! # Examples
+//! ```
+//! # use binary_file_reader::BinaryFileReader;
+//! # use binary_file_reader::endian::Endian;
+//! # use binary_file_reader::formats::elf::{self, Class, Elf};
+//! # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+//! // A 32-bit big-endian header, then a string table and two sections.
+//! let mut buffer = b"\x7fELF\x01\x02\x01\0\0\0\0\0\0\0\0\0".to_vec();
+//! buffer.extend(b"\0\x02\0\x08\0\0\0\x01\0\x40\0\0\0\0\0\0\0\0\0\x40\0\0\0\0");
+//! buffer.extend(b"\0\x34\0\x20\0\0\0\x28\0\x02\0\x01");
+//! buffer.extend(b"\0.shstrtab\0\0");
+//! buffer.extend([0; 40]);
+//! buffer.extend(b"\0\0\0\x01\0\0\0\x03\0\0\0\0\0\0\0\0\0\0\0\x34\0\0\0\x0b");
+//! buffer.extend([0; 16]);
+//!
+//! let elf = Elf::new(&BinaryFileReader::new(&buffer))?;
+//! assert_eq!(elf.header.ident.class, Class::Elf32);
+//! assert_eq!(elf.header.ident.endian, Endian::Big);
+//! assert_eq!(elf.header.file_type, elf::ET_EXEC);
+//!
+//! let names = elf
+//!     .section_headers()
+//!     .map(|section| elf.section_name(&section?))
+//!     .collect::<Result<Vec<_>, _>>()?;
+//! assert_eq!(names, [&b""[..], b".shstrtab"]);
+//! #
+//! # Ok(())
+//! # }
+//! # fn main() {
+//! #    try_main().unwrap();
+//! # }
+//! ```
+
+use crate::{endian::Endian, error::BinaryFileReaderError, BinaryFileReader};
+use core::iter::FusedIterator;
+
+/// The `file_type` of relocatable object files.
+pub const ET_REL: u16 = 1;
+/// The `file_type` of executables.
+pub const ET_EXEC: u16 = 2;
+/// The `file_type` of shared objects and position-independent executables.
+pub const ET_DYN: u16 = 3;
+/// The `file_type` of core dumps.
+pub const ET_CORE: u16 = 4;
+
+pub const PT_NULL: u32 = 0;
+pub const PT_LOAD: u32 = 1;
+pub const PT_DYNAMIC: u32 = 2;
+pub const PT_INTERP: u32 = 3;
+pub const PT_NOTE: u32 = 4;
+pub const PT_PHDR: u32 = 6;
+pub const PT_TLS: u32 = 7;
+
+pub const SHT_NULL: u32 = 0;
+pub const SHT_PROGBITS: u32 = 1;
+pub const SHT_SYMTAB: u32 = 2;
+pub const SHT_STRTAB: u32 = 3;
+pub const SHT_RELA: u32 = 4;
+pub const SHT_DYNAMIC: u32 = 6;
+pub const SHT_NOTE: u32 = 7;
+/// The type of sections that take no space in the file, such as `.bss`.
+pub const SHT_NOBITS: u32 = 8;
+pub const SHT_REL: u32 = 9;
+pub const SHT_DYNSYM: u32 = 11;
+
+/// The section name index that means the index is in the `link` of the
+/// first section header.
+const SHN_XINDEX: u16 = 0xffff;
+
+/// The width of addresses and offsets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Class {
+    Elf32,
+    Elf64,
+}
+
+/// The 16 ident bytes that start an ELF file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ident {
+    pub class: Class,
+    pub endian: Endian,
+    pub version: u8,
+    pub os_abi: u8,
+    pub abi_version: u8,
+}
+
+/// Reads the ident bytes and switches `reader` to the byte order they name.
+pub fn read_ident(reader: &mut BinaryFileReader<'_>) -> Result<Ident, BinaryFileReaderError> {
+    reader.expect(b"\x7fELF")?;
+    let class = match reader.read_u8()? {
+        1 => Class::Elf32,
+        2 => Class::Elf64,
+        _ => {
+            return Err(BinaryFileReaderError::malformed(
+                "unknown ELF class",
+                reader.current_offset() - 1,
+            ))
+        }
+    };
+    let endian = match reader.read_u8()? {
+        1 => Endian::Little,
+        2 => Endian::Big,
+        _ => {
+            return Err(BinaryFileReaderError::malformed(
+                "unknown ELF byte order",
+                reader.current_offset() - 1,
+            ))
+        }
+    };
+    let version = reader.read_u8()?;
+    let os_abi = reader.read_u8()?;
+    let abi_version = reader.read_u8()?;
+    reader.advance(7)?;
+    reader.set_endian(endian);
+    Ok(Ident {
+        class,
+        endian,
+        version,
+        os_abi,
+        abi_version,
+    })
+}
+
+/// Reads an address or offset of the width of `class`.
+fn read_word(
+    reader: &mut BinaryFileReader<'_>,
+    class: Class,
+) -> Result<u64, BinaryFileReaderError> {
+    match class {
+        Class::Elf32 => reader.read_u32().map(u64::from),
+        Class::Elf64 => reader.read_u64(),
+    }
+}
+
+fn to_usize(value: u64) -> usize {
+    usize::try_from(value).unwrap_or(usize::MAX)
+}
+
+/// The ELF file header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Header {
+    pub ident: Ident,
+    /// The file type, such as [`ET_EXEC`] or [`ET_DYN`].
+    pub file_type: u16,
+    pub machine: u16,
+    pub version: u32,
+    pub entry: u64,
+    pub program_header_offset: u64,
+    pub section_header_offset: u64,
+    pub flags: u32,
+    pub header_size: u16,
+    pub program_header_size: u16,
+    pub program_header_count: u16,
+    pub section_header_size: u16,
+    /// The number of sections, or 0 if it is in the first section header.
+    pub section_header_count: u16,
+    /// The index of the section names string table, or `0xffff` if it is in
+    /// the first section header.
+    pub section_name_index: u16,
+}
+
+/// Reads the ident bytes and the file header, switching `reader` to the
+/// file's byte order.
+pub fn read_header(reader: &mut BinaryFileReader<'_>) -> Result<Header, BinaryFileReaderError> {
+    let ident = read_ident(reader)?;
+    Ok(Header {
+        ident,
+        file_type: reader.read_u16()?,
+        machine: reader.read_u16()?,
+        version: reader.read_u32()?,
+        entry: read_word(reader, ident.class)?,
+        program_header_offset: read_word(reader, ident.class)?,
+        section_header_offset: read_word(reader, ident.class)?,
+        flags: reader.read_u32()?,
+        header_size: reader.read_u16()?,
+        program_header_size: reader.read_u16()?,
+        program_header_count: reader.read_u16()?,
+        section_header_size: reader.read_u16()?,
+        section_header_count: reader.read_u16()?,
+        section_name_index: reader.read_u16()?,
+    })
+}
+
+/// One entry of the program header table, describing a segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProgramHeader {
+    /// The segment type, such as [`PT_LOAD`].
+    pub segment_type: u32,
+    pub flags: u32,
+    pub offset: u64,
+    pub virtual_address: u64,
+    pub physical_address: u64,
+    pub file_size: u64,
+    pub memory_size: u64,
+    pub align: u64,
+}
+
+fn read_program_header(
+    reader: &mut BinaryFileReader<'_>,
+    class: Class,
+) -> Result<ProgramHeader, BinaryFileReaderError> {
+    let segment_type = reader.read_u32()?;
+    let mut flags = 0;
+    if class == Class::Elf64 {
+        flags = reader.read_u32()?;
+    }
+    let offset = read_word(reader, class)?;
+    let virtual_address = read_word(reader, class)?;
+    let physical_address = read_word(reader, class)?;
+    let file_size = read_word(reader, class)?;
+    let memory_size = read_word(reader, class)?;
+    if class == Class::Elf32 {
+        flags = reader.read_u32()?;
+    }
+    Ok(ProgramHeader {
+        segment_type,
+        flags,
+        offset,
+        virtual_address,
+        physical_address,
+        file_size,
+        memory_size,
+        align: read_word(reader, class)?,
+    })
+}
+
+/// One entry of the section header table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SectionHeader {
+    /// The offset of the name in the section names string table. See
+    /// [`Elf::section_name`].
+    pub name: u32,
+    /// The section type, such as [`SHT_PROGBITS`] or [`SHT_STRTAB`].
+    pub section_type: u32,
+    pub flags: u64,
+    pub address: u64,
+    pub offset: u64,
+    pub size: u64,
+    pub link: u32,
+    pub info: u32,
+    pub align: u64,
+    /// The size of each entry, for sections holding a table.
+    pub entry_size: u64,
+}
+
+fn read_section_header(
+    reader: &mut BinaryFileReader<'_>,
+    class: Class,
+) -> Result<SectionHeader, BinaryFileReaderError> {
+    Ok(SectionHeader {
+        name: reader.read_u32()?,
+        section_type: reader.read_u32()?,
+        flags: read_word(reader, class)?,
+        address: read_word(reader, class)?,
+        offset: read_word(reader, class)?,
+        size: read_word(reader, class)?,
+        link: reader.read_u32()?,
+        info: reader.read_u32()?,
+        align: read_word(reader, class)?,
+        entry_size: read_word(reader, class)?,
+    })
+}
+
+/// An ELF file, read in the byte order of its ident bytes.
+#[derive(Debug, Clone)]
+pub struct Elf<'a> {
+    // A reader at the start of the file, in the file's byte order.
+    data: BinaryFileReader<'a>,
+    pub header: Header,
+    section_count: u64,
+    section_name_index: u32,
+}
+
+impl<'a> Elf<'a> {
+    /// Reads the header at the cursor of `reader`, which is the start of
+    /// the file that offsets count from. `reader` is not advanced.
+    pub fn new(reader: &BinaryFileReader<'a>) -> Result<Self, BinaryFileReaderError> {
+        let mut data = reader.clone();
+        let header = read_header(&mut data)?;
+        data.set_offset(reader.current_offset())?;
+        let mut elf = Self {
+            data,
+            header,
+            section_count: header.section_header_count as u64,
+            section_name_index: header.section_name_index as u32,
+        };
+
+        if header.section_header_offset != 0
+            && (header.section_header_count == 0 || header.section_name_index == SHN_XINDEX)
+        {
+            let mut table = elf.table(header.section_header_offset, header.section_header_size, 1);
+            if let Some(first) = table.next(read_section_header).transpose()? {
+                if header.section_header_count == 0 {
+                    elf.section_count = first.size;
+                }
+                if header.section_name_index == SHN_XINDEX {
+                    elf.section_name_index = first.link;
+                }
+            }
+        }
+        Ok(elf)
+    }
+
+    /// A reader positioned `offset` bytes after the start of the file.
+    pub fn reader_at(&self, offset: u64) -> Result<BinaryFileReader<'a>, BinaryFileReaderError> {
+        let mut reader = self.data.clone();
+        reader.set_offset(reader.current_offset().saturating_add(to_usize(offset)))?;
+        Ok(reader)
+    }
+
+    /// The number of section headers, from the file header or, for files
+    /// with many sections, the first section header.
+    #[inline]
+    pub fn section_count(&self) -> u64 {
+        self.section_count
+    }
+
+    /// Iterates over the program header table. An entry that does not fit
+    /// is yielded as an error and ends the iteration.
+    pub fn program_headers(&self) -> ProgramHeaderIter<'a> {
+        ProgramHeaderIter {
+            table: self.table(
+                self.header.program_header_offset,
+                self.header.program_header_size,
+                self.header.program_header_count as u64,
+            ),
+        }
+    }
+
+    /// Iterates over the section header table. An entry that does not fit
+    /// is yielded as an error and ends the iteration.
+    pub fn section_headers(&self) -> SectionHeaderIter<'a> {
+        SectionHeaderIter {
+            table: self.table(
+                self.header.section_header_offset,
+                self.header.section_header_size,
+                self.section_count,
+            ),
+        }
+    }
+
+    fn table(&self, offset: u64, entry_size: u16, count: u64) -> Table<'a> {
+        Table {
+            data: self.data.clone(),
+            class: self.header.ident.class,
+            offset,
+            entry_size: entry_size as u64,
+            remaining: count,
+        }
+    }
+
+    /// The header of the section at `index`.
+    pub fn section_header(&self, index: u32) -> Result<SectionHeader, BinaryFileReaderError> {
+        let offset = (index as u64)
+            .checked_mul(self.header.section_header_size as u64)
+            .and_then(|offset| offset.checked_add(self.header.section_header_offset))
+            .unwrap_or(u64::MAX);
+        let mut reader = self.reader_at(offset)?;
+        let mut entry = reader.split_off_front(self.header.section_header_size as usize)?;
+        read_section_header(&mut entry, self.header.ident.class)
+    }
+
+    /// A reader over the bytes of a segment in the file.
+    pub fn segment_data(
+        &self,
+        segment: &ProgramHeader,
+    ) -> Result<BinaryFileReader<'a>, BinaryFileReaderError> {
+        self.reader_at(segment.offset)?
+            .split_off_front(to_usize(segment.file_size))
+    }
+
+    /// A reader over the bytes of a section, empty for [`SHT_NOBITS`].
+    pub fn section_data(
+        &self,
+        section: &SectionHeader,
+    ) -> Result<BinaryFileReader<'a>, BinaryFileReaderError> {
+        let size = match section.section_type {
+            SHT_NOBITS => 0,
+            _ => to_usize(section.size),
+        };
+        self.reader_at(section.offset)?.split_off_front(size)
+    }
+
+    /// The NUL-terminated string at `index` in the string table `table`,
+    /// such as a symbol name in the table named by the `link` of a symbol
+    /// table.
+    pub fn string_at(
+        &self,
+        table: &SectionHeader,
+        index: u32,
+    ) -> Result<&'a [u8], BinaryFileReaderError> {
+        let mut strings = self.section_data(table)?;
+        let start = strings.current_offset();
+        strings.set_offset(start.saturating_add(index as usize))?;
+        let length = strings.iter().position(|b| b == 0).ok_or_else(|| {
+            BinaryFileReaderError::malformed("string is not terminated", strings.current_offset())
+        })?;
+        strings.read_slice(length)
+    }
+
+    /// The name of `section` in the section names string table.
+    pub fn section_name(&self, section: &SectionHeader) -> Result<&'a [u8], BinaryFileReaderError> {
+        let table = self.section_header(self.section_name_index)?;
+        self.string_at(&table, section.name)
+    }
+
+    /// The first section named `name`, such as `.text`.
+    pub fn section_by_name(
+        &self,
+        name: &[u8],
+    ) -> Result<Option<SectionHeader>, BinaryFileReaderError> {
+        let table = self.section_header(self.section_name_index)?;
+        for section in self.section_headers() {
+            let section = section?;
+            if self.string_at(&table, section.name)? == name {
+                return Ok(Some(section));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// The state shared by the header table iterators.
+#[derive(Debug, Clone)]
+struct Table<'a> {
+    // A reader at the start of the file.
+    data: BinaryFileReader<'a>,
+    class: Class,
+    offset: u64,
+    entry_size: u64,
+    remaining: u64,
+}
+
+impl<'a> Table<'a> {
+    fn next<T>(
+        &mut self,
+        read: fn(&mut BinaryFileReader<'a>, Class) -> Result<T, BinaryFileReaderError>,
+    ) -> Option<Result<T, BinaryFileReaderError>> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let entry = (|| {
+            let mut reader = self.data.clone();
+            let start = reader
+                .current_offset()
+                .saturating_add(to_usize(self.offset));
+            reader.set_offset(start)?;
+            let mut entry = reader.split_off_front(to_usize(self.entry_size))?;
+            read(&mut entry, self.class)
+        })();
+        self.offset = self.offset.saturating_add(self.entry_size);
+        self.remaining = match entry {
+            Ok(_) => self.remaining - 1,
+            Err(_) => 0,
+        };
+        Some(entry)
+    }
+}
+
+/// Iterates over program headers. Created with [`Elf::program_headers`].
+#[derive(Debug, Clone)]
+pub struct ProgramHeaderIter<'a> {
+    table: Table<'a>,
+}
+
+impl Iterator for ProgramHeaderIter<'_> {
+    type Item = Result<ProgramHeader, BinaryFileReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.table.next(read_program_header)
+    }
+}
+
+impl FusedIterator for ProgramHeaderIter<'_> {}
+
+/// Iterates over section headers. Created with [`Elf::section_headers`].
+#[derive(Debug, Clone)]
+pub struct SectionHeaderIter<'a> {
+    table: Table<'a>,
+}
+
+impl Iterator for SectionHeaderIter<'_> {
+    type Item = Result<SectionHeader, BinaryFileReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.table.next(read_section_header)
+    }
+}
+
+impl FusedIterator for SectionHeaderIter<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_ident, Class, Elf, ET_DYN, PT_LOAD, SHT_NOBITS, SHT_STRTAB};
+    use crate::{
+        endian::Endian,
+        error::{BinaryFileReaderError, ErrorCode},
+        BinaryFileReader,
+    };
+
+    // A 64-bit little-endian file with one segment and the sections
+    // `.shstrtab`, `.text` and `.bss`. `section_count` and `name_index` go in
+    // the file header, and the first section header holds the real values.
+    fn elf64(section_count: u16, name_index: u16) -> Vec<u8> {
+        let mut buffer = b"\x7fELF\x02\x01\x01\x03\0\0\0\0\0\0\0\0".to_vec();
+        buffer.extend(ET_DYN.to_le_bytes());
+        buffer.extend(0x3e_u16.to_le_bytes());
+        buffer.extend(1_u32.to_le_bytes());
+        for word in [0x1040_u64, 64, 120] {
+            buffer.extend(word.to_le_bytes());
+        }
+        buffer.extend(0_u32.to_le_bytes());
+        for half in [64_u16, 56, 1, 64, section_count, name_index] {
+            buffer.extend(half.to_le_bytes());
+        }
+
+        buffer.extend(PT_LOAD.to_le_bytes());
+        buffer.extend(5_u32.to_le_bytes());
+        for word in [0_u64, 0x1000, 0x1000, 120, 0x2000, 0x1000] {
+            buffer.extend(word.to_le_bytes());
+        }
+
+        let sections: [(u32, u32, u64, u64, u32); 4] = [
+            (0, 0, 0, 4, 1),
+            (1, SHT_STRTAB, 376, 22, 0),
+            (11, 1, 398, 4, 0),
+            (17, SHT_NOBITS, 0, 0x100, 0),
+        ];
+        for (name, section_type, offset, size, link) in sections {
+            buffer.extend(name.to_le_bytes());
+            buffer.extend(section_type.to_le_bytes());
+            buffer.extend([0; 16]);
+            buffer.extend(offset.to_le_bytes());
+            buffer.extend(size.to_le_bytes());
+            buffer.extend(link.to_le_bytes());
+            buffer.extend([0; 20]);
+        }
+        buffer.extend(b"\0.shstrtab\0.text\0.bss\0");
+        buffer.extend(b"\xc3\x90\x90\x90");
+        buffer
+    }
+
+    #[test]
+    fn test_elf64() -> Result<(), BinaryFileReaderError> {
+        let buffer = elf64(4, 1);
+        let elf = Elf::new(&BinaryFileReader::new(&buffer))?;
+        assert_eq!(elf.header.ident.class, Class::Elf64);
+        assert_eq!(elf.header.ident.os_abi, 3);
+        assert_eq!(elf.header.entry, 0x1040);
+
+        let segments = elf.program_headers().collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].flags, 5);
+        assert_eq!(segments[0].memory_size, 0x2000);
+        assert_eq!(elf.segment_data(&segments[0])?.available_bytes(), 120);
+
+        let sections = elf.section_headers().collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(sections.len(), 4);
+        assert_eq!(elf.section_name(&sections[3])?, b".bss");
+        assert_eq!(elf.section_data(&sections[3])?.available_bytes(), 0);
+        let mut text = elf.section_data(&sections[2])?;
+        assert_eq!(text.read_u8()?, 0xc3);
+        assert_eq!(elf.string_at(&sections[1], 12)?, b"text");
+        assert_eq!(
+            elf.string_at(&sections[1], 22).unwrap_err().code(),
+            ErrorCode::Malformed
+        );
+
+        let text = elf.section_by_name(b".text")?.unwrap();
+        assert_eq!(text.offset, 398);
+        assert!(elf.section_by_name(b".data")?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extended_numbering() -> Result<(), BinaryFileReaderError> {
+        let buffer = elf64(0, 0xffff);
+        let elf = Elf::new(&BinaryFileReader::new(&buffer))?;
+        assert_eq!(elf.section_count(), 4);
+        assert_eq!(elf.section_headers().count(), 4);
+        assert!(elf.section_by_name(b".bss")?.is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_elf() -> Result<(), BinaryFileReaderError> {
+        let mut reader = BinaryFileReader::new(b"\x7fELF\x01\x02\x01\0\0\0\0\0\0\0\0\0");
+        let ident = read_ident(&mut reader)?;
+        assert_eq!((ident.class, ident.endian), (Class::Elf32, Endian::Big));
+        assert_eq!(reader.endian(), Endian::Big);
+        assert_eq!(reader.available_bytes(), 0);
+
+        let err = read_ident(&mut BinaryFileReader::new(b"\x7fELF\x03\x01")).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::Malformed);
+        assert_eq!(err.offset(), Some(4));
+
+        // The section table runs past the end.
+        let mut buffer = elf64(4, 1);
+        buffer.truncate(300);
+        let elf = Elf::new(&BinaryFileReader::new(&buffer))?;
+        let mut sections = elf.section_headers();
+        assert!(sections.next().unwrap().is_ok());
+        assert!(sections.next().unwrap().is_ok());
+        assert_eq!(
+            sections.next().unwrap().unwrap_err().code(),
+            ErrorCode::BufferUnderflow
+        );
+        assert!(sections.next().is_none());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,299 @@
+//! MPEG transport streams, as in DVB and ATSC broadcasts and `.ts` files:
+//! 188-byte packets that start with the sync byte `0x47`, each carrying an
+//! optional adaptation field and part of a stream identified by its PID.
+//!
+//! Only available with the `mpegts` feature.
+//!
+//! # Examples
+//! ```
+//! # use binary_file_reader::BinaryFileReader;
+//! # use binary_file_reader::formats::mpegts::{self, PacketIter};
+//! # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut buffer = vec![0x47, 0x40, 0x00, 0x10, 0x00, 0x00, 0xb0, 0x0d];
+//! buffer.resize(188, 0xff);
+//!
+//! for packet in PacketIter::new(BinaryFileReader::new(&buffer)) {
+//!     let packet = packet?;
+//!     assert_eq!(packet.pid, mpegts::PAT_PID);
+//!     assert!(packet.payload_unit_start);
+//!
+//!     let mut payload = packet.payload.unwrap();
+//!     let pointer = payload.read_u8()?;
+//!     payload.read_slice(pointer as usize)?;
+//!     assert_eq!(payload.read_u8()?, 0x00);
+//! }
+//! #
+//! # Ok(())
+//! # }
+//! # fn main() {
+//! #    try_main().unwrap();
+//! # }
+//! ```
+
+use crate::{endian::Endian, error::BinaryFileReaderError, BinaryFileReader};
+use core::iter::FusedIterator;
+
+pub const PACKET_SIZE: usize = 188;
+pub const SYNC_BYTE: u8 = 0x47;
+
+/// The PID of the program association table.
+pub const PAT_PID: u16 = 0x0000;
+/// The PID of null packets, which pad the stream.
+pub const NULL_PID: u16 = 0x1fff;
+
+/// The adaptation field of a packet.
+#[derive(Debug, Clone)]
+pub struct AdaptationField<'a> {
+    pub discontinuity: bool,
+    pub random_access: bool,
+    /// The program clock reference in 27 MHz ticks.
+    pub pcr: Option<u64>,
+    /// A reader over the field after its length byte, starting with the
+    /// flags.
+    pub data: BinaryFileReader<'a>,
+}
+
+/// One transport stream packet.
+#[derive(Debug, Clone)]
+pub struct Packet<'a> {
+    /// The absolute offset of the sync byte.
+    pub offset: usize,
+    /// The number of bytes skipped to find this packet after the stream lost
+    /// sync.
+    pub skipped: usize,
+    pub transport_error: bool,
+    /// Whether a PES packet or section starts in the payload.
+    pub payload_unit_start: bool,
+    pub priority: bool,
+    pub pid: u16,
+    pub scrambling: u8,
+    pub continuity_counter: u8,
+    pub adaptation_field: Option<AdaptationField<'a>>,
+    /// A big-endian reader over the payload.
+    pub payload: Option<BinaryFileReader<'a>>,
+}
+
+fn read_adaptation_field<'a>(
+    mut data: BinaryFileReader<'a>,
+) -> Result<AdaptationField<'a>, BinaryFileReaderError> {
+    let mut field = AdaptationField {
+        discontinuity: false,
+        random_access: false,
+        pcr: None,
+        data: data.clone(),
+    };
+    if data.available_bytes() == 0 {
+        return Ok(field);
+    }
+    let flags = data.read_u8()?;
+    field.discontinuity = flags & 0x80 != 0;
+    field.random_access = flags & 0x40 != 0;
+    if flags & 0x10 != 0 {
+        // A 33-bit base at 90 kHz, 6 reserved bits and a 9-bit extension.
+        let bytes = data.read_slice(6)?;
+        let word = bytes.iter().fold(0_u64, |word, &b| (word << 8) | b as u64);
+        let base = word >> 15;
+        let extension = word & 0x1ff;
+        field.pcr = Some(base * 300 + extension);
+    }
+    Ok(field)
+}
+
+/// Reads the 188-byte packet at the cursor, which must start with the sync
+/// byte. On failure the reader is left at the start of the packet.
+pub fn read_packet<'a>(
+    reader: &mut BinaryFileReader<'a>,
+) -> Result<Packet<'a>, BinaryFileReaderError> {
+    let offset = reader.current_offset();
+    let result = (|| {
+        let mut packet = reader.split_off_front(PACKET_SIZE)?;
+        packet.set_endian(Endian::Big);
+        packet.expect(&[SYNC_BYTE])?;
+        let header = packet.read_u16()?;
+        let flags = packet.read_u8()?;
+
+        let adaptation_field = match flags & 0x20 {
+            0 => None,
+            _ => {
+                let length_offset = packet.current_offset();
+                let length = packet.read_u8()? as usize;
+                if length > packet.available_bytes() {
+                    return Err(BinaryFileReaderError::Malformed {
+                        reason: "adaptation field is longer than the packet",
+                        current_offset: length_offset,
+                    });
+                }
+                Some(read_adaptation_field(packet.split_off_front(length)?)?)
+            }
+        };
+        Ok(Packet {
+            offset,
+            skipped: 0,
+            transport_error: header & 0x8000 != 0,
+            payload_unit_start: header & 0x4000 != 0,
+            priority: header & 0x2000 != 0,
+            pid: header & 0x1fff,
+            scrambling: flags >> 6,
+            continuity_counter: flags & 0x0f,
+            adaptation_field,
+            payload: (flags & 0x10 != 0).then_some(packet),
+        })
+    })();
+    if result.is_err() {
+        reader.set_offset(offset)?;
+    }
+    result
+}
+
+/// Iterates over the packets of a transport stream.
+///
+/// When the byte at the cursor is not the sync byte, the stream has lost
+/// sync, and the next offset with a sync byte that is followed by another
+/// one a packet later, or by the end of the data, is taken as the next
+/// packet. A packet that cannot be read, or a lost sync that is not found
+/// again, is yielded as an error and ends the iteration.
+#[derive(Debug, Clone)]
+pub struct PacketIter<'a> {
+    reader: BinaryFileReader<'a>,
+    done: bool,
+}
+
+impl<'a> PacketIter<'a> {
+    /// Iterates over the packets at the cursor of `reader`, such as a whole
+    /// `.ts` file.
+    pub fn new(reader: BinaryFileReader<'a>) -> Self {
+        Self {
+            reader,
+            done: false,
+        }
+    }
+
+    /// The reader after the packets yielded so far.
+    pub fn into_inner(self) -> BinaryFileReader<'a> {
+        self.reader
+    }
+
+    /// Moves the reader to the next offset that looks like the start of a
+    /// packet and returns the number of bytes skipped.
+    fn resync(&mut self) -> Result<usize, BinaryFileReaderError> {
+        let bytes = self.reader.peek_slice(self.reader.available_bytes())?;
+        let skipped = (1..bytes.len())
+            .find(|&i| {
+                bytes[i] == SYNC_BYTE
+                    && bytes
+                        .get(i + PACKET_SIZE)
+                        .is_none_or(|&next| next == SYNC_BYTE)
+            })
+            .ok_or(BinaryFileReaderError::Malformed {
+                reason: "sync byte not found",
+                current_offset: self.reader.current_offset(),
+            })?;
+        self.reader.advance(skipped)?;
+        Ok(skipped)
+    }
+}
+
+impl<'a> Iterator for PacketIter<'a> {
+    type Item = Result<Packet<'a>, BinaryFileReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.reader.available_bytes() == 0 {
+            return None;
+        }
+
+        let mut skipped = 0;
+        if self.reader.peek_u8().ok() != Some(SYNC_BYTE) {
+            match self.resync() {
+                Ok(count) => skipped = count,
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+        let packet = read_packet(&mut self.reader).map(|packet| Packet { skipped, ..packet });
+        self.done = packet.is_err();
+        Some(packet)
+    }
+}
+
+impl FusedIterator for PacketIter<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_packet, PacketIter, NULL_PID, PACKET_SIZE};
+    use crate::{
+        error::{BinaryFileReaderError, ErrorCode},
+        BinaryFileReader,
+    };
+
+    fn packet(header: [u8; 4], adaptation_field: &[u8]) -> Vec<u8> {
+        let mut packet = vec![0x47];
+        packet.extend(&header[1..]);
+        if !adaptation_field.is_empty() {
+            packet.push(adaptation_field.len() as u8);
+            packet.extend(adaptation_field);
+        }
+        packet.resize(PACKET_SIZE, 0xaa);
+        packet
+    }
+
+    #[test]
+    fn test_packets() -> Result<(), BinaryFileReaderError> {
+        let mut buffer = packet([0x47, 0x01, 0x00, 0x17], &[]);
+        // Lost sync, with a stray sync byte in the junk.
+        buffer.extend([0x00, 0x47, 0x01]);
+        // A PCR of 1 second and an extension of 5, with no payload.
+        buffer.extend(packet(
+            [0x47, 0xe1, 0x00, 0x2a],
+            &[0xd0, 0x00, 0x00, 0xaf, 0xc8, 0x7e, 0x05],
+        ));
+        buffer.extend(packet([0x47, 0x1f, 0xff, 0x30], &[0x00]));
+
+        let packets =
+            PacketIter::new(BinaryFileReader::new(&buffer)).collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(packets.len(), 3);
+
+        assert_eq!((packets[0].pid, packets[0].continuity_counter), (0x100, 7));
+        assert!(packets[0].adaptation_field.is_none());
+        assert_eq!(packets[0].payload.as_ref().unwrap().available_bytes(), 184);
+
+        assert_eq!((packets[1].offset, packets[1].skipped), (191, 3));
+        assert!(packets[1].transport_error && packets[1].payload_unit_start);
+        assert!(packets[1].priority);
+        assert!(packets[1].payload.is_none());
+        let field = packets[1].adaptation_field.as_ref().unwrap();
+        assert!(field.discontinuity && field.random_access);
+        assert_eq!(field.pcr, Some(27_000_005));
+        assert_eq!(field.data.available_bytes(), 7);
+
+        assert_eq!(packets[2].pid, NULL_PID);
+        assert_eq!(packets[2].skipped, 0);
+        assert_eq!(packets[2].payload.as_ref().unwrap().available_bytes(), 182);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_packets() {
+        let mut buffer = packet([0x47, 0x00, 0x00, 0x30], &[]);
+        buffer[4] = 184;
+        let mut reader = BinaryFileReader::new(&buffer);
+        let err = read_packet(&mut reader).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::Malformed);
+        assert_eq!(err.offset(), Some(4));
+        assert_eq!(reader.current_offset(), 0);
+
+        let mut buffer = packet([0x47, 0x00, 0x00, 0x10], &[]);
+        buffer.truncate(100);
+        let mut packets = PacketIter::new(BinaryFileReader::new(&buffer));
+        let err = packets.next().unwrap().unwrap_err();
+        assert_eq!(err.code(), ErrorCode::BufferUnderflow);
+        assert!(packets.next().is_none());
+
+        let mut packets = PacketIter::new(BinaryFileReader::new(&[0x00; 400]));
+        let err = packets.next().unwrap().unwrap_err();
+        assert_eq!(err.code(), ErrorCode::Malformed);
+        assert!(packets.next().is_none());
+    }
+}
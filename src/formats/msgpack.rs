@@ -0,0 +1,241 @@
+//! MessagePack values one marker at a time: the marker byte and the payload
+//! that immediately follows it, with the elements of arrays and maps left
+//! at the cursor. This is enough to skip or index values embedded in other
+//! data without deserializing them.
+//!
+//! Only available with the `msgpack` feature.
+//!
+//! # Examples
+//! ```
+//! # use binary_file_reader::BinaryFileReader;
+//! # use binary_file_reader::formats::msgpack::{self, Marker};
+//! # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+//! // {"id": 300, "tags": ["a"]}, then nil.
+//! let buffer = b"\x82\xa2id\xcd\x01\x2c\xa4tags\x91\xa1a\xc0".to_vec();
+//! let mut reader = BinaryFileReader::new(&buffer);
+//!
+//! assert_eq!(msgpack::read_marker(&mut reader)?, Marker::Map(2));
+//! assert_eq!(msgpack::read_marker(&mut reader)?, Marker::Str(b"id"));
+//! assert_eq!(msgpack::read_marker(&mut reader)?, Marker::Uint(300));
+//! assert_eq!(msgpack::read_marker(&mut reader)?, Marker::Str(b"tags"));
+//!
+//! let tags = msgpack::split_value(&mut reader)?;
+//! assert_eq!(tags.available_bytes(), 3);
+//! assert_eq!(msgpack::read_marker(&mut reader)?, Marker::Nil);
+//! #
+//! # Ok(())
+//! # }
+//! # fn main() {
+//! #    try_main().unwrap();
+//! # }
+//! ```
+
+use crate::{error::BinaryFileReaderError, BinaryFileReader};
+
+/// A marker and its immediate payload.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Marker<'a> {
+    Nil,
+    Bool(bool),
+    /// A non-negative integer: a positive fixint or a `uint` type.
+    Uint(u64),
+    /// A negative fixint or an `int` type, which may still be non-negative.
+    Int(i64),
+    F32(f32),
+    F64(f64),
+    /// The bytes of a string, which should be UTF-8 but are not checked.
+    Str(&'a [u8]),
+    Bin(&'a [u8]),
+    /// An array header with the number of elements that follow.
+    Array(u32),
+    /// A map header with the number of key-value pairs that follow.
+    Map(u32),
+    /// An extension value with its application-defined type.
+    Ext {
+        type_id: i8,
+        data: &'a [u8],
+    },
+}
+
+impl Marker<'_> {
+    /// The number of values that follow the marker as part of its value: the
+    /// elements of an array, the keys and values of a map, and none for
+    /// everything else.
+    pub fn children(&self) -> u64 {
+        match *self {
+            Marker::Array(count) => count as u64,
+            Marker::Map(count) => 2 * count as u64,
+            _ => 0,
+        }
+    }
+}
+
+/// Reads a big-endian unsigned integer of `size` bytes, whatever the
+/// reader's byte order.
+fn read_be(reader: &mut BinaryFileReader<'_>, size: usize) -> Result<u64, BinaryFileReaderError> {
+    let bytes = reader.read_slice(size)?;
+    Ok(bytes
+        .iter()
+        .fold(0, |value, &byte| (value << 8) | byte as u64))
+}
+
+/// Reads `length` bytes after a length of `size` bytes.
+fn read_payload<'a>(
+    reader: &mut BinaryFileReader<'a>,
+    size: usize,
+) -> Result<&'a [u8], BinaryFileReaderError> {
+    let length = read_be(reader, size)? as usize;
+    reader.read_slice(length)
+}
+
+/// Reads the marker at the cursor and its payload, leaving the reader after
+/// them, at the first element of an array or map. On failure the reader is
+/// left at the marker.
+///
+/// Fails with [`BinaryFileReaderError::Malformed`] for the marker `0xc1`,
+/// which is never used.
+pub fn read_marker<'a>(
+    reader: &mut BinaryFileReader<'a>,
+) -> Result<Marker<'a>, BinaryFileReaderError> {
+    let offset = reader.current_offset();
+    let result = (|| {
+        let marker = match reader.read_u8()? {
+            byte @ 0x00..=0x7f => Marker::Uint(byte as u64),
+            byte @ 0x80..=0x8f => Marker::Map((byte & 0x0f) as u32),
+            byte @ 0x90..=0x9f => Marker::Array((byte & 0x0f) as u32),
+            byte @ 0xa0..=0xbf => Marker::Str(reader.read_slice((byte & 0x1f) as usize)?),
+            0xc0 => Marker::Nil,
+            0xc1 => {
+                return Err(BinaryFileReaderError::malformed(
+                    "marker 0xc1 is never used",
+                    offset,
+                ))
+            }
+            0xc2 => Marker::Bool(false),
+            0xc3 => Marker::Bool(true),
+            byte @ 0xc4..=0xc6 => Marker::Bin(read_payload(reader, 1 << (byte - 0xc4))?),
+            byte @ 0xc7..=0xc9 => {
+                let length = read_be(reader, 1 << (byte - 0xc7))? as usize;
+                let type_id = reader.read_u8()? as i8;
+                Marker::Ext {
+                    type_id,
+                    data: reader.read_slice(length)?,
+                }
+            }
+            0xca => Marker::F32(f32::from_bits(read_be(reader, 4)? as u32)),
+            0xcb => Marker::F64(f64::from_bits(read_be(reader, 8)?)),
+            byte @ 0xcc..=0xcf => Marker::Uint(read_be(reader, 1 << (byte - 0xcc))?),
+            0xd0 => Marker::Int(read_be(reader, 1)? as i8 as i64),
+            0xd1 => Marker::Int(read_be(reader, 2)? as i16 as i64),
+            0xd2 => Marker::Int(read_be(reader, 4)? as i32 as i64),
+            0xd3 => Marker::Int(read_be(reader, 8)? as i64),
+            byte @ 0xd4..=0xd8 => {
+                let type_id = reader.read_u8()? as i8;
+                Marker::Ext {
+                    type_id,
+                    data: reader.read_slice(1 << (byte - 0xd4))?,
+                }
+            }
+            byte @ 0xd9..=0xdb => Marker::Str(read_payload(reader, 1 << (byte - 0xd9))?),
+            0xdc => Marker::Array(read_be(reader, 2)? as u32),
+            0xdd => Marker::Array(read_be(reader, 4)? as u32),
+            0xde => Marker::Map(read_be(reader, 2)? as u32),
+            0xdf => Marker::Map(read_be(reader, 4)? as u32),
+            byte @ 0xe0..=0xff => Marker::Int(byte as i8 as i64),
+        };
+        Ok(marker)
+    })();
+    if result.is_err() {
+        reader.set_offset(offset)?;
+    }
+    result
+}
+
+/// Splits off the whole value at the cursor, including the elements of
+/// arrays and maps, as a reader over its encoded bytes. On failure the
+/// reader is left at the start of the value.
+///
+/// Nested values are counted rather than recursed into, so deeply nested
+/// data does not need [`max_depth`](BinaryFileReader::max_depth).
+pub fn split_value<'a>(
+    reader: &mut BinaryFileReader<'a>,
+) -> Result<BinaryFileReader<'a>, BinaryFileReaderError> {
+    let offset = reader.current_offset();
+    let mut probe = reader.clone();
+    let mut remaining = 1_u64;
+    while remaining > 0 {
+        remaining = remaining - 1 + read_marker(&mut probe)?.children();
+    }
+    reader.split_off_front(probe.current_offset() - offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_marker, split_value, Marker};
+    use crate::{
+        endian::Endian,
+        error::{BinaryFileReaderError, ErrorCode},
+        BinaryFileReader,
+    };
+
+    #[test]
+    fn test_read_marker() -> Result<(), BinaryFileReaderError> {
+        let buffer = [
+            0xff, 0xd0, 0x80, 0xd1, 0x7f, 0xff, 0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xca, 0x3f, 0xc0, 0x00, 0x00, 0xc3, 0xc4, 0x02, 0x01, 0x02, 0xd9, 0x01, b'x',
+            0xd5, 0xfe, 0xaa, 0xbb, 0xc8, 0x00, 0x01, 0x05, 0xcc, 0xdd, 0x00, 0x01, 0x00, 0x00,
+        ];
+        let mut reader = BinaryFileReader::new(&buffer);
+        // Multi-byte payloads are big-endian whatever the reader's order is.
+        reader.set_endian(Endian::Little);
+
+        let expected = [
+            Marker::Int(-1),
+            Marker::Int(-128),
+            Marker::Int(0x7fff),
+            Marker::Uint(u64::MAX),
+            Marker::F32(1.5),
+            Marker::Bool(true),
+            Marker::Bin(&[0x01, 0x02]),
+            Marker::Str(b"x"),
+            Marker::Ext {
+                type_id: -2,
+                data: &[0xaa, 0xbb],
+            },
+            Marker::Ext {
+                type_id: 5,
+                data: &[0xcc],
+            },
+            Marker::Array(0x10000),
+        ];
+        for marker in expected {
+            assert_eq!(read_marker(&mut reader)?, marker);
+        }
+        assert_eq!(reader.available_bytes(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_value() -> Result<(), BinaryFileReaderError> {
+        // [{1: [nil]}, "ab"], then true.
+        let buffer = b"\x92\x81\x01\x91\xc0\xa2ab\xc3";
+        let mut reader = BinaryFileReader::new(buffer);
+        let value = split_value(&mut reader)?;
+        assert_eq!((value.current_offset(), value.available_bytes()), (0, 8));
+        assert_eq!(read_marker(&mut reader)?, Marker::Bool(true));
+
+        // A truncated array, and the marker that is never used.
+        let mut reader = BinaryFileReader::new(b"\x92\xc0");
+        let err = split_value(&mut reader).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::BufferUnderflow);
+        assert_eq!(reader.current_offset(), 0);
+
+        let mut reader = BinaryFileReader::new(b"\x91\xc1");
+        let err = split_value(&mut reader).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::Malformed);
+        assert_eq!(err.offset(), Some(1));
+
+        Ok(())
+    }
+}
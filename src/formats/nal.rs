@@ -0,0 +1,352 @@
+//! H.264 and H.265 NAL units: splitting Annex B byte streams at their start
+//! codes, and removing the emulation prevention bytes that keep start codes
+//! out of a NAL unit's payload.
+//!
+//! Inside a NAL unit, an encoder inserts `0x03` after every two zero bytes
+//! that would otherwise be followed by a byte of `0x03` or less. Syntax
+//! elements are read from the payload with those bytes removed, the raw
+//! byte sequence payload (RBSP), which [`Nal::rbsp`] and [`RbspReader`]
+//! produce.
+//!
+//! Only available with the `nal` feature.
+//!
+//! # Examples
+//! ```
+//! # use binary_file_reader::BinaryFileReader;
+//! # use binary_file_reader::formats::nal::{self, AnnexBIter};
+//! # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+//! // A sequence parameter set and the start of an IDR slice.
+//! let buffer = [
+//!     0x00, 0x00, 0x00, 0x01, 0x67, 0x42, 0x00, 0x00, 0x03, 0x01,
+//!     0x00, 0x00, 0x01, 0x65, 0x88, 0x84,
+//! ];
+//! let mut nals = AnnexBIter::new(BinaryFileReader::new(&buffer));
+//!
+//! let sps = nals.next().unwrap();
+//! assert_eq!(sps.h264_type(), nal::H264_SPS);
+//! assert_eq!(sps.data.available_bytes(), 6);
+//! let mut rbsp = sps.rbsp()?;
+//! assert_eq!(rbsp.reader().read_slice(5)?, [0x67, 0x42, 0x00, 0x00, 0x01]);
+//!
+//! assert_eq!(nals.next().unwrap().h264_type(), nal::H264_IDR_SLICE);
+//! assert!(nals.next().is_none());
+//! #
+//! # Ok(())
+//! # }
+//! # fn main() {
+//! #    try_main().unwrap();
+//! # }
+//! ```
+
+use crate::{
+    endian::Endian, error::BinaryFileReaderError, owned::OwnedBinaryFileReader, source::DataSource,
+    BinaryFileReader,
+};
+use alloc::vec::Vec;
+use core::iter::FusedIterator;
+
+pub const H264_NON_IDR_SLICE: u8 = 1;
+pub const H264_IDR_SLICE: u8 = 5;
+pub const H264_SEI: u8 = 6;
+pub const H264_SPS: u8 = 7;
+pub const H264_PPS: u8 = 8;
+pub const H264_AUD: u8 = 9;
+
+pub const H265_IDR_W_RADL: u8 = 19;
+pub const H265_IDR_N_LP: u8 = 20;
+pub const H265_VPS: u8 = 32;
+pub const H265_SPS: u8 = 33;
+pub const H265_PPS: u8 = 34;
+pub const H265_AUD: u8 = 35;
+
+/// Removes emulation prevention bytes one byte at a time, counting the zero
+/// bytes before the current one.
+#[derive(Debug, Clone, Default)]
+struct Unescaper {
+    zeros: usize,
+}
+
+impl Unescaper {
+    /// Returns the byte unless it is an emulation prevention byte.
+    fn push(&mut self, byte: u8) -> Option<u8> {
+        if self.zeros >= 2 && byte == 0x03 {
+            self.zeros = 0;
+            return None;
+        }
+        self.zeros = match byte {
+            0 => self.zeros + 1,
+            _ => 0,
+        };
+        Some(byte)
+    }
+}
+
+/// One NAL unit of an Annex B byte stream.
+#[derive(Debug, Clone)]
+pub struct Nal<'a> {
+    /// The absolute offset of the NAL unit header, after the start code.
+    pub offset: usize,
+    /// A reader over the NAL unit, starting with its header, with emulation
+    /// prevention bytes still in place.
+    pub data: BinaryFileReader<'a>,
+}
+
+impl Nal<'_> {
+    /// The first byte of the NAL unit, or 0 for an empty one.
+    fn first_byte(&self) -> u8 {
+        self.data.peek_u8().unwrap_or(0)
+    }
+
+    /// The `nal_unit_type` from the one-byte H.264 header.
+    #[inline]
+    pub fn h264_type(&self) -> u8 {
+        self.first_byte() & 0x1f
+    }
+
+    /// The `nal_ref_idc` from the one-byte H.264 header.
+    #[inline]
+    pub fn h264_ref_idc(&self) -> u8 {
+        (self.first_byte() >> 5) & 0x03
+    }
+
+    /// The `nal_unit_type` from the two-byte H.265 header.
+    #[inline]
+    pub fn h265_type(&self) -> u8 {
+        (self.first_byte() >> 1) & 0x3f
+    }
+
+    /// Copies the NAL unit into an owned reader with the emulation prevention
+    /// bytes removed. The header is included, so H.264 syntax elements start
+    /// one byte in and H.265 ones two bytes in.
+    ///
+    /// Fails if the NAL unit is larger than the
+    /// [allocation limit](BinaryFileReader::set_max_alloc).
+    pub fn rbsp(&self) -> Result<OwnedBinaryFileReader, BinaryFileReaderError> {
+        let bytes = self.data.peek_slice(self.data.available_bytes())?;
+        self.data.check_alloc(bytes.len())?;
+        let mut unescaper = Unescaper::default();
+        let rbsp = bytes
+            .iter()
+            .filter_map(|&byte| unescaper.push(byte))
+            .collect();
+        Ok(OwnedBinaryFileReader::derived(rbsp, &self.data))
+    }
+}
+
+/// Iterates over the NAL units of an Annex B byte stream, each following a
+/// three- or four-byte start code.
+///
+/// Bytes before the first start code are skipped, and the zero bytes that
+/// may trail a NAL unit before the next start code are not part of it.
+/// Without a start code in the remaining data, the iteration ends.
+#[derive(Debug, Clone)]
+pub struct AnnexBIter<'a> {
+    reader: BinaryFileReader<'a>,
+}
+
+impl<'a> AnnexBIter<'a> {
+    /// Iterates over the NAL units at the cursor of `reader`, such as a whole
+    /// `.h264` or `.h265` file.
+    pub fn new(reader: BinaryFileReader<'a>) -> Self {
+        Self { reader }
+    }
+
+    /// The reader after the NAL units yielded so far, at the start code of the
+    /// next one.
+    pub fn into_inner(self) -> BinaryFileReader<'a> {
+        self.reader
+    }
+}
+
+fn find_start_code(bytes: &[u8]) -> Option<usize> {
+    bytes
+        .windows(3)
+        .position(|window| window == [0x00, 0x00, 0x01])
+}
+
+impl<'a> Iterator for AnnexBIter<'a> {
+    type Item = Nal<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let bytes = self.reader.peek_slice(self.reader.available_bytes()).ok()?;
+            let Some(start) = find_start_code(bytes).map(|index| index + 3) else {
+                self.reader.advance(bytes.len()).ok()?;
+                return None;
+            };
+            let next = find_start_code(&bytes[start..]).map_or(bytes.len(), |index| start + index);
+            let end = bytes[start..next]
+                .iter()
+                .rposition(|&byte| byte != 0)
+                .map_or(start, |index| start + index + 1);
+
+            self.reader.advance(start).ok()?;
+            let offset = self.reader.current_offset();
+            let data = self.reader.split_off_front(end - start).ok()?;
+            self.reader.advance(next - end).ok()?;
+            if end > start {
+                return Some(Nal { offset, data });
+            }
+        }
+    }
+}
+
+impl FusedIterator for AnnexBIter<'_> {}
+
+/// A [`DataSource`] over the RBSP of a NAL unit read from another source,
+/// removing emulation prevention bytes as it reads, so that a stream need
+/// not be split into NAL units first.
+///
+/// Offsets are those of the RBSP. The inner source should hold a single NAL
+/// unit, such as one split off by its length prefix in MP4 samples, as start
+/// codes are not recognised.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::BinaryFileReader;
+/// # use binary_file_reader::formats::nal::RbspReader;
+/// # use binary_file_reader::source::DataSource;
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// let sample = [0x00, 0x00, 0x00, 0x05, 0x06, 0x00, 0x00, 0x03, 0x02, 0x80];
+/// let mut reader = BinaryFileReader::new(&sample);
+/// let length = reader.read_u32()? as usize;
+/// let mut rbsp = RbspReader::new(reader.split_off_front(length)?);
+///
+/// assert_eq!(rbsp.read_u8()?, 0x06);
+/// assert_eq!(rbsp.read_u16()?, 0);
+/// assert_eq!(rbsp.read_u8()?, 0x02);
+/// assert_eq!(rbsp.current_offset(), 4);
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RbspReader<S> {
+    inner: S,
+    unescaper: Unescaper,
+    // The RBSP bytes at the cursor, not consumed yet.
+    decoded: Vec<u8>,
+    offset: usize,
+    endian: Endian,
+}
+
+impl<S: DataSource> RbspReader<S> {
+    /// A reader in the byte order of `inner`.
+    pub fn new(inner: S) -> Self {
+        let endian = inner.endian();
+        Self {
+            inner,
+            unescaper: Unescaper::default(),
+            decoded: Vec::new(),
+            offset: 0,
+            endian,
+        }
+    }
+
+    #[inline]
+    pub fn set_endian(&mut self, endian: Endian) {
+        self.endian = endian;
+    }
+
+    /// Returns the inner source, positioned after the bytes read so far.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: DataSource> DataSource for RbspReader<S> {
+    fn fill(&mut self, length: usize) -> Result<&[u8], BinaryFileReaderError> {
+        while self.decoded.len() < length {
+            let wanted = length - self.decoded.len();
+            let raw = self.inner.fill(wanted.min(self.inner.window_size()))?;
+            if raw.is_empty() {
+                break;
+            }
+
+            let used = wanted.min(raw.len());
+            let unescaper = &mut self.unescaper;
+            self.decoded
+                .extend(raw[..used].iter().filter_map(|&byte| unescaper.push(byte)));
+            self.inner.consume(used);
+        }
+
+        Ok(&self.decoded)
+    }
+
+    fn consume(&mut self, length: usize) {
+        self.decoded.drain(..length);
+        self.offset += length;
+    }
+
+    #[inline]
+    fn current_offset(&self) -> usize {
+        self.offset
+    }
+
+    #[inline]
+    fn endian(&self) -> Endian {
+        self.endian
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AnnexBIter, RbspReader, H265_VPS};
+    use crate::{error::BinaryFileReaderError, source::DataSource, BinaryFileReader};
+
+    #[test]
+    fn test_annex_b() -> Result<(), BinaryFileReaderError> {
+        let buffer = [
+            0xff, 0x00, 0x00, 0x01, 0x40, 0x01, 0x0c, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00,
+            0x01, 0x00, 0x00, 0x01, 0x42, 0x01, 0x00, 0x00, 0x03, 0x00, 0x00, 0x03, 0x03,
+        ];
+        let nals = AnnexBIter::new(BinaryFileReader::new(&buffer)).collect::<Vec<_>>();
+        assert_eq!(nals.len(), 2);
+
+        // Trailing zeros and the four-byte start code's zero are dropped.
+        assert_eq!(nals[0].offset, 4);
+        assert_eq!(nals[0].h265_type(), H265_VPS);
+        assert_eq!(nals[0].data.available_bytes(), 3);
+
+        // An empty NAL unit between start codes is skipped.
+        assert_eq!(nals[1].offset, 18);
+        assert_eq!(nals[1].data.available_bytes(), 9);
+        let mut rbsp = nals[1].rbsp()?;
+        assert_eq!(
+            rbsp.reader().read_slice(7)?,
+            [0x42, 0x01, 0x00, 0x00, 0x00, 0x00, 0x03]
+        );
+
+        let mut nals = AnnexBIter::new(BinaryFileReader::new(&[0x00, 0x00, 0x02, 0x01]));
+        assert!(nals.next().is_none());
+        assert_eq!(nals.into_inner().available_bytes(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rbsp_reader() -> Result<(), BinaryFileReaderError> {
+        let buffer = [0x00, 0x00, 0x03, 0x01, 0x00, 0x00, 0x03, 0x00, 0x03, 0xaa];
+        let mut reader = BinaryFileReader::new(&buffer);
+        let mut rbsp = RbspReader::new(&mut reader);
+        assert_eq!(rbsp.read_u32()?, 0x0000_0100);
+        assert_eq!(rbsp.read_u16()?, 0x0000);
+        assert_eq!(rbsp.read_u16()?, 0x03aa);
+        assert!(rbsp.is_at_end()?);
+        assert_eq!(rbsp.current_offset(), 8);
+        assert_eq!(reader.available_bytes(), 0);
+
+        // Zeros split across reads are still counted.
+        let buffer = [0x00, 0x00, 0x03, 0x02];
+        let mut rbsp = RbspReader::new(BinaryFileReader::new(&buffer));
+        assert_eq!(rbsp.read_u8()?, 0x00);
+        assert_eq!(rbsp.read_u8()?, 0x00);
+        assert_eq!(rbsp.read_u8()?, 0x02);
+        assert!(rbsp.read_u8().is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,563 @@
+//! Packet captures in the classic pcap format and in pcapng, as written by
+//! tcpdump and Wireshark.
+//!
+//! The byte order comes from the magic number of a pcap file and from the
+//! section header blocks of a pcapng file, and the timestamp resolution from
+//! the magic number or the `if_tsresol` option of each interface.
+//!
+//! Only available with the `pcap` feature.
+//!
+//! # Examples
+//! ```
+//! # use binary_file_reader::BinaryFileReader;
+//! # use binary_file_reader::formats::pcap::PacketIter;
+//! # use std::time::Duration;
+//! # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+//! // A little-endian pcap file with nanosecond timestamps and one packet.
+//! let mut buffer = b"\x4d\x3c\xb2\xa1\x02\0\x04\0\0\0\0\0\0\0\0\0\xff\xff\0\0\x01\0\0\0".to_vec();
+//! buffer.extend(b"\x0a\0\0\0\xe8\x03\0\0\x02\0\0\0\x3c\0\0\0\x08\x00");
+//!
+//! let mut packets = PacketIter::new(BinaryFileReader::new(&buffer))?;
+//! let mut packet = packets.next().unwrap()?;
+//! assert_eq!(packet.link_type, 1);
+//! assert_eq!(packet.timestamp, Some(Duration::new(10, 1000)));
+//! assert_eq!(packet.original_len, 60);
+//! assert_eq!(packet.data.read_u16()?, 0x0800);
+//! assert!(packets.next().is_none());
+//! #
+//! # Ok(())
+//! # }
+//! # fn main() {
+//! #    try_main().unwrap();
+//! # }
+//! ```
+
+use crate::{endian::Endian, error::BinaryFileReaderError, BinaryFileReader};
+use alloc::vec::Vec;
+use core::{iter::FusedIterator, time::Duration};
+
+/// The type of pcapng section header blocks.
+pub const SECTION_HEADER_BLOCK: u32 = 0x0a0d_0d0a;
+/// The type of pcapng interface description blocks.
+pub const INTERFACE_DESCRIPTION_BLOCK: u32 = 0x0000_0001;
+/// The type of the obsolete pcapng packet blocks.
+pub const PACKET_BLOCK: u32 = 0x0000_0002;
+/// The type of pcapng simple packet blocks, which have no timestamp.
+pub const SIMPLE_PACKET_BLOCK: u32 = 0x0000_0003;
+/// The type of pcapng enhanced packet blocks.
+pub const ENHANCED_PACKET_BLOCK: u32 = 0x0000_0006;
+
+const PCAP_MICROS: &[u8; 4] = b"\xd4\xc3\xb2\xa1";
+const PCAP_MICROS_BE: &[u8; 4] = b"\xa1\xb2\xc3\xd4";
+const PCAP_NANOS: &[u8; 4] = b"\x4d\x3c\xb2\xa1";
+const PCAP_NANOS_BE: &[u8; 4] = b"\xa1\xb2\x3c\x4d";
+const PCAPNG: &[u8; 4] = b"\x0a\x0d\x0d\x0a";
+
+/// One pcapng block.
+#[derive(Debug, Clone)]
+pub struct Block<'a> {
+    pub block_type: u32,
+    /// The absolute offset of the block.
+    pub offset: usize,
+    /// A reader over the block body, in the section's byte order.
+    pub body: BinaryFileReader<'a>,
+}
+
+/// Reads the pcapng block at the cursor. A section header block switches
+/// `reader` to the byte order of its byte-order magic. On failure the reader
+/// is left at the start of the block.
+pub fn read_block<'a>(
+    reader: &mut BinaryFileReader<'a>,
+) -> Result<Block<'a>, BinaryFileReaderError> {
+    let offset = reader.current_offset();
+    let saved = reader.endian();
+    let result = (|| {
+        if reader.matches(PCAPNG) {
+            let mut magic = reader.clone();
+            magic.advance(8)?;
+            let endian = match magic.expect_one_of(&[b"\x1a\x2b\x3c\x4d", b"\x4d\x3c\x2b\x1a"])? {
+                0 => Endian::Big,
+                _ => Endian::Little,
+            };
+            reader.set_endian(endian);
+        }
+
+        let block_type = reader.read_u32()?;
+        let length = reader.read_u32()? as usize;
+        if length < 12 || !length.is_multiple_of(4) {
+            return Err(BinaryFileReaderError::malformed(
+                "block length is invalid",
+                offset + 4,
+            ));
+        }
+        let body = reader.split_off_front(length - 12)?;
+        if reader.read_u32()? as usize != length {
+            return Err(BinaryFileReaderError::malformed(
+                "block lengths differ",
+                offset + length - 4,
+            ));
+        }
+        Ok(Block {
+            block_type,
+            offset,
+            body,
+        })
+    })();
+    if result.is_err() {
+        reader.set_endian(saved);
+        reader.set_offset(offset)?;
+    }
+    result
+}
+
+/// Iterates over the blocks of a pcapng file. A block that cannot be read
+/// is yielded as an error and ends the iteration.
+#[derive(Debug, Clone)]
+pub struct BlockIter<'a> {
+    reader: BinaryFileReader<'a>,
+    done: bool,
+}
+
+impl<'a> BlockIter<'a> {
+    /// Iterates over the blocks at the cursor of `reader`, which should start
+    /// with a section header block.
+    pub fn new(reader: BinaryFileReader<'a>) -> Self {
+        Self {
+            reader,
+            done: false,
+        }
+    }
+
+    /// The reader after the blocks yielded so far.
+    pub fn into_inner(self) -> BinaryFileReader<'a> {
+        self.reader
+    }
+}
+
+impl<'a> Iterator for BlockIter<'a> {
+    type Item = Result<Block<'a>, BinaryFileReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.reader.available_bytes() == 0 {
+            return None;
+        }
+
+        let block = read_block(&mut self.reader);
+        self.done = block.is_err();
+        Some(block)
+    }
+}
+
+impl FusedIterator for BlockIter<'_> {}
+
+/// One captured packet.
+#[derive(Debug, Clone)]
+pub struct Packet<'a> {
+    /// The absolute offset of the record or block.
+    pub offset: usize,
+    /// The capturing interface, always 0 in pcap files.
+    pub interface: u32,
+    /// The link-layer header type of the interface, such as 1 for Ethernet.
+    pub link_type: u32,
+    /// The time since the Unix epoch, or `None` for simple packet blocks.
+    pub timestamp: Option<Duration>,
+    /// The length of the packet on the wire, which is more than the captured
+    /// data if the capture was truncated.
+    pub original_len: u32,
+    /// A big-endian reader over the captured data.
+    pub data: BinaryFileReader<'a>,
+}
+
+/// A pcapng interface, from its description block.
+#[derive(Debug, Clone, Copy)]
+struct Interface {
+    link_type: u32,
+    snap_len: u32,
+    units_per_second: u64,
+}
+
+#[derive(Debug, Clone)]
+enum Format {
+    Pcap { link_type: u32, nanos: bool },
+    Pcapng { interfaces: Vec<Interface> },
+}
+
+/// Iterates over the packets of a pcap or pcapng file, skipping pcapng
+/// blocks other than packets. A record that cannot be read is yielded as an
+/// error and ends the iteration.
+#[derive(Debug, Clone)]
+pub struct PacketIter<'a> {
+    reader: BinaryFileReader<'a>,
+    format: Format,
+    done: bool,
+}
+
+impl<'a> PacketIter<'a> {
+    /// Detects the format from the magic number at the cursor of `reader`,
+    /// reading the file header of a pcap file. Fails with
+    /// [`BinaryFileReaderError::ExpectOneOf`] for other files.
+    pub fn new(mut reader: BinaryFileReader<'a>) -> Result<Self, BinaryFileReaderError> {
+        let start = reader.current_offset();
+        let magics: [&[u8]; 5] = [
+            PCAP_MICROS,
+            PCAP_MICROS_BE,
+            PCAP_NANOS,
+            PCAP_NANOS_BE,
+            PCAPNG,
+        ];
+        let format = match reader.expect_one_of(&magics)? {
+            4 => {
+                reader.set_offset(start)?;
+                Format::Pcapng {
+                    interfaces: Vec::new(),
+                }
+            }
+            magic => {
+                reader.set_endian(match magic % 2 {
+                    0 => Endian::Little,
+                    _ => Endian::Big,
+                });
+                // The version, time zone, timestamp accuracy and snapshot
+                // length.
+                reader.advance(16)?;
+                Format::Pcap {
+                    link_type: reader.read_u32()?,
+                    nanos: magic >= 2,
+                }
+            }
+        };
+        Ok(Self {
+            reader,
+            format,
+            done: false,
+        })
+    }
+
+    /// The reader after the packets yielded so far.
+    pub fn into_inner(self) -> BinaryFileReader<'a> {
+        self.reader
+    }
+
+    fn read_record(
+        &mut self,
+        link_type: u32,
+        nanos: bool,
+    ) -> Result<Packet<'a>, BinaryFileReaderError> {
+        let reader = &mut self.reader;
+        let offset = reader.current_offset();
+        let result = (|| {
+            let seconds = Duration::from_secs(reader.read_u32()? as u64);
+            let fraction = reader.read_u32()? as u64;
+            let captured_len = reader.read_u32()? as usize;
+            let original_len = reader.read_u32()?;
+            let fraction = match nanos {
+                true => Duration::from_nanos(fraction),
+                false => Duration::from_micros(fraction),
+            };
+            Ok(Packet {
+                offset,
+                interface: 0,
+                link_type,
+                timestamp: Some(seconds + fraction),
+                original_len,
+                data: network_order(reader.split_off_front(captured_len)?),
+            })
+        })();
+        if result.is_err() {
+            reader.set_offset(offset)?;
+        }
+        result
+    }
+
+    /// Reads blocks up to the next packet, recording the interfaces on the
+    /// way.
+    fn read_packet_block(&mut self) -> Option<Result<Packet<'a>, BinaryFileReaderError>> {
+        let Format::Pcapng { interfaces } = &mut self.format else {
+            return None;
+        };
+        while self.reader.available_bytes() > 0 {
+            let block = match read_block(&mut self.reader) {
+                Ok(block) => block,
+                Err(err) => return Some(Err(err)),
+            };
+            let packet = match block.block_type {
+                SECTION_HEADER_BLOCK => {
+                    interfaces.clear();
+                    continue;
+                }
+                INTERFACE_DESCRIPTION_BLOCK => match read_interface(block.body) {
+                    Ok(interface) => {
+                        interfaces.push(interface);
+                        continue;
+                    }
+                    Err(err) => Err(err),
+                },
+                ENHANCED_PACKET_BLOCK | PACKET_BLOCK | SIMPLE_PACKET_BLOCK => {
+                    read_packet(&block, interfaces)
+                }
+                _ => continue,
+            };
+            return Some(packet);
+        }
+        None
+    }
+}
+
+/// Switches captured data to network byte order.
+fn network_order(mut data: BinaryFileReader<'_>) -> BinaryFileReader<'_> {
+    data.set_endian(Endian::Big);
+    data
+}
+
+/// Reads the link type, snapshot length and timestamp resolution of an
+/// interface description block.
+fn read_interface(mut body: BinaryFileReader<'_>) -> Result<Interface, BinaryFileReaderError> {
+    let link_type = body.read_u16()? as u32;
+    body.advance(2)?;
+    let snap_len = body.read_u32()?;
+    let mut units_per_second = 1_000_000;
+
+    while body.available_bytes() >= 4 {
+        let code = body.read_u16()?;
+        let length = body.read_u16()? as usize;
+        let value_offset = body.current_offset();
+        let value = body.read_slice(length)?;
+        body.advance((4 - length % 4) % 4)?;
+        match code {
+            0 => break,
+            // if_tsresol: a power of ten, or of two if the high bit is set.
+            9 if length == 1 => {
+                let exponent = (value[0] & 0x7f) as u32;
+                units_per_second = match value[0] & 0x80 {
+                    0 => 10_u64.checked_pow(exponent),
+                    _ => 2_u64.checked_pow(exponent),
+                }
+                .ok_or_else(|| {
+                    BinaryFileReaderError::malformed(
+                        "timestamp resolution is too fine",
+                        value_offset,
+                    )
+                })?;
+            }
+            _ => {}
+        }
+    }
+    Ok(Interface {
+        link_type,
+        snap_len,
+        units_per_second,
+    })
+}
+
+fn read_packet<'a>(
+    block: &Block<'a>,
+    interfaces: &[Interface],
+) -> Result<Packet<'a>, BinaryFileReaderError> {
+    let mut body = block.body.clone();
+    let unknown_interface =
+        BinaryFileReaderError::malformed("packet names an unknown interface", block.offset + 8);
+
+    if block.block_type == SIMPLE_PACKET_BLOCK {
+        let interface = interfaces.first().ok_or(unknown_interface)?;
+        let original_len = body.read_u32()?;
+        let mut captured_len = (original_len as usize).min(body.available_bytes());
+        if interface.snap_len != 0 {
+            captured_len = captured_len.min(interface.snap_len as usize);
+        }
+        return Ok(Packet {
+            offset: block.offset,
+            interface: 0,
+            link_type: interface.link_type,
+            timestamp: None,
+            original_len,
+            data: network_order(body.split_off_front(captured_len)?),
+        });
+    }
+
+    let index = match block.block_type {
+        PACKET_BLOCK => {
+            let index = body.read_u16()? as u32;
+            // The drop count.
+            body.advance(2)?;
+            index
+        }
+        _ => body.read_u32()?,
+    };
+    let interface = interfaces.get(index as usize).ok_or(unknown_interface)?;
+    let timestamp = (body.read_u32()? as u64) << 32 | body.read_u32()? as u64;
+    let captured_len = body.read_u32()? as usize;
+    let original_len = body.read_u32()?;
+
+    let units = interface.units_per_second;
+    let nanos = (timestamp % units) as u128 * 1_000_000_000 / units as u128;
+    Ok(Packet {
+        offset: block.offset,
+        interface: index,
+        link_type: interface.link_type,
+        timestamp: Some(Duration::new(timestamp / units, nanos as u32)),
+        original_len,
+        data: network_order(body.split_off_front(captured_len)?),
+    })
+}
+
+impl<'a> Iterator for PacketIter<'a> {
+    type Item = Result<Packet<'a>, BinaryFileReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.reader.available_bytes() == 0 {
+            return None;
+        }
+
+        let packet = match self.format {
+            Format::Pcap { link_type, nanos } => Some(self.read_record(link_type, nanos)),
+            Format::Pcapng { .. } => self.read_packet_block(),
+        };
+        self.done = !matches!(packet, Some(Ok(_)));
+        packet
+    }
+}
+
+impl FusedIterator for PacketIter<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_block, BlockIter, PacketIter, ENHANCED_PACKET_BLOCK, SECTION_HEADER_BLOCK};
+    use crate::{
+        endian::Endian,
+        error::{BinaryFileReaderError, ErrorCode},
+        BinaryFileReader,
+    };
+    use core::time::Duration;
+
+    fn block(buffer: &mut Vec<u8>, endian: Endian, block_type: u32, body: &[u8]) {
+        let length = 12 + body.len() as u32;
+        let bytes = |value: u32| match endian {
+            Endian::Big => value.to_be_bytes(),
+            Endian::Little => value.to_le_bytes(),
+        };
+        buffer.extend(bytes(block_type));
+        buffer.extend(bytes(length));
+        buffer.extend(body);
+        buffer.extend(bytes(length));
+    }
+
+    #[test]
+    fn test_pcapng() -> Result<(), BinaryFileReaderError> {
+        let mut buffer = Vec::new();
+        block(
+            &mut buffer,
+            Endian::Big,
+            SECTION_HEADER_BLOCK,
+            b"\x1a\x2b\x3c\x4d\0\x01\0\0\xff\xff\xff\xff\xff\xff\xff\xff",
+        );
+        // Ethernet with nanosecond timestamps, then a truncating interface.
+        block(
+            &mut buffer,
+            Endian::Big,
+            1,
+            b"\0\x01\0\0\0\0\0\0\0\x09\0\x01\x09\0\0\0\0\0\0\0",
+        );
+        block(&mut buffer, Endian::Big, 1, b"\0\x65\0\0\0\0\0\x02");
+        block(&mut buffer, Endian::Big, 0x0bad, b"");
+        block(
+            &mut buffer,
+            Endian::Big,
+            ENHANCED_PACKET_BLOCK,
+            b"\0\0\0\0\0\0\0\x01\x00\x00\x00\x05\0\0\0\x03\0\0\0\x40\xaa\xbb\xcc\0",
+        );
+        block(&mut buffer, Endian::Big, 3, b"\0\0\0\x40\x01\x02\x03\x04");
+        // A little-endian section with a microsecond interface.
+        block(
+            &mut buffer,
+            Endian::Little,
+            SECTION_HEADER_BLOCK,
+            b"\x4d\x3c\x2b\x1a\x01\0\0\0\xff\xff\xff\xff\xff\xff\xff\xff",
+        );
+        block(&mut buffer, Endian::Little, 1, b"\x71\0\0\0\0\0\0\0");
+        block(
+            &mut buffer,
+            Endian::Little,
+            2,
+            b"\0\0\0\0\0\0\0\0\x40\x42\x0f\0\x01\0\0\0\x01\0\0\0\xee\0\0\0",
+        );
+
+        assert_eq!(BlockIter::new(BinaryFileReader::new(&buffer)).count(), 9);
+        let packets =
+            PacketIter::new(BinaryFileReader::new(&buffer))?.collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(packets.len(), 3);
+
+        assert_eq!(packets[0].link_type, 1);
+        assert_eq!(packets[0].timestamp, Some(Duration::new(4, 294_967_301)));
+        assert_eq!(packets[0].original_len, 0x40);
+        assert_eq!(packets[0].data.available_bytes(), 3);
+        assert_eq!(packets[0].data.endian(), Endian::Big);
+
+        assert_eq!((packets[1].link_type, packets[1].timestamp), (1, None));
+        assert_eq!(packets[1].data.available_bytes(), 4);
+
+        assert_eq!((packets[2].link_type, packets[2].interface), (0x71, 0));
+        assert_eq!(packets[2].timestamp, Some(Duration::from_secs(1)));
+        assert_eq!(packets[2].data.peek_u8()?, 0xee);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pcap() -> Result<(), BinaryFileReaderError> {
+        let mut buffer = b"\xa1\xb2\xc3\xd4\0\x02\0\x04".to_vec();
+        buffer.extend([0; 8]);
+        buffer.extend(b"\0\0\xff\xff\0\0\0\x69");
+        buffer.extend(b"\0\0\0\x01\0\x07\xa1\x1f\0\0\0\x01\0\0\0\x01\x45");
+        buffer.extend(b"\0\0\0\x02\0\0\0\0\0\0\0\x04\0\0\0\x04\x01");
+
+        let mut packets = PacketIter::new(BinaryFileReader::new(&buffer))?;
+        let packet = packets.next().unwrap()?;
+        assert_eq!(packet.offset, 24);
+        assert_eq!(packet.link_type, 0x69);
+        assert_eq!(packet.timestamp, Some(Duration::new(1, 499_999_000)));
+        let err = packets.next().unwrap().unwrap_err();
+        assert_eq!(err.code(), ErrorCode::BufferUnderflow);
+        assert!(packets.next().is_none());
+        assert_eq!(packets.into_inner().current_offset(), 41);
+
+        assert_eq!(
+            PacketIter::new(BinaryFileReader::new(b"GIF89a"))
+                .unwrap_err()
+                .code(),
+            ErrorCode::ExpectOneOf
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_blocks() {
+        let mut buffer = Vec::new();
+        block(&mut buffer, Endian::Little, 6, b"\0\0\0\0");
+        buffer[12] = 0x11;
+        let mut reader = BinaryFileReader::new(&buffer);
+        reader.set_endian(Endian::Little);
+        assert!(matches!(
+            read_block(&mut reader),
+            Err(BinaryFileReaderError::Malformed {
+                current_offset: 12,
+                ..
+            })
+        ));
+        assert_eq!(reader.current_offset(), 0);
+
+        // A packet before any interface.
+        let mut buffer = Vec::new();
+        block(
+            &mut buffer,
+            Endian::Little,
+            SECTION_HEADER_BLOCK,
+            b"\x4d\x3c\x2b\x1a\x01\0\0\0\xff\xff\xff\xff\xff\xff\xff\xff",
+        );
+        block(&mut buffer, Endian::Little, 3, b"\0\0\0\0");
+        let mut packets = PacketIter::new(BinaryFileReader::new(&buffer)).unwrap();
+        let err = packets.next().unwrap().unwrap_err();
+        assert_eq!(err.code(), ErrorCode::Malformed);
+        assert!(packets.next().is_none());
+    }
+}
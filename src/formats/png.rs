@@ -0,0 +1,302 @@
+//! PNG files: the chunk sequence and the common metadata chunks.
+//!
+//! Only available with the `png` feature.
+//!
+//! # Examples
+//! ```
+//! # use binary_file_reader::BinaryFileReader;
+//! # use binary_file_reader::formats::png::{ChunkIter, Ihdr, Text};
+//! # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+//! let buffer = std::fs::read("sample-files/1.png")?;
+//! let mut width = 0;
+//! let mut comments = Vec::new();
+//!
+//! for chunk in ChunkIter::new(BinaryFileReader::new(&buffer))? {
+//!     let mut chunk = chunk?;
+//!     assert!(chunk.crc_valid);
+//!     match chunk.tag.as_bytes() {
+//!         b"IHDR" => width = chunk.data.read_struct::<Ihdr>()?.width,
+//!         b"tEXt" => comments.push(chunk.data.read_struct::<Text>()?),
+//!         _ => {}
+//!     }
+//! }
+//!
+//! assert_eq!(width, 100);
+//! assert_eq!(comments[0].keyword, "Comment");
+//! assert_eq!(comments[0].text, "Created with GIMP");
+//! #
+//! # Ok(())
+//! # }
+//! # fn main() {
+//! #    try_main().unwrap();
+//! # }
+//! ```
+
+use crate::{
+    endian::Endian, error::BinaryFileReaderError, fourcc::FourCC, read_from::ReadFrom,
+    BinaryFileReader,
+};
+use alloc::string::String;
+use core::iter::FusedIterator;
+
+/// The eight bytes every PNG file starts with.
+pub const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+
+/// One chunk of a PNG file.
+#[derive(Debug, Clone)]
+pub struct Chunk<'a> {
+    pub tag: FourCC,
+    /// A big-endian reader over the chunk data, with absolute offsets.
+    pub data: BinaryFileReader<'a>,
+    /// Whether the stored CRC matches the tag and data.
+    pub crc_valid: bool,
+}
+
+impl Chunk<'_> {
+    /// Whether a decoder must understand the chunk to show the image, which
+    /// PNG marks with an uppercase first letter.
+    pub fn is_critical(&self) -> bool {
+        self.tag.0[0].is_ascii_uppercase()
+    }
+}
+
+/// Iterates over the chunks of a PNG file, up to and including `IEND`.
+///
+/// A chunk with a wrong CRC is still yielded, with
+/// [`crc_valid`](Chunk::crc_valid) set to `false`, so damaged files can be
+/// inspected. A chunk that does not fit in the data is yielded as an error
+/// and ends the iteration, as does the end of the data.
+#[derive(Debug, Clone)]
+pub struct ChunkIter<'a> {
+    reader: BinaryFileReader<'a>,
+    done: bool,
+}
+
+impl<'a> ChunkIter<'a> {
+    /// Checks the PNG signature at the cursor of `reader` and iterates over
+    /// the chunks after it.
+    pub fn new(mut reader: BinaryFileReader<'a>) -> Result<Self, BinaryFileReaderError> {
+        reader.expect(&SIGNATURE)?;
+        Ok(Self::without_signature(reader))
+    }
+
+    /// Iterates over the chunks at the cursor of `reader`, which has no
+    /// signature, as in APNG frames extracted from a file.
+    pub fn without_signature(mut reader: BinaryFileReader<'a>) -> Self {
+        reader.set_endian(Endian::Big);
+        Self {
+            reader,
+            done: false,
+        }
+    }
+
+    /// The reader after the chunks yielded so far, such as to read data
+    /// after `IEND`.
+    pub fn into_inner(self) -> BinaryFileReader<'a> {
+        self.reader
+    }
+
+    fn read_chunk(&mut self) -> Result<Chunk<'a>, BinaryFileReaderError> {
+        let start = self.reader.current_offset();
+        let result = (|| {
+            let length = self.reader.read_u32()?;
+            let length = usize::try_from(length).unwrap_or(usize::MAX);
+            let ((tag, data), computed) = self.reader.with_crc32(|r| {
+                let tag = r.read_fourcc()?;
+                Ok((tag, r.split_off_front(length)?))
+            })?;
+            let stored = self.reader.read_u32()?;
+            Ok(Chunk {
+                tag,
+                data,
+                crc_valid: stored == computed,
+            })
+        })();
+        if result.is_err() {
+            self.reader.set_offset(start)?;
+        }
+        result
+    }
+}
+
+impl<'a> Iterator for ChunkIter<'a> {
+    type Item = Result<Chunk<'a>, BinaryFileReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.reader.available_bytes() == 0 {
+            return None;
+        }
+
+        let chunk = self.read_chunk();
+        self.done = match &chunk {
+            Ok(chunk) => chunk.tag == *b"IEND",
+            Err(_) => true,
+        };
+        Some(chunk)
+    }
+}
+
+impl FusedIterator for ChunkIter<'_> {}
+
+/// The `IHDR` chunk, which describes the image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ihdr {
+    pub width: u32,
+    pub height: u32,
+    pub bit_depth: u8,
+    pub color_type: u8,
+    pub compression_method: u8,
+    pub filter_method: u8,
+    pub interlace_method: u8,
+}
+
+impl ReadFrom for Ihdr {
+    const MIN_SIZE: usize = 13;
+
+    fn read_from(reader: &mut BinaryFileReader) -> Result<Self, BinaryFileReaderError> {
+        Ok(Self {
+            width: reader.read_u32()?,
+            height: reader.read_u32()?,
+            bit_depth: reader.read_u8()?,
+            color_type: reader.read_u8()?,
+            compression_method: reader.read_u8()?,
+            filter_method: reader.read_u8()?,
+            interlace_method: reader.read_u8()?,
+        })
+    }
+}
+
+/// The `pHYs` chunk, the intended pixel size or aspect ratio.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Phys {
+    pub pixels_per_unit_x: u32,
+    pub pixels_per_unit_y: u32,
+    /// 1 if the unit is the metre, 0 if only the aspect ratio is known.
+    pub unit: u8,
+}
+
+impl ReadFrom for Phys {
+    const MIN_SIZE: usize = 9;
+
+    fn read_from(reader: &mut BinaryFileReader) -> Result<Self, BinaryFileReaderError> {
+        Ok(Self {
+            pixels_per_unit_x: reader.read_u32()?,
+            pixels_per_unit_y: reader.read_u32()?,
+            unit: reader.read_u8()?,
+        })
+    }
+}
+
+/// The `tIME` chunk, the time of the last modification in UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Time {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl ReadFrom for Time {
+    const MIN_SIZE: usize = 7;
+
+    fn read_from(reader: &mut BinaryFileReader) -> Result<Self, BinaryFileReaderError> {
+        Ok(Self {
+            year: reader.read_u16()?,
+            month: reader.read_u8()?,
+            day: reader.read_u8()?,
+            hour: reader.read_u8()?,
+            minute: reader.read_u8()?,
+            second: reader.read_u8()?,
+        })
+    }
+}
+
+/// The `tEXt` chunk, a keyword and a text separated by a NUL byte. Both are
+/// Latin-1, and reading one consumes the rest of the chunk.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Text {
+    pub keyword: String,
+    pub text: String,
+}
+
+impl ReadFrom for Text {
+    const MIN_SIZE: usize = 1;
+
+    fn read_from(reader: &mut BinaryFileReader) -> Result<Self, BinaryFileReaderError> {
+        let rest = reader.peek_slice(reader.available_bytes())?;
+        let keyword = rest.iter().position(|&b| b == 0).unwrap_or(rest.len());
+        let keyword = latin1(reader.read_slice(keyword)?);
+        reader.expect(&[0])?;
+        let text = latin1(reader.read_slice(reader.available_bytes())?);
+        Ok(Self { keyword, text })
+    }
+}
+
+fn latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| b as char).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChunkIter, Phys, Text, Time, SIGNATURE};
+    use crate::{
+        error::{BinaryFileReaderError, ErrorCode},
+        BinaryFileReader,
+    };
+
+    #[test]
+    fn test_chunk_iter() -> Result<(), BinaryFileReaderError> {
+        let mut buffer = SIGNATURE.to_vec();
+        buffer.extend(b"\x00\x00\x00\x01sRGB\x00\xae\xce\x1c\xe9");
+        buffer.extend(b"\x00\x00\x00\x00abCD\x00\x00\x00\x00");
+        buffer.extend(b"\x00\x00\x00\x00IEND\xae\x42\x60\x82trailing");
+
+        let chunks =
+            ChunkIter::new(BinaryFileReader::new(&buffer))?.collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks[0].crc_valid && !chunks[0].is_critical());
+        assert_eq!(chunks[0].data.current_offset(), 16);
+        assert_eq!(chunks[0].data.available_bytes(), 1);
+        assert!(!chunks[1].crc_valid);
+        assert!(chunks[2].crc_valid && chunks[2].is_critical());
+
+        let mut chunks = ChunkIter::without_signature(BinaryFileReader::new(&buffer[8..]));
+        chunks.by_ref().take(3).for_each(drop);
+        assert!(chunks.next().is_none());
+        assert_eq!(chunks.into_inner().available_bytes(), 8);
+
+        let mut truncated = ChunkIter::new(BinaryFileReader::new(&buffer[..20]))?;
+        let err = truncated.next().unwrap().unwrap_err();
+        assert_eq!(err.code(), ErrorCode::BufferUnderflow);
+        assert!(truncated.next().is_none());
+        assert_eq!(truncated.into_inner().current_offset(), 8);
+        assert!(ChunkIter::new(BinaryFileReader::new(b"GIF89a")).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_chunk_types() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0, 0, 0x2e, 0x23, 0, 0, 0x2e, 0x23, 1];
+        let phys = BinaryFileReader::new(&buffer).read_struct::<Phys>()?;
+        assert_eq!(phys.pixels_per_unit_y, 11811);
+        assert_eq!(phys.unit, 1);
+
+        let buffer = vec![0x07, 0xe9, 1, 28, 9, 43, 9];
+        let time = BinaryFileReader::new(&buffer).read_struct::<Time>()?;
+        assert_eq!((time.year, time.month, time.day), (2025, 1, 28));
+        assert_eq!((time.hour, time.minute, time.second), (9, 43, 9));
+
+        let buffer = b"Title\0Caf\xe9".to_vec();
+        let text = BinaryFileReader::new(&buffer).read_struct::<Text>()?;
+        assert_eq!(text.keyword, "Title");
+        assert_eq!(text.text, "Café");
+        assert!(BinaryFileReader::new(b"Title")
+            .read_struct::<Text>()
+            .is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,322 @@
+//! RIFF containers, as used by WAV, AVI and WebP: chunks with a FourCC id
+//! and a `u32` size, padded to an even length, where `RIFF` and `LIST`
+//! chunks hold a form type followed by more chunks.
+//!
+//! Sizes are little-endian, or big-endian in files starting with `RIFX`.
+//!
+//! Only available with the `riff` feature.
+//!
+//! # Examples
+//! ```
+//! # use binary_file_reader::BinaryFileReader;
+//! # use binary_file_reader::formats::riff;
+//! # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut buffer = b"RIFF\x1e\x00\x00\x00WAVE".to_vec();
+//! buffer.extend(b"fmt \x04\x00\x00\x00\x01\x00\x02\x00");
+//! buffer.extend(b"data\x05\x00\x00\x00\x01\x02\x03\x04\x05\x00");
+//! let mut reader = BinaryFileReader::new(&buffer);
+//!
+//! let wave = riff::read_riff(&mut reader)?;
+//! assert_eq!(wave.form.unwrap(), "WAVE");
+//!
+//! for chunk in wave.children() {
+//!     let mut chunk = chunk?;
+//!     if chunk.id == "fmt " {
+//!         assert_eq!(chunk.data.read_u16()?, 1);
+//!         assert_eq!(chunk.data.read_u16()?, 2);
+//!     } else {
+//!         assert_eq!(chunk.data.available_bytes(), 5);
+//!     }
+//! }
+//! #
+//! # Ok(())
+//! # }
+//! # fn main() {
+//! #    try_main().unwrap();
+//! # }
+//! ```
+
+use crate::{endian::Endian, error::BinaryFileReaderError, fourcc::FourCC, BinaryFileReader};
+use alloc::{vec, vec::Vec};
+use core::iter::FusedIterator;
+
+/// One chunk of a RIFF file.
+#[derive(Debug, Clone)]
+pub struct Chunk<'a> {
+    pub id: FourCC,
+    /// The form type of a `RIFF`, `RIFX` or `LIST` chunk, such as `WAVE` or
+    /// `INFO`, and `None` for other chunks.
+    pub form: Option<FourCC>,
+    /// A reader over the chunk data, after the form type of list chunks,
+    /// with absolute offsets and the file's endianness.
+    pub data: BinaryFileReader<'a>,
+}
+
+impl<'a> Chunk<'a> {
+    #[inline]
+    pub fn is_list(&self) -> bool {
+        self.form.is_some()
+    }
+
+    /// The chunks inside a list chunk. Other chunks have no children.
+    pub fn children(&self) -> ChunkIter<'a> {
+        ChunkIter {
+            reader: self.data.clone(),
+            done: !self.is_list(),
+        }
+    }
+}
+
+fn is_list_id(id: FourCC) -> bool {
+    id == *b"RIFF" || id == *b"RIFX" || id == *b"LIST"
+}
+
+/// Reads the `RIFF` or `RIFX` chunk at the cursor, which holds the rest of
+/// the file. Fails with [`BinaryFileReaderError::ExpectOneOf`] for other
+/// ids.
+pub fn read_riff<'a>(
+    reader: &mut BinaryFileReader<'a>,
+) -> Result<Chunk<'a>, BinaryFileReaderError> {
+    let start = reader.current_offset();
+    let endian = match reader.expect_one_of(&[b"RIFF", b"RIFX"])? {
+        0 => Endian::Little,
+        _ => Endian::Big,
+    };
+    reader.set_offset(start)?;
+
+    let saved = reader.endian();
+    reader.set_endian(endian);
+    let chunk = read_chunk(reader);
+    reader.set_endian(saved);
+    chunk
+}
+
+fn read_chunk<'a>(reader: &mut BinaryFileReader<'a>) -> Result<Chunk<'a>, BinaryFileReaderError> {
+    let start = reader.current_offset();
+    let result = (|| {
+        let id = reader.read_fourcc()?;
+        let size = reader.read_u32()?;
+        let size = usize::try_from(size).unwrap_or(usize::MAX);
+        let mut data = reader.split_off_front(size)?;
+        if size % 2 == 1 && reader.available_bytes() > 0 {
+            reader.advance(1)?;
+        }
+        let form = if is_list_id(id) {
+            Some(data.read_fourcc()?)
+        } else {
+            None
+        };
+        Ok(Chunk { id, form, data })
+    })();
+    if result.is_err() {
+        reader.set_offset(start)?;
+    }
+    result
+}
+
+/// Iterates over a sequence of chunks, skipping the pad byte after chunks of
+/// odd size. A pad byte missing at the end of the data is tolerated.
+///
+/// A chunk that does not fit in the data is yielded as an error and ends the
+/// iteration.
+#[derive(Debug, Clone)]
+pub struct ChunkIter<'a> {
+    reader: BinaryFileReader<'a>,
+    done: bool,
+}
+
+impl<'a> ChunkIter<'a> {
+    /// Iterates over the little-endian chunks at the cursor of `reader`, such
+    /// as the body of a chunk read another way.
+    pub fn new(mut reader: BinaryFileReader<'a>) -> Self {
+        reader.set_endian(Endian::Little);
+        Self {
+            reader,
+            done: false,
+        }
+    }
+
+    /// Iterates depth-first over these chunks and the chunks nested in
+    /// them.
+    pub fn walk(self) -> Walk<'a> {
+        Walk { stack: vec![self] }
+    }
+
+    /// The reader after the chunks yielded so far.
+    pub fn into_inner(self) -> BinaryFileReader<'a> {
+        self.reader
+    }
+}
+
+impl<'a> Iterator for ChunkIter<'a> {
+    type Item = Result<Chunk<'a>, BinaryFileReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.reader.available_bytes() == 0 {
+            return None;
+        }
+
+        let chunk = read_chunk(&mut self.reader);
+        self.done = chunk.is_err();
+        Some(chunk)
+    }
+}
+
+impl FusedIterator for ChunkIter<'_> {}
+
+/// Iterates over chunks and their descendants, parents before their
+/// children, with the number of list chunks each is nested in. An error ends
+/// the walk. Created with [`ChunkIter::walk`].
+///
+/// A list chunk nested deeper than the reader's
+/// [`max_depth`](BinaryFileReader::max_depth) is yielded as
+/// [`BinaryFileReaderError::DepthExceeded`].
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::BinaryFileReader;
+/// # use binary_file_reader::formats::riff::ChunkIter;
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut buffer = b"LIST\x10\x00\x00\x00INFO".to_vec();
+/// buffer.extend(b"INAM\x03\x00\x00\x00abc\x00");
+/// buffer.extend(b"JUNK\x00\x00\x00\x00");
+///
+/// let chunks = ChunkIter::new(BinaryFileReader::new(&buffer))
+///     .walk()
+///     .map(|chunk| chunk.map(|(depth, chunk)| (depth, chunk.id.to_string())))
+///     .collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(chunks, [(0, "LIST".into()), (1, "INAM".into()), (0, "JUNK".into())]);
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Walk<'a> {
+    stack: Vec<ChunkIter<'a>>,
+}
+
+impl<'a> Iterator for Walk<'a> {
+    type Item = Result<(usize, Chunk<'a>), BinaryFileReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let depth = self.stack.len().checked_sub(1)?;
+            match self.stack[depth].next() {
+                Some(Ok(chunk)) => {
+                    if chunk.is_list() {
+                        let reader = &self.stack[depth].reader;
+                        if reader.depth() + self.stack.len() >= reader.max_depth() {
+                            let err = BinaryFileReaderError::DepthExceeded {
+                                max_depth: reader.max_depth(),
+                                current_offset: chunk.data.current_offset(),
+                            };
+                            self.stack.clear();
+                            return Some(Err(err));
+                        }
+                        self.stack.push(chunk.children());
+                    }
+                    return Some(Ok((depth, chunk)));
+                }
+                Some(Err(err)) => {
+                    self.stack.clear();
+                    return Some(Err(err));
+                }
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
+impl FusedIterator for Walk<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_riff, ChunkIter};
+    use crate::{
+        endian::Endian,
+        error::{BinaryFileReaderError, ErrorCode},
+        BinaryFileReader,
+    };
+
+    #[test]
+    fn test_riff() -> Result<(), BinaryFileReaderError> {
+        let mut buffer = b"RIFX\x00\x00\x00\x22AVI ".to_vec();
+        buffer.extend(b"LIST\x00\x00\x00\x0ehdrl");
+        buffer.extend(b"avih\x00\x00\x00\x01\x2a\x00");
+        buffer.extend(b"JUNK\x00\x00\x00\x00");
+        let mut reader = BinaryFileReader::new(&buffer);
+
+        let avi = read_riff(&mut reader)?;
+        assert_eq!(avi.form.unwrap(), "AVI ");
+        assert_eq!(reader.available_bytes(), 0);
+
+        let chunks = avi.children().walk().collect::<Result<Vec<_>, _>>()?;
+        let ids = chunks
+            .iter()
+            .map(|(depth, chunk)| (*depth, chunk.id.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ids,
+            [(0, "LIST".into()), (1, "avih".into()), (0, "JUNK".into())]
+        );
+        assert_eq!(chunks[1].1.data.current_offset(), 32);
+        assert_eq!(chunks[1].1.data.peek_u8()?, 0x2a);
+        assert_eq!(chunks[1].1.data.endian(), Endian::Big);
+        assert!(chunks[1].1.children().next().is_none());
+
+        assert!(read_riff(&mut BinaryFileReader::new(b"FORM\0\0\0\0AIFF")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_chunk_iter() -> Result<(), BinaryFileReaderError> {
+        // The pad byte of the last chunk is missing.
+        let buffer = b"abcd\x01\x00\x00\x00\xff\x00efgh\x01\x00\x00\x00\xee".to_vec();
+        let chunks =
+            ChunkIter::new(BinaryFileReader::new(&buffer)).collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[1].data.current_offset(), 18);
+
+        let mut chunks = ChunkIter::new(BinaryFileReader::new(&buffer[..12])).walk();
+        assert!(chunks.next().unwrap().is_ok());
+        let err = chunks.next().unwrap().unwrap_err();
+        assert_eq!(err.code(), ErrorCode::BufferUnderflow);
+        assert!(chunks.next().is_none());
+
+        let mut chunks = ChunkIter::new(BinaryFileReader::new(b"LIST\x02\x00\x00\x00ab"));
+        assert!(chunks.next().unwrap().is_err());
+        assert_eq!(chunks.into_inner().current_offset(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_deep_nesting() {
+        let mut buffer = Vec::new();
+        for _ in 0..4 {
+            let mut parent = b"LIST".to_vec();
+            parent.extend((buffer.len() as u32 + 4).to_le_bytes());
+            parent.extend(b"INFO");
+            parent.extend(buffer);
+            buffer = parent;
+        }
+
+        let mut reader = BinaryFileReader::new(&buffer);
+        reader.set_max_depth(4);
+        assert_eq!(ChunkIter::new(reader.clone()).walk().count(), 4);
+
+        reader.set_max_depth(3);
+        let mut walk = ChunkIter::new(reader).walk();
+        assert_eq!(walk.next().unwrap().unwrap().0, 0);
+        assert_eq!(walk.next().unwrap().unwrap().0, 1);
+        let err = walk.next().unwrap().unwrap_err();
+        assert_eq!(err.code(), ErrorCode::DepthExceeded);
+        assert_eq!(err.offset(), Some(36));
+        assert!(walk.next().is_none());
+    }
+}
@@ -0,0 +1,407 @@
+//! TAR archives: 512-byte headers with octal fields, each followed by the
+//! entry data padded to a whole block, up to a block of zeroes.
+//!
+//! Headers in the ustar, GNU and old formats are read, with base-256 numbers
+//! as GNU writes for large values. Extension entries, such as
+//! [`PAX_HEADER`] and [`GNU_LONG_NAME`], are yielded like other entries for
+//! the caller to apply to the entry after them.
+//!
+//! Only available with the `tar` feature.
+//!
+//! # Examples
+//! ```
+//! # use binary_file_reader::BinaryFileReader;
+//! # use binary_file_reader::formats::tar::{self, EntryIter};
+//! # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+//! # fn header(name: &[u8], size: &[u8]) -> Vec<u8> {
+//! #     let mut header = vec![0; 512];
+//! #     header[..name.len()].copy_from_slice(name);
+//! #     header[124..124 + size.len()].copy_from_slice(size);
+//! #     header[148..156].copy_from_slice(b"        ");
+//! #     header[156] = b'0';
+//! #     header[257..263].copy_from_slice(b"ustar\0");
+//! #     let sum: u32 = header.iter().map(|&b| b as u32).sum();
+//! #     header[148..155].copy_from_slice(format!("{:06o}\0", sum).as_bytes());
+//! #     header
+//! # }
+//! let mut buffer = header(b"hello.txt", b"00000000005");
+//! buffer.extend(b"hello");
+//! buffer.resize(1024 + 1024, 0);
+//!
+//! let mut entries = EntryIter::new(BinaryFileReader::new(&buffer));
+//! let mut entry = entries.next().unwrap()?;
+//! assert_eq!(&*entry.header.path(), b"hello.txt");
+//! assert_eq!(entry.header.entry_type, tar::REGULAR);
+//! assert_eq!(entry.data.read_utf8(5)?, "hello");
+//! assert!(entries.next().is_none());
+//! #
+//! # Ok(())
+//! # }
+//! # fn main() {
+//! #    try_main().unwrap();
+//! # }
+//! ```
+
+use crate::{error::BinaryFileReaderError, BinaryFileReader};
+use alloc::{borrow::Cow, vec::Vec};
+use core::iter::FusedIterator;
+
+/// The size of a header and of the blocks entry data is padded to.
+pub const BLOCK_SIZE: usize = 512;
+
+pub const REGULAR: u8 = b'0';
+/// The type of regular files in archives older than ustar.
+pub const OLD_REGULAR: u8 = 0;
+pub const HARD_LINK: u8 = b'1';
+pub const SYMBOLIC_LINK: u8 = b'2';
+pub const CHARACTER_DEVICE: u8 = b'3';
+pub const BLOCK_DEVICE: u8 = b'4';
+pub const DIRECTORY: u8 = b'5';
+pub const FIFO: u8 = b'6';
+/// Extended attributes for the next entry, as `length key=value\n` records.
+pub const PAX_HEADER: u8 = b'x';
+/// Extended attributes for all later entries.
+pub const PAX_GLOBAL_HEADER: u8 = b'g';
+/// The name of the next entry, NUL-terminated.
+pub const GNU_LONG_NAME: u8 = b'L';
+/// The link name of the next entry, NUL-terminated.
+pub const GNU_LONG_LINK_NAME: u8 = b'K';
+
+/// A parsed TAR header.
+#[derive(Debug, Clone)]
+pub struct Header<'a> {
+    /// The absolute offset of the header.
+    pub offset: usize,
+    /// The name, without the ustar prefix. See [`path`](Self::path).
+    pub name: &'a [u8],
+    pub mode: u32,
+    pub uid: u64,
+    pub gid: u64,
+    /// The size of the entry data, without padding.
+    pub size: u64,
+    /// The modification time in seconds since the Unix epoch.
+    pub mtime: u64,
+    /// The type flag, such as [`REGULAR`] or [`DIRECTORY`].
+    pub entry_type: u8,
+    pub link_name: &'a [u8],
+    /// Whether the header has the ustar magic of POSIX or GNU archives, which
+    /// the user, group and device fields need.
+    pub ustar: bool,
+    pub user_name: &'a [u8],
+    pub group_name: &'a [u8],
+    pub device_major: u32,
+    pub device_minor: u32,
+    /// The directory the name is in, in POSIX ustar archives only.
+    pub prefix: &'a [u8],
+}
+
+impl<'a> Header<'a> {
+    /// The name joined to the prefix with a `/`, as the archive stores names
+    /// longer than 100 bytes.
+    pub fn path(&self) -> Cow<'a, [u8]> {
+        if self.prefix.is_empty() {
+            return Cow::Borrowed(self.name);
+        }
+        let mut path = Vec::with_capacity(self.prefix.len() + 1 + self.name.len());
+        path.extend_from_slice(self.prefix);
+        path.push(b'/');
+        path.extend_from_slice(self.name);
+        Cow::Owned(path)
+    }
+
+    /// Whether the entry is a regular file, whose data is its contents.
+    #[inline]
+    pub fn is_file(&self) -> bool {
+        self.entry_type == REGULAR || self.entry_type == OLD_REGULAR
+    }
+
+    #[inline]
+    pub fn is_dir(&self) -> bool {
+        self.entry_type == DIRECTORY
+    }
+}
+
+/// The bytes of a field up to its first NUL.
+fn text(field: &[u8]) -> &[u8] {
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    &field[..end]
+}
+
+/// Parses a numeric field: octal digits after optional spaces, ended by a
+/// space, NUL or the end of the field, or a big-endian base-256 number after
+/// a first byte with its high bit set.
+fn number(field: &[u8], offset: usize) -> Result<u64, BinaryFileReaderError> {
+    let too_large = BinaryFileReaderError::Malformed {
+        reason: "numeric field is too large",
+        current_offset: offset,
+    };
+    if field[0] & 0x80 != 0 {
+        let mut value = (field[0] & 0x7f) as u64;
+        for &byte in &field[1..] {
+            if value > u64::MAX >> 8 {
+                return Err(too_large);
+            }
+            value = (value << 8) | byte as u64;
+        }
+        return Ok(value);
+    }
+
+    let mut value: u64 = 0;
+    for &byte in field.iter().skip_while(|&&b| b == b' ') {
+        match byte {
+            b'0'..=b'7' => {
+                if value > u64::MAX >> 3 {
+                    return Err(too_large);
+                }
+                value = (value << 3) | (byte - b'0') as u64;
+            }
+            b' ' | 0 => break,
+            _ => {
+                return Err(BinaryFileReaderError::Malformed {
+                    reason: "numeric field is not octal",
+                    current_offset: offset,
+                })
+            }
+        }
+    }
+    Ok(value)
+}
+
+/// Reads the 512-byte header at the cursor and verifies its checksum, which
+/// may be computed over signed or unsigned bytes. On failure the reader is
+/// left at the start of the header.
+///
+/// Fails with [`BinaryFileReaderError::ChecksumMismatch`] for a bad
+/// checksum, including for the zero block that ends an archive.
+pub fn read_header<'a>(
+    reader: &mut BinaryFileReader<'a>,
+) -> Result<Header<'a>, BinaryFileReaderError> {
+    let offset = reader.current_offset();
+    let block = reader.peek_slice(BLOCK_SIZE)?;
+    let field = |start: usize, end: usize| number(&block[start..end], offset + start);
+
+    let stored = field(148, 156)?;
+    let checksum_field = 148..156;
+    let (mut unsigned, mut signed) = (0_u64, 0_i64);
+    for (i, &byte) in block.iter().enumerate() {
+        let byte = if checksum_field.contains(&i) {
+            b' '
+        } else {
+            byte
+        };
+        unsigned += byte as u64;
+        signed += byte as i8 as i64;
+    }
+    if stored != unsigned && stored as i64 != signed {
+        return Err(BinaryFileReaderError::ChecksumMismatch {
+            stored,
+            computed: unsigned,
+            current_offset: offset + 148,
+        });
+    }
+
+    let ustar = block[257..262] == *b"ustar";
+    let posix = block[257..263] == *b"ustar\0";
+    let header = Header {
+        offset,
+        name: text(&block[0..100]),
+        mode: field(100, 108)? as u32,
+        uid: field(108, 116)?,
+        gid: field(116, 124)?,
+        size: field(124, 136)?,
+        mtime: field(136, 148)?,
+        entry_type: block[156],
+        link_name: text(&block[157..257]),
+        ustar,
+        user_name: if ustar { text(&block[265..297]) } else { &[] },
+        group_name: if ustar { text(&block[297..329]) } else { &[] },
+        device_major: if ustar { field(329, 337)? as u32 } else { 0 },
+        device_minor: if ustar { field(337, 345)? as u32 } else { 0 },
+        prefix: if posix { text(&block[345..500]) } else { &[] },
+    };
+    reader.advance(BLOCK_SIZE)?;
+    Ok(header)
+}
+
+/// One entry of an archive.
+#[derive(Debug, Clone)]
+pub struct Entry<'a> {
+    pub header: Header<'a>,
+    /// A reader over the entry data, without the padding.
+    pub data: BinaryFileReader<'a>,
+}
+
+/// Iterates over the entries of an archive up to the first zero block,
+/// skipping the padding after each entry's data. Padding missing at the end
+/// of the data is tolerated.
+///
+/// An entry that cannot be read is yielded as an error and ends the
+/// iteration.
+#[derive(Debug, Clone)]
+pub struct EntryIter<'a> {
+    reader: BinaryFileReader<'a>,
+    done: bool,
+}
+
+impl<'a> EntryIter<'a> {
+    /// Iterates over the entries at the cursor of `reader`, such as a whole
+    /// archive.
+    pub fn new(reader: BinaryFileReader<'a>) -> Self {
+        Self {
+            reader,
+            done: false,
+        }
+    }
+
+    /// The reader after the entries yielded so far, or at the zero block
+    /// that ended the archive.
+    pub fn into_inner(self) -> BinaryFileReader<'a> {
+        self.reader
+    }
+}
+
+fn read_entry<'a>(reader: &mut BinaryFileReader<'a>) -> Result<Entry<'a>, BinaryFileReaderError> {
+    let offset = reader.current_offset();
+    let result = (|| {
+        let header = read_header(reader)?;
+        let size = usize::try_from(header.size).unwrap_or(usize::MAX);
+        let data = reader.split_off_front(size)?;
+        let padding = size.wrapping_neg() % BLOCK_SIZE;
+        reader.advance(padding.min(reader.available_bytes()))?;
+        Ok(Entry { header, data })
+    })();
+    if result.is_err() {
+        reader.set_offset(offset)?;
+    }
+    result
+}
+
+impl<'a> Iterator for EntryIter<'a> {
+    type Item = Result<Entry<'a>, BinaryFileReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.reader.available_bytes() == 0 {
+            return None;
+        }
+        if let Ok(block) = self.reader.peek_slice(BLOCK_SIZE) {
+            if block.iter().all(|&b| b == 0) {
+                self.done = true;
+                return None;
+            }
+        }
+
+        let entry = read_entry(&mut self.reader);
+        self.done = entry.is_err();
+        Some(entry)
+    }
+}
+
+impl FusedIterator for EntryIter<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_header, EntryIter, DIRECTORY, OLD_REGULAR};
+    use crate::{
+        error::{BinaryFileReaderError, ErrorCode},
+        BinaryFileReader,
+    };
+
+    fn header(fields: &[(usize, &[u8])], magic: &[u8]) -> Vec<u8> {
+        let mut header = vec![0; 512];
+        for (start, value) in fields {
+            header[*start..start + value.len()].copy_from_slice(value);
+        }
+        header[257..257 + magic.len()].copy_from_slice(magic);
+        header[148..156].copy_from_slice(b"        ");
+        let sum: u32 = header.iter().map(|&b| b as u32).sum();
+        header[148..156].copy_from_slice(format!("{:06o}\0 ", sum).as_bytes());
+        header
+    }
+
+    #[test]
+    fn test_entries() -> Result<(), BinaryFileReaderError> {
+        let mut buffer = header(
+            &[
+                (0, b"file.bin"),
+                (100, b"0000644\0"),
+                (124, b" 1001 "),
+                (136, b"14567036371\0"),
+                (156, b"0"),
+                (265, b"user"),
+                (345, b"a/very/long/directory"),
+            ],
+            b"ustar\x0000",
+        );
+        buffer.extend([0xab; 513]);
+        buffer.resize(1536, 0);
+        buffer.extend(header(
+            &[
+                (0, b"dir/"),
+                (124, b"\x80\0\0\0\0\0\0\0\0\0\0\0"),
+                (156, b"5"),
+            ],
+            b"ustar  \0",
+        ));
+        buffer.extend(header(&[(0, b"old"), (124, b"3")], b""));
+        buffer.extend(b"abc");
+
+        let mut entries = EntryIter::new(BinaryFileReader::new(&buffer));
+        let first = entries.next().unwrap()?;
+        assert_eq!(&*first.header.path(), b"a/very/long/directory/file.bin");
+        assert_eq!(first.header.mode, 0o644);
+        assert_eq!(first.header.size, 513);
+        assert_eq!(first.header.mtime, 0o14567036371);
+        assert_eq!(first.header.user_name, b"user");
+        assert!(first.header.is_file());
+        assert_eq!(first.data.current_offset(), 512);
+        assert_eq!(first.data.available_bytes(), 513);
+
+        let second = entries.next().unwrap()?;
+        assert_eq!(second.header.offset, 1536);
+        assert_eq!(second.header.entry_type, DIRECTORY);
+        assert!(second.header.ustar && second.header.is_dir());
+        assert_eq!(&*second.header.path(), b"dir/");
+
+        let mut third = entries.next().unwrap()?;
+        assert_eq!(third.header.entry_type, OLD_REGULAR);
+        assert!(!third.header.ustar);
+        assert_eq!(third.data.read_slice(3)?, b"abc");
+        assert!(entries.next().is_none());
+
+        let mut buffer = header(&[(0, b"a")], b"ustar\0");
+        buffer.resize(2048, 0);
+        let mut entries = EntryIter::new(BinaryFileReader::new(&buffer));
+        assert_eq!(entries.next().unwrap()?.data.available_bytes(), 0);
+        assert!(entries.next().is_none());
+        assert_eq!(entries.into_inner().current_offset(), 512);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_headers() {
+        let mut buffer = header(&[(0, b"a")], b"ustar\0");
+        buffer[0] = b'b';
+        let mut reader = BinaryFileReader::new(&buffer);
+        assert!(matches!(
+            read_header(&mut reader),
+            Err(BinaryFileReaderError::ChecksumMismatch {
+                current_offset: 148,
+                ..
+            })
+        ));
+        assert_eq!(reader.current_offset(), 0);
+
+        let buffer = header(&[(124, b"0000000009")], b"ustar\0");
+        let err = read_header(&mut BinaryFileReader::new(&buffer)).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::Malformed);
+        assert_eq!(err.offset(), Some(124));
+
+        let buffer = header(&[(124, b"00000000010")], b"ustar\0");
+        let mut entries = EntryIter::new(BinaryFileReader::new(&buffer));
+        let err = entries.next().unwrap().unwrap_err();
+        assert_eq!(err.code(), ErrorCode::BufferUnderflow);
+        assert!(entries.next().is_none());
+        assert_eq!(entries.into_inner().current_offset(), 0);
+    }
+}
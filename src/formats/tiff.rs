@@ -0,0 +1,365 @@
+//! TIFF files and the TIFF structure inside EXIF metadata: a byte-order
+//! mark, then a chain of image file directories (IFDs) of tagged entries.
+//!
+//! Offsets in a TIFF structure count from its byte-order mark, so EXIF data
+//! can be read in place, such as from a JPEG `APP1` segment after its
+//! `Exif\0\0` prefix.
+//!
+//! Only available with the `tiff` feature.
+//!
+//! # Examples
+//! ```
+//! # use binary_file_reader::BinaryFileReader;
+//! # use binary_file_reader::formats::tiff::{self, Tiff};
+//! # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut app1 = b"Exif\0\0II*\0\x08\0\0\0".to_vec();
+//! // IFD0: Orientation = 1, and the EXIF IFD at offset 38.
+//! app1.extend(b"\x02\0\x12\x01\x03\0\x01\0\0\0\x01\0\0\0");
+//! app1.extend(b"\x69\x87\x04\0\x01\0\0\0\x26\0\0\0\0\0\0\0");
+//! // The EXIF IFD: ISO speed = 200.
+//! app1.extend(b"\x01\0\x27\x88\x03\0\x01\0\0\0\xc8\0\0\0\0\0\0\0");
+//!
+//! let mut reader = BinaryFileReader::new(&app1);
+//! reader.expect(b"Exif\0\0")?;
+//! let tiff = Tiff::new(&reader)?;
+//!
+//! let ifd0 = tiff.first_ifd()?;
+//! assert_eq!(ifd0.entry(0x0112).unwrap().as_u32()?, 1);
+//!
+//! let exif = tiff.ifd_at(ifd0.entry(tiff::EXIF_IFD).unwrap().as_u32()?)?;
+//! assert_eq!(exif.entry(0x8827).unwrap().as_u32()?, 200);
+//! #
+//! # Ok(())
+//! # }
+//! # fn main() {
+//! #    try_main().unwrap();
+//! # }
+//! ```
+
+use crate::{endian::Endian, error::BinaryFileReaderError, BinaryFileReader};
+use alloc::vec::Vec;
+use core::iter::FusedIterator;
+
+/// The tag of the entry pointing to the EXIF IFD.
+pub const EXIF_IFD: u16 = 0x8769;
+/// The tag of the entry pointing to the GPS IFD.
+pub const GPS_IFD: u16 = 0x8825;
+/// The tag of the entry pointing to the interoperability IFD.
+pub const INTEROP_IFD: u16 = 0xa005;
+
+/// Reads the `II` or `MM` byte-order mark and switches `reader` to the
+/// byte order it names.
+pub fn read_byte_order(reader: &mut BinaryFileReader<'_>) -> Result<Endian, BinaryFileReaderError> {
+    let endian = match reader.expect_one_of(&[b"II", b"MM"])? {
+        0 => Endian::Little,
+        _ => Endian::Big,
+    };
+    reader.set_endian(endian);
+    Ok(endian)
+}
+
+/// The type of the values of an IFD entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FieldType {
+    Byte = 1,
+    Ascii = 2,
+    Short = 3,
+    Long = 4,
+    Rational = 5,
+    SByte = 6,
+    Undefined = 7,
+    SShort = 8,
+    SLong = 9,
+    SRational = 10,
+    Float = 11,
+    Double = 12,
+    Ifd = 13,
+}
+
+impl FieldType {
+    /// Returns `None` for numbers that are not a known type.
+    pub fn from_u16(value: u16) -> Option<Self> {
+        Some(match value {
+            1 => Self::Byte,
+            2 => Self::Ascii,
+            3 => Self::Short,
+            4 => Self::Long,
+            5 => Self::Rational,
+            6 => Self::SByte,
+            7 => Self::Undefined,
+            8 => Self::SShort,
+            9 => Self::SLong,
+            10 => Self::SRational,
+            11 => Self::Float,
+            12 => Self::Double,
+            13 => Self::Ifd,
+            _ => return None,
+        })
+    }
+
+    /// The size of one value in bytes.
+    pub fn size(self) -> usize {
+        match self {
+            Self::Byte | Self::Ascii | Self::SByte | Self::Undefined => 1,
+            Self::Short | Self::SShort => 2,
+            Self::Long | Self::SLong | Self::Float | Self::Ifd => 4,
+            Self::Rational | Self::SRational | Self::Double => 8,
+        }
+    }
+}
+
+/// A TIFF structure, read in the byte order of its header.
+#[derive(Debug, Clone)]
+pub struct Tiff<'a> {
+    // A reader over the whole structure, at the byte-order mark.
+    data: BinaryFileReader<'a>,
+    first_ifd: u32,
+}
+
+impl<'a> Tiff<'a> {
+    /// Reads the header at the cursor of `reader`. Every byte after the
+    /// cursor belongs to the structure, and `reader` is not advanced.
+    pub fn new(reader: &BinaryFileReader<'a>) -> Result<Self, BinaryFileReaderError> {
+        let mut data = reader.clone();
+        read_byte_order(&mut data)?;
+        let header = data.current_offset();
+        if data.read_u16()? != 42 {
+            return Err(BinaryFileReaderError::Malformed {
+                reason: "not a TIFF header",
+                current_offset: header,
+            });
+        }
+        let first_ifd = data.read_u32()?;
+        data.set_offset(reader.current_offset())?;
+        Ok(Self { data, first_ifd })
+    }
+
+    #[inline]
+    pub fn endian(&self) -> Endian {
+        self.data.endian()
+    }
+
+    /// A reader positioned `offset` bytes after the byte-order mark.
+    pub fn reader_at(&self, offset: u32) -> Result<BinaryFileReader<'a>, BinaryFileReaderError> {
+        let mut reader = self.data.clone();
+        let offset = usize::try_from(offset).unwrap_or(usize::MAX);
+        reader.set_offset(reader.current_offset().saturating_add(offset))?;
+        Ok(reader)
+    }
+
+    /// The IFD the header points to.
+    pub fn first_ifd(&self) -> Result<Ifd<'a>, BinaryFileReaderError> {
+        self.ifd_at(self.first_ifd)
+    }
+
+    /// Reads the IFD at `offset`, such as one named by an [`EXIF_IFD`] entry.
+    pub fn ifd_at(&self, offset: u32) -> Result<Ifd<'a>, BinaryFileReaderError> {
+        let mut reader = self.reader_at(offset)?;
+        let count = reader.read_u16()? as usize;
+        let mut entries = Vec::with_capacity(count.min(reader.available_bytes() / 12));
+        for _ in 0..count {
+            let tag = reader.read_u16()?;
+            let field_type = reader.read_u16()?;
+            let count = reader.read_u32()?;
+            let value_offset = reader.current_offset();
+            reader.advance(4)?;
+            entries.push(IfdEntry {
+                tiff: self.clone(),
+                tag,
+                field_type,
+                count,
+                value_offset,
+            });
+        }
+        let next = reader.read_u32()?;
+        Ok(Ifd {
+            offset,
+            entries,
+            next: (next != 0).then_some(next),
+        })
+    }
+
+    /// Iterates over the chain of IFDs from the first one, such as the
+    /// image and its thumbnail. An IFD that cannot be read, or that points
+    /// back to an earlier one, is yielded as an error and ends the
+    /// iteration.
+    pub fn ifds(&self) -> IfdIter<'a> {
+        IfdIter {
+            tiff: self.clone(),
+            next: (self.first_ifd != 0).then_some(self.first_ifd),
+            visited: Vec::new(),
+        }
+    }
+}
+
+/// An image file directory: entries sorted by tag, and the offset of the
+/// next IFD.
+#[derive(Debug, Clone)]
+pub struct Ifd<'a> {
+    /// The offset of the IFD from the byte-order mark.
+    pub offset: u32,
+    pub entries: Vec<IfdEntry<'a>>,
+    pub next: Option<u32>,
+}
+
+impl<'a> Ifd<'a> {
+    /// The first entry tagged `tag`.
+    pub fn entry(&self, tag: u16) -> Option<&IfdEntry<'a>> {
+        self.entries.iter().find(|entry| entry.tag == tag)
+    }
+}
+
+/// One entry of an IFD. Its values are read lazily: inline if they fit in
+/// four bytes, and from the offset stored there otherwise.
+#[derive(Debug, Clone)]
+pub struct IfdEntry<'a> {
+    tiff: Tiff<'a>,
+    pub tag: u16,
+    /// The raw type number. See [`field_type`](Self::field_type).
+    pub field_type: u16,
+    /// The number of values, not bytes.
+    pub count: u32,
+    // The absolute offset of the value-or-offset field.
+    value_offset: usize,
+}
+
+impl<'a> IfdEntry<'a> {
+    /// Returns `None` for type numbers that are not a known type.
+    pub fn field_type(&self) -> Option<FieldType> {
+        FieldType::from_u16(self.field_type)
+    }
+
+    /// A reader over the values, in the structure's byte order. Fails with
+    /// [`BinaryFileReaderError::Malformed`] for an unknown type.
+    pub fn values(&self) -> Result<BinaryFileReader<'a>, BinaryFileReaderError> {
+        let size = self
+            .field_type()
+            .ok_or(BinaryFileReaderError::Malformed {
+                reason: "unknown IFD field type",
+                current_offset: self.value_offset - 8,
+            })?
+            .size();
+        let length = size.saturating_mul(self.count as usize);
+
+        let mut reader = self.tiff.data.clone();
+        reader.set_offset(self.value_offset)?;
+        if length > 4 {
+            let offset = reader.read_u32()?;
+            reader = self.tiff.reader_at(offset)?;
+        }
+        reader.split_off_front(length)
+    }
+
+    /// The first value of a `Byte`, `Short`, `Long` or `Ifd` entry, such as
+    /// the offset of a sub-IFD.
+    pub fn as_u32(&self) -> Result<u32, BinaryFileReaderError> {
+        let mut values = self.values()?;
+        match self.field_type() {
+            Some(FieldType::Byte) => values.read_u8().map(u32::from),
+            Some(FieldType::Short) => values.read_u16().map(u32::from),
+            Some(FieldType::Long | FieldType::Ifd) => values.read_u32(),
+            _ => Err(BinaryFileReaderError::Malformed {
+                reason: "IFD entry is not an unsigned integer",
+                current_offset: self.value_offset - 8,
+            }),
+        }
+    }
+}
+
+/// Iterates over a chain of IFDs. Created with [`Tiff::ifds`].
+#[derive(Debug, Clone)]
+pub struct IfdIter<'a> {
+    tiff: Tiff<'a>,
+    next: Option<u32>,
+    visited: Vec<u32>,
+}
+
+impl<'a> Iterator for IfdIter<'a> {
+    type Item = Result<Ifd<'a>, BinaryFileReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.next.take()?;
+        if self.visited.contains(&offset) {
+            return Some(Err(BinaryFileReaderError::Malformed {
+                reason: "IFD chain loops",
+                current_offset: self.tiff.data.current_offset() + offset as usize,
+            }));
+        }
+        self.visited.push(offset);
+
+        let ifd = self.tiff.ifd_at(offset);
+        if let Ok(ifd) = &ifd {
+            self.next = ifd.next;
+        }
+        Some(ifd)
+    }
+}
+
+impl FusedIterator for IfdIter<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::{FieldType, Tiff};
+    use crate::{
+        endian::Endian,
+        error::{BinaryFileReaderError, ErrorCode},
+        BinaryFileReader,
+    };
+
+    #[test]
+    fn test_big_endian_tiff() -> Result<(), BinaryFileReaderError> {
+        let mut buffer = b"MM\0*\0\0\0\x08".to_vec();
+        // IFD0: ImageWidth (Long), Make (Ascii, out of line), then IFD1.
+        buffer.extend(b"\0\x02\x01\x00\0\x04\0\0\0\x01\0\0\x01\0");
+        buffer.extend(b"\x01\x0f\0\x02\0\0\0\x06\0\0\0\x2c\0\0\0\x26");
+        // IFD1 at 38: no entries, no next IFD.
+        buffer.extend(b"\0\0\0\0\0\0");
+        buffer.extend(b"Canon\0");
+
+        let tiff = Tiff::new(&BinaryFileReader::new(&buffer))?;
+        assert_eq!(tiff.endian(), Endian::Big);
+        let ifds = tiff.ifds().collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(ifds.len(), 2);
+        assert_eq!(ifds[0].entries.len(), 2);
+        assert_eq!(ifds[1].offset, 38);
+
+        let width = ifds[0].entry(0x0100).unwrap();
+        assert_eq!(width.field_type(), Some(FieldType::Long));
+        assert_eq!(width.as_u32()?, 256);
+        let make = ifds[0].entry(0x010f).unwrap();
+        assert_eq!(make.values()?.read_slice(6)?, b"Canon\0");
+        assert_eq!(make.as_u32().unwrap_err().code(), ErrorCode::Malformed);
+        assert!(ifds[0].entry(0x0101).is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_tiff() -> Result<(), BinaryFileReaderError> {
+        assert_eq!(
+            Tiff::new(&BinaryFileReader::new(b"II+\0\x08\0\0\0"))
+                .unwrap_err()
+                .code(),
+            ErrorCode::Malformed
+        );
+        assert!(Tiff::new(&BinaryFileReader::new(b"XX*\0\x08\0\0\0")).is_err());
+
+        // An IFD whose next pointer is itself, with an entry of unknown type
+        // whose values lie past the end.
+        let mut buffer = b"II*\0\x08\0\0\0".to_vec();
+        buffer.extend(b"\x01\0\x00\x01\x63\0\x01\0\0\0\0\0\0\0\x08\0\0\0");
+        let tiff = Tiff::new(&BinaryFileReader::new(&buffer))?;
+        let mut ifds = tiff.ifds();
+        let ifd = ifds.next().unwrap()?;
+        assert_eq!(ifd.entries[0].field_type(), None);
+        assert_eq!(ifd.entries[0].values().unwrap_err().offset(), Some(10));
+        assert_eq!(
+            ifds.next().unwrap().unwrap_err().code(),
+            ErrorCode::Malformed
+        );
+        assert!(ifds.next().is_none());
+        assert_eq!(tiff.ifd_at(100).unwrap_err().code(), ErrorCode::OutOfRange);
+
+        Ok(())
+    }
+}
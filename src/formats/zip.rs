@@ -0,0 +1,636 @@
+//! The structure of ZIP archives: the end of central directory record found
+//! by searching back from the end, the central directory entries it points
+//! to, and the local file headers and compressed data of each entry.
+//!
+//! ZIP64 archives and data prepended to an archive, as in self-extracting
+//! archives, are supported. Decompressing is left to the caller, such as
+//! with [`inflate_sub_reader`](crate::BinaryFileReader::inflate_sub_reader)
+//! for [`DEFLATED`] entries.
+//!
+//! Only available with the `zip` feature.
+//!
+//! # Examples
+//! ```
+//! # use binary_file_reader::BinaryFileReader;
+//! # use binary_file_reader::formats::zip::{self, Archive};
+//! # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut buffer = b"PK\x03\x04\x0a\0\0\0\0\0\0\0\0\0".to_vec();
+//! buffer.extend(b"\x86\xa6\x10\x36\x05\0\0\0\x05\0\0\0\x05\0\0\0a.txthello");
+//! let central_directory = buffer.len();
+//! buffer.extend(b"PK\x01\x02\x14\0\x0a\0\0\0\0\0\0\0\0\0");
+//! buffer.extend(b"\x86\xa6\x10\x36\x05\0\0\0\x05\0\0\0\x05\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0a.txt");
+//! let size = (buffer.len() - central_directory) as u8;
+//! buffer.extend(b"PK\x05\x06\0\0\0\0\x01\0\x01\0");
+//! buffer.extend([size, 0, 0, 0, central_directory as u8, 0, 0, 0, 0, 0]);
+//!
+//! let archive = Archive::new(&BinaryFileReader::new(&buffer))?;
+//! for entry in archive.entries() {
+//!     let entry = entry?;
+//!     assert_eq!(entry.name, b"a.txt");
+//!     assert_eq!(entry.compression, zip::STORED);
+//!
+//!     let mut file = archive.local_file(&entry)?;
+//!     assert_eq!(file.data.read_utf8(5)?, "hello");
+//! }
+//! #
+//! # Ok(())
+//! # }
+//! # fn main() {
+//! #    try_main().unwrap();
+//! # }
+//! ```
+
+use crate::{
+    endian::Endian,
+    error::BinaryFileReaderError,
+    tlv::{TlvIter, TlvLayout},
+    BinaryFileReader,
+};
+use core::iter::FusedIterator;
+
+/// The compression method of entries stored without compression.
+pub const STORED: u16 = 0;
+/// The compression method of entries compressed with raw deflate.
+pub const DEFLATED: u16 = 8;
+
+const LOCAL_FILE_HEADER: &[u8; 4] = b"PK\x03\x04";
+const CENTRAL_DIRECTORY_ENTRY: &[u8; 4] = b"PK\x01\x02";
+const END_OF_CENTRAL_DIRECTORY: &[u8; 4] = b"PK\x05\x06";
+const ZIP64_END_OF_CENTRAL_DIRECTORY: &[u8; 4] = b"PK\x06\x06";
+const ZIP64_LOCATOR: &[u8; 4] = b"PK\x06\x07";
+
+/// The size of the end of central directory record without its comment.
+const END_OF_CENTRAL_DIRECTORY_LEN: usize = 22;
+const ZIP64_LOCATOR_LEN: usize = 20;
+const ZIP64_EXTRA_FIELD: u64 = 0x0001;
+
+fn to_usize(value: u64) -> usize {
+    usize::try_from(value).unwrap_or(usize::MAX)
+}
+
+/// Iterates over the fields of an extra field block, a little-endian `u16`
+/// header id and `u16` size followed by the data.
+pub fn extra_fields<'a>(extra: &BinaryFileReader<'a>) -> TlvIter<'a> {
+    TlvLayout::new(2, 2)
+        .endian(Endian::Little)
+        .iter(extra.clone())
+}
+
+/// The data of the ZIP64 extended information field, which holds the values
+/// of the fields saturated at their maximum, in the order of the record.
+fn zip64_extra<'a>(
+    extra: &BinaryFileReader<'a>,
+    record: usize,
+) -> Result<BinaryFileReader<'a>, BinaryFileReaderError> {
+    for field in extra_fields(extra) {
+        let field = field?;
+        if field.tag == ZIP64_EXTRA_FIELD {
+            return Ok(field.value);
+        }
+    }
+    Err(BinaryFileReaderError::malformed(
+        "ZIP64 extra field is missing",
+        record,
+    ))
+}
+
+/// The end of central directory record, with the values of the ZIP64 record
+/// when there is one.
+#[derive(Debug, Clone)]
+pub struct EndOfCentralDirectory<'a> {
+    /// The absolute offset of the record.
+    pub offset: usize,
+    pub disk: u32,
+    /// The disk the central directory starts on.
+    pub central_directory_disk: u32,
+    /// The number of entries on this disk.
+    pub disk_entries: u64,
+    pub entries: u64,
+    pub central_directory_size: u64,
+    /// The offset of the central directory from the start of the archive.
+    pub central_directory_offset: u64,
+    pub comment: &'a [u8],
+    /// Whether the values come from a ZIP64 record.
+    pub zip64: bool,
+}
+
+/// Finds the end of central directory record by searching back from the end
+/// of `reader` over the longest possible comment. The last record whose
+/// comment fits is used. Fails with [`BinaryFileReaderError::Malformed`] if
+/// there is none.
+///
+/// The ZIP64 record is read from the offset in its locator, counted from the
+/// cursor of `reader`. `reader` is not advanced.
+pub fn find_end_of_central_directory<'a>(
+    reader: &BinaryFileReader<'a>,
+) -> Result<EndOfCentralDirectory<'a>, BinaryFileReaderError> {
+    let start = reader.current_offset();
+    let end = start + reader.available_bytes();
+    let not_found = || BinaryFileReaderError::malformed("end of central directory not found", end);
+    let bytes = reader.peek_slice(reader.available_bytes())?;
+    let last = bytes
+        .len()
+        .checked_sub(END_OF_CENTRAL_DIRECTORY_LEN)
+        .ok_or_else(not_found)?;
+    let first = last.saturating_sub(u16::MAX as usize);
+    let position = (first..=last)
+        .rev()
+        .find(|&i| {
+            let comment_len = u16::from_le_bytes([bytes[i + 20], bytes[i + 21]]) as usize;
+            bytes[i..].starts_with(END_OF_CENTRAL_DIRECTORY)
+                && i + END_OF_CENTRAL_DIRECTORY_LEN + comment_len <= bytes.len()
+        })
+        .ok_or_else(not_found)?;
+
+    let mut record = reader.clone();
+    record.set_endian(Endian::Little);
+    record.set_offset(start + position + 4)?;
+    let mut eocd = EndOfCentralDirectory {
+        offset: start + position,
+        disk: record.read_u16()? as u32,
+        central_directory_disk: record.read_u16()? as u32,
+        disk_entries: record.read_u16()? as u64,
+        entries: record.read_u16()? as u64,
+        central_directory_size: record.read_u32()? as u64,
+        central_directory_offset: record.read_u32()? as u64,
+        comment: &[],
+        zip64: false,
+    };
+    let comment_len = record.read_u16()? as usize;
+    eocd.comment = record.read_slice(comment_len)?;
+
+    if position >= ZIP64_LOCATOR_LEN {
+        let mut locator = record.clone();
+        locator.set_offset(eocd.offset - ZIP64_LOCATOR_LEN)?;
+        if locator.try_expect(ZIP64_LOCATOR) {
+            locator.advance(4)?;
+            let zip64_offset = locator.read_u64()?;
+            let mut zip64 = record.clone();
+            zip64.set_offset(start.saturating_add(to_usize(zip64_offset)))?;
+            zip64.expect(ZIP64_END_OF_CENTRAL_DIRECTORY)?;
+            zip64.advance(12)?;
+            eocd.disk = zip64.read_u32()?;
+            eocd.central_directory_disk = zip64.read_u32()?;
+            eocd.disk_entries = zip64.read_u64()?;
+            eocd.entries = zip64.read_u64()?;
+            eocd.central_directory_size = zip64.read_u64()?;
+            eocd.central_directory_offset = zip64.read_u64()?;
+            eocd.zip64 = true;
+        }
+    }
+    Ok(eocd)
+}
+
+/// One entry of the central directory.
+#[derive(Debug, Clone)]
+pub struct CentralDirectoryEntry<'a> {
+    /// The absolute offset of the record.
+    pub offset: usize,
+    pub version_made_by: u16,
+    pub version_needed: u16,
+    pub flags: u16,
+    /// The compression method, such as [`STORED`] or [`DEFLATED`].
+    pub compression: u16,
+    /// The modification time in MS-DOS format.
+    pub modified_time: u16,
+    /// The modification date in MS-DOS format.
+    pub modified_date: u16,
+    pub crc32: u32,
+    pub compressed_size: u64,
+    pub uncompressed_size: u64,
+    pub disk_start: u32,
+    pub internal_attributes: u16,
+    pub external_attributes: u32,
+    /// The offset of the local file header from the start of the archive.
+    pub local_header_offset: u64,
+    /// The file name, in UTF-8 if [`is_utf8`](Self::is_utf8) and code page
+    /// 437 otherwise.
+    pub name: &'a [u8],
+    /// A little-endian reader over the extra fields. See [`extra_fields`].
+    pub extra: BinaryFileReader<'a>,
+    pub comment: &'a [u8],
+}
+
+impl CentralDirectoryEntry<'_> {
+    #[inline]
+    pub fn is_encrypted(&self) -> bool {
+        self.flags & 0x0001 != 0
+    }
+
+    #[inline]
+    pub fn is_utf8(&self) -> bool {
+        self.flags & 0x0800 != 0
+    }
+
+    /// Whether the entry is a directory, named with a trailing `/`.
+    #[inline]
+    pub fn is_dir(&self) -> bool {
+        self.name.ends_with(b"/")
+    }
+}
+
+fn read_entry<'a>(
+    reader: &mut BinaryFileReader<'a>,
+) -> Result<CentralDirectoryEntry<'a>, BinaryFileReaderError> {
+    let offset = reader.current_offset();
+    let result = (|| {
+        reader.expect(CENTRAL_DIRECTORY_ENTRY)?;
+        let version_made_by = reader.read_u16()?;
+        let version_needed = reader.read_u16()?;
+        let flags = reader.read_u16()?;
+        let compression = reader.read_u16()?;
+        let modified_time = reader.read_u16()?;
+        let modified_date = reader.read_u16()?;
+        let crc32 = reader.read_u32()?;
+        let mut compressed_size = reader.read_u32()? as u64;
+        let mut uncompressed_size = reader.read_u32()? as u64;
+        let name_len = reader.read_u16()? as usize;
+        let extra_len = reader.read_u16()? as usize;
+        let comment_len = reader.read_u16()? as usize;
+        let mut disk_start = reader.read_u16()? as u32;
+        let internal_attributes = reader.read_u16()?;
+        let external_attributes = reader.read_u32()?;
+        let mut local_header_offset = reader.read_u32()? as u64;
+        let name = reader.read_slice(name_len)?;
+        let extra = reader.split_off_front(extra_len)?;
+        let comment = reader.read_slice(comment_len)?;
+
+        let saturated = u32::MAX as u64;
+        if [uncompressed_size, compressed_size, local_header_offset].contains(&saturated)
+            || disk_start == u16::MAX as u32
+        {
+            let mut zip64 = zip64_extra(&extra, offset)?;
+            if uncompressed_size == saturated {
+                uncompressed_size = zip64.read_u64()?;
+            }
+            if compressed_size == saturated {
+                compressed_size = zip64.read_u64()?;
+            }
+            if local_header_offset == saturated {
+                local_header_offset = zip64.read_u64()?;
+            }
+            if disk_start == u16::MAX as u32 {
+                disk_start = zip64.read_u32()?;
+            }
+        }
+
+        Ok(CentralDirectoryEntry {
+            offset,
+            version_made_by,
+            version_needed,
+            flags,
+            compression,
+            modified_time,
+            modified_date,
+            crc32,
+            compressed_size,
+            uncompressed_size,
+            disk_start,
+            internal_attributes,
+            external_attributes,
+            local_header_offset,
+            name,
+            extra,
+            comment,
+        })
+    })();
+    if result.is_err() {
+        reader.set_offset(offset)?;
+    }
+    result
+}
+
+/// A local file header. Its sizes and CRC are zero when bit 3 of the flags
+/// is set, with the values in a data descriptor after the data and in the
+/// central directory.
+#[derive(Debug, Clone)]
+pub struct LocalFileHeader<'a> {
+    /// The absolute offset of the header.
+    pub offset: usize,
+    pub version_needed: u16,
+    pub flags: u16,
+    pub compression: u16,
+    pub modified_time: u16,
+    pub modified_date: u16,
+    pub crc32: u32,
+    pub compressed_size: u64,
+    pub uncompressed_size: u64,
+    pub name: &'a [u8],
+    /// A little-endian reader over the extra fields. See [`extra_fields`].
+    pub extra: BinaryFileReader<'a>,
+}
+
+/// Reads the local file header at the cursor, leaving the reader at the
+/// start of the file data. On failure the reader is left at the start of
+/// the header.
+pub fn read_local_file_header<'a>(
+    reader: &mut BinaryFileReader<'a>,
+) -> Result<LocalFileHeader<'a>, BinaryFileReaderError> {
+    let offset = reader.current_offset();
+    let saved = reader.endian();
+    reader.set_endian(Endian::Little);
+    let result = (|| {
+        reader.expect(LOCAL_FILE_HEADER)?;
+        let version_needed = reader.read_u16()?;
+        let flags = reader.read_u16()?;
+        let compression = reader.read_u16()?;
+        let modified_time = reader.read_u16()?;
+        let modified_date = reader.read_u16()?;
+        let crc32 = reader.read_u32()?;
+        let mut compressed_size = reader.read_u32()? as u64;
+        let mut uncompressed_size = reader.read_u32()? as u64;
+        let name_len = reader.read_u16()? as usize;
+        let extra_len = reader.read_u16()? as usize;
+        let name = reader.read_slice(name_len)?;
+        let extra = reader.split_off_front(extra_len)?;
+
+        let saturated = u32::MAX as u64;
+        if uncompressed_size == saturated || compressed_size == saturated {
+            let mut zip64 = zip64_extra(&extra, offset)?;
+            uncompressed_size = zip64.read_u64()?;
+            compressed_size = zip64.read_u64()?;
+        }
+
+        Ok(LocalFileHeader {
+            offset,
+            version_needed,
+            flags,
+            compression,
+            modified_time,
+            modified_date,
+            crc32,
+            compressed_size,
+            uncompressed_size,
+            name,
+            extra,
+        })
+    })();
+    reader.set_endian(saved);
+    if result.is_err() {
+        reader.set_offset(offset)?;
+    }
+    result
+}
+
+/// The local file header of an entry and its compressed data.
+#[derive(Debug, Clone)]
+pub struct LocalFile<'a> {
+    pub header: LocalFileHeader<'a>,
+    /// A reader over the compressed data, sized by the central directory.
+    pub data: BinaryFileReader<'a>,
+}
+
+/// A ZIP archive located by its end of central directory record.
+#[derive(Debug, Clone)]
+pub struct Archive<'a> {
+    // A little-endian reader at the start of the archive, after any
+    // prepended data.
+    data: BinaryFileReader<'a>,
+    central_directory: BinaryFileReader<'a>,
+    pub end_of_central_directory: EndOfCentralDirectory<'a>,
+}
+
+impl<'a> Archive<'a> {
+    /// Locates the archive in the bytes after the cursor of `reader` with
+    /// [`find_end_of_central_directory`]. `reader` is not advanced.
+    ///
+    /// Offsets are counted from the cursor, or from after the data
+    /// prepended to the archive when the central directory is found that
+    /// far past its recorded offset. Fails if the central directory does not
+    /// fit before the end of central directory record.
+    pub fn new(reader: &BinaryFileReader<'a>) -> Result<Self, BinaryFileReaderError> {
+        let eocd = find_end_of_central_directory(reader)?;
+        let mut data = reader.clone();
+        data.set_endian(Endian::Little);
+
+        if !eocd.zip64 {
+            let recorded = eocd
+                .central_directory_offset
+                .saturating_add(eocd.central_directory_size);
+            let actual = (eocd.offset - data.current_offset()) as u64;
+            if actual > recorded {
+                data.advance(to_usize(actual - recorded))?;
+            }
+        }
+        let mut archive = Self {
+            central_directory: data.clone(),
+            data,
+            end_of_central_directory: eocd,
+        };
+        let eocd = &archive.end_of_central_directory;
+        archive.central_directory = archive
+            .reader_at(eocd.central_directory_offset)?
+            .split_off_front(to_usize(eocd.central_directory_size))?;
+        Ok(archive)
+    }
+
+    /// A little-endian reader positioned `offset` bytes after the start of
+    /// the archive.
+    pub fn reader_at(&self, offset: u64) -> Result<BinaryFileReader<'a>, BinaryFileReaderError> {
+        let mut reader = self.data.clone();
+        reader.set_offset(reader.current_offset().saturating_add(to_usize(offset)))?;
+        Ok(reader)
+    }
+
+    /// Iterates over the entries of the central directory.
+    pub fn entries(&self) -> CentralDirectoryIter<'a> {
+        CentralDirectoryIter::new(self.central_directory.clone())
+    }
+
+    /// Reads the local file header of `entry` and splits off its compressed
+    /// data.
+    pub fn local_file(
+        &self,
+        entry: &CentralDirectoryEntry<'_>,
+    ) -> Result<LocalFile<'a>, BinaryFileReaderError> {
+        let mut reader = self.reader_at(entry.local_header_offset)?;
+        let header = read_local_file_header(&mut reader)?;
+        let data = reader.split_off_front(to_usize(entry.compressed_size))?;
+        Ok(LocalFile { header, data })
+    }
+}
+
+/// Iterates over central directory entries. A record that cannot be read is
+/// yielded as an error and ends the iteration.
+#[derive(Debug, Clone)]
+pub struct CentralDirectoryIter<'a> {
+    reader: BinaryFileReader<'a>,
+    done: bool,
+}
+
+impl<'a> CentralDirectoryIter<'a> {
+    /// Iterates over the entries at the cursor of `reader` until it is
+    /// exhausted, such as a central directory split off another way.
+    pub fn new(mut reader: BinaryFileReader<'a>) -> Self {
+        reader.set_endian(Endian::Little);
+        Self {
+            reader,
+            done: false,
+        }
+    }
+
+    /// The reader after the entries yielded so far.
+    pub fn into_inner(self) -> BinaryFileReader<'a> {
+        self.reader
+    }
+}
+
+impl<'a> Iterator for CentralDirectoryIter<'a> {
+    type Item = Result<CentralDirectoryEntry<'a>, BinaryFileReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.reader.available_bytes() == 0 {
+            return None;
+        }
+
+        let entry = read_entry(&mut self.reader);
+        self.done = entry.is_err();
+        Some(entry)
+    }
+}
+
+impl FusedIterator for CentralDirectoryIter<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::{extra_fields, find_end_of_central_directory, Archive, DEFLATED};
+    use crate::{
+        error::{BinaryFileReaderError, ErrorCode},
+        BinaryFileReader,
+    };
+
+    // Appends a local file and returns its offset.
+    fn local_file(buffer: &mut Vec<u8>, name: &[u8], data: &[u8]) -> u32 {
+        let offset = buffer.len() as u32;
+        buffer.extend(b"PK\x03\x04\x14\0\0\0\x08\0\0\0\0\0\0\0\0\0");
+        buffer.extend((data.len() as u32).to_le_bytes());
+        buffer.extend(0xffff_ffff_u32.to_le_bytes());
+        buffer.extend([name.len() as u8, 0, 20, 0]);
+        buffer.extend(name);
+        buffer.extend(b"\x01\0\x10\0");
+        buffer.extend(100_u64.to_le_bytes());
+        buffer.extend((data.len() as u64).to_le_bytes());
+        buffer.extend(data);
+        offset
+    }
+
+    fn entry(buffer: &mut Vec<u8>, name: &[u8], data: &[u8], offset: u32, zip64: bool) {
+        buffer.extend(b"PK\x01\x02\x14\x03\x14\0\0\x08\x08\0\0\0\0\0\0\0\0\0");
+        buffer.extend((data.len() as u32).to_le_bytes());
+        buffer.extend(100_u32.to_le_bytes());
+        buffer.extend([name.len() as u8, 0, if zip64 { 12 } else { 0 }, 0, 1, 0]);
+        buffer.extend([0; 8]);
+        buffer.extend(if zip64 { u32::MAX } else { offset }.to_le_bytes());
+        buffer.extend(name);
+        if zip64 {
+            buffer.extend(b"\x01\0\x08\0");
+            buffer.extend((offset as u64).to_le_bytes());
+        }
+        buffer.push(b'!');
+    }
+
+    #[test]
+    fn test_archive() -> Result<(), BinaryFileReaderError> {
+        let mut buffer = b"#!/bin/sh\n".to_vec();
+        let start = buffer.len() as u32;
+        let first = local_file(&mut buffer, b"dir/a", b"compressed") - start;
+        let second = local_file(&mut buffer, b"dir/", b"") - start;
+        let central_directory = buffer.len() as u32;
+        entry(&mut buffer, b"dir/a", b"compressed", first, false);
+        entry(&mut buffer, b"dir/", b"", second, true);
+        let size = buffer.len() as u32 - central_directory;
+        buffer.extend(b"PK\x05\x06\0\0\0\0\x02\0\x02\0");
+        buffer.extend(size.to_le_bytes());
+        buffer.extend((central_directory - start).to_le_bytes());
+        // The comment holds a record whose comment does not fit.
+        buffer.extend(b"\x16\0PK\x05\x06");
+        buffer.extend([0; 16]);
+        buffer.extend([0xff; 2]);
+
+        let archive = Archive::new(&BinaryFileReader::new(&buffer))?;
+        let eocd = &archive.end_of_central_directory;
+        assert_eq!((eocd.entries, eocd.comment.len()), (2, 22));
+        assert!(!eocd.zip64);
+
+        let entries = archive.entries().collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].name, b"dir/a");
+        assert_eq!(entries[0].compression, DEFLATED);
+        assert!(entries[0].is_utf8() && !entries[0].is_dir());
+        assert_eq!(entries[1].local_header_offset, second as u64);
+        assert_eq!(entries[1].comment, b"!");
+        assert!(entries[1].is_dir());
+        let extra = extra_fields(&entries[1].extra).next().unwrap()?;
+        assert_eq!((extra.tag, extra.value.available_bytes()), (1, 8));
+
+        let mut file = archive.local_file(&entries[0])?;
+        assert_eq!(file.header.offset, 10);
+        assert_eq!(file.header.uncompressed_size, 100);
+        assert_eq!(file.data.read_slice(10)?, b"compressed");
+        assert_eq!(archive.local_file(&entries[1])?.data.available_bytes(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zip64_end_of_central_directory() -> Result<(), BinaryFileReaderError> {
+        let mut buffer = b"PK\x01\x02".to_vec();
+        buffer.extend([0; 42]);
+        let zip64 = buffer.len() as u64;
+        buffer.extend(b"PK\x06\x06");
+        buffer.extend(44_u64.to_le_bytes());
+        buffer.extend([0x2d, 0, 0x2d, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        buffer.extend(1_u64.to_le_bytes());
+        buffer.extend(1_u64.to_le_bytes());
+        buffer.extend(46_u64.to_le_bytes());
+        buffer.extend(0_u64.to_le_bytes());
+        buffer.extend(b"PK\x06\x07\0\0\0\0");
+        buffer.extend(zip64.to_le_bytes());
+        buffer.extend(b"\x01\0\0\0");
+        buffer.extend(b"PK\x05\x06\xff\xff\xff\xff\xff\xff\xff\xff");
+        buffer.extend([0xff; 8]);
+        buffer.extend([0; 2]);
+
+        let archive = Archive::new(&BinaryFileReader::new(&buffer))?;
+        let eocd = &archive.end_of_central_directory;
+        assert!(eocd.zip64);
+        assert_eq!((eocd.entries, eocd.central_directory_size), (1, 46));
+        assert_eq!(archive.entries().next().unwrap()?.offset, 0);
+
+        let err = find_end_of_central_directory(&BinaryFileReader::new(&buffer[..140]));
+        assert_eq!(err.unwrap_err().code(), ErrorCode::Malformed);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_archive() {
+        assert_eq!(
+            Archive::new(&BinaryFileReader::new(b"PK\x05\x06"))
+                .unwrap_err()
+                .offset(),
+            Some(4)
+        );
+
+        // The central directory runs past the end.
+        let mut buffer = b"PK\x05\x06\0\0\0\0\x01\0\x01\0".to_vec();
+        buffer.extend(b"\x2e\0\0\0\x10\0\0\0\0\0");
+        assert_eq!(
+            Archive::new(&BinaryFileReader::new(&buffer))
+                .unwrap_err()
+                .code(),
+            ErrorCode::BufferUnderflow
+        );
+
+        let mut buffer = b"PK\x01\x02\0\0".to_vec();
+        buffer.extend(b"PK\x05\x06\0\0\0\0\x01\0\x01\0\x06\0\0\0\0\0\0\0\0\0");
+        let archive = Archive::new(&BinaryFileReader::new(&buffer)).unwrap();
+        let mut entries = archive.entries();
+        assert_eq!(
+            entries.next().unwrap().unwrap_err().code(),
+            ErrorCode::BufferUnderflow
+        );
+        assert!(entries.next().is_none());
+        assert_eq!(entries.into_inner().current_offset(), 0);
+    }
+}
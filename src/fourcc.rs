@@ -0,0 +1,97 @@
+use core::fmt;
+
+/// # Examples
+/// ```
+/// # use binary_file_reader::fourcc::FourCC;
+/// let tag = FourCC::from(b"IHDR");
+/// assert_eq!(tag, "IHDR");
+/// assert_eq!(tag.to_string(), "IHDR");
+/// assert_eq!(FourCC([0x00, b'A', b'B', 0xff]).to_string(), "\\x00AB\\xff");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FourCC(pub [u8; 4]);
+
+impl FourCC {
+    pub fn as_bytes(&self) -> &[u8; 4] {
+        &self.0
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        core::str::from_utf8(&self.0).ok()
+    }
+}
+
+impl From<[u8; 4]> for FourCC {
+    fn from(value: [u8; 4]) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&[u8; 4]> for FourCC {
+    fn from(value: &[u8; 4]) -> Self {
+        Self(*value)
+    }
+}
+
+impl PartialEq<str> for FourCC {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other.as_bytes()
+    }
+}
+
+impl PartialEq<&str> for FourCC {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == other.as_bytes()
+    }
+}
+
+impl PartialEq<[u8; 4]> for FourCC {
+    fn eq(&self, other: &[u8; 4]) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<&[u8; 4]> for FourCC {
+    fn eq(&self, other: &&[u8; 4]) -> bool {
+        self.0 == **other
+    }
+}
+
+impl fmt::Display for FourCC {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for b in self.0 {
+            if b.is_ascii_graphic() || b == b' ' {
+                write!(f, "{}", b as char)?;
+            } else {
+                write!(f, "\\x{:02x}", b)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for FourCC {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FourCC(\"{}\")", self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FourCC;
+    use alloc::{format, string::ToString};
+
+    #[test]
+    fn test_fourcc() {
+        let tag = FourCC(*b"fmt ");
+        assert_eq!(tag, "fmt ");
+        assert_eq!(tag, b"fmt ");
+        assert_ne!(tag, "fmt");
+        assert_eq!(tag.as_str(), Some("fmt "));
+        assert_eq!(format!("{:?}", tag), "FourCC(\"fmt \")");
+
+        let tag = FourCC([0x89, b'P', b'N', b'G']);
+        assert_eq!(tag.as_str(), None);
+        assert_eq!(tag.to_string(), "\\x89PNG");
+    }
+}
@@ -0,0 +1,65 @@
+use alloc::string::String;
+use core::fmt::Write;
+
+const BYTES_PER_LINE: usize = 16;
+
+/// Formats `bytes` like `xxd`: 16 bytes per line, in groups of two, with the
+/// offset of each line counted from `base_offset` and an ASCII gutter where
+/// unprintable bytes show as `.`.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::hexdump::hexdump;
+/// let dump = hexdump(b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR\x00\x01", 0x10);
+/// assert_eq!(
+///     dump,
+///     "\
+/// 00000010: 8950 4e47 0d0a 1a0a 0000 000d 4948 4452  .PNG........IHDR
+/// 00000020: 0001                                     ..
+/// "
+/// );
+/// ```
+pub fn hexdump(bytes: &[u8], base_offset: usize) -> String {
+    let mut out = String::new();
+    for (index, line) in bytes.chunks(BYTES_PER_LINE).enumerate() {
+        let _ = write!(out, "{:08x}:", base_offset + index * BYTES_PER_LINE);
+        for column in 0..BYTES_PER_LINE {
+            if column % 2 == 0 {
+                out.push(' ');
+            }
+            match line.get(column) {
+                Some(byte) => {
+                    let _ = write!(out, "{:02x}", byte);
+                }
+                None => out.push_str("  "),
+            }
+        }
+        out.push_str("  ");
+        out.extend(line.iter().map(|&byte| {
+            if byte.is_ascii_graphic() || byte == b' ' {
+                byte as char
+            } else {
+                '.'
+            }
+        }));
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::hexdump;
+
+    #[test]
+    fn test_hexdump() {
+        assert_eq!(hexdump(&[], 0), "");
+        assert_eq!(
+            hexdump(b"Hello, World!\x7f\xff ", 0xabc),
+            "00000abc: 4865 6c6c 6f2c 2057 6f72 6c64 217f ff20  Hello, World!.. \n"
+        );
+        let dump = hexdump(&[0; 33], 0);
+        assert_eq!(dump.lines().count(), 3);
+        assert!(dump.ends_with("00000020: 00                                       .\n"));
+    }
+}
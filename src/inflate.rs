@@ -0,0 +1,431 @@
+use crate::{checksum::adler32, error::BinaryFileReaderError};
+use alloc::vec::Vec;
+
+const MAX_BITS: usize = 15;
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+// The order code length code lengths are stored in.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// Reads a DEFLATE bit stream, least significant bit first.
+struct Bits<'d> {
+    data: &'d [u8],
+    position: usize,
+    buffer: u32,
+    count: u32,
+    base_offset: usize,
+}
+
+impl Bits<'_> {
+    fn error(&self, reason: &'static str) -> BinaryFileReaderError {
+        BinaryFileReaderError::Decompress {
+            reason,
+            current_offset: self.base_offset + self.position,
+        }
+    }
+
+    fn bits(&mut self, count: u32) -> Result<u32, BinaryFileReaderError> {
+        while self.count < count {
+            let byte = *self
+                .data
+                .get(self.position)
+                .ok_or_else(|| self.error("the stream ends early"))?;
+            self.buffer |= (byte as u32) << self.count;
+            self.position += 1;
+            self.count += 8;
+        }
+        let value = self.buffer & ((1 << count) - 1);
+        self.buffer >>= count;
+        self.count -= count;
+        Ok(value)
+    }
+
+    /// Drops the bits left in the current byte.
+    fn align(&mut self) {
+        self.buffer = 0;
+        self.count = 0;
+    }
+}
+
+/// A canonical Huffman code, decoded one bit at a time.
+struct Huffman {
+    counts: [u16; MAX_BITS + 1],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Option<Self> {
+        let mut counts = [0u16; MAX_BITS + 1];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+
+        // Reject codes that assign more codes of a length than exist.
+        let mut left = 1i32;
+        for &count in &counts[1..] {
+            left = (left << 1) - count as i32;
+            if left < 0 {
+                return None;
+            }
+        }
+
+        let mut offsets = [0u16; MAX_BITS + 2];
+        for length in 1..=MAX_BITS {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+        let mut symbols = alloc::vec![0; lengths.len()];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                symbols[offsets[length as usize] as usize] = symbol as u16;
+                offsets[length as usize] += 1;
+            }
+        }
+
+        counts[0] = 0;
+        Some(Self { counts, symbols })
+    }
+
+    fn decode(&self, bits: &mut Bits<'_>) -> Result<u16, BinaryFileReaderError> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for &count in &self.counts[1..] {
+            code |= bits.bits(1)? as i32;
+            let count = count as i32;
+            if code - count < first {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(bits.error("invalid Huffman code"))
+    }
+}
+
+fn fixed_codes() -> (Huffman, Huffman) {
+    let mut lengths = [0u8; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    let literals = Huffman::new(&lengths).expect("the fixed code is complete");
+    let distances = Huffman::new(&[5; 30]).expect("the fixed code is complete");
+    (literals, distances)
+}
+
+fn dynamic_codes(bits: &mut Bits<'_>) -> Result<(Huffman, Huffman), BinaryFileReaderError> {
+    let literal_count = bits.bits(5)? as usize + 257;
+    let distance_count = bits.bits(5)? as usize + 1;
+    let code_length_count = bits.bits(4)? as usize + 4;
+    if literal_count > 286 || distance_count > 30 {
+        return Err(bits.error("too many codes"));
+    }
+
+    let mut code_lengths = [0u8; 19];
+    for &symbol in &CODE_LENGTH_ORDER[..code_length_count] {
+        code_lengths[symbol] = bits.bits(3)? as u8;
+    }
+    let code_length_code =
+        Huffman::new(&code_lengths).ok_or_else(|| bits.error("invalid code length code"))?;
+
+    let mut lengths = [0u8; 286 + 30];
+    let total = literal_count + distance_count;
+    let mut index = 0;
+    while index < total {
+        let symbol = code_length_code.decode(bits)?;
+        let (length, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = index
+                    .checked_sub(1)
+                    .map(|previous| lengths[previous])
+                    .ok_or_else(|| bits.error("repeat with no previous length"))?;
+                (previous, 3 + bits.bits(2)? as usize)
+            }
+            17 => (0, 3 + bits.bits(3)? as usize),
+            _ => (0, 11 + bits.bits(7)? as usize),
+        };
+        if index + repeat > total {
+            return Err(bits.error("code lengths overflow the table"));
+        }
+        lengths[index..index + repeat].fill(length);
+        index += repeat;
+    }
+
+    if lengths[256] == 0 {
+        return Err(bits.error("no end-of-block code"));
+    }
+    let literals = Huffman::new(&lengths[..literal_count])
+        .ok_or_else(|| bits.error("invalid literal/length code"))?;
+    let distances = Huffman::new(&lengths[literal_count..total])
+        .ok_or_else(|| bits.error("invalid distance code"))?;
+    Ok((literals, distances))
+}
+
+fn reserve(
+    output: &[u8],
+    length: usize,
+    max_output: usize,
+    bits: &Bits<'_>,
+) -> Result<(), BinaryFileReaderError> {
+    let requested_bytes = output.len().saturating_add(length);
+    if requested_bytes > max_output {
+        return Err(BinaryFileReaderError::AllocationLimit {
+            requested_bytes,
+            max_alloc: max_output,
+            current_offset: bits.base_offset + bits.position,
+        });
+    }
+    Ok(())
+}
+
+fn codes(
+    bits: &mut Bits<'_>,
+    output: &mut Vec<u8>,
+    max_output: usize,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> Result<(), BinaryFileReaderError> {
+    loop {
+        let symbol = literals.decode(bits)? as usize;
+        match symbol {
+            0..=255 => {
+                reserve(output, 1, max_output, bits)?;
+                output.push(symbol as u8);
+            }
+            256 => return Ok(()),
+            _ => {
+                let index = symbol - 257;
+                if index >= LENGTH_BASE.len() {
+                    return Err(bits.error("invalid length symbol"));
+                }
+                let length =
+                    LENGTH_BASE[index] as usize + bits.bits(LENGTH_EXTRA[index] as u32)? as usize;
+
+                let index = distances.decode(bits)? as usize;
+                if index >= DISTANCE_BASE.len() {
+                    return Err(bits.error("invalid distance symbol"));
+                }
+                let distance = DISTANCE_BASE[index] as usize
+                    + bits.bits(DISTANCE_EXTRA[index] as u32)? as usize;
+                if distance > output.len() {
+                    return Err(bits.error("distance is before the start of the output"));
+                }
+
+                reserve(output, length, max_output, bits)?;
+                let start = output.len() - distance;
+                // The copy may overlap the bytes it produces.
+                for offset in 0..length {
+                    output.push(output[start + offset]);
+                }
+            }
+        }
+    }
+}
+
+/// Decompresses raw DEFLATE data (RFC 1951), as stored in ZIP entries.
+/// Trailing bytes after the final block are ignored. Fails with
+/// [`BinaryFileReaderError::Decompress`] for malformed data, and with
+/// [`BinaryFileReaderError::AllocationLimit`] if the output would exceed
+/// `max_output` bytes. Offsets in errors are relative to `compressed`.
+///
+/// Only available with the `inflate` feature.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::inflate::inflate;
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// // "hello hello hello" with fixed Huffman codes.
+/// let compressed = [0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0xc8, 0x40, 0x90, 0x00];
+/// assert_eq!(inflate(&compressed, usize::MAX)?, b"hello hello hello");
+/// assert!(inflate(&compressed, 4).is_err());
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+pub fn inflate(compressed: &[u8], max_output: usize) -> Result<Vec<u8>, BinaryFileReaderError> {
+    inflate_at(compressed, max_output, 0).map(|(output, _)| output)
+}
+
+/// Returns the output and the number of bytes of `compressed` used.
+pub(crate) fn inflate_at(
+    compressed: &[u8],
+    max_output: usize,
+    base_offset: usize,
+) -> Result<(Vec<u8>, usize), BinaryFileReaderError> {
+    let mut bits = Bits {
+        data: compressed,
+        position: 0,
+        buffer: 0,
+        count: 0,
+        base_offset,
+    };
+    let mut output = Vec::new();
+    loop {
+        let last = bits.bits(1)? == 1;
+        match bits.bits(2)? {
+            0 => {
+                bits.align();
+                let length = bits.bits(16)?;
+                if bits.bits(16)? != !length & 0xffff {
+                    return Err(bits.error("stored block length does not match its complement"));
+                }
+                let length = length as usize;
+                let stored = compressed
+                    .get(bits.position..bits.position + length)
+                    .ok_or_else(|| bits.error("the stream ends early"))?;
+                reserve(&output, length, max_output, &bits)?;
+                output.extend_from_slice(stored);
+                bits.position += length;
+            }
+            1 => {
+                let (literals, distances) = fixed_codes();
+                codes(&mut bits, &mut output, max_output, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_codes(&mut bits)?;
+                codes(&mut bits, &mut output, max_output, &literals, &distances)?;
+            }
+            _ => return Err(bits.error("invalid block type")),
+        }
+        if last {
+            return Ok((output, bits.position));
+        }
+    }
+}
+
+/// Decompresses a zlib stream (RFC 1950), as stored in PNG `IDAT` chunks and
+/// PDF streams, and verifies its Adler-32. Preset dictionaries are not
+/// supported. See [`inflate`] for the errors.
+///
+/// Only available with the `inflate` feature.
+pub fn zlib_decompress(
+    compressed: &[u8],
+    max_output: usize,
+) -> Result<Vec<u8>, BinaryFileReaderError> {
+    zlib_decompress_at(compressed, max_output, 0)
+}
+
+pub(crate) fn zlib_decompress_at(
+    compressed: &[u8],
+    max_output: usize,
+    base_offset: usize,
+) -> Result<Vec<u8>, BinaryFileReaderError> {
+    let error = |reason, offset| BinaryFileReaderError::Decompress {
+        reason,
+        current_offset: base_offset + offset,
+    };
+    let (cmf, flags) = match compressed {
+        [cmf, flags, ..] => (*cmf, *flags),
+        _ => return Err(error("the stream ends early", compressed.len())),
+    };
+    if cmf & 0x0f != 8 || cmf >> 4 > 7 || u16::from_be_bytes([cmf, flags]) % 31 != 0 {
+        return Err(error("invalid zlib header", 0));
+    }
+    if flags & 0x20 != 0 {
+        return Err(error("preset dictionaries are not supported", 1));
+    }
+
+    let (output, used) = inflate_at(&compressed[2..], max_output, base_offset + 2)?;
+    let trailer = 2 + used;
+    let stored = compressed
+        .get(trailer..trailer + 4)
+        .ok_or_else(|| error("the stream ends early", compressed.len()))?;
+    let stored = u32::from_be_bytes([stored[0], stored[1], stored[2], stored[3]]);
+    let computed = adler32(&output);
+    if stored != computed {
+        return Err(BinaryFileReaderError::ChecksumMismatch {
+            stored: stored as u64,
+            computed: computed as u64,
+            current_offset: base_offset + trailer,
+        });
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{inflate, zlib_decompress};
+    use crate::error::{BinaryFileReaderError, ErrorCode};
+    use alloc::{format, string::String, vec};
+
+    #[test]
+    fn test_inflate_blocks() -> Result<(), BinaryFileReaderError> {
+        // A stored block, then a final fixed block of "hello hello hello".
+        let mut stored = vec![0x00, 0x03, 0x00, 0xfc, 0xff, b'a', b'b', b'c'];
+        stored.extend([0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0xc8, 0x40, 0x90, 0x00]);
+        assert_eq!(inflate(&stored, usize::MAX)?, b"abchello hello hello");
+
+        // zlib level 9 with dynamic codes, of "abcabcabc" repeated with a
+        // counter.
+        let text = (0..50)
+            .map(|i| format!("abcabcabc{} ", i))
+            .collect::<String>();
+        assert_eq!(zlib_decompress(DYNAMIC, usize::MAX)?, text.as_bytes());
+
+        let mut corrupt = DYNAMIC.to_vec();
+        *corrupt.last_mut().unwrap() ^= 1;
+        assert_eq!(
+            zlib_decompress(&corrupt, usize::MAX).unwrap_err().code(),
+            ErrorCode::ChecksumMismatch
+        );
+        assert_eq!(
+            zlib_decompress(&DYNAMIC[..20], usize::MAX)
+                .unwrap_err()
+                .code(),
+            ErrorCode::Decompress
+        );
+        assert!(matches!(
+            zlib_decompress(&[0x78, 0x9c, 0x07], usize::MAX),
+            Err(BinaryFileReaderError::Decompress {
+                reason: "invalid block type",
+                current_offset: 3,
+            })
+        ));
+        assert!(matches!(
+            zlib_decompress(&[0x78, 0x9d], usize::MAX),
+            Err(BinaryFileReaderError::Decompress {
+                reason: "invalid zlib header",
+                ..
+            })
+        ));
+        assert!(matches!(
+            inflate(&[0x01, 0x03, 0x00, 0xfc, 0xfe], usize::MAX),
+            Err(BinaryFileReaderError::Decompress { .. })
+        ));
+
+        // A distance reaching before the output.
+        assert!(inflate(&[0x03, 0x02, 0x00], usize::MAX).is_err());
+
+        Ok(())
+    }
+
+    const DYNAMIC: &[u8] = &[
+        0x78, 0xda, 0x4d, 0xd1, 0xdb, 0x09, 0x03, 0x01, 0x08, 0x45, 0xc1, 0x56, 0xb6, 0x84, 0xa8,
+        0x37, 0x8f, 0x2d, 0x67, 0x93, 0xfe, 0x7b, 0x08, 0x04, 0x82, 0x03, 0x7e, 0x9c, 0xbf, 0x41,
+        0xbd, 0xde, 0x9f, 0xeb, 0x37, 0xb7, 0xe3, 0xfa, 0x67, 0x6d, 0xf6, 0xe6, 0x6c, 0x66, 0xf3,
+        0xbe, 0xf9, 0xd8, 0x7c, 0x6e, 0xbe, 0x36, 0x4f, 0x08, 0x39, 0xbc, 0x02, 0x2c, 0xc4, 0x82,
+        0x2c, 0xcc, 0x02, 0x2d, 0xd4, 0x82, 0x2d, 0xdc, 0xc6, 0x6d, 0xf7, 0xc4, 0x6d, 0xdc, 0xc6,
+        0x6d, 0xdc, 0xc6, 0x6d, 0xdc, 0xc6, 0x6d, 0xdc, 0xc1, 0x1d, 0xdc, 0xf1, 0xc0, 0xb8, 0x83,
+        0x3b, 0xb8, 0x83, 0x3b, 0xb8, 0x83, 0x3b, 0xb8, 0xc1, 0x0d, 0x6e, 0x70, 0xe3, 0x67, 0x71,
+        0x83, 0x1b, 0xdc, 0xe0, 0x06, 0x37, 0xe7, 0xf1, 0x05, 0xac, 0xe6, 0xc4, 0xaa,
+    ];
+}
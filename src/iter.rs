@@ -0,0 +1,198 @@
+use crate::{
+    error::BinaryFileReaderError, read_from::ReadFrom, source::DataSource, BinaryFileReader,
+};
+use alloc::boxed::Box;
+use core::{iter::FusedIterator, marker::PhantomData};
+
+#[derive(Debug, Clone)]
+pub struct Windows<'a> {
+    reader: BinaryFileReader<'a>,
+    size: usize,
+}
+
+impl<'a> Windows<'a> {
+    pub(crate) fn new(reader: BinaryFileReader<'a>, size: usize) -> Self {
+        assert!(size != 0, "window size must be non-zero");
+        // Sliding the window is not a read.
+        #[cfg(feature = "std")]
+        let reader = BinaryFileReader {
+            coverage: None,
+            ..reader
+        };
+        Self { reader, size }
+    }
+}
+
+impl<'a> Iterator for Windows<'a> {
+    type Item = (usize, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.reader.current_offset();
+        let window = self.reader.peek_slice(self.size).ok()?;
+        self.reader.advance(1).ok()?;
+        Some((offset, window))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.reader.available_bytes().saturating_sub(self.size - 1);
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Windows<'_> {}
+
+#[derive(Debug, Clone)]
+pub struct Split<'a, 'b> {
+    reader: BinaryFileReader<'a>,
+    delimiter: &'b [u8],
+    finished: bool,
+}
+
+impl<'a, 'b> Split<'a, 'b> {
+    pub(crate) fn new(reader: BinaryFileReader<'a>, delimiter: &'b [u8]) -> Self {
+        assert!(!delimiter.is_empty(), "delimiter must be non-empty");
+        // Finding the delimiters is not a read.
+        #[cfg(feature = "std")]
+        let reader = BinaryFileReader {
+            coverage: None,
+            ..reader
+        };
+        Self {
+            reader,
+            delimiter,
+            finished: false,
+        }
+    }
+}
+
+impl<'a> Iterator for Split<'a, '_> {
+    type Item = BinaryFileReader<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let remaining = self.reader.remaining();
+        let position = remaining
+            .windows(self.delimiter.len())
+            .position(|window| window == self.delimiter);
+
+        let block = match position {
+            Some(position) => {
+                let block = self.reader.split_off_front(position).ok()?;
+                self.reader.advance(self.delimiter.len()).ok()?;
+                block
+            }
+            None => {
+                self.finished = true;
+                let length = self.reader.available_bytes();
+                self.reader.split_off_front(length).ok()?
+            }
+        };
+
+        Some(block)
+    }
+}
+
+#[derive(Debug)]
+pub struct IterOf<'r, 'a, T> {
+    reader: &'r mut BinaryFileReader<'a>,
+    index: usize,
+    finished: bool,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<'r, 'a, T> IterOf<'r, 'a, T> {
+    pub(crate) fn new(reader: &'r mut BinaryFileReader<'a>) -> Self {
+        Self {
+            reader,
+            index: 0,
+            finished: false,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ReadFrom> Iterator for IterOf<'_, '_, T> {
+    type Item = Result<T, BinaryFileReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished || self.reader.available_bytes() == 0 {
+            return None;
+        }
+        if let Err(err) = self.reader.check_cancelled() {
+            self.finished = true;
+            return Some(Err(err));
+        }
+
+        let start = self.reader.current_offset;
+        match T::read_from(self.reader) {
+            Ok(item) => {
+                // A record that consumed nothing would be yielded forever.
+                if self.reader.current_offset == start {
+                    self.finished = true;
+                }
+                self.index += 1;
+                Some(Ok(item))
+            }
+            Err(source) => {
+                self.finished = true;
+                self.reader.current_offset = start;
+                Some(Err(BinaryFileReaderError::Element {
+                    index: self.index,
+                    source: Box::new(source),
+                }))
+            }
+        }
+    }
+}
+
+impl<T: ReadFrom> FusedIterator for IterOf<'_, '_, T> {}
+
+/// Yields the bytes of a [`DataSource`] one at a time, advancing it. Created
+/// with [`DataSource::bytes`].
+#[derive(Debug)]
+pub struct Bytes<'s, S: ?Sized> {
+    source: &'s mut S,
+    finished: bool,
+}
+
+impl<'s, S: DataSource + ?Sized> Bytes<'s, S> {
+    pub(crate) fn new(source: &'s mut S) -> Self {
+        Self {
+            source,
+            finished: false,
+        }
+    }
+}
+
+impl<S: DataSource + ?Sized> Iterator for Bytes<'_, S> {
+    type Item = Result<u8, BinaryFileReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let byte = match self.source.fill(1) {
+            Ok(buffered) => buffered.first().copied(),
+            Err(err) => {
+                self.finished = true;
+                return Some(Err(err));
+            }
+        };
+        match byte {
+            Some(byte) => {
+                self.source.consume(1);
+                Some(Ok(byte))
+            }
+            None => {
+                self.finished = true;
+                None
+            }
+        }
+    }
+}
+
+impl<S: DataSource + ?Sized> FusedIterator for Bytes<'_, S> {}
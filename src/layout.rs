@@ -0,0 +1,210 @@
+use crate::tree::{ParseNode, ParseTree};
+use alloc::borrow::Cow;
+use core::{fmt, ops::Range};
+use std::sync::{Arc, Mutex};
+
+/// The annotations recorded since
+/// [`enable_layout`](crate::BinaryFileReader::enable_layout).
+#[derive(Debug, Default)]
+pub(crate) struct Recorder {
+    depth: usize,
+    entries: Vec<LayoutEntry>,
+}
+
+pub(crate) type SharedRecorder = Arc<Mutex<Recorder>>;
+
+impl Recorder {
+    pub(crate) fn shared() -> SharedRecorder {
+        Arc::default()
+    }
+
+    /// Called before the annotated read. Returns the depth of its entry.
+    pub(crate) fn enter(&mut self) -> usize {
+        self.depth += 1;
+        self.depth - 1
+    }
+
+    /// Called after the annotated read, whether or not it succeeded.
+    pub(crate) fn exit(&mut self, entry: Option<LayoutEntry>) {
+        self.depth -= 1;
+        self.entries.extend(entry);
+    }
+
+    pub(crate) fn layout(&self) -> Layout {
+        let mut entries = self.entries.clone();
+        entries.sort_by_key(|entry| (entry.range.start, entry.depth));
+        Layout { entries }
+    }
+}
+
+/// A labelled range, recorded by
+/// [`annotate`](crate::BinaryFileReader::annotate).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutEntry {
+    /// The absolute offsets the annotated read consumed.
+    pub range: Range<usize>,
+    pub label: Cow<'static, str>,
+    /// The number of annotations this one is nested in.
+    pub depth: usize,
+}
+
+/// A map from offset ranges to the labels given to the reads that consumed
+/// them, returned by [`layout`](crate::BinaryFileReader::layout). Entries are
+/// ordered by offset, with enclosing entries before the entries nested in
+/// them. `Display` renders it as an indented table.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::BinaryFileReader;
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// let buffer = vec![0x00, 0x02, 0x00, 0x10, 0x00, 0x20];
+/// let mut reader = BinaryFileReader::new(&buffer);
+/// reader.enable_layout();
+///
+/// let count = reader.annotate("count", |r| r.read_u16())?;
+/// reader.annotate("points", |r| {
+///     (0..count).try_for_each(|i| r.annotate(format!("points[{}]", i), |r| r.read_u16()).map(drop))
+/// })?;
+///
+/// assert_eq!(
+///     reader.layout().unwrap().to_string(),
+///     "\
+/// 0x00000000..0x00000002  count
+/// 0x00000002..0x00000006  points
+/// 0x00000002..0x00000004    points[0]
+/// 0x00000004..0x00000006    points[1]
+/// "
+/// );
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Layout {
+    pub entries: Vec<LayoutEntry>,
+}
+
+impl Layout {
+    /// Converts the entries to a [`ParseTree`], nesting each entry in the
+    /// nearest enclosing entry one level up, for rendering with
+    /// [`ParseTree`]'s dissection `Display`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0x00, 0x00, 0x00, 0x64, 0x08];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// reader.enable_layout();
+    ///
+    /// reader.annotate("IHDR", |r| {
+    ///     r.annotate("width", |r| r.read_u32())?;
+    ///     r.annotate("bit_depth", |r| r.read_u8())
+    /// })?;
+    ///
+    /// assert_eq!(
+    ///     reader.layout().unwrap().to_tree().to_string(),
+    ///     "\
+    /// 0000-0004  IHDR
+    /// 0000-0003    IHDR.width
+    /// 0004-0004    IHDR.bit_depth
+    /// "
+    /// );
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn to_tree(&self) -> ParseTree {
+        let mut open: Vec<ParseNode> = Vec::new();
+        let mut roots = Vec::new();
+        for entry in &self.entries {
+            while open.len() > entry.depth {
+                close(&mut open, &mut roots);
+            }
+            open.push(ParseNode {
+                name: entry.label.to_string(),
+                range: entry.range.clone(),
+                value: None,
+                children: Vec::new(),
+            });
+        }
+        while !open.is_empty() {
+            close(&mut open, &mut roots);
+        }
+        ParseTree { roots }
+    }
+}
+
+fn close(open: &mut Vec<ParseNode>, roots: &mut Vec<ParseNode>) {
+    if let Some(node) = open.pop() {
+        match open.last_mut() {
+            Some(parent) => parent.children.push(node),
+            None => roots.push(node),
+        }
+    }
+}
+
+impl fmt::Display for Layout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            writeln!(
+                f,
+                "{:#010x}..{:#010x}  {:indent$}{}",
+                entry.range.start,
+                entry.range.end,
+                "",
+                entry.label,
+                indent = entry.depth * 2
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LayoutEntry, Recorder};
+    use alloc::borrow::Cow;
+
+    fn entry(range: core::ops::Range<usize>, label: &'static str, depth: usize) -> LayoutEntry {
+        LayoutEntry {
+            range,
+            label: Cow::Borrowed(label),
+            depth,
+        }
+    }
+
+    #[test]
+    fn test_recorder() {
+        let mut recorder = Recorder::default();
+        let outer = recorder.enter();
+        let inner = recorder.enter();
+        recorder.exit(Some(entry(0..4, "inner", inner)));
+        let failed = recorder.enter();
+        assert_eq!(failed, 1);
+        recorder.exit(None);
+        recorder.exit(Some(entry(0..8, "outer", outer)));
+        let next = recorder.enter();
+        recorder.exit(Some(entry(8..9, "next", next)));
+
+        let layout = recorder.layout();
+        assert_eq!(
+            layout.entries,
+            vec![
+                entry(0..8, "outer", 0),
+                entry(0..4, "inner", 1),
+                entry(8..9, "next", 0)
+            ]
+        );
+        assert_eq!(
+            layout.to_string().lines().nth(1),
+            Some("0x00000000..0x00000004    inner")
+        );
+    }
+}
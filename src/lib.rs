@@ -1,50 +1,223 @@
-use error::BinaryFileReaderError;
+#![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
+use alloc::{borrow::Cow, boxed::Box, string::String, sync::Arc, vec::Vec};
+use cancel::CancelToken;
+use chain::ChainedReader;
+use core::{
+    fmt,
+    hash::{Hash, Hasher},
+    ops::{Index, Range},
+};
+use endian::Endian;
+use error::{BinaryFileReaderError, ErrorBytes};
+use fourcc::FourCC;
+use iter::{IterOf, Split, Windows};
+use owned::{CowBinaryFileReader, OwnedBinaryFileReader};
+use primitive::Primitive;
+use read_from::ReadFrom;
+use state::ReaderState;
+use text::TextEncoding;
+
+pub mod cancel;
+pub mod chain;
+pub mod checksum;
+pub mod chunk;
+#[cfg(feature = "std")]
+pub mod coverage;
+pub mod endian;
 pub mod error;
+#[cfg(feature = "std")]
+pub mod events;
+pub mod feed;
+pub mod formats;
+pub mod fourcc;
+pub mod hexdump;
+#[cfg(feature = "inflate")]
+pub mod inflate;
+pub mod iter;
+#[cfg(feature = "std")]
+pub mod layout;
+#[cfg(feature = "lz4")]
+pub mod lz4;
+mod macros;
+pub mod owned;
+#[cfg(feature = "std")]
+pub mod paged;
+#[cfg(feature = "pod")]
+pub mod pod;
+pub mod primitive;
+pub mod read_from;
+#[cfg(feature = "std")]
+pub mod seek;
+pub mod source;
+pub mod state;
+#[cfg(feature = "std")]
+pub mod stream;
+pub mod text;
+pub mod tlv;
+#[cfg(feature = "std")]
+pub mod trace;
+pub mod transform;
+pub mod tree;
+#[cfg(feature = "std")]
+pub mod volume;
 
-#[derive(Debug, Clone)]
+#[cfg(feature = "derive")]
+pub use binary_file_reader_derive::BinaryRead;
+
+/// A cursor over a byte buffer. All offset arithmetic is checked: lengths taken
+/// from untrusted data that would move past the end of the buffer, or overflow
+/// `usize`, fail with [`BinaryFileReaderError::BufferUnderflow`] instead of
+/// panicking.
+#[derive(Clone)]
 pub struct BinaryFileReader<'a> {
     current_offset: usize,
+    own_start: usize,
     own_left: usize,
     buf: &'a [u8],
+    endian: Endian,
+    depth: usize,
+    max_depth: usize,
+    max_alloc: usize,
+    max_error_bytes: usize,
+    cancel: Option<CancelToken>,
+    source_name: Option<Arc<str>>,
+    #[cfg(feature = "std")]
+    coverage: Option<coverage::SharedCoverage>,
+    #[cfg(feature = "std")]
+    layout: Option<layout::SharedRecorder>,
+    #[cfg(feature = "std")]
+    trace: Option<trace::SharedTrace>,
+    #[cfg(feature = "std")]
+    events: Option<events::SharedSink>,
 }
 
 impl<'a> BinaryFileReader<'a> {
-    fn peek(&self, buffer: &mut [u8]) -> Result<(), BinaryFileReaderError> {
-        if buffer.len() > self.available_bytes() {
-            return Err(BinaryFileReaderError::BufferUnderflow {
-                requested_bytes: buffer.len(),
+    fn end_offset(&self, length: usize) -> Result<usize, BinaryFileReaderError> {
+        match self.current_offset.checked_add(length) {
+            Some(end) if end <= self.own_left => Ok(end),
+            _ => Err(BinaryFileReaderError::BufferUnderflow {
+                requested_bytes: length,
                 current_offset: self.current_offset,
                 available_bytes: self.available_bytes(),
-            });
+            }),
+        }
+    }
+
+    fn advance(&mut self, length: usize) -> Result<(), BinaryFileReaderError> {
+        let end = self.end_offset(length)?;
+        #[cfg(feature = "std")]
+        if let Some(coverage) = &self.coverage {
+            lock(coverage).record(self.current_offset..end);
+        }
+        self.current_offset = end;
+        Ok(())
+    }
+
+    /// Copies the cursor state onto another buffer holding the same bytes.
+    pub(crate) fn rebind<'b>(&self, buf: &'b [u8]) -> BinaryFileReader<'b> {
+        BinaryFileReader {
+            current_offset: self.current_offset,
+            own_start: self.own_start,
+            own_left: self.own_left,
+            buf,
+            endian: self.endian,
+            depth: self.depth,
+            max_depth: self.max_depth,
+            max_alloc: self.max_alloc,
+            max_error_bytes: self.max_error_bytes,
+            cancel: self.cancel.clone(),
+            source_name: self.source_name.clone(),
+            #[cfg(feature = "std")]
+            coverage: self.coverage.clone(),
+            #[cfg(feature = "std")]
+            layout: self.layout.clone(),
+            #[cfg(feature = "std")]
+            trace: self.trace.clone(),
+            #[cfg(feature = "std")]
+            events: self.events.clone(),
         }
+    }
+
+    fn remaining(&self) -> &'a [u8] {
+        &self.buf[self.current_offset..self.own_left]
+    }
 
-        buffer.copy_from_slice(&self.buf[self.current_offset..self.current_offset + buffer.len()]);
+    fn peek(&self, buffer: &mut [u8]) -> Result<(), BinaryFileReaderError> {
+        buffer.copy_from_slice(self.get_slice(buffer.len())?);
 
         Ok(())
     }
 
     fn read(&mut self, buffer: &mut [u8]) -> Result<(), BinaryFileReaderError> {
         self.peek(buffer)?;
-        self.current_offset += buffer.len();
-        Ok(())
+        self.advance(buffer.len())
     }
 
     fn get_slice(&self, length: usize) -> Result<&'a [u8], BinaryFileReaderError> {
-        if length > self.available_bytes() {
-            return Err(BinaryFileReaderError::BufferUnderflow {
-                requested_bytes: length,
-                current_offset: self.current_offset,
-                available_bytes: self.available_bytes(),
-            });
+        let end = self.end_offset(length)?;
+        Ok(&self.buf[self.current_offset..end])
+    }
+
+    /// Runs `f`, recording it in the trace if one is enabled.
+    #[cfg(feature = "std")]
+    fn traced<T, F>(&mut self, method: &'static str, f: F) -> Result<T, BinaryFileReaderError>
+    where
+        T: trace::TraceValue,
+        F: FnOnce(&mut Self) -> Result<T, BinaryFileReaderError>,
+    {
+        let Some(recorder) = self.trace.clone() else {
+            return f(self);
+        };
+
+        let offset = self.current_offset;
+        let result = f(self);
+        let value = match &result {
+            Ok(value) => value.trace_value(),
+            Err(err) => format!("Err({:?})", err.code()),
         };
+        lock(&recorder).events.push(trace::TraceEvent {
+            method: Cow::Borrowed(method),
+            offset,
+            length: self.current_offset.saturating_sub(offset),
+            value,
+        });
+        result
+    }
+
+    #[cfg(not(feature = "std"))]
+    #[inline]
+    fn traced<T, F>(&mut self, _method: &'static str, f: F) -> Result<T, BinaryFileReaderError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, BinaryFileReaderError>,
+    {
+        f(self)
+    }
 
-        let slice = &self.buf[self.current_offset..self.current_offset + length];
-        Ok(slice)
+    fn error_bytes(&self, bytes: &[u8]) -> ErrorBytes {
+        ErrorBytes::with_cap(bytes, self.max_error_bytes)
+    }
+
+    fn convert_discriminant<R, T>(&self, value: R) -> Result<T, BinaryFileReaderError>
+    where
+        R: Copy + Into<u64>,
+        T: TryFrom<R>,
+    {
+        T::try_from(value).map_err(|_| BinaryFileReaderError::InvalidDiscriminant {
+            type_name: core::any::type_name::<T>(),
+            value: value.into(),
+            current_offset: self.current_offset,
+        })
     }
 }
 
 impl<'a> BinaryFileReader<'a> {
+    /// The nesting depth allowed by [`nested`](Self::nested) unless changed
+    /// with [`set_max_depth`](Self::set_max_depth).
+    pub const DEFAULT_MAX_DEPTH: usize = 64;
+
     /// # Examples
     /// ```
     /// # use binary_file_reader::BinaryFileReader;
@@ -63,24 +236,34 @@ impl<'a> BinaryFileReader<'a> {
         let current_offset = 0;
         let own_left = buffer.len();
         Self {
+            own_start: 0,
             own_left,
             current_offset,
             buf: buffer,
+            endian: Endian::Big,
+            depth: 0,
+            max_depth: Self::DEFAULT_MAX_DEPTH,
+            max_alloc: usize::MAX,
+            max_error_bytes: ErrorBytes::CAPACITY,
+            cancel: None,
+            source_name: None,
+            #[cfg(feature = "std")]
+            coverage: None,
+            #[cfg(feature = "std")]
+            layout: None,
+            #[cfg(feature = "std")]
+            trace: None,
+            #[cfg(feature = "std")]
+            events: None,
         }
     }
 
     /// # Examples
     /// ```
     /// # use binary_file_reader::BinaryFileReader;
-    /// # use std::fs;
     /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let buffer = vec![0,1,2,3,4,5];
-    /// let mut reader = BinaryFileReader::new(&buffer);
-    /// assert_eq!(reader.current_offset(),0);
-    /// reader.read_u8()?;
-    /// assert_eq!(reader.current_offset(),1);
-    /// reader.read_u16()?;
-    /// assert_eq!(reader.current_offset(),3);
+    /// let mut owned = BinaryFileReader::from_owned(vec![0x00, 0x2a]);
+    /// assert_eq!(owned.reader().read_u16()?, 42);
     /// #
     /// # Ok(())
     /// # }
@@ -88,23 +271,16 @@ impl<'a> BinaryFileReader<'a> {
     /// #    try_main().unwrap();
     /// # }
     /// ```
-    #[inline]
-    pub fn current_offset(&self) -> usize {
-        self.current_offset
+    pub fn from_owned(buffer: Vec<u8>) -> OwnedBinaryFileReader {
+        OwnedBinaryFileReader::new(buffer)
     }
 
     /// # Examples
     /// ```
     /// # use binary_file_reader::BinaryFileReader;
-    /// # use std::fs;
     /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let buffer = vec![0,1,2,3,4,5];
-    /// let mut reader = BinaryFileReader::new(&buffer);
-    /// assert_eq!(reader.available_bytes(),6);
-    /// reader.read_u8()?;
-    /// assert_eq!(reader.available_bytes(),5);
-    /// reader.read_u16()?;
-    /// assert_eq!(reader.available_bytes(),3);
+    /// let mut reader = BinaryFileReader::from_path("./sample-files/1.png")?;
+    /// reader.reader().expect(&[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A])?;
     /// #
     /// # Ok(())
     /// # }
@@ -112,24 +288,26 @@ impl<'a> BinaryFileReader<'a> {
     /// #    try_main().unwrap();
     /// # }
     /// ```
-    #[inline]
-    pub fn available_bytes(&self) -> usize {
-        self.own_left - self.current_offset
+    #[cfg(feature = "std")]
+    pub fn from_path(
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<OwnedBinaryFileReader, BinaryFileReaderError> {
+        OwnedBinaryFileReader::from_path(path)
     }
 
     /// # Examples
     /// ```
     /// # use binary_file_reader::BinaryFileReader;
-    /// # use std::fs;
+    /// # use std::borrow::Cow;
     /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let buffer = vec![0xab,0xcd,0xef];
-    /// let mut reader = BinaryFileReader::new(&buffer);
-    /// let (upper,lower) = reader.read_u4()?;
-    /// assert_eq!(upper,0x0a);
-    /// assert_eq!(lower,0x0b);
-    /// assert_eq!(reader.read_u4()?,(0x0c,0x0d));
-    /// assert_eq!(reader.read_u4()?,(0x0e,0x0f));
-    /// assert!(reader.read_u4().is_err());
+    /// fn parse(data: Cow<[u8]>) -> Result<u16, Box<dyn std::error::Error>> {
+    ///     let mut reader = BinaryFileReader::from_cow(data);
+    ///     let value = reader.reader().read_u16()?;
+    ///     Ok(value)
+    /// }
+    ///
+    /// assert_eq!(parse(Cow::Borrowed(&[0x00, 0x2a]))?, 42);
+    /// assert_eq!(parse(Cow::Owned(vec![0x00, 0x2a]))?, 42);
     /// #
     /// # Ok(())
     /// # }
@@ -137,24 +315,19 @@ impl<'a> BinaryFileReader<'a> {
     /// #    try_main().unwrap();
     /// # }
     /// ```
-    pub fn read_u4(&mut self) -> Result<(u8, u8), BinaryFileReaderError> {
-        let mut buffer = [0; 1];
-        self.read(&mut buffer)?;
-        let upper = buffer[0] >> 4;
-        let lower = buffer[0] & 0x0f;
-        Ok((upper, lower))
+    pub fn from_cow(buffer: Cow<'a, [u8]>) -> CowBinaryFileReader<'a> {
+        CowBinaryFileReader::new(buffer)
     }
 
+    /// Reads `segments` back to back as one buffer. See [`ChainedReader`].
+    ///
     /// # Examples
     /// ```
     /// # use binary_file_reader::BinaryFileReader;
+    /// # use binary_file_reader::source::DataSource;
     /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let buffer = vec![1,2,3];
-    /// let mut reader = BinaryFileReader::new(&buffer);
-    /// assert_eq!(reader.read_u8()?,1);
-    /// assert_eq!(reader.read_u8()?,2);
-    /// assert_eq!(reader.read_u8()?,3);
-    /// assert!(reader.read_u8().is_err());
+    /// let mut reader = BinaryFileReader::chain(&[&[0x00], &[0x2a]]);
+    /// assert_eq!(reader.read_u16()?, 42);
     /// #
     /// # Ok(())
     /// # }
@@ -162,22 +335,42 @@ impl<'a> BinaryFileReader<'a> {
     /// #    try_main().unwrap();
     /// # }
     /// ```
-    pub fn read_u8(&mut self) -> Result<u8, BinaryFileReaderError> {
-        let mut buffer = [0; 1];
-        self.read(&mut buffer)?;
-        Ok(buffer[0])
+    pub fn chain(segments: &[&'a [u8]]) -> ChainedReader<'a> {
+        ChainedReader::new(segments)
+    }
+
+    /// Captures the position and settings of this reader. See
+    /// [`ReaderState`].
+    pub fn save_state(&self) -> ReaderState {
+        ReaderState {
+            current_offset: self.current_offset,
+            start: self.own_start,
+            end: self.own_left,
+            endian: self.endian,
+            max_depth: self.max_depth,
+            max_alloc: self.max_alloc,
+            max_error_bytes: self.max_error_bytes,
+        }
     }
 
+    /// Rebuilds a reader over `buffer` from a [`ReaderState`] saved over the
+    /// same data. The nesting depth starts at 0.
+    ///
+    /// Fails with [`BinaryFileReaderError::OutOfRange`] if the state does not
+    /// fit in `buffer`.
+    ///
     /// # Examples
     /// ```
     /// # use binary_file_reader::BinaryFileReader;
     /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let buffer = vec![0x12,0x34,0x56,0x78,0x90];
+    /// let buffer = vec![0x00, 0x01, 0x00, 0x02];
     /// let mut reader = BinaryFileReader::new(&buffer);
-    /// assert_eq!(reader.read_u16()?,0x1234);
-    /// assert_eq!(reader.read_u16()?,0x5678);
-    /// assert!(reader.read_u16().is_err());
-    /// assert_eq!(reader.read_u8()?,0x90);
+    /// reader.read_u16()?;
+    /// let state = reader.save_state();
+    ///
+    /// let mut resumed = BinaryFileReader::resume(&buffer, state)?;
+    /// assert_eq!(resumed.read_u16()?, 2);
+    /// assert!(BinaryFileReader::resume(&buffer[..1], state).is_err());
     /// #
     /// # Ok(())
     /// # }
@@ -185,21 +378,38 @@ impl<'a> BinaryFileReader<'a> {
     /// #    try_main().unwrap();
     /// # }
     /// ```
-    pub fn read_u16(&mut self) -> Result<u16, BinaryFileReaderError> {
-        let mut buffer = [0; 2];
-        self.read(&mut buffer)?;
-        Ok(u16::from_be_bytes(buffer))
+    pub fn resume(buffer: &'a [u8], state: ReaderState) -> Result<Self, BinaryFileReaderError> {
+        if state.end > buffer.len() || !(state.start..=state.end).contains(&state.current_offset) {
+            return Err(BinaryFileReaderError::OutOfRange {
+                buffer_size: buffer.len(),
+                got: state.end.max(state.current_offset),
+            });
+        }
+
+        Ok(Self {
+            current_offset: state.current_offset,
+            own_start: state.start,
+            own_left: state.end,
+            endian: state.endian,
+            max_depth: state.max_depth,
+            max_alloc: state.max_alloc,
+            max_error_bytes: state.max_error_bytes,
+            ..Self::new(buffer)
+        })
     }
 
     /// # Examples
     /// ```
     /// # use binary_file_reader::BinaryFileReader;
+    /// # use std::fs;
     /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let buffer = vec![0x12,0x34,0x56,0x78,0x90];
+    /// let buffer = vec![0,1,2,3,4,5];
     /// let mut reader = BinaryFileReader::new(&buffer);
-    /// assert_eq!(reader.read_u32()?,0x12345678);
-    /// assert!(reader.read_u32().is_err());
-    /// assert_eq!(reader.read_u8()?,0x90);
+    /// assert_eq!(reader.current_offset(),0);
+    /// reader.read_u8()?;
+    /// assert_eq!(reader.current_offset(),1);
+    /// reader.read_u16()?;
+    /// assert_eq!(reader.current_offset(),3);
     /// #
     /// # Ok(())
     /// # }
@@ -207,21 +417,23 @@ impl<'a> BinaryFileReader<'a> {
     /// #    try_main().unwrap();
     /// # }
     /// ```
-    pub fn read_u32(&mut self) -> Result<u32, BinaryFileReaderError> {
-        let mut buffer = [0; 4];
-        self.read(&mut buffer)?;
-        Ok(u32::from_be_bytes(buffer))
+    #[inline]
+    pub fn current_offset(&self) -> usize {
+        self.current_offset
     }
 
     /// # Examples
     /// ```
     /// # use binary_file_reader::BinaryFileReader;
+    /// # use std::fs;
     /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let buffer = vec![0x12,0x34,0x56,0x78,0x90,0x12,0x34,0x56,0x78,0x90];
+    /// let buffer = vec![0,1,2,3,4,5];
     /// let mut reader = BinaryFileReader::new(&buffer);
-    /// assert_eq!(reader.read_u64()?,0x1234567890123456);
-    /// assert!(reader.read_u64().is_err());
-    /// assert_eq!(reader.read_u8()?,0x78);
+    /// assert_eq!(reader.available_bytes(),6);
+    /// reader.read_u8()?;
+    /// assert_eq!(reader.available_bytes(),5);
+    /// reader.read_u16()?;
+    /// assert_eq!(reader.available_bytes(),3);
     /// #
     /// # Ok(())
     /// # }
@@ -229,30 +441,30 @@ impl<'a> BinaryFileReader<'a> {
     /// #    try_main().unwrap();
     /// # }
     /// ```
-    pub fn read_u64(&mut self) -> Result<u64, BinaryFileReaderError> {
-        let mut buffer = [0; 8];
-        self.read(&mut buffer)?;
-        Ok(u64::from_be_bytes(buffer))
-    }
-
-    pub fn read_u128(&mut self) -> Result<u128, BinaryFileReaderError> {
-        let mut buffer = [0; 16];
-        self.read(&mut buffer)?;
-        Ok(u128::from_be_bytes(buffer))
+    #[inline]
+    pub fn available_bytes(&self) -> usize {
+        self.own_left - self.current_offset
     }
 
+    /// Moves the cursor to `offset`, which may be anywhere in this reader,
+    /// including before the current position. A sub-reader cannot move
+    /// outside the region it was split off with.
+    ///
     /// # Examples
     /// ```
     /// # use binary_file_reader::BinaryFileReader;
     /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let buffer = vec![0,1,2,3,4,5,6,7,8,9];
+    /// let buffer = vec![0x00, 0x01, 0x02, 0x03];
     /// let mut reader = BinaryFileReader::new(&buffer);
-    /// let mut buf = vec![0;5];
-    /// reader.read_bytes(&mut buf);
-    /// assert_eq!(buf,vec![0,1,2,3,4]);
-    /// reader.read_bytes(&mut buf);
-    /// assert_eq!(buf,vec![5,6,7,8,9]);
-    /// assert!(reader.read_u8().is_err());
+    /// reader.set_offset(2)?;
+    /// assert_eq!(reader.read_u8()?, 2);
+    /// reader.set_offset(0)?;
+    /// assert_eq!(reader.read_u8()?, 0);
+    ///
+    /// let mut sub = reader.split_off_front(2)?;
+    /// assert!(sub.set_offset(0).is_err());
+    /// assert!(sub.set_offset(4).is_err());
+    /// sub.set_offset(3)?;
     /// #
     /// # Ok(())
     /// # }
@@ -260,20 +472,37 @@ impl<'a> BinaryFileReader<'a> {
     /// #    try_main().unwrap();
     /// # }
     /// ```
-    pub fn read_bytes(&mut self, buffer: &mut [u8]) -> Result<(), BinaryFileReaderError> {
-        self.read(buffer)?;
+    pub fn set_offset(&mut self, offset: usize) -> Result<(), BinaryFileReaderError> {
+        if !(self.own_start..=self.own_left).contains(&offset) {
+            return Err(BinaryFileReaderError::OutOfRange {
+                buffer_size: self.own_left,
+                got: offset,
+            });
+        }
+
+        self.current_offset = offset;
         Ok(())
     }
 
+    /// The current offset as a `u64`, like [`std::io::Cursor::position`].
+    #[inline]
+    pub fn position(&self) -> u64 {
+        self.current_offset as u64
+    }
+
+    /// Like [`std::io::Cursor::set_position`], but a position outside this
+    /// reader is an error instead of making later reads fail. See
+    /// [`set_offset`](Self::set_offset).
+    ///
     /// # Examples
     /// ```
     /// # use binary_file_reader::BinaryFileReader;
     /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let buffer = vec![0,1,2,3,4,5,6,7];
-    /// let mut reader = BinaryFileReader::new(&buffer);
-    /// assert_eq!(reader.read_slice(3)?,&[0,1,2]);
-    /// assert_eq!(reader.read_slice(3)?,&[3,4,5]);
-    /// assert!(reader.read_slice(3).is_err());
+    /// let mut reader = BinaryFileReader::new(&[0x00, 0x01, 0x02]);
+    /// reader.set_position(1)?;
+    /// assert_eq!(reader.read_u16()?, 0x0102);
+    /// assert_eq!(reader.position(), 3);
+    /// assert!(reader.set_position(u64::MAX).is_err());
     /// #
     /// # Ok(())
     /// # }
@@ -281,21 +510,28 @@ impl<'a> BinaryFileReader<'a> {
     /// #    try_main().unwrap();
     /// # }
     /// ```
-    pub fn read_slice(&mut self, length: usize) -> Result<&'a [u8], BinaryFileReaderError> {
-        let slice = self.get_slice(length)?;
-        self.current_offset += length;
-        Ok(slice)
+    pub fn set_position(&mut self, position: u64) -> Result<(), BinaryFileReaderError> {
+        self.set_offset(usize::try_from(position).unwrap_or(usize::MAX))
     }
 
+    /// Returns the buffer up to the end of this reader and the current offset
+    /// into it, so `&buffer[offset..]` is exactly the remaining bytes. As with
+    /// [`current_offset`](Self::current_offset), a sub-reader's buffer also
+    /// holds the bytes before its region.
+    ///
     /// # Examples
     /// ```
     /// # use binary_file_reader::BinaryFileReader;
     /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let text = "Hello, world!";
-    /// let binary_data: Vec<u8> = text.as_bytes().to_vec();
-    /// let mut reader = BinaryFileReader::new(&binary_data);
-    /// assert_eq!(reader.read_utf8(13)?,"Hello, world!");
-    /// assert!(reader.read_utf8(10).is_err());
+    /// let buffer = vec![0x00, 0x01, 0x02, 0x03, 0x04];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// reader.read_u8()?;
+    /// let mut sub = reader.split_off_front(3)?;
+    /// sub.read_u8()?;
+    ///
+    /// assert_eq!(sub.as_parts(), (&buffer[..4], 2));
+    /// let (buffer, offset) = sub.into_inner();
+    /// assert_eq!(&buffer[offset..], &[0x02, 0x03]);
     /// #
     /// # Ok(())
     /// # }
@@ -303,30 +539,30 @@ impl<'a> BinaryFileReader<'a> {
     /// #    try_main().unwrap();
     /// # }
     /// ```
-    pub fn read_utf8(&mut self, bytes: usize) -> Result<&'a str, BinaryFileReaderError> {
-        if bytes > self.available_bytes() {
-            return Err(BinaryFileReaderError::BufferUnderflow {
-                requested_bytes: bytes,
-                current_offset: self.current_offset,
-                available_bytes: self.available_bytes(),
-            });
-        }
+    #[inline]
+    pub fn as_parts(&self) -> (&'a [u8], usize) {
+        (&self.buf[..self.own_left], self.current_offset)
+    }
 
-        let slice = &self.buf[self.current_offset..self.current_offset + bytes];
-        let result = std::str::from_utf8(slice)?;
-        self.current_offset += bytes;
-        Ok(result)
+    /// Consumes the reader and returns its [`as_parts`](Self::as_parts).
+    #[inline]
+    pub fn into_inner(self) -> (&'a [u8], usize) {
+        self.as_parts()
     }
 
     /// # Examples
     /// ```
     /// # use binary_file_reader::BinaryFileReader;
+    /// # use binary_file_reader::endian::Endian;
     /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let buffer = vec![0xab,0xcd];
-    /// let reader = BinaryFileReader::new(&buffer);
-    /// assert_eq!(reader.peek_u4()?,(0x0a,0x0b));
-    /// assert_eq!(reader.peek_u4()?,(0x0a,0x0b));
-    /// assert_eq!(reader.peek_u4()?,(0x0a,0x0b));
+    /// let buffer = vec![0x12, 0x34, 0x12, 0x34];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// assert_eq!(reader.endian(), Endian::Big);
+    /// assert_eq!(reader.read_u16()?, 0x1234);
+    ///
+    /// reader.set_endian(Endian::Little);
+    /// assert_eq!(reader.endian(), Endian::Little);
+    /// assert_eq!(reader.read_u16()?, 0x3412);
     /// #
     /// # Ok(())
     /// # }
@@ -334,23 +570,25 @@ impl<'a> BinaryFileReader<'a> {
     /// #    try_main().unwrap();
     /// # }
     /// ```
-    pub fn peek_u4(&self) -> Result<(u8, u8), BinaryFileReaderError> {
-        let mut buffer = [0; 1];
-        self.peek(&mut buffer)?;
-        let upper = buffer[0] >> 4;
-        let lower = buffer[0] & 0x0f;
-        Ok((upper, lower))
+    #[inline]
+    pub fn endian(&self) -> Endian {
+        self.endian
     }
 
     /// # Examples
     /// ```
     /// # use binary_file_reader::BinaryFileReader;
+    /// # use binary_file_reader::endian::Endian;
     /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let buffer = vec![0x01,0x02,0x03];
-    /// let reader = BinaryFileReader::new(&buffer);
-    /// assert_eq!(reader.peek_u8()?,0x01);
-    /// assert_eq!(reader.peek_u8()?,0x01);
-    /// assert_eq!(reader.peek_u8()?,0x01);
+    /// let buffer = b"II\x2a\x00\x08\x00\x00\x00".to_vec();
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// if reader.try_expect(b"II") {
+    ///     reader.set_endian(Endian::Little);
+    /// }
+    /// assert_eq!(reader.read_u16()?, 42);
+    ///
+    /// let mut ifd = reader.split_off_front(4)?;
+    /// assert_eq!(ifd.read_u32()?, 8);
     /// #
     /// # Ok(())
     /// # }
@@ -358,21 +596,23 @@ impl<'a> BinaryFileReader<'a> {
     /// #    try_main().unwrap();
     /// # }
     /// ```
-    pub fn peek_u8(&self) -> Result<u8, BinaryFileReaderError> {
-        let mut buffer = [0; 1];
-        self.peek(&mut buffer)?;
-        Ok(u8::from_be_bytes(buffer))
+    #[inline]
+    pub fn set_endian(&mut self, endian: Endian) {
+        self.endian = endian;
     }
 
     /// # Examples
     /// ```
     /// # use binary_file_reader::BinaryFileReader;
     /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let buffer = vec![0x01,0x02,0x03];
-    /// let reader = BinaryFileReader::new(&buffer);
-    /// assert_eq!(reader.peek_u16()?,0x0102);
-    /// assert_eq!(reader.peek_u16()?,0x0102);
-    /// assert_eq!(reader.peek_u16()?,0x0102);
+    /// let buffer = vec![0; 4];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// assert_eq!(reader.depth(), 0);
+    /// reader.nested(|r| {
+    ///     assert_eq!(r.depth(), 1);
+    ///     Ok(())
+    /// })?;
+    /// assert_eq!(reader.depth(), 0);
     /// #
     /// # Ok(())
     /// # }
@@ -380,79 +620,84 @@ impl<'a> BinaryFileReader<'a> {
     /// #    try_main().unwrap();
     /// # }
     /// ```
-    pub fn peek_u16(&self) -> Result<u16, BinaryFileReaderError> {
-        let mut buffer = [0; 2];
-        self.peek(&mut buffer)?;
-        Ok(u16::from_be_bytes(buffer))
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.depth
     }
 
     /// # Examples
     /// ```
     /// # use binary_file_reader::BinaryFileReader;
-    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let buffer = vec![0x01,0x02,0x03,0x04,0x05];
+    /// let buffer = vec![0; 4];
     /// let reader = BinaryFileReader::new(&buffer);
-    /// assert_eq!(reader.peek_u32()?,0x01020304);
-    /// assert_eq!(reader.peek_u32()?,0x01020304);
-    /// assert_eq!(reader.peek_u32()?,0x01020304);
-    /// #
-    /// # Ok(())
-    /// # }
-    /// # fn main() {
-    /// #    try_main().unwrap();
-    /// # }
+    /// assert_eq!(reader.max_depth(), BinaryFileReader::DEFAULT_MAX_DEPTH);
     /// ```
-    pub fn peek_u32(&self) -> Result<u32, BinaryFileReaderError> {
-        let mut buffer = [0; 4];
-        self.peek(&mut buffer)?;
-        Ok(u32::from_be_bytes(buffer))
+    #[inline]
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
     }
 
     /// # Examples
     /// ```
     /// # use binary_file_reader::BinaryFileReader;
-    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let buffer = vec![1,2,3,4,5,6,7,8,9];
-    /// let reader = BinaryFileReader::new(&buffer);
-    /// assert_eq!(reader.peek_u64()?,0x0102030405060708);
-    /// assert_eq!(reader.peek_u64()?,0x0102030405060708);
-    /// assert_eq!(reader.peek_u64()?,0x0102030405060708);
-    /// #
-    /// # Ok(())
-    /// # }
-    /// # fn main() {
-    /// #    try_main().unwrap();
-    /// # }
+    /// let buffer = vec![0; 4];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// reader.set_max_depth(1);
+    /// assert!(reader.nested(|r| r.nested(|_| Ok(()))).is_err());
     /// ```
-    pub fn peek_u64(&self) -> Result<u64, BinaryFileReaderError> {
-        let mut buffer = [0; 8];
-        self.peek(&mut buffer)?;
-        Ok(u64::from_be_bytes(buffer))
-    }
-
-    pub fn peek_u128(&self) -> Result<u128, BinaryFileReaderError> {
-        let mut buffer = [0; 16];
-        self.peek(&mut buffer)?;
-        Ok(u128::from_be_bytes(buffer))
+    #[inline]
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
     }
 
     /// # Examples
     /// ```
     /// # use binary_file_reader::BinaryFileReader;
-    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let buffer = vec![0,1,2,3,4,5,6,7,8,9];
+    /// let buffer = vec![0; 4];
     /// let reader = BinaryFileReader::new(&buffer);
+    /// assert_eq!(reader.max_alloc(), usize::MAX);
+    /// ```
+    #[inline]
+    pub fn max_alloc(&self) -> usize {
+        self.max_alloc
+    }
+
+    /// Limits how many bytes helpers such as
+    /// [`read_vec_of`](Self::read_vec_of) may allocate for a single value.
+    /// Sub-readers inherit the limit.
     ///
-    /// let mut buf = vec![0;5];
-    /// reader.peek_bytes(&mut buf);
-    /// assert_eq!(buf,vec![0,1,2,3,4]);
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # use binary_file_reader::error::BinaryFileReaderError;
+    /// let buffer = vec![0; 16];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// reader.set_max_alloc(4);
+    /// assert!(reader.read_vec_of::<u8>(4).is_ok());
+    /// assert!(matches!(
+    ///     reader.read_vec_of::<u32>(2),
+    ///     Err(BinaryFileReaderError::AllocationLimit { requested_bytes: 8, .. })
+    /// ));
+    /// ```
+    #[inline]
+    pub fn set_max_alloc(&mut self, max_alloc: usize) {
+        self.max_alloc = max_alloc;
+    }
+
+    /// Fails with [`BinaryFileReaderError::AllocationLimit`] if allocating
+    /// `bytes` would exceed [`max_alloc`](Self::max_alloc). Call it before
+    /// allocating for a length read from the data.
     ///
-    /// let mut buf = vec![0;5];
-    /// reader.peek_bytes(&mut buf);
-    /// assert_eq!(buf,vec![0,1,2,3,4]);
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0xff, 0xff, 0xff, 0xff, b'a'];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// reader.set_max_alloc(1024);
     ///
-    /// let mut buf = vec![0; 11];
-    /// assert!(reader.peek_bytes(&mut buf).is_err());
+    /// let length = reader.read_u32()? as usize;
+    /// assert!(reader.check_alloc(length).is_err());
     /// #
     /// # Ok(())
     /// # }
@@ -460,20 +705,81 @@ impl<'a> BinaryFileReader<'a> {
     /// #    try_main().unwrap();
     /// # }
     /// ```
-    pub fn peek_bytes(&self, buffer: &mut [u8]) -> Result<(), BinaryFileReaderError> {
-        self.peek(buffer)?;
+    pub fn check_alloc(&self, bytes: usize) -> Result<(), BinaryFileReaderError> {
+        if bytes > self.max_alloc {
+            return Err(BinaryFileReaderError::AllocationLimit {
+                requested_bytes: bytes,
+                max_alloc: self.max_alloc,
+                current_offset: self.current_offset,
+            });
+        }
+
         Ok(())
     }
 
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # use binary_file_reader::error::ErrorBytes;
+    /// let reader = BinaryFileReader::new(&[]);
+    /// assert_eq!(reader.max_error_bytes(), ErrorBytes::CAPACITY);
+    /// ```
+    #[inline]
+    pub fn max_error_bytes(&self) -> usize {
+        self.max_error_bytes
+    }
+
+    /// Limits how many bytes the `expect` family keeps in its errors. The
+    /// total length is always recorded. Values above
+    /// [`ErrorBytes::CAPACITY`] have no further effect. Sub-readers inherit
+    /// the limit.
+    ///
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # use binary_file_reader::error::BinaryFileReaderError;
+    /// let blob = vec![0xaa; 1 << 20];
+    /// let buffer = vec![0x00; 1 << 20];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// reader.set_max_error_bytes(4);
+    ///
+    /// match reader.expect(&blob) {
+    ///     Err(BinaryFileReaderError::Expect { require, got, .. }) => {
+    ///         assert_eq!(require.to_string(), "AA AA AA AA .. (1048576 bytes)");
+    ///         assert_eq!(got.len(), 4);
+    ///     }
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    #[inline]
+    pub fn set_max_error_bytes(&mut self, max_error_bytes: usize) {
+        self.max_error_bytes = max_error_bytes;
+    }
+
+    /// Starts recording which bytes of this reader's region are read, by this
+    /// reader and by the sub-readers split off from it afterwards. Skipped
+    /// bytes count as unread. Any earlier recording is discarded. See
+    /// [`coverage_report`](Self::coverage_report).
+    ///
+    /// Only available with the `std` feature.
+    ///
     /// # Examples
     /// ```
     /// # use binary_file_reader::BinaryFileReader;
     /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let buffer = vec![0,1,2,3,4,5,6,7];
-    /// let reader = BinaryFileReader::new(&buffer);
-    /// assert_eq!(reader.peek_slice(3)?,&[0,1,2]);
-    /// assert_eq!(reader.peek_slice(3)?,&[0,1,2]);
-    /// assert_eq!(reader.peek_slice(3)?,&[0,1,2]);
+    /// let buffer = vec![0x00, 0x04, 0xaa, 0xbb, 0xcc, 0xdd, 0x01];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// reader.enable_coverage();
+    ///
+    /// let length = reader.read_u16()? as usize;
+    /// let mut body = reader.split_off_front(length)?;
+    /// body.read_u8()?;
+    /// reader.set_offset(0)?;
+    /// reader.read_u8()?;
+    ///
+    /// let report = reader.coverage_report().unwrap();
+    /// assert_eq!(report.gaps, vec![3..7]);
+    /// assert_eq!(report.overlaps, vec![0..1]);
     /// #
     /// # Ok(())
     /// # }
@@ -481,20 +787,103 @@ impl<'a> BinaryFileReader<'a> {
     /// #    try_main().unwrap();
     /// # }
     /// ```
-    pub fn peek_slice(&self, length: usize) -> Result<&'a [u8], BinaryFileReaderError> {
-        self.get_slice(length)
+    #[cfg(feature = "std")]
+    pub fn enable_coverage(&mut self) {
+        self.coverage = Some(coverage::Coverage::shared(self.own_start..self.own_left));
+    }
+
+    /// The unread and multiply-read ranges recorded since
+    /// [`enable_coverage`](Self::enable_coverage), or `None` if coverage is
+    /// not enabled.
+    ///
+    /// Only available with the `std` feature.
+    #[cfg(feature = "std")]
+    pub fn coverage_report(&self) -> Option<coverage::CoverageReport> {
+        self.coverage
+            .as_ref()
+            .map(|coverage| lock(coverage).report())
+    }
+
+    /// Starts recording the reads labelled with [`annotate`](Self::annotate),
+    /// by this reader and by the sub-readers split off from it afterwards.
+    /// Any earlier recording is discarded. See [`layout`](Self::layout).
+    ///
+    /// Only available with the `std` feature.
+    #[cfg(feature = "std")]
+    pub fn enable_layout(&mut self) {
+        self.layout = Some(layout::Recorder::shared());
+    }
+
+    /// The annotations recorded since [`enable_layout`](Self::enable_layout),
+    /// or `None` if recording is not enabled. See [`layout::Layout`].
+    ///
+    /// Only available with the `std` feature.
+    #[cfg(feature = "std")]
+    pub fn layout(&self) -> Option<layout::Layout> {
+        self.layout.as_ref().map(|recorder| lock(recorder).layout())
+    }
+
+    /// Starts recording the primitive reads, slice reads, `expect` calls and
+    /// splits made by this reader and by the sub-readers split off from it
+    /// afterwards. Any earlier recording is discarded. See
+    /// [`trace::Trace`].
+    ///
+    /// Only available with the `std` feature.
+    #[cfg(feature = "std")]
+    pub fn enable_trace(&mut self) {
+        self.trace = Some(trace::SharedTrace::default());
+    }
+
+    /// The operations recorded since [`enable_trace`](Self::enable_trace),
+    /// or `None` if tracing is not enabled.
+    ///
+    /// Only available with the `std` feature.
+    #[cfg(feature = "std")]
+    pub fn trace(&self) -> Option<trace::Trace> {
+        self.trace.as_ref().map(|recorder| lock(recorder).clone())
+    }
+
+    /// Sends the events of [`section`](Self::section) and
+    /// [`field`](Self::field) calls on this reader and on the sub-readers split
+    /// off from it afterwards to `sink`, replacing any earlier sink. See
+    /// [`events::ParseEvent`].
+    ///
+    /// Only available with the `std` feature.
+    #[cfg(feature = "std")]
+    pub fn set_event_sink<F>(&mut self, sink: F)
+    where
+        F: FnMut(&events::ParseEvent<'_>) + Send + 'static,
+    {
+        self.events = Some(events::shared(sink));
+    }
+
+    #[cfg(feature = "std")]
+    fn emit(&self, event: events::ParseEvent<'_>) {
+        if let Some(sink) = &self.events {
+            (lock(sink))(&event);
+        }
     }
 
+    /// Runs `f` and, if [`enable_layout`](Self::enable_layout) was called,
+    /// records the bytes it consumed under `label`. Annotations can be
+    /// nested. Nothing is recorded if `f` fails. Without the `std` feature
+    /// this only runs `f`.
+    ///
     /// # Examples
     /// ```
     /// # use binary_file_reader::BinaryFileReader;
     /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let text = "Hello, world!";
-    /// let binary_data: Vec<u8> = text.as_bytes().to_vec();
-    /// let mut reader = BinaryFileReader::new(&binary_data);
-    /// assert_eq!(reader.peek_utf8(13)?,"Hello, world!");
-    /// assert_eq!(reader.peek_utf8(13)?,"Hello, world!");
-    /// assert!(reader.peek_utf8(14).is_err());
+    /// let buffer = vec![0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x80];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// reader.enable_layout();
+    ///
+    /// let width = reader.annotate("IHDR.width", |r| r.read_u32())?;
+    /// reader.annotate("IHDR.height", |r| r.read_u32())?;
+    /// assert_eq!(width, 256);
+    ///
+    /// let layout = reader.layout().unwrap();
+    /// assert_eq!(layout.entries[1].label, "IHDR.height");
+    /// assert_eq!(layout.entries[1].range, 4..8);
     /// #
     /// # Ok(())
     /// # }
@@ -502,56 +891,167 @@ impl<'a> BinaryFileReader<'a> {
     /// #    try_main().unwrap();
     /// # }
     /// ```
-    pub fn peek_utf8(&self, bytes: usize) -> Result<&'a str, BinaryFileReaderError> {
-        if bytes > self.available_bytes() {
-            return Err(BinaryFileReaderError::BufferUnderflow {
-                requested_bytes: bytes,
-                current_offset: self.current_offset,
-                available_bytes: self.available_bytes(),
+    pub fn annotate<T, F>(
+        &mut self,
+        label: impl Into<Cow<'static, str>>,
+        f: F,
+    ) -> Result<T, BinaryFileReaderError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, BinaryFileReaderError>,
+    {
+        #[cfg(feature = "std")]
+        if let Some(recorder) = self.layout.clone() {
+            let start = self.current_offset;
+            let depth = lock(&recorder).enter();
+            let result = f(self);
+            let entry = result.is_ok().then(|| layout::LayoutEntry {
+                range: start.min(self.current_offset)..start.max(self.current_offset),
+                label: label.into(),
+                depth,
             });
+            lock(&recorder).exit(entry);
+            return result;
         }
 
-        let slice = &self.buf[self.current_offset..self.current_offset + bytes];
-        let result = std::str::from_utf8(slice)?;
-        Ok(result)
+        #[cfg(not(feature = "std"))]
+        let _ = label;
+        f(self)
+    }
+
+    /// Runs `f` between a `SectionStart` and a `SectionEnd` event named
+    /// `name`, if an [event sink](Self::set_event_sink) is set. Without the
+    /// `std` feature this only runs `f`.
+    pub fn section<T, F>(&mut self, name: &str, f: F) -> Result<T, BinaryFileReaderError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, BinaryFileReaderError>,
+    {
+        #[cfg(feature = "std")]
+        if self.events.is_some() {
+            let offset = self.current_offset;
+            self.emit(events::ParseEvent::SectionStart { name, offset });
+            let result = f(self);
+            self.emit(events::ParseEvent::SectionEnd {
+                name,
+                offset,
+                length: self.current_offset.saturating_sub(offset),
+                ok: result.is_ok(),
+            });
+            return result;
+        }
+
+        #[cfg(not(feature = "std"))]
+        let _ = name;
+        f(self)
+    }
+
+    /// Runs `f` and, if an [event sink](Self::set_event_sink) is set and `f`
+    /// succeeds, emits a `Field` event with its value and the bytes it
+    /// consumed. Without the `std` feature this only runs `f`.
+    pub fn field<T, F>(&mut self, name: &str, f: F) -> Result<T, BinaryFileReaderError>
+    where
+        T: fmt::Debug,
+        F: FnOnce(&mut Self) -> Result<T, BinaryFileReaderError>,
+    {
+        #[cfg(feature = "std")]
+        if self.events.is_some() {
+            let offset = self.current_offset;
+            let value = f(self)?;
+            self.emit(events::ParseEvent::Field {
+                name,
+                offset,
+                length: self.current_offset.saturating_sub(offset),
+                value: &value,
+            });
+            return Ok(value);
+        }
+
+        #[cfg(not(feature = "std"))]
+        let _ = name;
+        f(self)
     }
 
+    /// Runs `f` and returns its value with the absolute offsets of the bytes
+    /// it consumed, so the value can be mapped back to the file.
+    ///
     /// # Examples
     /// ```
     /// # use binary_file_reader::BinaryFileReader;
     /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let buffer = vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+    /// let buffer = vec![0xff, 0x00, 0x00, 0x01, 0x00];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// reader.read_u8()?;
+    ///
+    /// let (width, span) = reader.spanned(|r| r.read_u32())?;
+    /// assert_eq!((width, span.clone()), (256, 1..5));
+    ///
+    /// // Patch the field in place.
+    /// let mut patched = buffer.clone();
+    /// patched[span].copy_from_slice(&512u32.to_be_bytes());
+    /// assert_eq!(patched, [0xff, 0x00, 0x00, 0x02, 0x00]);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn spanned<T, F>(&mut self, f: F) -> Result<(T, Range<usize>), BinaryFileReaderError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, BinaryFileReaderError>,
+    {
+        let start = self.current_offset;
+        let value = f(self)?;
+        Ok((value, start..self.current_offset))
+    }
+
+    /// Runs `f` and returns its value with the CRC-32 of the bytes it
+    /// consumed, including those consumed by sub-readers split off inside it.
     ///
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = b"\x00\x00\x00\x00IEND\xae\x42\x60\x82".to_vec();
     /// let mut reader = BinaryFileReader::new(&buffer);
-    /// reader.expect(&[0, 1, 2, 3])?;
-    /// reader.expect(&[4, 5, 6, 7])?;
-    /// assert!(reader.expect(&[0, 0, 0]).is_err());
-    /// assert!(reader.expect(&[8, 9, 0, 0, 0]).is_err());
-    /// reader.expect(&[8, 9])?;
     ///
+    /// let length = reader.read_u32()? as usize;
+    /// let (tag, crc) = reader.with_crc32(|r| {
+    ///     let tag = r.read_fourcc()?;
+    ///     r.read_slice(length)?;
+    ///     Ok(tag)
+    /// })?;
+    /// assert_eq!(tag, *b"IEND");
+    /// assert_eq!(crc, reader.read_u32()?);
+    /// #
     /// # Ok(())
     /// # }
     /// # fn main() {
     /// #    try_main().unwrap();
     /// # }
     /// ```
-    pub fn expect(&mut self, expect_bytes: &[u8]) -> Result<(), BinaryFileReaderError> {
-        self.expect_peek(expect_bytes)?;
-        self.current_offset += expect_bytes.len();
-        Ok(())
+    pub fn with_crc32<T, F>(&mut self, f: F) -> Result<(T, u32), BinaryFileReaderError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, BinaryFileReaderError>,
+    {
+        self.with_checksum(checksum::Crc32::new(), f)
     }
 
+    /// Runs `f` and returns its value with `checksum` updated with the bytes
+    /// it consumed and finished, so a section can be verified in the same
+    /// pass that parses it. See [`checksum::Checksum`].
+    ///
     /// # Examples
     /// ```
     /// # use binary_file_reader::BinaryFileReader;
+    /// # use binary_file_reader::checksum::{Checksum, Crc32};
     /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let text = "Hello, world!";
-    /// let binary_data: Vec<u8> = text.as_bytes().to_vec();
-    /// let mut reader = BinaryFileReader::new(&binary_data);
+    /// let buffer = b"123456789".to_vec();
+    /// let mut reader = BinaryFileReader::new(&buffer);
     ///
-    /// reader.expect_utf8("Hello")?;
-    /// assert!(reader.expect_utf8("Hello").is_err());
-    /// reader.expect_utf8(", world!")?;
+    /// let mut running = Crc32::new();
+    /// running.update(b"12");
+    /// let ((), crc) = reader.with_checksum(running, |r| r.read_slice(9).map(drop))?;
+    /// assert_eq!(crc, 0xc398_5abd);
     /// #
     /// # Ok(())
     /// # }
@@ -559,324 +1059,4176 @@ impl<'a> BinaryFileReader<'a> {
     /// #    try_main().unwrap();
     /// # }
     /// ```
-    pub fn expect_utf8(&mut self, expect_str: &str) -> Result<(), BinaryFileReaderError> {
-        self.expect(expect_str.as_bytes())?;
-        Ok(())
+    pub fn with_checksum<C, T, F>(
+        &mut self,
+        mut checksum: C,
+        f: F,
+    ) -> Result<(T, C::Output), BinaryFileReaderError>
+    where
+        C: checksum::Checksum,
+        F: FnOnce(&mut Self) -> Result<T, BinaryFileReaderError>,
+    {
+        let (value, span) = self.spanned(f)?;
+        checksum.update(self.buf.get(span).unwrap_or_default());
+        Ok((value, checksum.finish()))
     }
 
+    /// Reads a PNG-style chunk: a `u32` length, a four-byte tag, the payload
+    /// and a CRC-32 of the tag and payload. Returns the tag and a sub-reader
+    /// over the payload. The length and CRC are read with the reader's
+    /// endianness.
+    ///
+    /// Fails with [`BinaryFileReaderError::ChecksumMismatch`] if the CRC is
+    /// wrong. On failure the reader is left at the start of the chunk.
+    ///
     /// # Examples
     /// ```
     /// # use binary_file_reader::BinaryFileReader;
+    /// # use binary_file_reader::error::BinaryFileReaderError;
     /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = b"\x00\x00\x00\x01sRGB\x00\xae\xce\x1c\xe9\x00\x00\x00\x00IEND\xae\x42\x60\x00".to_vec();
+    /// let mut reader = BinaryFileReader::new(&buffer);
     ///
-    /// let buffer = vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
-    /// let reader = BinaryFileReader::new(&buffer);
-    /// reader.expect_peek(&[0, 1, 2, 3])?;
-    /// reader.expect_peek(&[0, 1, 2, 3])?;
+    /// let (tag, mut payload) = reader.read_crc32_chunk()?;
+    /// assert_eq!(tag, *b"sRGB");
+    /// assert_eq!(payload.read_u8()?, 0);
     ///
+    /// let err = reader.read_crc32_chunk().unwrap_err();
+    /// assert!(matches!(
+    ///     err,
+    ///     BinaryFileReaderError::ChecksumMismatch {
+    ///         stored: 0xae42_6000,
+    ///         computed: 0xae42_6082,
+    ///         current_offset: 21,
+    ///     }
+    /// ));
+    /// assert_eq!(reader.current_offset(), 13);
+    /// #
     /// # Ok(())
     /// # }
     /// # fn main() {
     /// #    try_main().unwrap();
     /// # }
     /// ```
-    pub fn expect_peek(&self, expect_bytes: &[u8]) -> Result<(), BinaryFileReaderError> {
-        if self.available_bytes() < expect_bytes.len() {
-            let require = Vec::from(expect_bytes);
-            return Err(BinaryFileReaderError::ExpectInsufficientBytes {
-                require,
-                available_bytes: self.available_bytes(),
-                current_offset: self.current_offset(),
-            });
-        }
-
-        let slice = &self.buf[self.current_offset..self.current_offset + expect_bytes.len()];
-
-        for (req, got) in expect_bytes.iter().zip(slice) {
-            if *req == *got {
-                continue;
+    pub fn read_crc32_chunk(&mut self) -> Result<(FourCC, Self), BinaryFileReaderError> {
+        let start = self.current_offset;
+        let result = (|| {
+            let length = self.read_u32()?;
+            let length = usize::try_from(length).unwrap_or(usize::MAX);
+            let ((tag, payload), computed) = self.with_crc32(|r| {
+                let tag = r.read_fourcc()?;
+                Ok((tag, r.split_off_front(length)?))
+            })?;
+            let current_offset = self.current_offset;
+            let stored = self.read_u32()?;
+            if stored != computed {
+                return Err(BinaryFileReaderError::ChecksumMismatch {
+                    stored: stored as u64,
+                    computed: computed as u64,
+                    current_offset,
+                });
             }
-
-            let require = Vec::from(expect_bytes);
-            let got = Vec::from(slice);
-            return Err(BinaryFileReaderError::Expect {
-                require,
-                got,
-                current_offset: self.current_offset(),
-                available_bytes: self.available_bytes(),
-            });
+            Ok((tag, payload))
+        })();
+        if result.is_err() {
+            self.current_offset = start;
         }
-
-        Ok(())
+        result
     }
 
+    /// Decompresses the next `compressed_len` bytes as raw DEFLATE data, as
+    /// in ZIP entries, and returns a reader over the result. The new reader
+    /// keeps this reader's endianness, limits, cancel token and source name,
+    /// and the output may not exceed [`max_alloc`](Self::max_alloc) bytes.
+    /// This reader advances past the compressed bytes only on success.
+    ///
+    /// Only available with the `inflate` feature.
+    ///
     /// # Examples
     /// ```
     /// # use binary_file_reader::BinaryFileReader;
     /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
-    ///
-    /// let buffer = vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+    /// let mut buffer = vec![0x00, 0x0a];
+    /// buffer.extend([0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0xc8, 0x40, 0x90, 0x00]);
+    /// buffer.push(0xff);
     /// let mut reader = BinaryFileReader::new(&buffer);
-    /// let mut splited = reader.split_off_front(5)?;
     ///
-    /// splited.expect_peek(&[0,1,2,3,4])?;
-    /// assert_eq!(splited.current_offset(),0);
+    /// let length = reader.read_u16()? as usize;
+    /// let mut text = reader.inflate_sub_reader(length)?;
+    /// assert_eq!(text.reader().read_utf8(5)?, "hello");
+    /// assert_eq!(text.available_bytes(), 12);
+    /// assert_eq!(reader.read_u8()?, 0xff);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    #[cfg(feature = "inflate")]
+    pub fn inflate_sub_reader(
+        &mut self,
+        compressed_len: usize,
+    ) -> Result<OwnedBinaryFileReader, BinaryFileReaderError> {
+        let compressed = self.peek_slice(compressed_len)?;
+        let (output, _) = inflate::inflate_at(compressed, self.max_alloc, self.current_offset)?;
+        self.advance(compressed_len)?;
+        Ok(OwnedBinaryFileReader::derived(output, self))
+    }
+
+    /// Like [`inflate_sub_reader`](Self::inflate_sub_reader), for a zlib
+    /// stream, as in PNG `IDAT` chunks and PDF streams. The Adler-32 trailer
+    /// is verified.
     ///
-    /// reader.expect_peek(&[5,6,7,8,9])?;
-    /// assert_eq!(reader.current_offset(),5);
+    /// Only available with the `inflate` feature.
+    #[cfg(feature = "inflate")]
+    pub fn zlib_sub_reader(
+        &mut self,
+        compressed_len: usize,
+    ) -> Result<OwnedBinaryFileReader, BinaryFileReaderError> {
+        let compressed = self.peek_slice(compressed_len)?;
+        let output = inflate::zlib_decompress_at(compressed, self.max_alloc, self.current_offset)?;
+        self.advance(compressed_len)?;
+        Ok(OwnedBinaryFileReader::derived(output, self))
+    }
+
+    /// Like [`inflate_sub_reader`](Self::inflate_sub_reader), for an LZ4
+    /// frame. See [`lz4::lz4_frame_decompress`].
+    ///
+    /// Only available with the `lz4` feature.
     ///
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![
+    ///     0x04, 0x22, 0x4d, 0x18, 0x60, 0x40, 0x82, 0x06, 0x00, 0x00, 0x00, 0x35, 0x61, 0x62,
+    ///     0x63, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00,
+    /// ];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    ///
+    /// let mut asset = reader.lz4_sub_reader(buffer.len())?;
+    /// assert_eq!(asset.reader().read_utf8(12)?, "abcabcabcabc");
+    /// #
     /// # Ok(())
     /// # }
     /// # fn main() {
     /// #    try_main().unwrap();
     /// # }
     /// ```
-    pub fn split_off_front(&mut self, size: usize) -> Result<Self, BinaryFileReaderError> {
-        if size > self.available_bytes() {
-            return Err(BinaryFileReaderError::BufferUnderflow {
-                requested_bytes: size,
-                current_offset: self.current_offset,
-                available_bytes: self.available_bytes(),
-            });
-        }
-
-        let splited_offset = self.current_offset;
-        let new_offset = self.current_offset + size;
+    #[cfg(feature = "lz4")]
+    pub fn lz4_sub_reader(
+        &mut self,
+        compressed_len: usize,
+    ) -> Result<OwnedBinaryFileReader, BinaryFileReaderError> {
+        let compressed = self.peek_slice(compressed_len)?;
+        let output = lz4::frame_at(compressed, self.max_alloc, self.current_offset)?;
+        self.advance(compressed_len)?;
+        Ok(OwnedBinaryFileReader::derived(output, self))
+    }
 
-        self.current_offset = new_offset;
+    /// Like [`lz4_sub_reader`](Self::lz4_sub_reader), for a single raw LZ4
+    /// block. See [`lz4::lz4_block_decompress`].
+    ///
+    /// Only available with the `lz4` feature.
+    #[cfg(feature = "lz4")]
+    pub fn lz4_block_sub_reader(
+        &mut self,
+        compressed_len: usize,
+    ) -> Result<OwnedBinaryFileReader, BinaryFileReaderError> {
+        let compressed = self.peek_slice(compressed_len)?;
+        let output = lz4::block_at(compressed, self.max_alloc, self.current_offset)?;
+        self.advance(compressed_len)?;
+        Ok(OwnedBinaryFileReader::derived(output, self))
+    }
 
-        Ok(Self {
-            current_offset: splited_offset,
-            own_left: new_offset,
-            buf: self.buf,
-        })
+    /// Decodes the next `text_len` bytes as hex digits into an owned reader,
+    /// which inherits this reader's endianness and limits. ASCII whitespace
+    /// is ignored. Fails with
+    /// [`InvalidEncoding`](BinaryFileReaderError::InvalidEncoding) for
+    /// malformed text, and with
+    /// [`AllocationLimit`](BinaryFileReaderError::AllocationLimit) if the
+    /// output would exceed [`max_alloc`](Self::max_alloc). This reader only
+    /// advances if decoding succeeds.
+    ///
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut reader = BinaryFileReader::new(b"KEY=00 01 02 0a\n");
+    /// reader.expect(b"KEY=")?;
+    ///
+    /// let mut key = reader.hex_sub_reader(11)?;
+    /// assert_eq!(key.reader().read_u32()?, 0x0001020a);
+    /// assert_eq!(reader.read_u8()?, b'\n');
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn hex_sub_reader(
+        &mut self,
+        text_len: usize,
+    ) -> Result<OwnedBinaryFileReader, BinaryFileReaderError> {
+        self.decode_sub_reader(text_len, TextEncoding::Hex)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::error::BinaryFileReaderError;
+    /// Like [`hex_sub_reader`](Self::hex_sub_reader), for base64 text, as in
+    /// the body of a PEM block or a `data:` URI.
+    ///
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let pem = b"-----BEGIN BLOB-----\nAAEC\nAw==\n-----END BLOB-----\n";
+    /// let mut reader = BinaryFileReader::new(pem);
+    /// reader.expect(b"-----BEGIN BLOB-----\n")?;
+    ///
+    /// let mut blob = reader.base64_sub_reader(10)?;
+    /// assert_eq!(blob.reader().read_u32()?, 0x00010203);
+    /// reader.expect(b"-----END BLOB-----")?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn base64_sub_reader(
+        &mut self,
+        text_len: usize,
+    ) -> Result<OwnedBinaryFileReader, BinaryFileReaderError> {
+        self.decode_sub_reader(text_len, TextEncoding::Base64)
+    }
 
-    use super::BinaryFileReader;
+    fn decode_sub_reader(
+        &mut self,
+        text_len: usize,
+        encoding: TextEncoding,
+    ) -> Result<OwnedBinaryFileReader, BinaryFileReaderError> {
+        let text = self.peek_slice(text_len)?;
+        let output = text::decode_at(text, encoding, self.max_alloc, self.current_offset)?;
+        self.advance(text_len)?;
+        Ok(OwnedBinaryFileReader::derived(output, self))
+    }
 
-    #[test]
-    fn test_read() -> Result<(), BinaryFileReaderError> {
-        let mut reader = BinaryFileReader::new(&[0, 1, 2, 3, 4, 5]);
-        assert_eq!(reader.read_u8()?, 0);
-        assert_eq!(reader.read_u8()?, 1);
+    /// The XXH64 of the unread bytes, without consuming them. Useful as a
+    /// cache or deduplication key for a split-off region.
+    ///
+    /// Only available with the `xxhash` feature.
+    ///
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = b"\x00\x03abc\x00\x03abc".to_vec();
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    ///
+    /// let mut hashes = Vec::new();
+    /// while reader.available_bytes() > 0 {
+    ///     let length = reader.read_u16()? as usize;
+    ///     hashes.push(reader.split_off_front(length)?.xxh64(0));
+    /// }
+    /// assert_eq!(hashes, [0x44bc_2cf5_ad77_0999; 2]);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    #[cfg(feature = "xxhash")]
+    pub fn xxh64(&self, seed: u64) -> u64 {
+        checksum::xxh64(self.remaining(), seed)
+    }
+
+    /// Names the input, such as a file name, for errors raised inside
+    /// [`parse`](Self::parse). Sub-readers inherit the name.
+    /// [`from_path`](Self::from_path) sets it to the path.
+    pub fn with_source_name(mut self, name: impl Into<Arc<str>>) -> Self {
+        self.source_name = Some(name.into());
+        self
+    }
+
+    #[inline]
+    pub fn source_name(&self) -> Option<&str> {
+        self.source_name.as_deref()
+    }
+
+    /// Runs `f` and, if the reader has a [source name](Self::with_source_name),
+    /// wraps any error it returns in [`BinaryFileReaderError::Named`] so the
+    /// message says which input failed. An error that already carries a name
+    /// is returned as is.
+    ///
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// let inputs = [("a.bin", vec![0x00, 0x01]), ("b.bin", vec![0x00])];
+    ///
+    /// let errors = inputs
+    ///     .iter()
+    ///     .filter_map(|(name, data)| {
+    ///         let mut reader = BinaryFileReader::new(data).with_source_name(*name);
+    ///         reader.parse(|r| r.read_u16()).err()
+    ///     })
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(errors.len(), 1);
+    /// assert_eq!(errors[0].source_name(), Some("b.bin"));
+    /// assert!(errors[0].to_string().starts_with("b.bin: Buffer underflow"));
+    /// ```
+    pub fn parse<T, F>(&mut self, f: F) -> Result<T, BinaryFileReaderError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, BinaryFileReaderError>,
+    {
+        let result = f(self);
+        match (result, &self.source_name) {
+            (Err(err), Some(name)) if err.source_name().is_none() => {
+                Err(BinaryFileReaderError::Named {
+                    name: name.clone(),
+                    source: Box::new(err),
+                })
+            }
+            (result, _) => result,
+        }
+    }
+
+    /// Lets `token` abort the looping methods of this reader and of the
+    /// sub-readers split off from it. See [`CancelToken`].
+    pub fn set_cancel_token(&mut self, token: CancelToken) {
+        self.cancel = Some(token);
+    }
+
+    /// Fails with [`BinaryFileReaderError::Cancelled`] if the token set with
+    /// [`set_cancel_token`](Self::set_cancel_token) was cancelled. Call it in
+    /// long loops of your own.
+    ///
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # use binary_file_reader::cancel::CancelToken;
+    /// let mut reader = BinaryFileReader::new(&[]);
+    /// assert!(reader.check_cancelled().is_ok());
+    ///
+    /// let token = CancelToken::new();
+    /// reader.set_cancel_token(token.clone());
+    /// token.cancel();
+    /// assert!(reader.check_cancelled().is_err());
+    /// ```
+    #[inline]
+    pub fn check_cancelled(&self) -> Result<(), BinaryFileReaderError> {
+        match &self.cancel {
+            Some(token) if token.is_cancelled() => Err(BinaryFileReaderError::Cancelled {
+                current_offset: self.current_offset,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Runs `f` one nesting level deeper. Sub-readers created with
+    /// [`split_off_front`](Self::split_off_front) inherit the depth, so
+    /// recursive container parsers fail with
+    /// [`BinaryFileReaderError::DepthExceeded`] instead of overflowing the
+    /// stack.
+    ///
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # use binary_file_reader::error::BinaryFileReaderError;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// // Each box is a length byte followed by its children.
+    /// fn count_boxes(reader: &mut BinaryFileReader) -> Result<usize, BinaryFileReaderError> {
+    ///     let mut count = 0;
+    ///     while reader.available_bytes() > 0 {
+    ///         let length = reader.read_u8()? as usize;
+    ///         let mut children = reader.split_off_front(length)?;
+    ///         count += 1 + children.nested(count_boxes)?;
+    ///     }
+    ///     Ok(count)
+    /// }
+    ///
+    /// let buffer = vec![3, 2, 1, 0];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// assert_eq!(count_boxes(&mut reader.clone())?, 4);
+    ///
+    /// reader.set_max_depth(2);
+    /// assert!(matches!(
+    ///     count_boxes(&mut reader),
+    ///     Err(BinaryFileReaderError::DepthExceeded { max_depth: 2, .. })
+    /// ));
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn nested<T, F>(&mut self, f: F) -> Result<T, BinaryFileReaderError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, BinaryFileReaderError>,
+    {
+        if self.depth >= self.max_depth {
+            return Err(BinaryFileReaderError::DepthExceeded {
+                max_depth: self.max_depth,
+                current_offset: self.current_offset,
+            });
+        }
+
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// Runs `f` and wraps any error it returns in
+    /// [`BinaryFileReaderError::Context`] with `label`, so the error says what
+    /// was being parsed. Nested calls build up a stack of labels, available
+    /// through [`BinaryFileReaderError::contexts`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # use binary_file_reader::error::BinaryFileReaderError;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// // Two chunks, each a length byte followed by a big-endian u16.
+    /// let buffer = vec![2, 0x00, 0x01, 1, 0x00];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    ///
+    /// let mut index = 0;
+    /// let result = reader.repeat(|r| {
+    ///     index += 1;
+    ///     r.context(format!("chunk {}", index), |r| {
+    ///         let length = r.read_u8()? as usize;
+    ///         r.split_off_front(length)?.read_u16()
+    ///     })
+    /// });
+    ///
+    /// let err = result.unwrap_err();
+    /// assert_eq!(err.contexts(), ["chunk 2"]);
+    /// assert!(err
+    ///     .to_string()
+    ///     .starts_with("Failed to read element 1: chunk 2: Buffer underflow"));
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn context<T, F>(
+        &mut self,
+        label: impl Into<Cow<'static, str>>,
+        f: F,
+    ) -> Result<T, BinaryFileReaderError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, BinaryFileReaderError>,
+    {
+        f(self).map_err(|source| BinaryFileReaderError::Context {
+            label: label.into(),
+            source: Box::new(source),
+        })
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # use std::fs;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0xab,0xcd,0xef];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// let (upper,lower) = reader.read_u4()?;
+    /// assert_eq!(upper,0x0a);
+    /// assert_eq!(lower,0x0b);
+    /// assert_eq!(reader.read_u4()?,(0x0c,0x0d));
+    /// assert_eq!(reader.read_u4()?,(0x0e,0x0f));
+    /// assert!(reader.read_u4().is_err());
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn read_u4(&mut self) -> Result<(u8, u8), BinaryFileReaderError> {
+        self.traced("read_u4", |r| {
+            let mut buffer = [0; 1];
+            r.read(&mut buffer)?;
+            let upper = buffer[0] >> 4;
+            let lower = buffer[0] & 0x0f;
+            Ok((upper, lower))
+        })
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![1,2,3];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// assert_eq!(reader.read_u8()?,1);
+    /// assert_eq!(reader.read_u8()?,2);
+    /// assert_eq!(reader.read_u8()?,3);
+    /// assert!(reader.read_u8().is_err());
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn read_u8(&mut self) -> Result<u8, BinaryFileReaderError> {
+        self.traced("read_u8", |r| {
+            let mut buffer = [0; 1];
+            r.read(&mut buffer)?;
+            Ok(buffer[0])
+        })
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0x12,0x34,0x56,0x78,0x90];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// assert_eq!(reader.read_u16()?,0x1234);
+    /// assert_eq!(reader.read_u16()?,0x5678);
+    /// assert!(reader.read_u16().is_err());
+    /// assert_eq!(reader.read_u8()?,0x90);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn read_u16(&mut self) -> Result<u16, BinaryFileReaderError> {
+        self.traced("read_u16", |r| {
+            let mut buffer = [0; 2];
+            r.read(&mut buffer)?;
+            Ok(r.endian.u16_from_bytes(buffer))
+        })
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0x12,0x34,0x56,0x78,0x90];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// assert_eq!(reader.read_u32()?,0x12345678);
+    /// assert!(reader.read_u32().is_err());
+    /// assert_eq!(reader.read_u8()?,0x90);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn read_u32(&mut self) -> Result<u32, BinaryFileReaderError> {
+        self.traced("read_u32", |r| {
+            let mut buffer = [0; 4];
+            r.read(&mut buffer)?;
+            Ok(r.endian.u32_from_bytes(buffer))
+        })
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0x12,0x34,0x56,0x78,0x90,0x12,0x34,0x56,0x78,0x90];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// assert_eq!(reader.read_u64()?,0x1234567890123456);
+    /// assert!(reader.read_u64().is_err());
+    /// assert_eq!(reader.read_u8()?,0x78);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn read_u64(&mut self) -> Result<u64, BinaryFileReaderError> {
+        self.traced("read_u64", |r| {
+            let mut buffer = [0; 8];
+            r.read(&mut buffer)?;
+            Ok(r.endian.u64_from_bytes(buffer))
+        })
+    }
+
+    pub fn read_u128(&mut self) -> Result<u128, BinaryFileReaderError> {
+        self.traced("read_u128", |r| {
+            let mut buffer = [0; 16];
+            r.read(&mut buffer)?;
+            Ok(r.endian.u128_from_bytes(buffer))
+        })
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0, 1, 2];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// assert!(!reader.read_bool()?);
+    /// assert!(reader.read_bool()?);
+    /// assert!(reader.read_bool().is_err());
+    /// assert_eq!(reader.current_offset(), 2);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn read_bool(&mut self) -> Result<bool, BinaryFileReaderError> {
+        self.traced("read_bool", |r| {
+            let value = match r.peek_u8()? {
+                0 => false,
+                1 => true,
+                value => {
+                    return Err(BinaryFileReaderError::InvalidDiscriminant {
+                        type_name: "bool",
+                        value: value.into(),
+                        current_offset: r.current_offset,
+                    })
+                }
+            };
+            r.advance(1)?;
+            Ok(value)
+        })
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0, 1, 0xff];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// assert!(!reader.read_bool_lenient()?);
+    /// assert!(reader.read_bool_lenient()?);
+    /// assert!(reader.read_bool_lenient()?);
+    /// assert!(reader.read_bool_lenient().is_err());
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn read_bool_lenient(&mut self) -> Result<bool, BinaryFileReaderError> {
+        Ok(self.read_u8()? != 0)
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// #[derive(Debug, PartialEq)]
+    /// enum ColorType {
+    ///     Grayscale,
+    ///     Truecolor,
+    /// }
+    ///
+    /// impl TryFrom<u8> for ColorType {
+    ///     type Error = ();
+    ///
+    ///     fn try_from(value: u8) -> Result<Self, Self::Error> {
+    ///         match value {
+    ///             0 => Ok(ColorType::Grayscale),
+    ///             2 => Ok(ColorType::Truecolor),
+    ///             _ => Err(()),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let buffer = vec![2, 7];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// assert_eq!(reader.read_enum::<ColorType>()?, ColorType::Truecolor);
+    /// assert!(reader.read_enum::<ColorType>().is_err());
+    /// assert_eq!(reader.current_offset(), 1);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn read_enum<T: TryFrom<u8>>(&mut self) -> Result<T, BinaryFileReaderError> {
+        let value = self.peek_u8()?;
+        let result = self.convert_discriminant(value)?;
+        self.advance(1)?;
+        Ok(result)
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// #[derive(Debug, PartialEq)]
+    /// struct Machine(u16);
+    ///
+    /// impl TryFrom<u16> for Machine {
+    ///     type Error = ();
+    ///
+    ///     fn try_from(value: u16) -> Result<Self, Self::Error> {
+    ///         match value {
+    ///             0x003e | 0x00b7 => Ok(Machine(value)),
+    ///             _ => Err(()),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let buffer = vec![0x00, 0x3e, 0xff, 0xff];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// assert_eq!(reader.read_enum_u16::<Machine>()?, Machine(0x3e));
+    /// assert!(reader.read_enum_u16::<Machine>().is_err());
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn read_enum_u16<T: TryFrom<u16>>(&mut self) -> Result<T, BinaryFileReaderError> {
+        let value = self.peek_u16()?;
+        let result = self.convert_discriminant(value)?;
+        self.advance(2)?;
+        Ok(result)
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// #[derive(Debug, PartialEq)]
+    /// enum BlockType {
+    ///     SectionHeader,
+    /// }
+    ///
+    /// impl TryFrom<u32> for BlockType {
+    ///     type Error = ();
+    ///
+    ///     fn try_from(value: u32) -> Result<Self, Self::Error> {
+    ///         match value {
+    ///             0x0a0d0d0a => Ok(BlockType::SectionHeader),
+    ///             _ => Err(()),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let buffer = vec![0x0a, 0x0d, 0x0d, 0x0a];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// assert_eq!(reader.read_enum_u32::<BlockType>()?, BlockType::SectionHeader);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn read_enum_u32<T: TryFrom<u32>>(&mut self) -> Result<T, BinaryFileReaderError> {
+        let value = self.peek_u32()?;
+        let result = self.convert_discriminant(value)?;
+        self.advance(4)?;
+        Ok(result)
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0x00, 0x64, 0x00, 0x32, 0x08, 0x02];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// let (size, depth): ([u16; 2], u8) = reader.read_struct()?;
+    /// assert_eq!(size, [100, 50]);
+    /// assert_eq!(depth, 8);
+    /// assert_eq!(reader.read_struct::<u8>()?, 2);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn read_struct<T: ReadFrom>(&mut self) -> Result<T, BinaryFileReaderError> {
+        T::read_from(self)
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0, 0, 0, 8, 0, 0, 0, 16, 0, 0, 0, 32];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// let entries = reader.read_vec_of::<(u16, u16)>(2)?;
+    /// assert_eq!(entries, vec![(0, 8), (0, 16)]);
+    /// assert!(reader.read_vec_of::<u32>(2).is_err());
+    /// assert_eq!(reader.read_vec_of::<u32>(1)?, vec![32]);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn read_vec_of<T: ReadFrom>(
+        &mut self,
+        count: usize,
+    ) -> Result<Vec<T>, BinaryFileReaderError> {
+        let min_bytes = count.saturating_mul(T::MIN_SIZE);
+        if min_bytes > self.available_bytes() {
+            return Err(BinaryFileReaderError::BufferUnderflow {
+                requested_bytes: min_bytes,
+                current_offset: self.current_offset,
+                available_bytes: self.available_bytes(),
+            });
+        }
+
+        let capacity = if T::MIN_SIZE == 0 { 0 } else { count };
+        self.check_alloc(capacity.saturating_mul(core::mem::size_of::<T>()))?;
+        let mut items = Vec::with_capacity(capacity);
+        for index in 0..count {
+            let item = T::read_from(self).map_err(|source| BinaryFileReaderError::Element {
+                index,
+                source: Box::new(source),
+            })?;
+            items.push(item);
+        }
+
+        Ok(items)
+    }
+
+    /// Reads a one-byte presence marker (`0` for absent, `1` for present)
+    /// followed by the value if it is present.
+    ///
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0x01, 0x00, 0x2a, 0x00, 0x02];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// assert_eq!(reader.read_option::<u16>()?, Some(0x2a));
+    /// assert_eq!(reader.read_option::<u16>()?, None);
+    /// assert!(reader.read_option::<u16>().is_err());
+    /// assert_eq!(reader.current_offset(), 4);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn read_option<T: ReadFrom>(&mut self) -> Result<Option<T>, BinaryFileReaderError> {
+        self.read_option_with(0, 1)
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0xff, 0x2a, 0xc0];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// assert_eq!(reader.read_option_with::<u8>(0xc0, 0xff)?, Some(0x2a));
+    /// assert_eq!(reader.read_option_with::<u8>(0xc0, 0xff)?, None);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn read_option_with<T: ReadFrom>(
+        &mut self,
+        absent: u8,
+        present: u8,
+    ) -> Result<Option<T>, BinaryFileReaderError> {
+        let marker = self.peek_u8()?;
+        if marker == absent {
+            self.advance(1)?;
+            Ok(None)
+        } else if marker == present {
+            self.advance(1)?;
+            T::read_from(self).map(Some)
+        } else {
+            Err(BinaryFileReaderError::InvalidPresenceMarker {
+                got: marker,
+                absent,
+                present,
+                current_offset: self.current_offset,
+            })
+        }
+    }
+
+    /// Records are read until the reader is exhausted. After an error the
+    /// reader is left at the start of the failing record and the iterator
+    /// ends.
+    ///
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0, 1, 0xaa, 0, 2, 0xbb, 0, 3];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    ///
+    /// let records = reader.iter_of::<(u16, u8)>().take(2).collect::<Result<Vec<_>, _>>()?;
+    /// assert_eq!(records, vec![(1, 0xaa), (2, 0xbb)]);
+    ///
+    /// let mut rest = reader.iter_of::<(u16, u8)>();
+    /// assert!(rest.next().unwrap().is_err());
+    /// assert!(rest.next().is_none());
+    /// assert_eq!(reader.current_offset(), 6);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn iter_of<T: ReadFrom>(&mut self) -> IterOf<'_, 'a, T> {
+        IterOf::new(self)
+    }
+
+    /// Calls `f` until the reader is exhausted and collects the results. On
+    /// failure the reader is left at the start of the failing item and the
+    /// error is wrapped in [`BinaryFileReaderError::Element`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![2, 0xaa, 0xbb, 1, 0xcc];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    ///
+    /// let chunks = reader.repeat(|r| {
+    ///     let length = r.read_u8()? as usize;
+    ///     r.read_slice(length)
+    /// })?;
+    /// assert_eq!(chunks, vec![&[0xaa, 0xbb][..], &[0xcc][..]]);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn repeat<T, F>(&mut self, mut f: F) -> Result<Vec<T>, BinaryFileReaderError>
+    where
+        F: FnMut(&mut Self) -> Result<T, BinaryFileReaderError>,
+    {
+        self.repeat_until(|reader| f(reader).map(Some))
+    }
+
+    /// Like [`repeat`](Self::repeat), but `f` may return `Ok(None)` to stop
+    /// before the reader is exhausted.
+    ///
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![1, 2, 3, 0, 0xff];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    ///
+    /// let values = reader.repeat_until(|r| match r.read_u8()? {
+    ///     0 => Ok(None),
+    ///     value => Ok(Some(value)),
+    /// })?;
+    /// assert_eq!(values, vec![1, 2, 3]);
+    /// assert_eq!(reader.current_offset(), 4);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn repeat_until<T, F>(&mut self, mut f: F) -> Result<Vec<T>, BinaryFileReaderError>
+    where
+        F: FnMut(&mut Self) -> Result<Option<T>, BinaryFileReaderError>,
+    {
+        let mut items = Vec::new();
+        while self.available_bytes() > 0 {
+            self.check_cancelled()?;
+            let start = self.current_offset;
+            match f(self) {
+                Ok(Some(item)) => items.push(item),
+                Ok(None) => break,
+                Err(source) => {
+                    self.current_offset = start;
+                    return Err(BinaryFileReaderError::Element {
+                        index: items.len(),
+                        source: Box::new(source),
+                    });
+                }
+            }
+            // An item that consumed nothing would be produced forever.
+            if self.current_offset == start {
+                break;
+            }
+        }
+
+        Ok(items)
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0,1,2,3,4,5,6,7,8,9];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// let mut buf = vec![0;5];
+    /// reader.read_bytes(&mut buf);
+    /// assert_eq!(buf,vec![0,1,2,3,4]);
+    /// reader.read_bytes(&mut buf);
+    /// assert_eq!(buf,vec![5,6,7,8,9]);
+    /// assert!(reader.read_u8().is_err());
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn read_bytes(&mut self, buffer: &mut [u8]) -> Result<(), BinaryFileReaderError> {
+        self.traced("read_bytes", |r| {
+            r.read(buffer)?;
+            Ok(())
+        })
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0,1,2,3,4,5,6,7];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// assert_eq!(reader.read_slice(3)?,&[0,1,2]);
+    /// assert_eq!(reader.read_slice(3)?,&[3,4,5]);
+    /// assert!(reader.read_slice(3).is_err());
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn read_slice(&mut self, length: usize) -> Result<&'a [u8], BinaryFileReaderError> {
+        self.traced("read_slice", |r| {
+            let slice = r.get_slice(length)?;
+            r.advance(length)?;
+            Ok(slice)
+        })
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # use binary_file_reader::endian::Endian;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// assert_eq!(&*reader.read_slice_of::<u32>(2)?, &[1, 2]);
+    /// assert!(reader.read_slice_of::<u32>(2).is_err());
+    ///
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// reader.set_endian(Endian::Little);
+    /// assert_eq!(&*reader.read_slice_of::<u16>(2)?, &[0, 0x0100]);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn read_slice_of<T: Primitive>(
+        &mut self,
+        count: usize,
+    ) -> Result<Cow<'a, [T]>, BinaryFileReaderError> {
+        let size = core::mem::size_of::<T>();
+        let slice = self.get_slice(count.saturating_mul(size))?;
+
+        if (self.endian == Endian::NATIVE || size == 1)
+            && slice.as_ptr().align_offset(core::mem::align_of::<T>()) == 0
+        {
+            self.advance(slice.len())?;
+            // SAFETY: `Primitive` is sealed to integer types for which every bit
+            // pattern is valid, the pointer is aligned for `T`, and `slice` holds
+            // exactly `count` elements borrowed for `'a`.
+            let values = unsafe { core::slice::from_raw_parts(slice.as_ptr() as *const T, count) };
+            return Ok(Cow::Borrowed(values));
+        }
+
+        self.check_alloc(slice.len())?;
+        self.advance(slice.len())?;
+        let values = slice
+            .chunks_exact(size)
+            .map(|bytes| T::from_bytes(bytes, self.endian))
+            .collect();
+        Ok(Cow::Owned(values))
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let text = "Hello, world!";
+    /// let binary_data: Vec<u8> = text.as_bytes().to_vec();
+    /// let mut reader = BinaryFileReader::new(&binary_data);
+    /// assert_eq!(reader.read_utf8(13)?,"Hello, world!");
+    /// assert!(reader.read_utf8(10).is_err());
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn read_utf8(&mut self, bytes: usize) -> Result<&'a str, BinaryFileReaderError> {
+        self.traced("read_utf8", |r| {
+            if bytes > r.available_bytes() {
+                return Err(BinaryFileReaderError::BufferUnderflow {
+                    requested_bytes: bytes,
+                    current_offset: r.current_offset,
+                    available_bytes: r.available_bytes(),
+                });
+            }
+
+            let slice = &r.remaining()[..bytes];
+            let result = core::str::from_utf8(slice)?;
+            r.advance(bytes)?;
+            Ok(result)
+        })
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = b"IHDRIDAT".to_vec();
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// assert_eq!(reader.read_fourcc()?, "IHDR");
+    /// assert_eq!(reader.read_fourcc()?, b"IDAT");
+    /// assert!(reader.read_fourcc().is_err());
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn read_fourcc(&mut self) -> Result<FourCC, BinaryFileReaderError> {
+        self.traced("read_fourcc", |r| {
+            let mut buffer = [0; 4];
+            r.read(&mut buffer)?;
+            Ok(FourCC(buffer))
+        })
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0xab,0xcd];
+    /// let reader = BinaryFileReader::new(&buffer);
+    /// assert_eq!(reader.peek_u4()?,(0x0a,0x0b));
+    /// assert_eq!(reader.peek_u4()?,(0x0a,0x0b));
+    /// assert_eq!(reader.peek_u4()?,(0x0a,0x0b));
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn peek_u4(&self) -> Result<(u8, u8), BinaryFileReaderError> {
+        let mut buffer = [0; 1];
+        self.peek(&mut buffer)?;
+        let upper = buffer[0] >> 4;
+        let lower = buffer[0] & 0x0f;
+        Ok((upper, lower))
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0x01,0x02,0x03];
+    /// let reader = BinaryFileReader::new(&buffer);
+    /// assert_eq!(reader.peek_u8()?,0x01);
+    /// assert_eq!(reader.peek_u8()?,0x01);
+    /// assert_eq!(reader.peek_u8()?,0x01);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn peek_u8(&self) -> Result<u8, BinaryFileReaderError> {
+        let mut buffer = [0; 1];
+        self.peek(&mut buffer)?;
+        Ok(u8::from_be_bytes(buffer))
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0x01,0x02,0x03];
+    /// let reader = BinaryFileReader::new(&buffer);
+    /// assert_eq!(reader.peek_u16()?,0x0102);
+    /// assert_eq!(reader.peek_u16()?,0x0102);
+    /// assert_eq!(reader.peek_u16()?,0x0102);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn peek_u16(&self) -> Result<u16, BinaryFileReaderError> {
+        let mut buffer = [0; 2];
+        self.peek(&mut buffer)?;
+        Ok(self.endian.u16_from_bytes(buffer))
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0x01,0x02,0x03,0x04,0x05];
+    /// let reader = BinaryFileReader::new(&buffer);
+    /// assert_eq!(reader.peek_u32()?,0x01020304);
+    /// assert_eq!(reader.peek_u32()?,0x01020304);
+    /// assert_eq!(reader.peek_u32()?,0x01020304);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn peek_u32(&self) -> Result<u32, BinaryFileReaderError> {
+        let mut buffer = [0; 4];
+        self.peek(&mut buffer)?;
+        Ok(self.endian.u32_from_bytes(buffer))
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![1,2,3,4,5,6,7,8,9];
+    /// let reader = BinaryFileReader::new(&buffer);
+    /// assert_eq!(reader.peek_u64()?,0x0102030405060708);
+    /// assert_eq!(reader.peek_u64()?,0x0102030405060708);
+    /// assert_eq!(reader.peek_u64()?,0x0102030405060708);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn peek_u64(&self) -> Result<u64, BinaryFileReaderError> {
+        let mut buffer = [0; 8];
+        self.peek(&mut buffer)?;
+        Ok(self.endian.u64_from_bytes(buffer))
+    }
+
+    pub fn peek_u128(&self) -> Result<u128, BinaryFileReaderError> {
+        let mut buffer = [0; 16];
+        self.peek(&mut buffer)?;
+        Ok(self.endian.u128_from_bytes(buffer))
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0,1,2,3,4,5,6,7,8,9];
+    /// let reader = BinaryFileReader::new(&buffer);
+    ///
+    /// let mut buf = vec![0;5];
+    /// reader.peek_bytes(&mut buf);
+    /// assert_eq!(buf,vec![0,1,2,3,4]);
+    ///
+    /// let mut buf = vec![0;5];
+    /// reader.peek_bytes(&mut buf);
+    /// assert_eq!(buf,vec![0,1,2,3,4]);
+    ///
+    /// let mut buf = vec![0; 11];
+    /// assert!(reader.peek_bytes(&mut buf).is_err());
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn peek_bytes(&self, buffer: &mut [u8]) -> Result<(), BinaryFileReaderError> {
+        self.peek(buffer)?;
+        Ok(())
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0,1,2,3,4,5,6,7];
+    /// let reader = BinaryFileReader::new(&buffer);
+    /// assert_eq!(reader.peek_slice(3)?,&[0,1,2]);
+    /// assert_eq!(reader.peek_slice(3)?,&[0,1,2]);
+    /// assert_eq!(reader.peek_slice(3)?,&[0,1,2]);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn peek_slice(&self, length: usize) -> Result<&'a [u8], BinaryFileReaderError> {
+        self.get_slice(length)
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let text = "Hello, world!";
+    /// let binary_data: Vec<u8> = text.as_bytes().to_vec();
+    /// let mut reader = BinaryFileReader::new(&binary_data);
+    /// assert_eq!(reader.peek_utf8(13)?,"Hello, world!");
+    /// assert_eq!(reader.peek_utf8(13)?,"Hello, world!");
+    /// assert!(reader.peek_utf8(14).is_err());
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn peek_utf8(&self, bytes: usize) -> Result<&'a str, BinaryFileReaderError> {
+        if bytes > self.available_bytes() {
+            return Err(BinaryFileReaderError::BufferUnderflow {
+                requested_bytes: bytes,
+                current_offset: self.current_offset,
+                available_bytes: self.available_bytes(),
+            });
+        }
+
+        let slice = &self.remaining()[..bytes];
+        let result = core::str::from_utf8(slice)?;
+        Ok(result)
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+    ///
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// reader.expect(&[0, 1, 2, 3])?;
+    /// reader.expect(&[4, 5, 6, 7])?;
+    /// assert!(reader.expect(&[0, 0, 0]).is_err());
+    /// assert!(reader.expect(&[8, 9, 0, 0, 0]).is_err());
+    /// reader.expect(&[8, 9])?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn expect(&mut self, expect_bytes: &[u8]) -> Result<(), BinaryFileReaderError> {
+        self.traced("expect", |r| {
+            r.expect_peek(expect_bytes)?;
+            r.advance(expect_bytes.len())?;
+            Ok(())
+        })
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let text = "Hello, world!";
+    /// let binary_data: Vec<u8> = text.as_bytes().to_vec();
+    /// let mut reader = BinaryFileReader::new(&binary_data);
+    ///
+    /// reader.expect_utf8("Hello")?;
+    /// assert!(reader.expect_utf8("Hello").is_err());
+    /// reader.expect_utf8(", world!")?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn expect_utf8(&mut self, expect_str: &str) -> Result<(), BinaryFileReaderError> {
+        self.expect(expect_str.as_bytes())?;
+        Ok(())
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = b"RIFF\x00\x00\x00\x00WAVE".to_vec();
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    ///
+    /// reader.expect_fourcc("RIFF")?;
+    /// reader.read_u32()?;
+    /// assert!(reader.expect_fourcc("AVI ").is_err());
+    /// reader.expect_fourcc("WAVE")?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `fourcc` is not exactly 4 bytes long.
+    pub fn expect_fourcc(&mut self, fourcc: &str) -> Result<(), BinaryFileReaderError> {
+        assert_eq!(fourcc.len(), 4, "fourcc must be exactly 4 bytes long");
+        self.expect(fourcc.as_bytes())
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    ///
+    /// let buffer = vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+    /// let reader = BinaryFileReader::new(&buffer);
+    /// reader.expect_peek(&[0, 1, 2, 3])?;
+    /// reader.expect_peek(&[0, 1, 2, 3])?;
+    ///
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn expect_peek(&self, expect_bytes: &[u8]) -> Result<(), BinaryFileReaderError> {
+        if self.available_bytes() < expect_bytes.len() {
+            let require = self.error_bytes(expect_bytes);
+            return Err(BinaryFileReaderError::ExpectInsufficientBytes {
+                require,
+                available_bytes: self.available_bytes(),
+                current_offset: self.current_offset(),
+            });
+        }
+
+        let slice = &self.remaining()[..expect_bytes.len()];
+
+        for (req, got) in expect_bytes.iter().zip(slice) {
+            if *req == *got {
+                continue;
+            }
+
+            let require = self.error_bytes(expect_bytes);
+            let got = self.error_bytes(slice);
+            return Err(BinaryFileReaderError::Expect {
+                require,
+                got,
+                current_offset: self.current_offset(),
+                available_bytes: self.available_bytes(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = b"RIFF\x24\x08\x00\x00WAVEfmt ".to_vec();
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    ///
+    /// let pattern = b"RIFF\0\0\0\0WAVE";
+    /// let mask = [0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0, 0xff, 0xff, 0xff, 0xff];
+    /// reader.expect_masked(pattern, &mask)?;
+    /// assert_eq!(reader.current_offset(), 12);
+    /// assert!(reader.expect_masked(b"fmt!", &[0xff, 0xff, 0xff, 0xff]).is_err());
+    /// reader.expect_masked(b"fmt!", &[0xff, 0xff, 0xff, 0x00])?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `pattern` and `mask` have different lengths.
+    pub fn expect_masked(
+        &mut self,
+        pattern: &[u8],
+        mask: &[u8],
+    ) -> Result<(), BinaryFileReaderError> {
+        self.expect_masked_peek(pattern, mask)?;
+        self.advance(pattern.len())?;
+        Ok(())
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0x4d, 0x5a, 0x90, 0x00];
+    /// let reader = BinaryFileReader::new(&buffer);
+    ///
+    /// reader.expect_masked_peek(&[0x4d, 0x5a, 0x9f], &[0xff, 0xff, 0xf0])?;
+    /// reader.expect_masked_peek(&[0x4d, 0x5a, 0x9f], &[0xff, 0xff, 0xf0])?;
+    /// assert!(reader.expect_masked_peek(&[0x4d, 0x5a, 0x9f], &[0xff, 0xff, 0xff]).is_err());
+    /// assert_eq!(reader.current_offset(), 0);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `pattern` and `mask` have different lengths.
+    pub fn expect_masked_peek(
+        &self,
+        pattern: &[u8],
+        mask: &[u8],
+    ) -> Result<(), BinaryFileReaderError> {
+        assert_eq!(
+            pattern.len(),
+            mask.len(),
+            "pattern and mask must have the same length"
+        );
+
+        if self.available_bytes() < pattern.len() {
+            let require = self.error_bytes(pattern);
+            return Err(BinaryFileReaderError::ExpectInsufficientBytes {
+                require,
+                available_bytes: self.available_bytes(),
+                current_offset: self.current_offset(),
+            });
+        }
+
+        let slice = &self.remaining()[..pattern.len()];
+
+        for ((req, bits), got) in pattern.iter().zip(mask).zip(slice) {
+            if *req & *bits == *got & *bits {
+                continue;
+            }
+
+            return Err(BinaryFileReaderError::ExpectMasked {
+                require: self.error_bytes(pattern),
+                mask: self.error_bytes(mask),
+                got: self.error_bytes(slice),
+                current_offset: self.current_offset(),
+                available_bytes: self.available_bytes(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = b"GIF89a\x01\x00".to_vec();
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    ///
+    /// assert_eq!(reader.expect_one_of(&[b"GIF87a", b"GIF89a"])?, 1);
+    /// assert_eq!(reader.current_offset(), 6);
+    /// assert!(reader.expect_one_of(&[b"GIF87a", b"GIF89a"]).is_err());
+    /// assert_eq!(reader.current_offset(), 6);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn expect_one_of(&mut self, candidates: &[&[u8]]) -> Result<usize, BinaryFileReaderError> {
+        for (index, candidate) in candidates.iter().enumerate() {
+            if self.try_expect(candidate) {
+                return Ok(index);
+            }
+        }
+
+        let longest = candidates.iter().map(|c| c.len()).max().unwrap_or(0);
+        let got_length = longest.min(self.available_bytes());
+        let got = self.error_bytes(&self.remaining()[..got_length]);
+
+        Err(BinaryFileReaderError::ExpectOneOf {
+            candidates: candidates.iter().map(|c| self.error_bytes(c)).collect(),
+            got,
+            available_bytes: self.available_bytes(),
+            current_offset: self.current_offset(),
+        })
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0, 0, 0, 0, 1, 0];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// reader.expect_zeroes(3)?;
+    /// assert!(reader.expect_zeroes(2).is_err());
+    /// assert_eq!(reader.current_offset(), 3);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn expect_zeroes(&mut self, length: usize) -> Result<(), BinaryFileReaderError> {
+        self.expect_filled(length, 0)
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # use binary_file_reader::error::BinaryFileReaderError;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0xff, 0xff, 0xff, 0x00];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// assert!(matches!(
+    ///     reader.expect_filled(4, 0xff),
+    ///     Err(BinaryFileReaderError::ExpectFilled { offending_offset: 3, .. })
+    /// ));
+    /// reader.expect_filled(3, 0xff)?;
+    /// assert_eq!(reader.current_offset(), 3);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn expect_filled(&mut self, length: usize, value: u8) -> Result<(), BinaryFileReaderError> {
+        let slice = self.get_slice(length)?;
+
+        if let Some(position) = slice.iter().position(|b| *b != value) {
+            return Err(BinaryFileReaderError::ExpectFilled {
+                require: value,
+                got: slice[position],
+                offending_offset: self.current_offset + position,
+                current_offset: self.current_offset,
+            });
+        }
+
+        self.advance(length)?;
+        Ok(())
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = b"GIF89a".to_vec();
+    /// let reader = BinaryFileReader::new(&buffer);
+    /// assert!(reader.matches(b"GIF"));
+    /// assert!(reader.matches(b"GIF89a"));
+    /// assert!(!reader.matches(b"GIF87a"));
+    /// assert!(!reader.matches(b"GIF89a!"));
+    /// assert_eq!(reader.current_offset(), 0);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn matches(&self, expect_bytes: &[u8]) -> bool {
+        self.remaining().starts_with(expect_bytes)
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0x1f, 0x8b, 0x08];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    ///
+    /// assert!(!reader.try_expect(&[0x78, 0x9c]));
+    /// assert_eq!(reader.current_offset(), 0);
+    /// assert!(reader.try_expect(&[0x1f, 0x8b]));
+    /// assert_eq!(reader.current_offset(), 2);
+    /// assert_eq!(reader.read_u8()?, 0x08);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn try_expect(&mut self, expect_bytes: &[u8]) -> bool {
+        if !self.matches(expect_bytes) {
+            return false;
+        }
+
+        self.advance(expect_bytes.len()).is_ok()
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    ///
+    /// let buffer = vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// let mut splited = reader.split_off_front(5)?;
+    ///
+    /// splited.expect_peek(&[0,1,2,3,4])?;
+    /// assert_eq!(splited.current_offset(),0);
+    ///
+    /// reader.expect_peek(&[5,6,7,8,9])?;
+    /// assert_eq!(reader.current_offset(),5);
+    ///
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn split_off_front(&mut self, size: usize) -> Result<Self, BinaryFileReaderError> {
+        self.traced("split_off_front", |r| {
+            if size > r.available_bytes() {
+                return Err(BinaryFileReaderError::BufferUnderflow {
+                    requested_bytes: size,
+                    current_offset: r.current_offset,
+                    available_bytes: r.available_bytes(),
+                });
+            }
+
+            let splited_offset = r.current_offset;
+            let new_offset = r.end_offset(size)?;
+
+            r.current_offset = new_offset;
+
+            Ok(Self {
+                current_offset: splited_offset,
+                own_start: splited_offset,
+                own_left: new_offset,
+                ..r.clone()
+            })
+        })
+    }
+
+    /// Splits off `size` bytes like [`split_off_front`](Self::split_off_front)
+    /// and runs `f` on them. Any error, including a short section, is wrapped
+    /// in [`BinaryFileReaderError::Section`] with `label` and the section's
+    /// absolute start, so nested sections say where in the file they failed.
+    /// The reader moves past the section even if `f` fails.
+    ///
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # use binary_file_reader::error::BinaryFileReaderError;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0xff, 0xff, 0x00, 0x03, 0x00, 0x01, 0x00];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// reader.read_u16()?;
+    ///
+    /// let err = reader
+    ///     .split_off_front_labeled(5, "directory", |r| {
+    ///         let count = r.read_u16()?;
+    ///         r.split_off_front_labeled(3, "entries", |r| r.repeat(|r| r.read_u16()))?;
+    ///         Ok(count)
+    ///     })
+    ///     .unwrap_err();
+    ///
+    /// assert_eq!(err.contexts(), ["directory", "entries"]);
+    /// assert_eq!(err.offset(), Some(6));
+    /// assert!(err
+    ///     .to_string()
+    ///     .starts_with("directory at 0x2 (2), 5 bytes: entries at 0x4 (4), 3 bytes: "));
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn split_off_front_labeled<T, F>(
+        &mut self,
+        size: usize,
+        label: impl Into<Cow<'static, str>>,
+        f: F,
+    ) -> Result<T, BinaryFileReaderError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, BinaryFileReaderError>,
+    {
+        let start = self.current_offset;
+        self.split_off_front(size)
+            .and_then(|mut section| f(&mut section))
+            .map_err(|source| BinaryFileReaderError::Section {
+                label: label.into(),
+                start,
+                length: size,
+                source: Box::new(source),
+            })
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0x01, 0x00, 0x2a];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    ///
+    /// let has_offset = reader.read_bool()?;
+    /// let offset = reader.read_if(has_offset, |r| r.read_u16())?;
+    /// assert_eq!(offset, Some(0x2a));
+    /// assert_eq!(reader.read_if(false, |r| r.read_u8())?, None);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn read_if<T, F>(&mut self, cond: bool, f: F) -> Result<Option<T>, BinaryFileReaderError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, BinaryFileReaderError>,
+    {
+        if cond {
+            f(self).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Runs `f` only if bit `bit` (counting from the least significant bit) is
+    /// set in `flags`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0b0000_0100, 0x12, 0x34];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    ///
+    /// let flags = reader.read_u8()?;
+    /// let width = reader.read_if_flag(flags, 0, |r| r.read_u8())?;
+    /// let height = reader.read_if_flag(flags, 2, |r| r.read_u16())?;
+    /// assert_eq!(width, None);
+    /// assert_eq!(height, Some(0x1234));
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `bit` is 64 or greater.
+    pub fn read_if_flag<T, F>(
+        &mut self,
+        flags: impl Into<u64>,
+        bit: u32,
+        f: F,
+    ) -> Result<Option<T>, BinaryFileReaderError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, BinaryFileReaderError>,
+    {
+        assert!(bit < u64::BITS, "bit index must be less than 64");
+        self.read_if(flags.into() & (1 << bit) != 0, f)
+    }
+
+    /// Reads a version field of type `V` and passes it to `f`. If `f` returns
+    /// `Ok(None)` the version is unsupported: the reader is left before the
+    /// version field and [`BinaryFileReaderError::UnsupportedVersion`] is
+    /// returned.
+    ///
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # use binary_file_reader::error::BinaryFileReaderError;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0x02, 0x00, 0x10, 0x00, 0x20, 0x03];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    ///
+    /// let parse = |reader: &mut BinaryFileReader| {
+    ///     reader.read_versioned::<u8, _, _>(|version, r| match version {
+    ///         1 => Ok(Some((r.read_u16()?, 0))),
+    ///         2 => Ok(Some((r.read_u16()?, r.read_u16()?))),
+    ///         _ => Ok(None),
+    ///     })
+    /// };
+    /// assert_eq!(parse(&mut reader)?, (0x10, 0x20));
+    /// assert!(matches!(
+    ///     parse(&mut reader),
+    ///     Err(BinaryFileReaderError::UnsupportedVersion {
+    ///         version: 3,
+    ///         current_offset: 5
+    ///     })
+    /// ));
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn read_versioned<V, T, F>(&mut self, f: F) -> Result<T, BinaryFileReaderError>
+    where
+        V: ReadFrom + Copy + Into<u64>,
+        F: FnOnce(V, &mut Self) -> Result<Option<T>, BinaryFileReaderError>,
+    {
+        let start = self.current_offset;
+        let version = V::read_from(self)?;
+        match f(version, self)? {
+            Some(value) => Ok(value),
+            None => {
+                self.current_offset = start;
+                Err(BinaryFileReaderError::UnsupportedVersion {
+                    version: version.into(),
+                    current_offset: start,
+                })
+            }
+        }
+    }
+
+    /// Runs `f` and restores the reader to its previous state if it fails,
+    /// returning the error unchanged.
+    ///
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = b"GIF89a".to_vec();
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    ///
+    /// let format = reader
+    ///     .try_parse(|r| r.expect(b"\x89PNG").map(|_| "png"))
+    ///     .or_else(|_| reader.try_parse(|r| r.expect(b"GIF8").map(|_| "gif")))?;
+    /// assert_eq!(format, "gif");
+    /// assert_eq!(reader.current_offset(), 4);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn try_parse<T, E, F>(&mut self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&mut Self) -> Result<T, E>,
+    {
+        let saved = self.clone();
+        let result = f(self);
+        if result.is_err() {
+            *self = saved;
+        }
+        result
+    }
+
+    /// Reads a length of type `L`, runs `f` on a sub-reader bounded to that
+    /// many bytes and checks that `f` consumed all of them. On failure the
+    /// reader is left before the length prefix.
+    ///
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0, 3, 0x01, 0x00, 0x02, 0xff];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    ///
+    /// let value = reader.read_length_prefixed::<u16, _, _>(|sub| {
+    ///     Ok((sub.read_u8()?, sub.read_u16()?))
+    /// })?;
+    /// assert_eq!(value, (1, 2));
+    /// assert_eq!(reader.current_offset(), 5);
+    ///
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// assert!(reader.read_length_prefixed::<u16, _, _>(|sub| sub.read_u8()).is_err());
+    /// assert_eq!(reader.current_offset(), 0);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn read_length_prefixed<L, T, F>(&mut self, f: F) -> Result<T, BinaryFileReaderError>
+    where
+        L: ReadFrom + TryInto<usize>,
+        F: FnOnce(&mut Self) -> Result<T, BinaryFileReaderError>,
+    {
+        let start = self.current_offset;
+        let result = (|| {
+            let length = L::read_from(self)?.try_into().unwrap_or(usize::MAX);
+            let mut sub = self.split_off_front(length)?;
+            let value = f(&mut sub)?;
+            sub.finish()?;
+            Ok(value)
+        })();
+
+        if result.is_err() {
+            self.current_offset = start;
+        }
+        result
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0, 0, 0, 1, 0xff, 0xff];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// let mut chunk = reader.split_off_front(4)?;
+    ///
+    /// chunk.read_u16()?;
+    /// assert!(chunk.finish().is_err());
+    /// chunk.read_u16()?;
+    /// chunk.finish()?;
+    ///
+    /// assert!(reader.finish().is_err());
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn finish(&self) -> Result<(), BinaryFileReaderError> {
+        if self.available_bytes() != 0 {
+            return Err(BinaryFileReaderError::TrailingBytes {
+                remaining_bytes: self.available_bytes(),
+                current_offset: self.current_offset,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0x00, 0x50, 0x4B, 0x03, 0x50, 0x4B];
+    /// let reader = BinaryFileReader::new(&buffer);
+    ///
+    /// let found = reader
+    ///     .windows(2)
+    ///     .filter(|(_, window)| *window == b"PK")
+    ///     .map(|(offset, _)| offset)
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(found, vec![1, 4]);
+    /// assert_eq!(reader.current_offset(), 0);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `size` is 0.
+    pub fn windows(&self, size: usize) -> Windows<'a> {
+        Windows::new(self.clone(), size)
+    }
+
+    /// Iterates over the remaining bytes without moving the reader.
+    ///
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = b"name\0value";
+    /// let mut reader = BinaryFileReader::new(buffer);
+    ///
+    /// let length = reader.iter().position(|b| b == 0).unwrap_or(reader.available_bytes());
+    /// assert_eq!(reader.read_utf8(length)?, "name");
+    /// assert_eq!(reader.iter().skip(1).take_while(|b| *b != b'u').count(), 3);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn iter(&self) -> core::iter::Copied<core::slice::Iter<'a, u8>> {
+        self.remaining().iter().copied()
+    }
+
+    /// Returns the bytes at `range`, relative to the current offset, without
+    /// moving the reader. Fails with [`BinaryFileReaderError::OutOfRange`] if
+    /// the range is reversed or runs past the remaining bytes; indexing the
+    /// reader with a range panics instead.
+    ///
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # use binary_file_reader::error::BinaryFileReaderError;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = b"\x89PNG\r\n\x1a\n";
+    /// let mut reader = BinaryFileReader::new(buffer);
+    /// reader.read_u8()?;
+    ///
+    /// assert_eq!(reader.get(0..3)?, b"PNG");
+    /// assert_eq!(&reader[3..5], b"\r\n");
+    /// assert_eq!(reader.as_ref().len(), 7);
+    /// assert!(matches!(
+    ///     reader.get(4..8),
+    ///     Err(BinaryFileReaderError::OutOfRange {
+    ///         buffer_size: 7,
+    ///         got: 8
+    ///     })
+    /// ));
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn get(&self, range: Range<usize>) -> Result<&'a [u8], BinaryFileReaderError> {
+        let remaining = self.remaining();
+        if range.start > range.end {
+            return Err(BinaryFileReaderError::OutOfRange {
+                buffer_size: remaining.len(),
+                got: range.start,
+            });
+        }
+
+        remaining
+            .get(range.clone())
+            .ok_or(BinaryFileReaderError::OutOfRange {
+                buffer_size: remaining.len(),
+                got: range.end,
+            })
+    }
+
+    /// Formats the bytes at absolute offsets `offset..offset + length` with
+    /// [`hexdump::hexdump`]. The range is clamped to this reader's region, so
+    /// it can be used with offsets from an error without checking them.
+    ///
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// let buffer = b"\x00\x00\x00\x0dIHDR\x00\x00\x01\x00".to_vec();
+    /// let reader = BinaryFileReader::new(&buffer);
+    /// assert_eq!(
+    ///     reader.hexdump(4, 100),
+    ///     "00000004: 4948 4452 0000 0100                      IHDR....\n"
+    /// );
+    /// assert_eq!(reader.hexdump(100, 4), "");
+    /// ```
+    pub fn hexdump(&self, offset: usize, length: usize) -> String {
+        let start = offset.clamp(self.own_start, self.own_left);
+        let end = offset.saturating_add(length).clamp(start, self.own_left);
+        hexdump::hexdump(&self.buf[start..end], start)
+    }
+
+    /// Formats up to `context` bytes on each side of the current offset with
+    /// [`hexdump`](Self::hexdump), for printing next to an error.
+    ///
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = (0..64).collect::<Vec<u8>>();
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// reader.set_offset(40)?;
+    ///
+    /// let dump = reader.hexdump_around_cursor(8);
+    /// assert!(dump.starts_with("00000020: 2021 2223 2425 2627 2829 2a2b 2c2d 2e2f"));
+    /// assert_eq!(dump.lines().count(), 1);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn hexdump_around_cursor(&self, context: usize) -> String {
+        let start = self.current_offset.saturating_sub(context);
+        self.hexdump(start, (self.current_offset - start).saturating_add(context))
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = b"key=value\r\nname=binary\r\n".to_vec();
+    /// let reader = BinaryFileReader::new(&buffer);
+    ///
+    /// let mut lines = reader.split(b"\r\n");
+    ///
+    /// let mut line = lines.next().unwrap();
+    /// assert_eq!(line.current_offset(), 0);
+    /// assert_eq!(line.read_utf8(line.available_bytes())?, "key=value");
+    ///
+    /// let mut line = lines.next().unwrap();
+    /// assert_eq!(line.current_offset(), 11);
+    /// assert_eq!(line.read_utf8(line.available_bytes())?, "name=binary");
+    ///
+    /// assert_eq!(lines.next().unwrap().available_bytes(), 0);
+    /// assert!(lines.next().is_none());
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `delimiter` is empty.
+    pub fn split<'b>(&self, delimiter: &'b [u8]) -> Split<'a, 'b> {
+        Split::new(self.clone(), delimiter)
+    }
+
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let buffer = vec![0, 0, 0, 0, 0x12, 0x34];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// assert_eq!(reader.skip_while(|b| b == 0), 4);
+    /// assert_eq!(reader.skip_while(|b| b == 0), 0);
+    /// assert_eq!(reader.read_u16()?, 0x1234);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn skip_while<F>(&mut self, mut predicate: F) -> usize
+    where
+        F: FnMut(u8) -> bool,
+    {
+        let skipped = self
+            .remaining()
+            .iter()
+            .take_while(|b| predicate(**b))
+            .count();
+        // The skipped bytes are all remaining, so advancing cannot fail.
+        let _ = self.advance(skipped);
+        skipped
+    }
+}
+
+/// Locks a recorder even if a thread panicked while holding it, since the
+/// recorded ranges stay consistent.
+#[cfg(feature = "std")]
+fn lock<T>(recorder: &std::sync::Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    recorder
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+impl<'a> IntoIterator for BinaryFileReader<'a> {
+    type Item = u8;
+    type IntoIter = core::iter::Copied<core::slice::Iter<'a, u8>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &BinaryFileReader<'a> {
+    type Item = u8;
+    type IntoIter = core::iter::Copied<core::slice::Iter<'a, u8>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Shows the position and a preview of the next bytes instead of the whole
+/// buffer.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::BinaryFileReader;
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// let buffer = vec![0xab; 1 << 20];
+/// let mut reader = BinaryFileReader::new(&buffer).with_source_name("firmware.bin");
+/// reader.read_u16()?;
+///
+/// let debug = format!("{:?}", reader);
+/// assert!(debug.starts_with("BinaryFileReader { source_name: \"firmware.bin\", region: 0..1048576, current_offset: 2,"));
+/// assert!(debug.contains("upcoming: AB AB AB AB AB AB AB AB AB AB AB AB AB AB AB AB .. (1048574 bytes)"));
+/// assert_eq!(
+///     reader.to_string(),
+///     "firmware.bin: 1048574 bytes left at offset 0x2 (2)"
+/// );
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+impl fmt::Debug for BinaryFileReader<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Readers detached from their buffer by the owning readers keep only
+        // the cursor.
+        let upcoming = self
+            .buf
+            .get(self.current_offset..self.own_left)
+            .unwrap_or_default();
+
+        let mut debug = f.debug_struct("BinaryFileReader");
+        if let Some(name) = &self.source_name {
+            debug.field("source_name", name);
+        }
+        debug
+            .field("region", &(self.own_start..self.own_left))
+            .field("current_offset", &self.current_offset)
+            .field("available_bytes", &self.available_bytes())
+            .field("endian", &self.endian)
+            .field("upcoming", &format_args!("{}", ErrorBytes::new(upcoming)))
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for BinaryFileReader<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(name) = &self.source_name {
+            write!(f, "{}: ", name)?;
+        }
+        write!(
+            f,
+            "{} bytes left at offset {}",
+            self.available_bytes(),
+            error::Offset(self.current_offset)
+        )
+    }
+}
+
+/// Two readers are equal if their remaining bytes are equal and they cover
+/// the same region at the same offset, whether or not they share a buffer.
+/// Settings such as the endianness are not compared.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::BinaryFileReader;
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// let first = vec![0x00, 0x01, 0x02];
+/// let second = first.clone();
+/// let mut a = BinaryFileReader::new(&first);
+/// let mut b = BinaryFileReader::new(&second);
+/// assert_eq!(a, b);
+///
+/// a.read_u8()?;
+/// assert_ne!(a, b);
+/// b.read_u8()?;
+/// assert_eq!(a, b);
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+impl PartialEq for BinaryFileReader<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.own_start == other.own_start
+            && self.current_offset == other.current_offset
+            && self.own_left == other.own_left
+            && self.remaining() == other.remaining()
+    }
+}
+
+impl Eq for BinaryFileReader<'_> {}
+
+/// Hashes the remaining bytes, which is consistent with [`PartialEq`].
+impl Hash for BinaryFileReader<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.remaining().hash(state);
+    }
+}
+
+/// The remaining bytes, as returned by [`BinaryFileReader::iter`].
+impl AsRef<[u8]> for BinaryFileReader<'_> {
+    fn as_ref(&self) -> &[u8] {
+        self.remaining()
+    }
+}
+
+/// Indexes the remaining bytes. See [`BinaryFileReader::get`] for a checked
+/// version.
+impl Index<Range<usize>> for BinaryFileReader<'_> {
+    type Output = [u8];
+
+    fn index(&self, index: Range<usize>) -> &Self::Output {
+        &self.remaining()[index]
+    }
+}
+
+/// Starts at the cursor's position, clamped to the end of the buffer.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::BinaryFileReader;
+/// # use std::io::Cursor;
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// let buffer = [0x00, 0x01, 0x02];
+/// let mut cursor = Cursor::new(&buffer[..]);
+/// cursor.set_position(1);
+///
+/// let mut reader = BinaryFileReader::from(cursor);
+/// assert_eq!(reader.read_u16()?, 0x0102);
+///
+/// let cursor = Cursor::from(reader);
+/// assert_eq!(cursor.position(), 3);
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+#[cfg(feature = "std")]
+impl<'a> From<std::io::Cursor<&'a [u8]>> for BinaryFileReader<'a> {
+    fn from(value: std::io::Cursor<&'a [u8]>) -> Self {
+        let position = usize::try_from(value.position()).unwrap_or(usize::MAX);
+        let buffer = value.into_inner();
+        Self {
+            current_offset: position.min(buffer.len()),
+            ..Self::new(buffer)
+        }
+    }
+}
+
+/// Keeps the position. The cursor spans the buffer up to the end of the
+/// reader, so a sub-reader's cursor also sees the bytes before its region.
+#[cfg(feature = "std")]
+impl<'a> From<BinaryFileReader<'a>> for std::io::Cursor<&'a [u8]> {
+    fn from(value: BinaryFileReader<'a>) -> Self {
+        let mut cursor = std::io::Cursor::new(&value.buf[..value.own_left]);
+        cursor.set_position(value.position());
+        cursor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{
+        borrow::Cow,
+        boxed::Box,
+        format,
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+
+    use crate::{
+        cancel::CancelToken,
+        endian::Endian,
+        error::{BinaryFileReaderError, ErrorBytes, ErrorCode, ErrorKind},
+        source::DataSource,
+    };
+
+    use super::BinaryFileReader;
+
+    #[test]
+    fn test_read() -> Result<(), BinaryFileReaderError> {
+        let mut reader = BinaryFileReader::new(&[0, 1, 2, 3, 4, 5]);
+        assert_eq!(reader.read_u8()?, 0);
+        assert_eq!(reader.read_u8()?, 1);
         assert_eq!(reader.read_u8()?, 2);
         assert_eq!(reader.read_u16()?, 0x0304);
         assert_eq!(reader.read_u8()?, 5);
 
-        let mut reader = BinaryFileReader::new(&[15, 1, 2, 3, 4, 5]);
-        assert_eq!(reader.read_u4()?, (0x00, 0x0f));
-        assert_eq!(reader.read_u32()?, 0x01020304);
+        let mut reader = BinaryFileReader::new(&[15, 1, 2, 3, 4, 5]);
+        assert_eq!(reader.read_u4()?, (0x00, 0x0f));
+        assert_eq!(reader.read_u32()?, 0x01020304);
+
+        let mut reader = BinaryFileReader::new(&[
+            0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3,
+        ]);
+        assert_eq!(reader.read_u64()?, 0x0001020304050607);
+        assert_eq!(reader.read_u128()?, 0x08090001020304050607080900010203);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_peek() -> Result<(), BinaryFileReaderError> {
+        let reader = BinaryFileReader::new(&[
+            0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3,
+        ]);
+        assert_eq!(reader.peek_u8()?, 0);
+        assert_eq!(reader.peek_u8()?, 0);
+        assert_eq!(reader.peek_u4()?, (0, 0));
+        assert_eq!(reader.peek_u4()?, (0, 0));
+        assert_eq!(reader.peek_u16()?, 1);
+        assert_eq!(reader.peek_u16()?, 1);
+        assert_eq!(reader.peek_u32()?, 0x00010203);
+        assert_eq!(reader.peek_u32()?, 0x00010203);
+        assert_eq!(reader.peek_u64()?, 0x0001020304050607);
+        assert_eq!(reader.peek_u64()?, 0x0001020304050607);
+        assert_eq!(reader.peek_u128()?, 0x00010203040506070809000102030405);
+        assert_eq!(reader.peek_u128()?, 0x00010203040506070809000102030405);
+
+        let reader = BinaryFileReader::new(&[]);
+        assert!(reader.peek_u4().is_err());
+        assert!(reader.peek_u8().is_err());
+        assert!(reader.peek_u16().is_err());
+        assert!(reader.peek_u32().is_err());
+        assert!(reader.peek_u64().is_err());
+        assert!(reader.peek_u128().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_little_endian() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![
+            0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3,
+        ];
+        let mut reader = BinaryFileReader::new(&buffer);
+        reader.set_endian(Endian::Little);
+        assert_eq!(reader.peek_u16()?, 0x0100);
+        assert_eq!(reader.peek_u32()?, 0x03020100);
+        assert_eq!(reader.peek_u64()?, 0x0706050403020100);
+        assert_eq!(reader.peek_u128()?, 0x05040302010009080706050403020100);
+        assert_eq!(reader.read_u16()?, 0x0100);
+        assert_eq!(reader.read_u32()?, 0x05040302);
+
+        let mut sub = reader.split_off_front(16)?;
+        assert_eq!(sub.endian(), Endian::Little);
+        assert_eq!(sub.read_u64()?, 0x0302010009080706);
+        sub.set_endian(Endian::Big);
+        assert_eq!(sub.read_u64()?, 0x0405060708090001);
+        assert_eq!(reader.endian(), Endian::Little);
+        assert_eq!(reader.read_u16()?, 0x0302);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_slice_of() -> Result<(), BinaryFileReaderError> {
+        let words: Vec<u32> = vec![1, 2, 3, 0xfffffffe];
+        let native = words
+            .iter()
+            .flat_map(|w| w.to_ne_bytes())
+            .collect::<Vec<_>>();
+
+        let mut reader = BinaryFileReader::new(&native);
+        reader.set_endian(Endian::NATIVE);
+        let values = reader.read_slice_of::<u32>(4)?;
+        assert_eq!(&*values, &[1, 2, 3, 0xfffffffe]);
+        if native.as_ptr().align_offset(4) == 0 {
+            assert!(matches!(values, Cow::Borrowed(_)));
+        }
+
+        let mut reader = BinaryFileReader::new(&native[4..]);
+        reader.set_endian(Endian::NATIVE);
+        assert_eq!(&*reader.read_slice_of::<i32>(3)?, &[2, 3, -2]);
+        assert_eq!(&*reader.read_slice_of::<u8>(0)?, &[]);
+
+        let big = words
+            .iter()
+            .flat_map(|w| w.to_be_bytes())
+            .collect::<Vec<_>>();
+        let mut reader = BinaryFileReader::new(&big[1..]);
+        assert_eq!(&*reader.read_slice_of::<u8>(3)?, &[0, 0, 1]);
+        assert_eq!(&*reader.read_slice_of::<u16>(2)?, &[0, 2]);
+        assert!(matches!(
+            reader.read_slice_of::<u64>(usize::MAX),
+            Err(BinaryFileReaderError::BufferUnderflow {
+                requested_bytes: usize::MAX,
+                current_offset: 7,
+                available_bytes: 8
+            })
+        ));
+        assert_eq!(&*reader.read_slice_of::<u64>(1)?, &[0x00000003fffffffe]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_off_front() -> Result<(), BinaryFileReaderError> {
+        let buffer = (0..=255).collect::<Vec<u8>>();
+        let mut a = BinaryFileReader::new(&buffer);
+        let mut b = a.split_off_front(128)?;
+        assert_eq!(b.current_offset(), 0);
+        assert_eq!(b.read_u8()?, 0);
+        assert_eq!(a.current_offset(), 128);
+        assert_eq!(a.read_u8()?, 128);
+
+        let buffer = (0..=255).collect::<Vec<u8>>();
+        let mut a = BinaryFileReader::new(&buffer);
+        let mut b = a.split_off_front(128)?;
+        assert_eq!(b.current_offset(), 0);
+        assert_eq!(a.current_offset(), 128);
+        assert!(b.split_off_front(129).is_err());
+        assert!(a.split_off_front(129).is_err());
+        let mut c = a.split_off_front(64)?;
+        let mut d = b.split_off_front(64)?;
+        assert_eq!(a.current_offset(), 128 + 64);
+        assert_eq!(b.current_offset(), 64);
+        assert_eq!(c.current_offset(), 128);
+        assert_eq!(d.current_offset(), 0);
+        assert_eq!(a.available_bytes(), 64);
+        assert_eq!(b.available_bytes(), 64);
+        assert_eq!(c.available_bytes(), 64);
+        assert_eq!(d.available_bytes(), 64);
+        let mut c = c.split_off_front(64)?;
+        assert_eq!(c.available_bytes(), 64);
+
+        assert_eq!(a.read_u8()?, 128 + 64);
+        assert_eq!(b.read_u8()?, 64);
+        assert_eq!(c.read_u8()?, 128);
+        assert_eq!(d.read_u8()?, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expect() -> Result<(), BinaryFileReaderError> {
+        let buffer = (0..=255).collect::<Vec<u8>>();
+        let mut reader = BinaryFileReader::new(&buffer);
+        reader.expect(&[0, 1, 2, 3, 4])?;
+
+        let buffer = vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+        let mut reader = BinaryFileReader::new(&buffer);
+        reader.expect(&[0, 1, 2, 3])?;
+        assert_eq!(reader.current_offset(), 4);
+        assert_eq!(reader.available_bytes(), 6);
+        reader.expect(&[4, 5, 6, 7])?;
+        assert_eq!(reader.current_offset(), 8);
+        assert_eq!(reader.available_bytes(), 2);
+
+        let buffer = vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+        let reader = BinaryFileReader::new(&buffer);
+        reader.expect_peek(&[0, 1, 2, 3])?;
+        reader.expect_peek(&[0, 1, 2, 3])?;
+
+        let buffer = vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+        let mut reader = BinaryFileReader::new(&buffer);
+        let mut sub = reader.split_off_front(4)?;
+        assert!(sub.matches(&[]));
+        assert!(!sub.matches(&[0, 1, 2, 3, 4]));
+        assert!(sub.try_expect(&[0, 1]));
+        assert!(!sub.try_expect(&[0, 1]));
+        assert!(sub.try_expect(&[2, 3]));
+        assert_eq!(sub.available_bytes(), 0);
+        assert!(reader.try_expect(&[4, 5, 6, 7, 8, 9]));
+        assert_eq!(reader.available_bytes(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expect_masked() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0x12, 0x34, 0x56, 0x78];
+        let mut reader = BinaryFileReader::new(&buffer);
+        reader.expect_masked(&[0x12, 0x00], &[0xff, 0x00])?;
+        assert_eq!(reader.current_offset(), 2);
+        assert!(matches!(
+            reader.expect_masked(&[0x50, 0x00], &[0xf0, 0x0f]),
+            Err(BinaryFileReaderError::ExpectMasked {
+                current_offset: 2,
+                available_bytes: 2,
+                ..
+            })
+        ));
+        assert!(matches!(
+            reader.expect_masked(&[0, 0, 0], &[0, 0, 0]),
+            Err(BinaryFileReaderError::ExpectInsufficientBytes {
+                current_offset: 2,
+                available_bytes: 2,
+                ..
+            })
+        ));
+        reader.expect_masked(&[0x50, 0x08], &[0xf0, 0x0f])?;
+        assert_eq!(reader.available_bytes(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expect_one_of() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0x78, 0x9c, 0x1f, 0x8b];
+        let mut reader = BinaryFileReader::new(&buffer);
+        let magics: &[&[u8]] = &[&[0x1f, 0x8b], &[0x78, 0x01], &[0x78, 0x9c], &[0x78]];
+        assert_eq!(reader.expect_one_of(magics)?, 2);
+        assert_eq!(reader.expect_one_of(magics)?, 0);
+        assert_eq!(reader.available_bytes(), 0);
+
+        let mut reader = BinaryFileReader::new(&buffer);
+        assert!(matches!(
+            reader.expect_one_of(&[&[0x00, 0x00, 0x00, 0x00, 0x00], &[0x42]]),
+            Err(BinaryFileReaderError::ExpectOneOf {
+                ref got,
+                current_offset: 0,
+                available_bytes: 4,
+                ..
+            }) if got == &[0x78, 0x9c, 0x1f, 0x8b]
+        ));
+        assert!(reader.expect_one_of(&[]).is_err());
+        assert_eq!(reader.current_offset(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expect_filled() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0xaa, 0, 0, 0, 0xcc, 0xcc, 0xcd];
+        let mut reader = BinaryFileReader::new(&buffer);
+        reader.read_u8()?;
+        reader.expect_zeroes(0)?;
+        reader.expect_zeroes(3)?;
+        assert!(matches!(
+            reader.expect_filled(3, 0xcc),
+            Err(BinaryFileReaderError::ExpectFilled {
+                require: 0xcc,
+                got: 0xcd,
+                offending_offset: 6,
+                current_offset: 4,
+            })
+        ));
+        assert!(matches!(
+            reader.expect_filled(4, 0xcc),
+            Err(BinaryFileReaderError::BufferUnderflow {
+                requested_bytes: 4,
+                current_offset: 4,
+                available_bytes: 3
+            })
+        ));
+        reader.expect_filled(2, 0xcc)?;
+        assert_eq!(reader.read_u8()?, 0xcd);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_utf8() -> Result<(), BinaryFileReaderError> {
+        let text = "Hello, world!";
+        let binary_data: Vec<u8> = text.as_bytes().to_vec();
+        let mut reader = BinaryFileReader::new(&binary_data);
+        assert_eq!(reader.peek_utf8(13)?, "Hello, world!");
+        assert_eq!(reader.read_utf8(13)?, "Hello, world!");
+        assert!(reader.read_utf8(10).is_err());
+
+        let text = "こんにちは";
+        let binary_data: Vec<u8> = text.as_bytes().to_vec();
+        let mut reader = BinaryFileReader::new(&binary_data);
+        assert_eq!(reader.peek_utf8(15)?, "こんにちは");
+        assert!(reader.read_utf8(10).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test1() -> Result<(), BinaryFileReaderError> {
+        let buffer = (0..=255).collect::<Vec<u8>>();
+        let mut reader = BinaryFileReader::new(&buffer);
+
+        reader.read_u8()?;
+        assert_eq!(reader.available_bytes(), 255);
+        reader.read_u8()?;
+        assert_eq!(reader.available_bytes(), 254);
+        reader.read_u4()?;
+        assert_eq!(reader.available_bytes(), 253);
+        reader.read_u16()?;
+        assert_eq!(reader.available_bytes(), 251);
+        reader.read_u16()?;
+        assert_eq!(reader.available_bytes(), 249);
+
+        let mut b = vec![0; 200];
+        reader.read_bytes(&mut b)?;
+        assert_eq!(reader.available_bytes(), 49);
+
+        let mut splited = reader.split_off_front(20)?;
+
+        assert_eq!(reader.available_bytes(), 29);
+        assert_eq!(splited.available_bytes(), 20);
+
+        let a = splited.split_off_front(20)?;
+        assert_eq!(splited.available_bytes(), 0);
+        assert!(splited.read_u8().is_err());
+        assert!(splited.read_u4().is_err());
+        assert!(splited.read_u128().is_err());
+        assert!(splited.read_u32().is_err());
+        assert!(splited.read_u64().is_err());
+        assert!(splited.read_bytes(&mut b).is_err());
+        assert!(splited.read_u8().is_err());
+
+        assert_eq!(a.available_bytes(), 20);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test2() -> Result<(), BinaryFileReaderError> {
+        let buffer: Vec<u8> = (0..=255).collect();
+        let mut reader = BinaryFileReader::new(&buffer);
+        assert_eq!(reader.available_bytes(), 256);
+
+        let slice = reader.peek_slice(10)?;
+        assert_eq!(slice, &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(reader.available_bytes(), 256);
+        assert_eq!(reader.current_offset(), 0);
+
+        let slice = reader.read_slice(10)?;
+        assert_eq!(slice, &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(reader.available_bytes(), 246);
+        assert_eq!(reader.current_offset(), 10);
+
+        let mut reader2 = reader.split_off_front(100)?;
+        assert_eq!(reader.available_bytes(), 146);
+        assert_eq!(reader2.available_bytes(), 100);
+        assert_eq!(reader2.read_u8()?, 10);
+        assert_eq!(reader2.read_u16()?, 0x0b0c);
+        assert_eq!(reader2.read_u32()?, 0x0d0e0f10);
+        assert_eq!(reader2.read_u64()?, 0x1112131415161718);
+        assert_eq!(reader2.available_bytes(), 85);
+        assert_eq!(reader2.current_offset(), 25);
+        assert!(matches!(
+            reader2.split_off_front(86),
+            Err(BinaryFileReaderError::BufferUnderflow {
+                requested_bytes: 86,
+                current_offset: 25,
+                available_bytes: 85
+            })
+        ));
+        let mut reader2 = reader2.split_off_front(85)?;
+        assert!(matches!(
+            reader2.split_off_front(86),
+            Err(BinaryFileReaderError::BufferUnderflow {
+                requested_bytes: 86,
+                current_offset: 25,
+                available_bytes: 85
+            })
+        ));
+
+        let slice = reader.peek_slice(10)?;
+        assert_eq!(slice, &[110, 111, 112, 113, 114, 115, 116, 117, 118, 119]);
+        let slice = reader.read_slice(10)?;
+        assert_eq!(slice, &[110, 111, 112, 113, 114, 115, 116, 117, 118, 119]);
+        assert_eq!(reader.available_bytes(), 136);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_windows() -> Result<(), BinaryFileReaderError> {
+        let buffer = (0..10).collect::<Vec<u8>>();
+        let mut reader = BinaryFileReader::new(&buffer);
+        reader.read_u16()?;
+        let mut sub = reader.split_off_front(4)?;
+
+        let windows = sub.windows(3).collect::<Vec<_>>();
+        assert_eq!(windows, vec![(2, &[2, 3, 4][..]), (3, &[3, 4, 5][..])]);
+        assert_eq!(sub.windows(3).len(), 2);
+        assert_eq!(sub.windows(4).count(), 1);
+        assert_eq!(sub.windows(5).count(), 0);
+
+        sub.read_slice(4)?;
+        assert_eq!(sub.windows(1).count(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_split() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![9, 1, 2, 0, 3, 0, 0, 4, 5, 6];
+        let mut reader = BinaryFileReader::new(&buffer);
+        reader.read_u8()?;
+        let sub = reader.split_off_front(8)?;
+
+        let blocks = sub
+            .split(&[0])
+            .map(|block| (block.current_offset(), block.available_bytes()))
+            .collect::<Vec<_>>();
+        assert_eq!(blocks, vec![(1, 2), (4, 1), (6, 0), (7, 2)]);
+
+        let mut blocks = sub.split(&[0]);
+        let mut first = blocks.next().unwrap();
+        assert_eq!(first.read_u16()?, 0x0102);
+        assert!(matches!(
+            first.read_u8(),
+            Err(BinaryFileReaderError::BufferUnderflow {
+                requested_bytes: 1,
+                current_offset: 3,
+                available_bytes: 0
+            })
+        ));
+
+        let blocks = sub.split(&[0, 0]).collect::<Vec<_>>();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].available_bytes(), 4);
+        assert_eq!(blocks[1].current_offset(), 7);
+
+        let empty = BinaryFileReader::new(&[]);
+        assert_eq!(empty.split(&[0]).count(), 1);
+
+        #[cfg(feature = "std")]
+        {
+            let mut sub = sub.clone();
+            sub.enable_coverage();
+            assert_eq!(sub.split(&[0]).count(), 4);
+            assert_eq!(sub.coverage_report().unwrap().unread_bytes(), 8);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_skip_while() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0xff, 0xff, 0, 0, 0, 1, 0, 0];
+        let mut reader = BinaryFileReader::new(&buffer);
+        assert_eq!(reader.skip_while(|b| b == 0), 0);
+        assert_eq!(reader.skip_while(|b| b == 0xff), 2);
+        assert_eq!(reader.current_offset(), 2);
+
+        let mut sub = reader.split_off_front(4)?;
+        assert_eq!(sub.skip_while(|_| true), 4);
+        assert_eq!(sub.available_bytes(), 0);
+        assert_eq!(sub.skip_while(|_| true), 0);
+
+        assert_eq!(reader.skip_while(|b| b == 0), 2);
+        assert_eq!(reader.available_bytes(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_finish() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![1, 2, 3, 4, 5];
+        let mut reader = BinaryFileReader::new(&buffer);
+        let mut sub = reader.split_off_front(3)?;
+        sub.read_u8()?;
+        assert!(matches!(
+            sub.finish(),
+            Err(BinaryFileReaderError::TrailingBytes {
+                remaining_bytes: 2,
+                current_offset: 1
+            })
+        ));
+        sub.read_u16()?;
+        sub.finish()?;
+
+        reader.read_u16()?;
+        reader.finish()?;
+        BinaryFileReader::new(&[]).finish()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_bool() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![1, 0, 0x80, 0x80];
+        let mut reader = BinaryFileReader::new(&buffer);
+        assert!(reader.read_bool()?);
+        assert!(!reader.read_bool()?);
+        assert!(matches!(
+            reader.read_bool(),
+            Err(BinaryFileReaderError::InvalidDiscriminant {
+                type_name: "bool",
+                value: 0x80,
+                current_offset: 2,
+            })
+        ));
+        assert!(reader.read_bool_lenient()?);
+        assert!(reader.read_bool_lenient()?);
+        assert!(matches!(
+            reader.read_bool(),
+            Err(BinaryFileReaderError::BufferUnderflow { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_enum() -> Result<(), BinaryFileReaderError> {
+        #[derive(Debug, PartialEq)]
+        struct Even(u32);
+
+        impl TryFrom<u8> for Even {
+            type Error = ();
+            fn try_from(value: u8) -> Result<Self, Self::Error> {
+                Even::try_from(value as u32)
+            }
+        }
+
+        impl TryFrom<u16> for Even {
+            type Error = ();
+            fn try_from(value: u16) -> Result<Self, Self::Error> {
+                Even::try_from(value as u32)
+            }
+        }
+
+        impl TryFrom<u32> for Even {
+            type Error = ();
+            fn try_from(value: u32) -> Result<Self, Self::Error> {
+                if value.is_multiple_of(2) {
+                    Ok(Even(value))
+                } else {
+                    Err(())
+                }
+            }
+        }
+
+        let buffer = vec![0x02, 0x01, 0x03, 0x00, 0x00, 0x00, 0x04, 0x00];
+        let mut reader = BinaryFileReader::new(&buffer);
+        assert_eq!(reader.read_enum::<Even>()?, Even(2));
+        assert!(matches!(
+            reader.read_enum::<Even>(),
+            Err(BinaryFileReaderError::InvalidDiscriminant {
+                value: 1,
+                current_offset: 1,
+                ..
+            })
+        ));
+        assert!(matches!(
+            reader.read_enum_u16::<Even>(),
+            Err(BinaryFileReaderError::InvalidDiscriminant {
+                value: 0x0103,
+                current_offset: 1,
+                ..
+            })
+        ));
+        reader.read_u16()?;
+        assert_eq!(reader.read_enum_u32::<Even>()?, Even(4));
+        assert!(matches!(
+            reader.read_enum_u16::<Even>(),
+            Err(BinaryFileReaderError::BufferUnderflow {
+                requested_bytes: 2,
+                current_offset: 7,
+                available_bytes: 1
+            })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_iter_of() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0, 1, 0, 2, 0, 3];
+        let mut reader = BinaryFileReader::new(&buffer);
+        let values = reader.iter_of::<u16>().collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(values, vec![1, 2, 3]);
+        assert_eq!(reader.available_bytes(), 0);
+        assert!(reader.iter_of::<u16>().next().is_none());
+
+        let buffer = vec![1, 0, 5];
+        let mut reader = BinaryFileReader::new(&buffer);
+        let mut iter = reader.iter_of::<bool>();
+        assert!(matches!(iter.next(), Some(Ok(true))));
+        assert!(matches!(iter.next(), Some(Ok(false))));
+        match iter.next() {
+            Some(Err(BinaryFileReaderError::Element { index, source })) => {
+                assert_eq!(index, 2);
+                assert!(matches!(
+                    *source,
+                    BinaryFileReaderError::InvalidDiscriminant { value: 5, .. }
+                ));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(iter.next().is_none());
+        assert_eq!(reader.current_offset(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_repeat() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0, 1, 0, 2, 0];
+        let mut reader = BinaryFileReader::new(&buffer);
+        match reader.repeat(|r| r.read_u16()) {
+            Err(BinaryFileReaderError::Element { index, source }) => {
+                assert_eq!(index, 2);
+                assert!(matches!(
+                    *source,
+                    BinaryFileReaderError::BufferUnderflow {
+                        current_offset: 4,
+                        ..
+                    }
+                ));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(reader.current_offset(), 4);
+
+        let mut reader = BinaryFileReader::new(&buffer[..4]);
+        assert_eq!(reader.repeat(|r| r.read_u16())?, vec![1, 2]);
+        assert_eq!(reader.repeat(|r| r.read_u16())?, vec![]);
+
+        let mut reader = BinaryFileReader::new(&buffer);
+        assert_eq!(reader.repeat(|r| r.peek_u8())?, vec![0]);
+        assert_eq!(reader.current_offset(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_length_prefixed() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![2, 0xaa, 0xbb, 5, 0xcc];
+        let mut reader = BinaryFileReader::new(&buffer);
+        let block = reader.read_length_prefixed::<u8, _, _>(|sub| sub.read_slice(2))?;
+        assert_eq!(block, &[0xaa, 0xbb]);
+
+        assert!(matches!(
+            reader.read_length_prefixed::<u8, _, _>(|sub| sub.read_u8()),
+            Err(BinaryFileReaderError::BufferUnderflow {
+                requested_bytes: 5,
+                current_offset: 4,
+                available_bytes: 1
+            })
+        ));
+        assert_eq!(reader.current_offset(), 3);
+
+        let mut reader = BinaryFileReader::new(&buffer);
+        assert!(matches!(
+            reader.read_length_prefixed::<u8, _, _>(|sub| sub.read_u8()),
+            Err(BinaryFileReaderError::TrailingBytes {
+                remaining_bytes: 1,
+                current_offset: 2
+            })
+        ));
+        assert_eq!(reader.current_offset(), 0);
+
+        let buffer = vec![0, 0, 0, 0];
+        let mut reader = BinaryFileReader::new(&buffer);
+        reader.read_length_prefixed::<u32, _, _>(|_| Ok(()))?;
+        assert_eq!(reader.available_bytes(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_if() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0x81, 0x01, 0x02];
+        let mut reader = BinaryFileReader::new(&buffer);
+        let flags = reader.read_u8()?;
+        assert_eq!(reader.read_if_flag(flags, 0, |r| r.read_u8())?, Some(1));
+        assert_eq!(reader.read_if_flag(flags, 1, |r| r.read_u8())?, None);
+        assert_eq!(reader.read_if_flag(flags, 7, |r| r.read_u8())?, Some(2));
+        assert!(matches!(
+            reader.read_if(true, |r| r.read_u8()),
+            Err(BinaryFileReaderError::BufferUnderflow { .. })
+        ));
+        assert_eq!(reader.read_if(false, |r| r.read_u8())?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_option() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![1, 0xaa, 0, 7];
+        let mut reader = BinaryFileReader::new(&buffer);
+        assert_eq!(reader.read_option::<u8>()?, Some(0xaa));
+        assert_eq!(reader.read_option::<u8>()?, None);
+        assert!(matches!(
+            reader.read_option::<u8>(),
+            Err(BinaryFileReaderError::InvalidPresenceMarker {
+                got: 7,
+                absent: 0,
+                present: 1,
+                current_offset: 3
+            })
+        ));
+        assert_eq!(reader.current_offset(), 3);
+
+        let mut reader = BinaryFileReader::new(&buffer[..1]);
+        assert!(matches!(
+            reader.read_option::<u8>(),
+            Err(BinaryFileReaderError::BufferUnderflow { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_parse() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0x00, 0x01, 0x02];
+        let mut reader = BinaryFileReader::new(&buffer);
+        let result = reader.try_parse(|r| {
+            r.set_endian(Endian::Little);
+            r.read_u16()?;
+            r.read_u16()
+        });
+        assert!(matches!(
+            result,
+            Err(BinaryFileReaderError::BufferUnderflow {
+                current_offset: 2,
+                ..
+            })
+        ));
+        assert_eq!(reader.current_offset(), 0);
+        assert_eq!(reader.endian(), Endian::Big);
+
+        let value = reader.try_parse(|r| r.read_u16())?;
+        assert_eq!(value, 1);
+        assert_eq!(reader.current_offset(), 2);
+
+        let custom: Result<u8, &str> = reader.try_parse(|r| {
+            r.read_u8().ok();
+            Err("rejected")
+        });
+        assert_eq!(custom, Err("rejected"));
+        assert_eq!(reader.current_offset(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_versioned() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0x00, 0x01, 0xaa, 0x00, 0x07];
+        let mut reader = BinaryFileReader::new(&buffer);
+        let parse = |reader: &mut BinaryFileReader| {
+            reader.read_versioned::<u16, _, _>(|version, r| match version {
+                1 => r.read_u8().map(Some),
+                _ => Ok(None),
+            })
+        };
+        assert_eq!(parse(&mut reader)?, 0xaa);
+        assert!(matches!(
+            parse(&mut reader),
+            Err(BinaryFileReaderError::UnsupportedVersion {
+                version: 7,
+                current_offset: 3
+            })
+        ));
+        assert_eq!(reader.current_offset(), 3);
+
+        let mut reader = BinaryFileReader::new(&buffer[..2]);
+        assert!(matches!(
+            parse(&mut reader),
+            Err(BinaryFileReaderError::BufferUnderflow { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nested() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0, 1, 2, 3];
+        let mut reader = BinaryFileReader::new(&buffer);
+        reader.set_max_depth(2);
+        reader.nested(|r| {
+            let mut sub = r.split_off_front(2)?;
+            assert_eq!(sub.depth(), 1);
+            assert_eq!(sub.max_depth(), 2);
+            sub.nested(|s| s.read_u16())?;
+            assert!(matches!(
+                sub.nested(|s| s.nested(|_| Ok(()))),
+                Err(BinaryFileReaderError::DepthExceeded {
+                    max_depth: 2,
+                    current_offset: 2
+                })
+            ));
+            Ok(())
+        })?;
+        assert_eq!(reader.depth(), 0);
+
+        reader.set_max_depth(0);
+        assert!(reader.nested(|r| r.read_u8()).is_err());
+        assert_eq!(reader.current_offset(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_alloc() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0x00, 0x01, 0x00, 0x02, 0x00, 0x03];
+        let mut reader = BinaryFileReader::new(&buffer);
+        reader.set_max_alloc(4);
+
+        let mut sub = reader.clone().split_off_front(6)?;
+        assert_eq!(sub.max_alloc(), 4);
+        assert!(matches!(
+            sub.read_vec_of::<u16>(3),
+            Err(BinaryFileReaderError::AllocationLimit {
+                requested_bytes: 6,
+                max_alloc: 4,
+                current_offset: 0
+            })
+        ));
+
+        reader.set_endian(match Endian::NATIVE {
+            Endian::Big => Endian::Little,
+            Endian::Little => Endian::Big,
+        });
+        assert!(matches!(
+            reader.read_slice_of::<u16>(3),
+            Err(BinaryFileReaderError::AllocationLimit { .. })
+        ));
+        assert_eq!(reader.current_offset(), 0);
+        assert_eq!(reader.read_slice_of::<u16>(2)?.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_overflowing_lengths() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0x00, 0x01, 0x02, 0x03];
+        let mut reader = BinaryFileReader::new(&buffer);
+        reader.read_u8()?;
+
+        assert!(matches!(
+            reader.read_slice(usize::MAX),
+            Err(BinaryFileReaderError::BufferUnderflow {
+                requested_bytes: usize::MAX,
+                current_offset: 1,
+                available_bytes: 3
+            })
+        ));
+        assert!(reader.peek_slice(usize::MAX).is_err());
+        assert!(reader.read_utf8(usize::MAX).is_err());
+        assert!(reader.expect_filled(usize::MAX, 0).is_err());
+        assert!(reader.split_off_front(usize::MAX).is_err());
+        assert!(reader.read_slice_of::<u64>(usize::MAX).is_err());
+        assert!(reader.read_vec_of::<u32>(usize::MAX / 2).is_err());
+        assert!(reader
+            .read_length_prefixed::<u8, _, _>(|sub| sub.read_slice(usize::MAX))
+            .is_err());
+        assert_eq!(reader.current_offset(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_error_bytes() {
+        let buffer = vec![0; 20];
+        let reader = BinaryFileReader::new(&buffer);
+        match reader.expect_peek(&[1; 40]) {
+            Err(BinaryFileReaderError::ExpectInsufficientBytes { require, .. }) => {
+                assert_eq!(require.total_len(), 40);
+                assert_eq!(require.as_slice(), &[1; ErrorBytes::CAPACITY]);
+                assert_ne!(require, [1; 40]);
+                assert!(format!("{:?}", require).ends_with(", 1, ..] (40 bytes)"));
+            }
+            _ => unreachable!(),
+        }
+
+        match reader.expect_peek(&[0, 0, 7]) {
+            Err(BinaryFileReaderError::Expect { require, got, .. }) => {
+                assert_eq!(require, [0, 0, 7]);
+                assert_eq!(got, [0, 0, 0]);
+                assert_eq!(format!("{:?}", got), "[0, 0, 0]");
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_cancellation() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0; 64];
+        let mut reader = BinaryFileReader::new(&buffer);
+        let token = CancelToken::new();
+        reader.set_cancel_token(token.clone());
+
+        let mut sub = reader.split_off_front(32)?;
+        let mut items = sub.iter_of::<u64>();
+        assert!(matches!(items.next(), Some(Ok(0))));
+        token.cancel();
+        assert!(matches!(
+            items.next(),
+            Some(Err(BinaryFileReaderError::Cancelled { current_offset: 8 }))
+        ));
+        assert!(items.next().is_none());
+
+        assert!(matches!(
+            reader.repeat_until(|r| r.read_u8().map(Some)),
+            Err(BinaryFileReaderError::Cancelled { current_offset: 32 })
+        ));
+        assert_eq!(reader.read_u8()?, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_cursor_interop() -> Result<(), BinaryFileReaderError> {
+        let buffer = (0..8).collect::<Vec<u8>>();
+        let mut cursor = std::io::Cursor::new(&buffer[..]);
+        cursor.set_position(100);
+        let reader = BinaryFileReader::from(cursor);
+        assert_eq!(reader.current_offset(), 8);
+        assert_eq!(reader.available_bytes(), 0);
+
+        let mut reader = BinaryFileReader::new(&buffer);
+        reader.read_u16()?;
+        let mut sub = reader.split_off_front(4)?;
+        sub.read_u8()?;
+        sub.set_offset(sub.current_offset() - 1)?;
+        assert_eq!(sub.read_u8()?, 2);
+        assert!(matches!(
+            sub.set_position(1),
+            Err(BinaryFileReaderError::OutOfRange {
+                buffer_size: 6,
+                got: 1
+            })
+        ));
+
+        let cursor = std::io::Cursor::from(sub);
+        assert_eq!(cursor.position(), 3);
+        assert_eq!(cursor.get_ref().len(), 6);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_iter_bytes() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![1, 2, 3, 4, 5, 6];
+        let mut reader = BinaryFileReader::new(&buffer);
+        reader.read_u8()?;
+        let sub = reader.split_off_front(3)?;
+        assert_eq!((&sub).into_iter().sum::<u8>(), 9);
+        assert_eq!(sub.into_iter().collect::<Vec<_>>(), vec![2, 3, 4]);
+
+        let bytes = DataSource::bytes(&mut reader).collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(bytes, vec![5, 6]);
+        assert_eq!(reader.available_bytes(), 0);
+        assert_eq!(reader.iter().next(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_range() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0, 1, 2, 3, 4, 5, 6, 7];
+        let mut reader = BinaryFileReader::new(&buffer);
+        reader.read_u16()?;
+        let sub = reader.split_off_front(4)?;
+
+        assert_eq!(sub.as_ref(), &[2, 3, 4, 5]);
+        assert_eq!(sub.get(1..3)?, &[3, 4]);
+        assert_eq!(&sub[0..4], &[2, 3, 4, 5]);
+        assert_eq!(sub.get(4..4)?, &[]);
+        assert!(matches!(
+            sub.get(2..5),
+            Err(BinaryFileReaderError::OutOfRange {
+                buffer_size: 4,
+                got: 5
+            })
+        ));
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = sub.get(3..1);
+        assert!(matches!(
+            reversed,
+            Err(BinaryFileReaderError::OutOfRange {
+                buffer_size: 4,
+                got: 3
+            })
+        ));
+        assert_eq!(sub.current_offset(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_into_inner() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0, 1, 2, 3, 4, 5];
+        let mut reader = BinaryFileReader::new(&buffer);
+        assert_eq!(reader.as_parts(), (&buffer[..], 0));
+
+        reader.read_u16()?;
+        let mut sub = reader.split_off_front(3)?;
+        sub.read_u8()?;
+        let (inner, offset) = sub.into_inner();
+        assert_eq!(inner, &buffer[..5]);
+        assert_eq!(offset, 3);
+
+        let (inner, offset) = reader.into_inner();
+        assert_eq!(&inner[offset..], &[5]);
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_eq_hash() -> Result<(), BinaryFileReaderError> {
+        use core::hash::{Hash, Hasher};
+        use std::collections::hash_map::DefaultHasher;
+
+        let buffer = vec![1, 2, 3, 1, 2, 3];
+        let copy = buffer.clone();
+        let mut reader = BinaryFileReader::new(&buffer);
+        let mut other = BinaryFileReader::new(&copy);
+        other.set_endian(Endian::Little);
+        assert_eq!(reader, other);
+
+        let first = reader.split_off_front(3)?;
+        let second = reader.split_off_front(3)?;
+        assert_eq!(first.as_ref(), second.as_ref());
+        assert_ne!(first, second);
+
+        let hash = |reader: &BinaryFileReader| {
+            let mut hasher = DefaultHasher::new();
+            reader.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(&first), hash(&first.clone()));
+        assert_eq!(hash(&first), hash(&BinaryFileReader::new(&[1, 2, 3])));
+        assert_ne!(hash(&first), hash(&other));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_context() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0x00, 0x01, 0x02, 0x03];
+        let mut reader = BinaryFileReader::new(&buffer);
+
+        let value = reader.context("first", |r| r.read_u16())?;
+        assert_eq!(value, 1);
+
+        let err = reader
+            .context("outer", |r| {
+                r.iter_of::<u8>().count();
+                r.context(Cow::Owned(String::from("inner")), |r| r.expect(b"\x02\x04"))
+            })
+            .unwrap_err();
+        assert_eq!(err.contexts(), ["outer", "inner"]);
+        assert!(matches!(
+            err,
+            BinaryFileReaderError::Context { ref label, ref source }
+                if label == "outer"
+                    && matches!(**source, BinaryFileReaderError::Context { .. })
+        ));
+
+        let err = BinaryFileReaderError::Element {
+            index: 1,
+            source: Box::new(err),
+        };
+        assert_eq!(err.contexts(), ["outer", "inner"]);
+        assert!(reader.read_u8().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_error_accessors() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0x00, 0x00, 0x07, 0x01];
+        let mut reader = BinaryFileReader::new(&buffer);
+
+        let err = reader.expect_zeroes(4).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Mismatch);
+        assert_eq!(err.offset(), Some(2));
+        assert_eq!(err.available(), None);
+
+        reader.read_u16()?;
+        let err = reader.expect(b"\x07\x01\x00").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+        assert_eq!(
+            (err.offset(), err.requested(), err.available()),
+            (Some(2), Some(3), Some(2))
+        );
+
+        let err = reader.read_bool().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidValue);
+        let err = reader.finish().unwrap_err();
+        assert_eq!(
+            (err.kind(), err.offset()),
+            (ErrorKind::TrailingBytes, Some(2))
+        );
+
+        let err = reader
+            .repeat(|r| r.read_u16().and_then(|_| r.read_u8()))
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+        assert_eq!(err.offset(), Some(4));
+
+        let err = BinaryFileReaderError::OutOfRange {
+            buffer_size: 1,
+            got: 2,
+        };
+        assert_eq!((err.kind(), err.offset()), (ErrorKind::OutOfRange, None));
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_into_io_error() {
+        use std::io;
+
+        let err = io::Error::from(BinaryFileReaderError::IO(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "denied",
+        )));
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        assert!(err
+            .get_ref()
+            .is_some_and(|inner| inner.to_string() == "denied"));
+
+        let mut reader = BinaryFileReader::new(&[0xff, 0xfe]);
+        let err = reader
+            .context("text", |r| Ok(r.read_utf8(2)?.len()))
+            .unwrap_err();
+        let err = io::Error::from(err);
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().starts_with("text: "));
+
+        let err = io::Error::from(reader.read_u32().unwrap_err());
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert!(matches!(
+            err.into_inner().map(|inner| inner.downcast::<BinaryFileReaderError>()),
+            Some(Ok(inner)) if matches!(*inner, BinaryFileReaderError::BufferUnderflow { .. })
+        ));
+    }
+
+    #[test]
+    fn test_error_display() {
+        let mut reader = BinaryFileReader::new(b"\x89PNF\r\n\x1a\n");
+        let err = reader.expect(b"\x89PNG").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Expectation failed: required 89 50 4E 47, got 89 50 4E 46, available bytes: 8, offset: 0x0 (0)"
+        );
+
+        reader.read_slice(4).unwrap();
+        let err = reader.expect_one_of(&[b"\x00", b"\x0a\x0d"]).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Expectation failed: required one of [00, 0A 0D], got 0D 0A,"));
+
+        let err = reader.expect_zeroes(4).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Expectation failed: required bytes filled with 0x00 from offset 0x4 (4), got 0x0d at offset 0x4 (4)"
+        );
+        let err = BinaryFileReaderError::Cancelled {
+            current_offset: 4213,
+        };
+        assert_eq!(err.to_string(), "Cancelled at offset 0x1075 (4213)");
+        let err = BinaryFileReaderError::OutOfRange {
+            buffer_size: 16,
+            got: 32,
+        };
+        assert_eq!(
+            err.to_string(),
+            "Out of range error: attempted to access index 0x20 (32) in a buffer of size 0x10 (16)"
+        );
+    }
+
+    #[test]
+    fn test_max_error_bytes() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0x11; 64];
+        let mut reader = BinaryFileReader::new(&buffer);
+        reader.set_max_error_bytes(2);
+        let sub = reader.split_off_front(32)?;
+        assert_eq!(sub.max_error_bytes(), 2);
 
-        let mut reader = BinaryFileReader::new(&[
-            0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3,
-        ]);
-        assert_eq!(reader.read_u64()?, 0x0001020304050607);
-        assert_eq!(reader.read_u128()?, 0x08090001020304050607080900010203);
+        match sub.expect_peek(&[0x22; 40]) {
+            Err(BinaryFileReaderError::ExpectInsufficientBytes { require, .. }) => {
+                assert_eq!(require.as_slice(), &[0x22, 0x22]);
+                assert_eq!(require.total_len(), 40);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match sub.expect_masked_peek(&[0x22; 8], &[0x0f; 8]) {
+            Err(BinaryFileReaderError::ExpectMasked { mask, got, .. }) => {
+                assert_eq!(mask.as_slice(), &[0x0f, 0x0f]);
+                assert!(got.is_truncated());
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match reader.expect_one_of(&[b"abc", b"d"]) {
+            Err(BinaryFileReaderError::ExpectOneOf {
+                candidates, got, ..
+            }) => {
+                assert_eq!(candidates[0].as_slice(), b"ab");
+                assert_eq!(candidates[1], *b"d");
+                assert_eq!(got.as_slice(), &[0x11, 0x11]);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        reader.set_max_error_bytes(usize::MAX);
+        match reader.expect(&[0; 20]) {
+            Err(BinaryFileReaderError::Expect { require, .. }) => {
+                assert_eq!(require.len(), ErrorBytes::CAPACITY);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(ErrorBytes::with_cap(b"abc", 0).len(), 0);
 
         Ok(())
     }
 
     #[test]
-    fn test_peek() -> Result<(), BinaryFileReaderError> {
-        let reader = BinaryFileReader::new(&[
-            0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3,
-        ]);
-        assert_eq!(reader.peek_u8()?, 0);
-        assert_eq!(reader.peek_u8()?, 0);
-        assert_eq!(reader.peek_u4()?, (0, 0));
-        assert_eq!(reader.peek_u4()?, (0, 0));
-        assert_eq!(reader.peek_u16()?, 1);
-        assert_eq!(reader.peek_u16()?, 1);
-        assert_eq!(reader.peek_u32()?, 0x00010203);
-        assert_eq!(reader.peek_u32()?, 0x00010203);
-        assert_eq!(reader.peek_u64()?, 0x0001020304050607);
-        assert_eq!(reader.peek_u64()?, 0x0001020304050607);
-        assert_eq!(reader.peek_u128()?, 0x00010203040506070809000102030405);
-        assert_eq!(reader.peek_u128()?, 0x00010203040506070809000102030405);
+    fn test_error_code() {
+        let mut reader = BinaryFileReader::new(&[0x02]);
+        let err = reader
+            .context("flag", |r| r.repeat(|r| r.read_bool()))
+            .unwrap_err();
+        assert_eq!(err.code(), ErrorCode::InvalidDiscriminant);
+        assert_eq!(err.code().as_u16(), 40);
 
-        let reader = BinaryFileReader::new(&[]);
-        assert!(reader.peek_u4().is_err());
-        assert!(reader.peek_u8().is_err());
-        assert!(reader.peek_u16().is_err());
-        assert!(reader.peek_u32().is_err());
-        assert!(reader.peek_u64().is_err());
-        assert!(reader.peek_u128().is_err());
+        for code in 0..=u16::MAX {
+            if let Some(known) = ErrorCode::from_u16(code) {
+                assert_eq!(known.as_u16(), code);
+            }
+        }
+        assert_eq!(ErrorCode::from_u16(70), Some(ErrorCode::OutOfRange));
+    }
+
+    #[test]
+    fn test_source_name() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0x00, 0x01, 0x02];
+        let mut reader = BinaryFileReader::new(&buffer);
+        assert_eq!(reader.parse(|r| r.read_u8())?, 0);
+        assert!(reader
+            .parse(|r| r.read_u32())
+            .unwrap_err()
+            .source_name()
+            .is_none());
+
+        let mut reader = reader.with_source_name("firmware.bin");
+        let mut sub = reader.split_off_front(1)?;
+        assert_eq!(sub.source_name(), Some("firmware.bin"));
+
+        let err = reader
+            .parse(|r| r.context("header", |r| r.parse(|r| r.read_u16())))
+            .unwrap_err();
+        assert_eq!(err.source_name(), Some("firmware.bin"));
+        assert_eq!(err.contexts(), ["header"]);
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+        assert_eq!(
+            err.to_string(),
+            "header: firmware.bin: Buffer underflow: requested 2 bytes at offset 0x2 (2), but only 1 bytes are available"
+        );
+        assert!(matches!(
+            sub.parse(|r| r.expect(b"\x00")),
+            Err(BinaryFileReaderError::Named { .. })
+        ));
 
         Ok(())
     }
 
     #[test]
-    fn test_split_off_front() -> Result<(), BinaryFileReaderError> {
-        let buffer = (0..=255).collect::<Vec<u8>>();
-        let mut a = BinaryFileReader::new(&buffer);
-        let mut b = a.split_off_front(128)?;
-        assert_eq!(b.current_offset(), 0);
-        assert_eq!(b.read_u8()?, 0);
-        assert_eq!(a.current_offset(), 128);
-        assert_eq!(a.read_u8()?, 128);
+    fn test_split_off_front_labeled() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0x00, 0x02, 0xaa, 0xbb, 0xcc];
+        let mut reader = BinaryFileReader::new(&buffer);
+        reader.read_u8()?;
 
-        let buffer = (0..=255).collect::<Vec<u8>>();
-        let mut a = BinaryFileReader::new(&buffer);
-        let mut b = a.split_off_front(128)?;
-        assert_eq!(b.current_offset(), 0);
-        assert_eq!(a.current_offset(), 128);
-        assert!(b.split_off_front(129).is_err());
-        assert!(a.split_off_front(129).is_err());
-        let mut c = a.split_off_front(64)?;
-        let mut d = b.split_off_front(64)?;
-        assert_eq!(a.current_offset(), 128 + 64);
-        assert_eq!(b.current_offset(), 64);
-        assert_eq!(c.current_offset(), 128);
-        assert_eq!(d.current_offset(), 0);
-        assert_eq!(a.available_bytes(), 64);
-        assert_eq!(b.available_bytes(), 64);
-        assert_eq!(c.available_bytes(), 64);
-        assert_eq!(d.available_bytes(), 64);
-        let mut c = c.split_off_front(64)?;
-        assert_eq!(c.available_bytes(), 64);
+        let value = reader.split_off_front_labeled(3, "body", |r| {
+            let length = r.read_u8()? as usize;
+            r.read_slice(length)
+        })?;
+        assert_eq!(value, &[0xaa, 0xbb]);
+        assert_eq!(reader.current_offset(), 4);
 
-        assert_eq!(a.read_u8()?, 128 + 64);
-        assert_eq!(b.read_u8()?, 64);
-        assert_eq!(c.read_u8()?, 128);
-        assert_eq!(d.read_u8()?, 0);
+        assert!(matches!(
+            reader.split_off_front_labeled(2, "trailer", |r| r.read_u8()),
+            Err(BinaryFileReaderError::Section {
+                start: 4,
+                length: 2,
+                ref source,
+                ..
+            }) if matches!(**source, BinaryFileReaderError::BufferUnderflow { .. })
+        ));
+        assert_eq!(reader.current_offset(), 4);
+
+        let err = reader
+            .split_off_front_labeled(1, "trailer", |r| r.expect(b"\xdd"))
+            .unwrap_err();
+        assert_eq!(err.code(), ErrorCode::Expect);
+        assert_eq!(err.offset(), Some(4));
+        assert_eq!(reader.available_bytes(), 0);
 
         Ok(())
     }
 
     #[test]
-    fn test_expect() -> Result<(), BinaryFileReaderError> {
-        let buffer = (0..=255).collect::<Vec<u8>>();
+    #[cfg(feature = "std")]
+    fn test_coverage() -> Result<(), BinaryFileReaderError> {
+        let buffer = (0..16).collect::<Vec<u8>>();
         let mut reader = BinaryFileReader::new(&buffer);
-        reader.expect(&[0, 1, 2, 3, 4])?;
+        assert!(reader.coverage_report().is_none());
+        reader.read_u16()?;
 
-        let buffer = vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
-        let mut reader = BinaryFileReader::new(&buffer);
-        reader.expect(&[0, 1, 2, 3])?;
-        assert_eq!(reader.current_offset(), 4);
-        assert_eq!(reader.available_bytes(), 6);
-        reader.expect(&[4, 5, 6, 7])?;
-        assert_eq!(reader.current_offset(), 8);
-        assert_eq!(reader.available_bytes(), 2);
+        let mut sub = reader.split_off_front(12)?;
+        sub.enable_coverage();
+        sub.read_u32()?;
+        sub.peek_u32()?;
+        assert_eq!(sub.windows(2).count(), 7);
+        sub.skip_while(|b| b < 8);
+        let mut inner = sub.split_off_front(4)?;
+        inner.expect(&[8, 9])?;
+        DataSource::read_u8(&mut inner)?;
+        inner.set_offset(10)?;
+        inner.read_u8()?;
+        reader.read_u8()?;
 
-        let buffer = vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
-        let reader = BinaryFileReader::new(&buffer);
-        reader.expect_peek(&[0, 1, 2, 3])?;
-        reader.expect_peek(&[0, 1, 2, 3])?;
+        let report = sub.coverage_report().unwrap();
+        assert_eq!(report.gaps, vec![11..14]);
+        assert_eq!(report.overlaps, vec![10..11]);
+        assert_eq!(report.unread_bytes(), 3);
+        assert!(reader.coverage_report().is_none());
 
         Ok(())
     }
 
     #[test]
-    fn test_utf8() -> Result<(), BinaryFileReaderError> {
-        let text = "Hello, world!";
-        let binary_data: Vec<u8> = text.as_bytes().to_vec();
-        let mut reader = BinaryFileReader::new(&binary_data);
-        assert_eq!(reader.peek_utf8(13)?, "Hello, world!");
-        assert_eq!(reader.read_utf8(13)?, "Hello, world!");
-        assert!(reader.read_utf8(10).is_err());
+    #[cfg(feature = "std")]
+    fn test_annotate() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0x00, 0x03, b'a', b'b', b'c', 0xff];
+        let mut reader = BinaryFileReader::new(&buffer);
+        assert_eq!(reader.annotate("ignored", |r| r.read_u8())?, 0);
+        assert!(reader.layout().is_none());
 
-        let text = "こんにちは";
-        let binary_data: Vec<u8> = text.as_bytes().to_vec();
-        let mut reader = BinaryFileReader::new(&binary_data);
-        assert_eq!(reader.peek_utf8(15)?, "こんにちは");
-        assert!(reader.read_utf8(10).is_err());
+        reader.set_offset(0)?;
+        reader.enable_layout();
+        reader.annotate("name", |r| {
+            let length = r.annotate("name.length", |r| r.read_u16())? as usize;
+            let mut body = r.split_off_front(length)?;
+            body.annotate("name.text", |r| r.read_utf8(3))
+        })?;
+        assert!(reader.annotate("flag", |r| r.read_bool()).is_err());
+        // Seeking backward records the span it covered.
+        reader.annotate("rewind", |r| r.set_offset(1))?;
+
+        let layout = reader.layout().unwrap();
+        let entries = layout
+            .entries
+            .iter()
+            .map(|entry| (entry.range.clone(), entry.label.as_ref(), entry.depth))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            vec![
+                (0..5, "name", 0),
+                (0..2, "name.length", 1),
+                (1..5, "rewind", 0),
+                (2..5, "name.text", 1)
+            ]
+        );
 
         Ok(())
     }
 
     #[test]
-    fn test1() -> Result<(), BinaryFileReaderError> {
-        let buffer = (0..=255).collect::<Vec<u8>>();
+    fn test_hexdump() -> Result<(), BinaryFileReaderError> {
+        let buffer = (0..40).collect::<Vec<u8>>();
         let mut reader = BinaryFileReader::new(&buffer);
+        reader.read_u32()?;
+        let mut sub = reader.split_off_front(8)?;
+        sub.read_u16()?;
 
+        assert_eq!(
+            sub.hexdump(0, 6),
+            "00000004: 0405                                     ..\n"
+        );
+        assert_eq!(sub.hexdump_around_cursor(1), sub.hexdump(5, 2));
+        assert_eq!(sub.hexdump_around_cursor(usize::MAX), sub.hexdump(4, 8));
+        assert_eq!(reader.hexdump(38, usize::MAX).lines().count(), 1);
+        assert_eq!(reader.hexdump(39, 0), "");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_debug() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0x00, 0x01, 0x02, 0x03];
+        let mut reader = BinaryFileReader::new(&buffer);
         reader.read_u8()?;
-        assert_eq!(reader.available_bytes(), 255);
-        reader.read_u8()?;
-        assert_eq!(reader.available_bytes(), 254);
+        let sub = reader.split_off_front(2)?;
+        assert_eq!(
+            format!("{:?}", sub),
+            "BinaryFileReader { region: 1..3, current_offset: 1, available_bytes: 2, endian: Big, upcoming: 01 02, .. }"
+        );
+        assert_eq!(sub.to_string(), "2 bytes left at offset 0x1 (1)");
+
+        let detached = reader.rebind(&[]);
+        assert!(format!("{:?}", detached).contains("upcoming: , .."));
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_trace() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0x01, 0x02, 0x02, b'h', b'i', 0xa5];
+        let mut reader = BinaryFileReader::new(&buffer);
+        reader.read_bool()?;
+        assert!(reader.trace().is_none());
+
+        reader.enable_trace();
+        reader.set_endian(Endian::Little);
+        let length = reader.read_u8()? as usize;
+        let mut text = reader.split_off_front(length + 1)?;
+        text.read_u8()?;
+        text.read_utf8(3).unwrap_err();
+        text.read_utf8(1)?;
         reader.read_u4()?;
-        assert_eq!(reader.available_bytes(), 253);
-        reader.read_u16()?;
-        assert_eq!(reader.available_bytes(), 251);
-        reader.read_u16()?;
-        assert_eq!(reader.available_bytes(), 249);
+        reader.read_u16().unwrap_err();
 
-        let mut b = vec![0; 200];
-        reader.read_bytes(&mut b)?;
-        assert_eq!(reader.available_bytes(), 49);
+        let trace = reader.trace().unwrap();
+        assert_eq!(
+            trace.to_string(),
+            "\
+0x00000001 1 read_u8 2
+0x00000002 3 split_off_front 2..5
+0x00000002 1 read_u8 2
+0x00000003 0 read_utf8 Err(BufferUnderflow)
+0x00000003 1 read_utf8 \"h\"
+0x00000005 1 read_u4 (10, 5)
+0x00000006 0 read_u16 Err(BufferUnderflow)
+"
+        );
 
-        let mut splited = reader.split_off_front(20)?;
+        Ok(())
+    }
 
-        assert_eq!(reader.available_bytes(), 29);
-        assert_eq!(splited.available_bytes(), 20);
+    #[test]
+    fn test_spanned() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0x00, 0x02, b'o', b'k', 0x01];
+        let mut reader = BinaryFileReader::new(&buffer);
 
-        let a = splited.split_off_front(20)?;
-        assert_eq!(splited.available_bytes(), 0);
-        assert!(splited.read_u8().is_err());
-        assert!(splited.read_u4().is_err());
-        assert!(splited.read_u128().is_err());
-        assert!(splited.read_u32().is_err());
-        assert!(splited.read_u64().is_err());
-        assert!(splited.read_bytes(&mut b).is_err());
-        assert!(splited.read_u8().is_err());
+        let (text, span) = reader.spanned(|r| {
+            let length = r.read_u16()? as usize;
+            r.split_off_front(length)?.read_utf8(length)
+        })?;
+        assert_eq!((text, span), ("ok", 0..4));
 
-        assert_eq!(a.available_bytes(), 20);
+        let (value, span) = reader.spanned(|r| r.peek_u8())?;
+        assert_eq!((value, span), (1, 4..4));
+        assert!(reader.spanned(|r| r.read_u16()).is_err());
+        assert_eq!(reader.current_offset(), 4);
 
         Ok(())
     }
 
+    #[cfg(feature = "std")]
     #[test]
-    fn test2() -> Result<(), BinaryFileReaderError> {
-        let buffer: Vec<u8> = (0..=255).collect();
+    fn test_events() -> Result<(), BinaryFileReaderError> {
+        use crate::{
+            chunk::{ChunkDispatcher, ChunkLayout},
+            events::ParseEvent,
+        };
+        use std::sync::{Arc, Mutex};
+
+        let buffer = vec![
+            0x00, 0x00, 0x00, 0x02, b'd', b'a', b't', b'a', 0x01, 0x02, 0xff,
+        ];
         let mut reader = BinaryFileReader::new(&buffer);
-        assert_eq!(reader.available_bytes(), 256);
+        assert_eq!(reader.field("ignored", |r| r.peek_u8())?, 0);
 
-        let slice = reader.peek_slice(10)?;
-        assert_eq!(slice, &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
-        assert_eq!(reader.available_bytes(), 256);
-        assert_eq!(reader.current_offset(), 0);
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let sink = log.clone();
+        reader.set_event_sink(move |event| {
+            let line = match event {
+                ParseEvent::SectionStart { name, offset } => format!("start {} {}", name, offset),
+                ParseEvent::Field {
+                    name,
+                    offset,
+                    length,
+                    value,
+                } => format!("field {} {}+{} {:?}", name, offset, length, value),
+                ParseEvent::SectionEnd {
+                    name,
+                    offset,
+                    length,
+                    ok,
+                } => format!("end {} {}+{} {}", name, offset, length, ok),
+            };
+            sink.lock().unwrap().push(line);
+        });
 
-        let slice = reader.read_slice(10)?;
-        assert_eq!(slice, &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
-        assert_eq!(reader.available_bytes(), 246);
-        assert_eq!(reader.current_offset(), 10);
+        let mut dispatcher = ChunkDispatcher::new(ChunkLayout::LengthTag).on(b"data", |sub| {
+            sub.field("values", |r| r.read_struct::<[u8; 2]>())
+        });
+        assert_eq!(dispatcher.dispatch(&mut reader)?, Some([1, 2]));
+        assert!(reader
+            .section("tail", |r| {
+                r.field("flag", |r| r.read_u8())?;
+                r.field("missing", |r| r.read_u8())
+            })
+            .is_err());
 
-        let mut reader2 = reader.split_off_front(100)?;
-        assert_eq!(reader.available_bytes(), 146);
-        assert_eq!(reader2.available_bytes(), 100);
-        assert_eq!(reader2.read_u8()?, 10);
-        assert_eq!(reader2.read_u16()?, 0x0b0c);
-        assert_eq!(reader2.read_u32()?, 0x0d0e0f10);
-        assert_eq!(reader2.read_u64()?, 0x1112131415161718);
-        assert_eq!(reader2.available_bytes(), 85);
-        assert_eq!(reader2.current_offset(), 25);
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                "start data 8",
+                "field values 8+2 [1, 2]",
+                "end data 8+2 true",
+                "start tail 10",
+                "field flag 10+1 255",
+                "end tail 10+1 false",
+            ]
+        );
+
+        // Seeking back past the start gives an empty span.
+        log.lock().unwrap().clear();
+        reader.section("rewind", |r| r.field("back", |r| r.set_offset(8)))?;
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                "start rewind 11",
+                "field back 11+0 ()",
+                "end rewind 11+0 true"
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_with_crc32() -> Result<(), BinaryFileReaderError> {
+        let buffer = b"xx123456789".to_vec();
+        let mut reader = BinaryFileReader::new(&buffer);
+        reader.read_slice(2)?;
+
+        let ((), crc) = reader.with_crc32(|r| {
+            r.read_slice(4)?;
+            let mut sub = r.split_off_front(5)?;
+            sub.read_slice(1).map(drop)
+        })?;
+        assert_eq!(crc, 0xcbf4_3926);
+
+        let ((), crc) = reader.with_crc32(|_| Ok(()))?;
+        assert_eq!(crc, 0);
+        assert!(reader.with_crc32(|r| r.read_u8()).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_crc32_chunk() -> Result<(), BinaryFileReaderError> {
+        let buffer = b"\x00\x00\x00\x00IEND\xae\x42\x60\x82\x00\x00\x00\x00IEND\xae\x42".to_vec();
+        let mut reader = BinaryFileReader::new(&buffer);
+
+        let (tag, payload) = reader.read_crc32_chunk()?;
+        assert_eq!(tag, *b"IEND");
+        assert_eq!(payload.available_bytes(), 0);
         assert!(matches!(
-            reader2.split_off_front(86),
+            reader.read_crc32_chunk(),
             Err(BinaryFileReaderError::BufferUnderflow {
-                requested_bytes: 86,
-                current_offset: 25,
-                available_bytes: 85
+                current_offset: 20,
+                ..
             })
         ));
-        let mut reader2 = reader2.split_off_front(85)?;
+        assert_eq!(reader.current_offset(), 12);
+
+        let err = BinaryFileReaderError::ChecksumMismatch {
+            stored: 0x1234,
+            computed: 0xabcd,
+            current_offset: 20,
+        };
+        assert_eq!(err.kind(), ErrorKind::ChecksumMismatch);
+        assert_eq!(err.code(), ErrorCode::ChecksumMismatch);
+        assert_eq!(ErrorCode::from_u16(80), Some(ErrorCode::ChecksumMismatch));
+        assert_eq!(err.offset(), Some(20));
+        assert_eq!(
+            err.to_string(),
+            "Checksum mismatch: stored 0x1234, but the data checksums to 0xabcd, at offset 0x14 (20)"
+        );
+
+        Ok(())
+    }
+
+    #[cfg(feature = "inflate")]
+    #[test]
+    fn test_zlib_sub_reader() -> Result<(), BinaryFileReaderError> {
+        // zlib level 0 of 00 01 02 03 with a 2-byte prefix.
+        let buffer = vec![
+            0xaa, 0xbb, 0x78, 0x01, 0x01, 0x04, 0x00, 0xfb, 0xff, 0x00, 0x01, 0x02, 0x03, 0x00,
+            0x0e, 0x00, 0x07,
+        ];
+        let mut reader = BinaryFileReader::new(&buffer);
+        reader.read_u16()?;
+        reader.set_endian(Endian::Little);
+
+        let mut checked = reader.clone();
+        checked.set_max_alloc(3);
         assert!(matches!(
-            reader2.split_off_front(86),
-            Err(BinaryFileReaderError::BufferUnderflow {
-                requested_bytes: 86,
-                current_offset: 25,
-                available_bytes: 85
+            checked.zlib_sub_reader(15),
+            Err(BinaryFileReaderError::AllocationLimit {
+                requested_bytes: 4,
+                max_alloc: 3,
+                ..
             })
         ));
+        assert_eq!(checked.current_offset(), 2);
 
-        let slice = reader.peek_slice(10)?;
-        assert_eq!(slice, &[110, 111, 112, 113, 114, 115, 116, 117, 118, 119]);
-        let slice = reader.read_slice(10)?;
-        assert_eq!(slice, &[110, 111, 112, 113, 114, 115, 116, 117, 118, 119]);
-        assert_eq!(reader.available_bytes(), 136);
+        let mut corrupt = buffer.clone();
+        corrupt[16] = 0;
+        let mut corrupt = BinaryFileReader::new(&corrupt);
+        corrupt.read_u16()?;
+        assert!(matches!(
+            corrupt.zlib_sub_reader(15),
+            Err(BinaryFileReaderError::ChecksumMismatch {
+                current_offset: 13,
+                ..
+            })
+        ));
+
+        let mut decompressed = reader.zlib_sub_reader(15)?;
+        assert_eq!(decompressed.reader().read_u32()?, 0x0302_0100);
+        assert_eq!(reader.available_bytes(), 0);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "lz4")]
+    #[test]
+    fn test_lz4_block_sub_reader() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0x00, 0x06, 0x35, b'a', b'b', b'c', 0x03, 0x00, 0x01];
+        let mut reader = BinaryFileReader::new(&buffer);
+        let length = reader.read_u16()? as usize;
+
+        let mut bad = reader.clone();
+        assert!(matches!(
+            bad.lz4_block_sub_reader(2),
+            Err(BinaryFileReaderError::Decompress {
+                reason: "the stream ends early",
+                current_offset: 3,
+            })
+        ));
+        assert_eq!(bad.current_offset(), 2);
+
+        let mut block = reader.lz4_block_sub_reader(length)?;
+        assert_eq!(block.reader().read_u32()?, 0x6162_6361);
+        assert_eq!(reader.read_u8()?, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_text_sub_readers() -> Result<(), BinaryFileReaderError> {
+        let buffer = b"2:Zm9v\n\"ff-00\"";
+        let mut reader = BinaryFileReader::new(buffer);
+        reader.set_endian(Endian::Little);
+        reader.set_max_alloc(2);
+        reader.expect(b"2:")?;
+
+        let err = reader.base64_sub_reader(4).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::AllocationLimit);
+        reader.set_max_alloc(3);
+        let mut foo = reader.base64_sub_reader(4)?;
+        assert_eq!(foo.reader().read_utf8(3)?, "foo");
+        assert_eq!(foo.reader().max_alloc(), 3);
+        reader.expect(b"\n\"")?;
+
+        let err = reader.hex_sub_reader(5).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidValue);
+        assert_eq!(err.offset(), Some(10));
+        assert_eq!(
+            err.to_string(),
+            "Invalid hex text: invalid character at offset 0xa (10)"
+        );
+        assert_eq!(reader.current_offset(), 8);
+        assert_eq!(reader.hex_sub_reader(2)?.reader().read_u8()?, 0xff);
+        assert_eq!(ErrorCode::from_u16(91), Some(ErrorCode::InvalidEncoding));
 
         Ok(())
     }
@@ -0,0 +1,317 @@
+use crate::{checksum::xxh32, error::BinaryFileReaderError};
+use alloc::vec::Vec;
+
+const FRAME_MAGIC: u32 = 0x184d_2204;
+
+/// Reads the input of a block or frame, reporting absolute offsets.
+struct Input<'d> {
+    data: &'d [u8],
+    position: usize,
+    base_offset: usize,
+}
+
+impl<'d> Input<'d> {
+    fn error(&self, reason: &'static str) -> BinaryFileReaderError {
+        BinaryFileReaderError::Decompress {
+            reason,
+            current_offset: self.base_offset + self.position,
+        }
+    }
+
+    fn take(&mut self, length: usize) -> Result<&'d [u8], BinaryFileReaderError> {
+        let bytes = self
+            .position
+            .checked_add(length)
+            .and_then(|end| self.data.get(self.position..end))
+            .ok_or_else(|| self.error("the stream ends early"))?;
+        self.position += length;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, BinaryFileReaderError> {
+        self.take(1).map(|bytes| bytes[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, BinaryFileReaderError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// Reads the extra bytes of a literal or match length whose token
+    /// nibble was 15.
+    fn length(&mut self, mut length: usize) -> Result<usize, BinaryFileReaderError> {
+        loop {
+            let byte = self.byte()?;
+            length = length
+                .checked_add(byte as usize)
+                .ok_or_else(|| self.error("length overflows"))?;
+            if byte != 255 {
+                return Ok(length);
+            }
+        }
+    }
+
+    fn checksum(&mut self, computed: u32) -> Result<(), BinaryFileReaderError> {
+        let current_offset = self.base_offset + self.position;
+        let stored = self.u32()?;
+        if stored != computed {
+            return Err(BinaryFileReaderError::ChecksumMismatch {
+                stored: stored as u64,
+                computed: computed as u64,
+                current_offset,
+            });
+        }
+        Ok(())
+    }
+}
+
+fn reserve(
+    output: &[u8],
+    length: usize,
+    max_output: usize,
+    input: &Input<'_>,
+) -> Result<(), BinaryFileReaderError> {
+    let requested_bytes = output.len().saturating_add(length);
+    if requested_bytes > max_output {
+        return Err(BinaryFileReaderError::AllocationLimit {
+            requested_bytes,
+            max_alloc: max_output,
+            current_offset: input.base_offset + input.position,
+        });
+    }
+    Ok(())
+}
+
+/// Decodes the sequences of one block, appending to `output`. Matches may
+/// reach back into bytes already in `output`, as linked frame blocks do.
+fn block(
+    input: &mut Input<'_>,
+    output: &mut Vec<u8>,
+    max_output: usize,
+) -> Result<(), BinaryFileReaderError> {
+    while input.position < input.data.len() {
+        let token = input.byte()?;
+        let mut literals = (token >> 4) as usize;
+        if literals == 15 {
+            literals = input.length(literals)?;
+        }
+        reserve(output, literals, max_output, input)?;
+        output.extend_from_slice(input.take(literals)?);
+        if input.position == input.data.len() {
+            break;
+        }
+
+        let offset = input.take(2)?;
+        let distance = u16::from_le_bytes([offset[0], offset[1]]) as usize;
+        if distance == 0 || distance > output.len() {
+            return Err(input.error("match offset is outside the output"));
+        }
+        let mut length = (token & 0x0f) as usize;
+        if length == 15 {
+            length = input.length(length)?;
+        }
+        let length = length + 4;
+        reserve(output, length, max_output, input)?;
+        let start = output.len() - distance;
+        // The copy may overlap the bytes it produces.
+        for offset in 0..length {
+            output.push(output[start + offset]);
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn block_at(
+    compressed: &[u8],
+    max_output: usize,
+    base_offset: usize,
+) -> Result<Vec<u8>, BinaryFileReaderError> {
+    let mut output = Vec::new();
+    let mut input = Input {
+        data: compressed,
+        position: 0,
+        base_offset,
+    };
+    block(&mut input, &mut output, max_output)?;
+    Ok(output)
+}
+
+/// Decompresses one raw LZ4 block, as stored by formats that record the
+/// compressed and decompressed sizes themselves. Fails with
+/// [`BinaryFileReaderError::Decompress`] for malformed data, and with
+/// [`BinaryFileReaderError::AllocationLimit`] if the output would exceed
+/// `max_output` bytes. Offsets in errors are relative to `compressed`.
+///
+/// Only available with the `lz4` feature.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::lz4::lz4_block_decompress;
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// // Three literals, then a 9-byte match 3 bytes back.
+/// let compressed = [0x35, b'a', b'b', b'c', 0x03, 0x00];
+/// assert_eq!(lz4_block_decompress(&compressed, usize::MAX)?, b"abcabcabcabc");
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+pub fn lz4_block_decompress(
+    compressed: &[u8],
+    max_output: usize,
+) -> Result<Vec<u8>, BinaryFileReaderError> {
+    block_at(compressed, max_output, 0)
+}
+
+pub(crate) fn frame_at(
+    compressed: &[u8],
+    max_output: usize,
+    base_offset: usize,
+) -> Result<Vec<u8>, BinaryFileReaderError> {
+    let mut input = Input {
+        data: compressed,
+        position: 0,
+        base_offset,
+    };
+    if input.u32()? != FRAME_MAGIC {
+        return Err(BinaryFileReaderError::Decompress {
+            reason: "not an LZ4 frame",
+            current_offset: base_offset,
+        });
+    }
+
+    let header_start = input.position;
+    let flags = input.byte()?;
+    input.byte()?;
+    if flags >> 6 != 0b01 {
+        return Err(input.error("unsupported frame version"));
+    }
+    if flags & 0x01 != 0 {
+        return Err(input.error("dictionaries are not supported"));
+    }
+    let block_checksums = flags & 0x10 != 0;
+    let content_checksum = flags & 0x04 != 0;
+    if flags & 0x08 != 0 {
+        input.take(8)?;
+    }
+    let header = &compressed[header_start..input.position];
+    let stored = input.byte()?;
+    let computed = (xxh32(header, 0) >> 8) as u8;
+    if stored != computed {
+        return Err(BinaryFileReaderError::ChecksumMismatch {
+            stored: stored as u64,
+            computed: computed as u64,
+            current_offset: base_offset + input.position - 1,
+        });
+    }
+
+    let mut output = Vec::new();
+    loop {
+        let size = input.u32()?;
+        if size == 0 {
+            break;
+        }
+        let data = input.take((size & 0x7fff_ffff) as usize)?;
+        let data_offset = base_offset + input.position - data.len();
+        if size & 0x8000_0000 != 0 {
+            reserve(&output, data.len(), max_output, &input)?;
+            output.extend_from_slice(data);
+        } else {
+            let mut block_input = Input {
+                data,
+                position: 0,
+                base_offset: data_offset,
+            };
+            block(&mut block_input, &mut output, max_output)?;
+        }
+        if block_checksums {
+            input.checksum(xxh32(data, 0))?;
+        }
+    }
+    if content_checksum {
+        input.checksum(xxh32(&output, 0))?;
+    }
+    Ok(output)
+}
+
+/// Decompresses an LZ4 frame, as written by the `lz4` command-line tool,
+/// and verifies its header, block and content checksums. Dictionaries are
+/// not supported, and bytes after the frame are ignored. See
+/// [`lz4_block_decompress`] for the errors.
+///
+/// Only available with the `lz4` feature.
+pub fn lz4_frame_decompress(
+    compressed: &[u8],
+    max_output: usize,
+) -> Result<Vec<u8>, BinaryFileReaderError> {
+    frame_at(compressed, max_output, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lz4_block_decompress, lz4_frame_decompress};
+    use crate::error::{BinaryFileReaderError, ErrorCode};
+    use alloc::vec;
+
+    #[test]
+    fn test_lz4_block() -> Result<(), BinaryFileReaderError> {
+        assert_eq!(lz4_block_decompress(&[], usize::MAX)?, b"");
+        assert_eq!(lz4_block_decompress(&[0x10, b'x'], usize::MAX)?, b"x");
+
+        // 15 + 5 literals, then a 4 + 15 + 1 byte match of the last one.
+        let mut long = vec![0xff, 0x05];
+        long.extend(b"abcdefghijklmnopqrst");
+        long.extend([0x01, 0x00, 0x01]);
+        let output = lz4_block_decompress(&long, usize::MAX)?;
+        assert_eq!(output.len(), 40);
+        assert!(output.ends_with(&[b't'; 21]));
+
+        assert!(matches!(
+            lz4_block_decompress(&long, 30),
+            Err(BinaryFileReaderError::AllocationLimit {
+                requested_bytes: 40,
+                ..
+            })
+        ));
+        assert!(matches!(
+            lz4_block_decompress(&[0x10, b'x', 0x02, 0x00], usize::MAX),
+            Err(BinaryFileReaderError::Decompress {
+                reason: "match offset is outside the output",
+                current_offset: 4,
+            })
+        ));
+        assert!(lz4_block_decompress(&[0x20, b'x'], usize::MAX).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lz4_frame() -> Result<(), BinaryFileReaderError> {
+        // A frame with block and content checksums, holding a compressed
+        // block and a stored one.
+        let frame = [
+            0x04, 0x22, 0x4d, 0x18, 0x74, 0x40, 0xbd, 0x06, 0x00, 0x00, 0x00, 0x35, 0x61, 0x62,
+            0x63, 0x03, 0x00, 0x61, 0xd2, 0xb7, 0x9f, 0x02, 0x00, 0x00, 0x80, 0x21, 0x21, 0xb2,
+            0x9a, 0x23, 0xb3, 0x00, 0x00, 0x00, 0x00, 0xbb, 0x4d, 0x00, 0xc2,
+        ];
+        assert_eq!(lz4_frame_decompress(&frame, usize::MAX)?, b"abcabcabcabc!!");
+
+        for (index, code) in [
+            (4, ErrorCode::Decompress),
+            (6, ErrorCode::ChecksumMismatch),
+            (14, ErrorCode::ChecksumMismatch),
+            (35, ErrorCode::ChecksumMismatch),
+        ] {
+            let mut corrupt = frame;
+            corrupt[index] ^= 0x40;
+            let err = lz4_frame_decompress(&corrupt, usize::MAX).unwrap_err();
+            assert_eq!(err.code(), code, "{}", index);
+        }
+        assert!(lz4_frame_decompress(&frame[..30], usize::MAX).is_err());
+        assert!(lz4_frame_decompress(b"PK\x03\x04", usize::MAX).is_err());
+
+        Ok(())
+    }
+}
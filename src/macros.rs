@@ -0,0 +1,47 @@
+/// # Examples
+/// ```
+/// # use binary_file_reader::{read_fields, BinaryFileReader};
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// #[derive(Debug, PartialEq)]
+/// struct Ihdr {
+///     width: u32,
+///     height: u32,
+///     bit_depth: u8,
+/// }
+///
+/// let buffer = vec![0, 0, 0, 100, 0, 0, 0, 50, 8, 0x12, 0x34, 0x56];
+/// let mut reader = BinaryFileReader::new(&buffer);
+///
+/// let ihdr = read_fields!(reader, Ihdr { width: u32, height: u32, bit_depth: u8 })?;
+/// assert_eq!(ihdr, Ihdr { width: 100, height: 50, bit_depth: 8 });
+///
+/// let (tag, level) = read_fields!(reader, { tag: u16, level: u8 })?;
+/// assert_eq!((tag, level), (0x1234, 0x56));
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+#[macro_export]
+macro_rules! read_fields {
+    ($reader:expr, $($name:ident)::+ { $($field:ident : $ty:ty),* $(,)? }) => {{
+        use $crate::read_from::ReadWith as _;
+        ($reader).read_with(|__reader| {
+            $(
+                let $field = <$ty as $crate::read_from::ReadFrom>::read_from(__reader)?;
+            )*
+            ::core::result::Result::Ok($($name)::+ { $($field),* })
+        })
+    }};
+    ($reader:expr, { $($field:ident : $ty:ty),* $(,)? }) => {{
+        use $crate::read_from::ReadWith as _;
+        ($reader).read_with(|__reader| {
+            $(
+                let $field = <$ty as $crate::read_from::ReadFrom>::read_from(__reader)?;
+            )*
+            ::core::result::Result::Ok(($($field,)*))
+        })
+    }};
+}
@@ -0,0 +1,559 @@
+use crate::{error::BinaryFileReaderError, BinaryFileReader};
+use alloc::{borrow::Cow, sync::Arc, vec::Vec};
+use core::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+#[cfg(feature = "std")]
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// A reader that owns its buffer, so it can be stored in structs or returned
+/// from functions. Reads go through [`reader`](Self::reader), which borrows it
+/// as a [`BinaryFileReader`] and keeps the position when the borrow ends.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::BinaryFileReader;
+/// # use binary_file_reader::owned::OwnedBinaryFileReader;
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// fn open() -> OwnedBinaryFileReader {
+///     BinaryFileReader::from_owned(vec![0x00, 0x01, 0x02, 0x03])
+/// }
+///
+/// let mut owned = open();
+/// assert_eq!(owned.reader().read_u16()?, 1);
+/// assert_eq!(owned.reader().read_u8()?, 2);
+/// assert_eq!(owned.current_offset(), 3);
+/// assert_eq!(owned.into_inner(), vec![0x00, 0x01, 0x02, 0x03]);
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct OwnedBinaryFileReader {
+    buf: Vec<u8>,
+    // The cursor state, detached from `buf` while no borrow is active.
+    state: BinaryFileReader<'static>,
+    #[cfg(feature = "std")]
+    path: Option<PathBuf>,
+}
+
+impl OwnedBinaryFileReader {
+    pub fn new(buffer: Vec<u8>) -> Self {
+        let state = BinaryFileReader::new(&buffer).rebind(&[]);
+        Self {
+            buf: buffer,
+            state,
+            #[cfg(feature = "std")]
+            path: None,
+        }
+    }
+
+    /// An owned reader over `buffer`, such as decompressed data, with the
+    /// endianness, limits, cancel token and source name of `parent`.
+    pub(crate) fn derived(buffer: Vec<u8>, parent: &BinaryFileReader<'_>) -> Self {
+        let mut reader = Self::new(buffer);
+        reader.state.endian = parent.endian;
+        reader.state.max_depth = parent.max_depth;
+        reader.state.max_alloc = parent.max_alloc;
+        reader.state.max_error_bytes = parent.max_error_bytes;
+        reader.state.cancel = parent.cancel.clone();
+        reader.state.source_name = parent.source_name.clone();
+        reader
+    }
+
+    /// Reads the whole file at `path`. The path is kept for diagnostics, used
+    /// as the [source name](BinaryFileReader::with_source_name) and included
+    /// in the error if the file cannot be read.
+    #[cfg(feature = "std")]
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, BinaryFileReaderError> {
+        let path = path.as_ref();
+        let buffer = fs::read(path)
+            .map_err(|err| io::Error::new(err.kind(), format!("{}: {}", path.display(), err)))?;
+        let mut reader = Self::new(buffer);
+        reader.state = reader.state.with_source_name(path.display().to_string());
+        reader.path = Some(path.to_path_buf());
+        Ok(reader)
+    }
+
+    /// The path the buffer was read from, if it was created with
+    /// [`from_path`](Self::from_path).
+    #[cfg(feature = "std")]
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Borrows the reader. Reads through the returned guard advance this
+    /// reader.
+    pub fn reader(&mut self) -> ReaderGuard<'_> {
+        ReaderGuard {
+            reader: self.state.rebind(&self.buf),
+            state: &mut self.state,
+            len: self.buf.len(),
+        }
+    }
+
+    /// Borrows a copy of the reader at the current position. Reads through it
+    /// do not advance this reader.
+    pub fn as_reader(&self) -> BinaryFileReader<'_> {
+        self.state.rebind(&self.buf)
+    }
+
+    #[inline]
+    pub fn current_offset(&self) -> usize {
+        self.state.current_offset
+    }
+
+    #[inline]
+    pub fn available_bytes(&self) -> usize {
+        self.state.available_bytes()
+    }
+
+    pub fn into_inner(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+/// Shows the reader, not the buffer. See [`BinaryFileReader`]'s `Debug`.
+impl fmt::Debug for OwnedBinaryFileReader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("OwnedBinaryFileReader");
+        #[cfg(feature = "std")]
+        debug.field("path", &self.path);
+        debug.field("reader", &self.as_reader()).finish()
+    }
+}
+
+impl From<Vec<u8>> for OwnedBinaryFileReader {
+    fn from(value: Vec<u8>) -> Self {
+        Self::new(value)
+    }
+}
+
+/// A reader over a reference-counted buffer. Sub-readers share the buffer, so
+/// they are `'static` and can be sent to other threads.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::owned::ArcBinaryFileReader;
+/// # use std::thread;
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut reader = ArcBinaryFileReader::from(vec![0x00, 0x01, 0x00, 0x02]);
+///
+/// let handles = (0..2)
+///     .map(|_| {
+///         let mut chunk = reader.split_off_front(2)?;
+///         Ok(thread::spawn(move || chunk.reader().read_u16()))
+///     })
+///     .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+///
+/// let values = handles
+///     .into_iter()
+///     .map(|handle| handle.join().unwrap())
+///     .collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(values, vec![1, 2]);
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ArcBinaryFileReader {
+    buf: Arc<[u8]>,
+    // The cursor state, detached from `buf` while no borrow is active.
+    state: BinaryFileReader<'static>,
+}
+
+impl ArcBinaryFileReader {
+    pub fn new(buffer: Arc<[u8]>) -> Self {
+        let state = BinaryFileReader::new(&buffer).rebind(&[]);
+        Self { buf: buffer, state }
+    }
+
+    /// Borrows the reader. Reads through the returned guard advance this
+    /// reader.
+    pub fn reader(&mut self) -> ReaderGuard<'_> {
+        ReaderGuard {
+            reader: self.state.rebind(&self.buf),
+            state: &mut self.state,
+            len: self.buf.len(),
+        }
+    }
+
+    /// Borrows a copy of the reader at the current position. Reads through it
+    /// do not advance this reader.
+    pub fn as_reader(&self) -> BinaryFileReader<'_> {
+        self.state.rebind(&self.buf)
+    }
+
+    #[inline]
+    pub fn current_offset(&self) -> usize {
+        self.state.current_offset
+    }
+
+    #[inline]
+    pub fn available_bytes(&self) -> usize {
+        self.state.available_bytes()
+    }
+
+    /// Like [`BinaryFileReader::split_off_front`], but the sub-reader shares
+    /// this reader's buffer instead of borrowing it.
+    pub fn split_off_front(&mut self, size: usize) -> Result<Self, BinaryFileReaderError> {
+        let mut reader = self.state.rebind(&self.buf);
+        let state = reader.split_off_front(size)?.rebind(&[]);
+        self.state = reader.rebind(&[]);
+        Ok(Self {
+            buf: Arc::clone(&self.buf),
+            state,
+        })
+    }
+
+    pub fn into_inner(self) -> Arc<[u8]> {
+        self.buf
+    }
+}
+
+impl fmt::Debug for ArcBinaryFileReader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArcBinaryFileReader")
+            .field("reader", &self.as_reader())
+            .finish()
+    }
+}
+
+impl From<Arc<[u8]>> for ArcBinaryFileReader {
+    fn from(value: Arc<[u8]>) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<Vec<u8>> for ArcBinaryFileReader {
+    fn from(value: Vec<u8>) -> Self {
+        Self::new(value.into())
+    }
+}
+
+/// A reader over either a borrowed or an owned buffer, for APIs that accept
+/// both.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::BinaryFileReader;
+/// # use std::borrow::Cow;
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// let buffer = vec![0x00, 0x01, 0x00, 0x02];
+///
+/// let mut borrowed = BinaryFileReader::from_cow(Cow::Borrowed(&buffer));
+/// let mut owned = BinaryFileReader::from_cow(Cow::Owned(buffer.clone()));
+/// assert_eq!(borrowed.reader().read_u16()?, 1);
+/// assert_eq!(owned.reader().read_u16()?, 1);
+///
+/// let mut sub = borrowed.split_off_front(2)?;
+/// assert!(sub.is_borrowed());
+/// assert_eq!(sub.reader().read_u16()?, 2);
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct CowBinaryFileReader<'a> {
+    buf: Cow<'a, [u8]>,
+    // The cursor state, detached from `buf` while no borrow is active.
+    state: BinaryFileReader<'static>,
+}
+
+impl<'a> CowBinaryFileReader<'a> {
+    pub fn new(buffer: Cow<'a, [u8]>) -> Self {
+        let state = BinaryFileReader::new(&buffer).rebind(&[]);
+        Self { buf: buffer, state }
+    }
+
+    /// Borrows the reader. Reads through the returned guard advance this
+    /// reader.
+    pub fn reader(&mut self) -> ReaderGuard<'_> {
+        ReaderGuard {
+            reader: self.state.rebind(&self.buf),
+            state: &mut self.state,
+            len: self.buf.len(),
+        }
+    }
+
+    /// Borrows a copy of the reader at the current position. Reads through it
+    /// do not advance this reader.
+    pub fn as_reader(&self) -> BinaryFileReader<'_> {
+        self.state.rebind(&self.buf)
+    }
+
+    #[inline]
+    pub fn current_offset(&self) -> usize {
+        self.state.current_offset
+    }
+
+    #[inline]
+    pub fn available_bytes(&self) -> usize {
+        self.state.available_bytes()
+    }
+
+    #[inline]
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self.buf, Cow::Borrowed(_))
+    }
+
+    /// Like [`BinaryFileReader::split_off_front`]. A borrowed buffer is
+    /// shared with the sub-reader. An owned buffer has the split-off bytes
+    /// copied into a new buffer, so offsets in that sub-reader start at 0.
+    pub fn split_off_front(&mut self, size: usize) -> Result<Self, BinaryFileReaderError> {
+        match self.buf {
+            Cow::Borrowed(buf) => {
+                let mut reader = self.state.rebind(buf);
+                let sub = reader.split_off_front(size)?;
+                self.state = reader.rebind(&[]);
+                Ok(Self {
+                    buf: Cow::Borrowed(buf),
+                    state: sub.rebind(&[]),
+                })
+            }
+            Cow::Owned(ref buf) => {
+                let mut reader = self.state.rebind(buf);
+                let sub = reader.split_off_front(size)?;
+                self.state = reader.rebind(&[]);
+
+                let bytes = sub.remaining().to_vec();
+                let mut state = sub.rebind(&[]);
+                state.current_offset = 0;
+                state.own_start = 0;
+                state.own_left = bytes.len();
+                Ok(Self {
+                    buf: Cow::Owned(bytes),
+                    state,
+                })
+            }
+        }
+    }
+
+    pub fn into_inner(self) -> Cow<'a, [u8]> {
+        self.buf
+    }
+}
+
+impl fmt::Debug for CowBinaryFileReader<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CowBinaryFileReader")
+            .field("borrowed", &matches!(self.buf, Cow::Borrowed(_)))
+            .field("reader", &self.as_reader())
+            .finish()
+    }
+}
+
+impl<'a> From<Cow<'a, [u8]>> for CowBinaryFileReader<'a> {
+    fn from(value: Cow<'a, [u8]>) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<'a> From<&'a [u8]> for CowBinaryFileReader<'a> {
+    fn from(value: &'a [u8]) -> Self {
+        Self::new(Cow::Borrowed(value))
+    }
+}
+
+impl From<Vec<u8>> for CowBinaryFileReader<'_> {
+    fn from(value: Vec<u8>) -> Self {
+        Self::new(Cow::Owned(value))
+    }
+}
+
+/// A [`BinaryFileReader`] borrowed from an owning reader. The position is
+/// written back when the guard is dropped.
+///
+/// If the reader was replaced by one whose region does not fit the owned
+/// buffer, such as a reader over a longer slice, the owning reader keeps the
+/// position it had before the borrow.
+#[derive(Debug)]
+pub struct ReaderGuard<'r> {
+    reader: BinaryFileReader<'r>,
+    state: &'r mut BinaryFileReader<'static>,
+    // The length of the owned buffer, to check the region written back.
+    len: usize,
+}
+
+impl<'r> Deref for ReaderGuard<'r> {
+    type Target = BinaryFileReader<'r>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.reader
+    }
+}
+
+impl DerefMut for ReaderGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.reader
+    }
+}
+
+impl Drop for ReaderGuard<'_> {
+    fn drop(&mut self) {
+        let reader = &self.reader;
+        if reader.own_start <= reader.current_offset
+            && reader.current_offset <= reader.own_left
+            && reader.own_left <= self.len
+        {
+            *self.state = reader.rebind(&[]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArcBinaryFileReader, CowBinaryFileReader, OwnedBinaryFileReader};
+    use crate::{endian::Endian, error::BinaryFileReaderError, BinaryFileReader};
+    use alloc::{borrow::Cow, sync::Arc, vec};
+
+    struct Archive {
+        reader: OwnedBinaryFileReader,
+    }
+
+    #[test]
+    fn test_owned_reader() -> Result<(), BinaryFileReaderError> {
+        let mut archive = Archive {
+            reader: BinaryFileReader::from_owned(vec![0x02, 0x00, 0x01, 0x00, 0x02, 0xff]),
+        };
+
+        {
+            let mut reader = archive.reader.reader();
+            reader.set_endian(Endian::Little);
+            assert_eq!(reader.read_u16()?, 2);
+        }
+        assert_eq!(archive.reader.current_offset(), 2);
+        assert_eq!(archive.reader.as_reader().endian(), Endian::Little);
+
+        let mut peek = archive.reader.as_reader();
+        assert_eq!(peek.read_u16()?, 1);
+        assert_eq!(archive.reader.current_offset(), 2);
+
+        let mut reader = archive.reader.reader();
+        let mut sub = reader.split_off_front(2)?;
+        assert_eq!(sub.read_u16()?, 1);
+        drop(reader);
+        assert_eq!(archive.reader.available_bytes(), 2);
+
+        let cloned = archive.reader.clone();
+        assert_eq!(cloned.current_offset(), 4);
+        assert!(archive.reader.reader().read_u32().is_err());
+        assert_eq!(archive.reader.current_offset(), 4);
+
+        // A replacement reader over a longer buffer is not written back.
+        let longer = [0; 100];
+        *archive.reader.reader() = BinaryFileReader::new(&longer);
+        assert_eq!(archive.reader.current_offset(), 4);
+        assert_eq!(archive.reader.reader().read_u16()?, 0xff02);
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_from_path() -> Result<(), BinaryFileReaderError> {
+        use std::{io, path::Path};
+
+        let mut reader = BinaryFileReader::from_path("sample-files/1.png")?;
+        assert_eq!(reader.path(), Some(Path::new("sample-files/1.png")));
+        reader
+            .reader()
+            .expect(&[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A])?;
+        assert_eq!(reader.current_offset(), 8);
+        assert_eq!(reader.as_reader().source_name(), Some("sample-files/1.png"));
+        let debug = format!("{:?}", reader);
+        assert!(debug.starts_with("OwnedBinaryFileReader { path: Some(\"sample-files/1.png\"), reader: BinaryFileReader {"));
+        assert!(debug.len() < 400);
+        assert_eq!(OwnedBinaryFileReader::new(Vec::new()).path(), None);
+
+        match BinaryFileReader::from_path("sample-files/missing.png") {
+            Err(BinaryFileReaderError::IO(err)) => {
+                assert_eq!(err.kind(), io::ErrorKind::NotFound);
+                assert!(err.to_string().starts_with("sample-files/missing.png: "));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_arc_reader() -> Result<(), BinaryFileReaderError> {
+        fn assert_send_static<T: Send + Sync + 'static>(_: &T) {}
+
+        let buffer: Arc<[u8]> = Arc::from(vec![0x00, 0x01, 0x02, 0x03, 0x04]);
+        let mut reader = ArcBinaryFileReader::new(Arc::clone(&buffer));
+        reader.reader().read_u8()?;
+
+        let mut sub = reader.split_off_front(2)?;
+        assert_send_static(&sub);
+        assert_eq!(Arc::strong_count(&buffer), 3);
+        assert_eq!(reader.current_offset(), 3);
+        assert_eq!(sub.current_offset(), 1);
+        assert_eq!(sub.available_bytes(), 2);
+        assert_eq!(sub.reader().read_u16()?, 0x0102);
+        assert!(sub.reader().read_u8().is_err());
+
+        assert!(matches!(
+            reader.split_off_front(3),
+            Err(BinaryFileReaderError::BufferUnderflow {
+                requested_bytes: 3,
+                current_offset: 3,
+                available_bytes: 2
+            })
+        ));
+        assert_eq!(reader.as_reader().read_u16()?, 0x0304);
+
+        drop(sub);
+        assert_eq!(Arc::strong_count(&reader.into_inner()), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cow_reader() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0x00, 0x01, 0x02, 0x03, 0x04];
+
+        let mut borrowed = CowBinaryFileReader::from(&buffer[..]);
+        borrowed.reader().read_u8()?;
+        let mut sub = borrowed.split_off_front(2)?;
+        assert!(sub.is_borrowed());
+        assert_eq!(sub.current_offset(), 1);
+        assert_eq!(sub.reader().read_u16()?, 0x0102);
+        assert!(matches!(sub.into_inner(), Cow::Borrowed(b) if b.as_ptr() == buffer.as_ptr()));
+        assert_eq!(borrowed.current_offset(), 3);
+
+        let mut owned = CowBinaryFileReader::from(buffer.clone());
+        owned.reader().read_u8()?;
+        let mut sub = owned.split_off_front(2)?;
+        assert!(!sub.is_borrowed());
+        assert_eq!(sub.current_offset(), 0);
+        assert_eq!(sub.reader().read_u16()?, 0x0102);
+        assert!(sub.reader().read_u8().is_err());
+        assert_eq!(owned.as_reader().read_u16()?, 0x0304);
+        assert!(owned.split_off_front(3).is_err());
+        assert_eq!(owned.current_offset(), 3);
+
+        // The copied sub-reader's region is the whole new buffer.
+        sub.reader().set_offset(0)?;
+        assert_eq!(sub.reader().read_u8()?, 0x01);
+        assert!(sub.as_reader().hexdump(0, 2).starts_with("00000000: 0102"));
+        let state = sub.as_reader().save_state();
+        assert_eq!((state.start, state.current_offset, state.end), (0, 1, 2));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,303 @@
+use crate::{endian::Endian, error::BinaryFileReaderError, source::DataSource};
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+/// A reader over a [`Read`] + [`Seek`] source that loads fixed-size pages on
+/// demand and keeps the most recently used ones, so jumping around a large
+/// file only reads the pages that are touched. A read that spans pages is
+/// stitched together in a scratch buffer.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::paged::PagedReader;
+/// # use binary_file_reader::source::DataSource;
+/// # use std::io::Cursor;
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// // An offset table pointing into a large file.
+/// let mut data = vec![0; 1 << 20];
+/// data[..4].copy_from_slice(&0x000f_fff0u32.to_be_bytes());
+/// data[0x000f_fff0..0x000f_fff2].copy_from_slice(&[0xca, 0xfe]);
+///
+/// let mut reader = PagedReader::new(Cursor::new(data))?;
+/// let offset = reader.read_u32()? as usize;
+/// let value = reader.follow_offset(offset, |r| r.read_u16())?;
+/// assert_eq!(value, 0xcafe);
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct PagedReader<R> {
+    inner: R,
+    page_size: usize,
+    max_pages: usize,
+    // Cached pages with their index, most recently used first.
+    pages: Vec<(usize, Vec<u8>)>,
+    scratch: Vec<u8>,
+    offset: usize,
+    length: usize,
+    endian: Endian,
+}
+
+impl PagedReader<File> {
+    /// Opens the file at `path`. The path is included in the error if the
+    /// file cannot be opened.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, BinaryFileReaderError> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .map_err(|err| io::Error::new(err.kind(), format!("{}: {}", path.display(), err)))?;
+        Self::new(file)
+    }
+}
+
+impl<R: Read + Seek> PagedReader<R> {
+    /// The page size used by [`new`](Self::new).
+    pub const DEFAULT_PAGE_SIZE: usize = 4 * 1024;
+    /// The number of pages kept by [`new`](Self::new).
+    pub const DEFAULT_MAX_PAGES: usize = 16;
+
+    pub fn new(inner: R) -> Result<Self, BinaryFileReaderError> {
+        Self::with_pages(Self::DEFAULT_PAGE_SIZE, Self::DEFAULT_MAX_PAGES, inner)
+    }
+
+    /// # Panics
+    /// Panics if `page_size` or `max_pages` is 0.
+    pub fn with_pages(
+        page_size: usize,
+        max_pages: usize,
+        mut inner: R,
+    ) -> Result<Self, BinaryFileReaderError> {
+        assert!(page_size != 0, "page size must be non-zero");
+        assert!(max_pages != 0, "page count must be non-zero");
+        let length = inner.seek(SeekFrom::End(0))?;
+        let length = usize::try_from(length)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "source is too large"))?;
+
+        Ok(Self {
+            inner,
+            page_size,
+            max_pages,
+            pages: Vec::with_capacity(max_pages),
+            scratch: Vec::new(),
+            offset: 0,
+            length,
+            endian: Endian::Big,
+        })
+    }
+
+    #[inline]
+    pub fn current_offset(&self) -> usize {
+        self.offset
+    }
+
+    #[inline]
+    pub fn available_bytes(&self) -> usize {
+        self.length - self.offset
+    }
+
+    #[inline]
+    pub fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    #[inline]
+    pub fn set_endian(&mut self, endian: Endian) {
+        self.endian = endian;
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Moves the cursor to `offset`, which may be anywhere up to the end of
+    /// the source.
+    pub fn set_offset(&mut self, offset: usize) -> Result<(), BinaryFileReaderError> {
+        if offset > self.length {
+            return Err(BinaryFileReaderError::OutOfRange {
+                buffer_size: self.length,
+                got: offset,
+            });
+        }
+
+        self.offset = offset;
+        Ok(())
+    }
+
+    /// Runs `f` with the cursor at `offset` and restores the cursor
+    /// afterwards, whether or not `f` succeeds.
+    pub fn follow_offset<T, F>(&mut self, offset: usize, f: F) -> Result<T, BinaryFileReaderError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, BinaryFileReaderError>,
+    {
+        let saved = self.offset;
+        self.set_offset(offset)?;
+        let result = f(self);
+        self.offset = saved;
+        result
+    }
+
+    /// Moves the page `index` to the front of the cache, reading it if it is
+    /// not cached.
+    fn load(&mut self, index: usize) -> Result<(), BinaryFileReaderError> {
+        if let Some(position) = self.pages.iter().position(|(cached, _)| *cached == index) {
+            let page = self.pages.remove(position);
+            self.pages.insert(0, page);
+            return Ok(());
+        }
+
+        let mut page = if self.pages.len() == self.max_pages {
+            self.pages.pop().map(|(_, page)| page).unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        let start = index * self.page_size;
+        page.resize(self.page_size.min(self.length - start), 0);
+
+        self.inner.seek(SeekFrom::Start(start as u64))?;
+        self.inner.read_exact(&mut page)?;
+        self.pages.insert(0, (index, page));
+        Ok(())
+    }
+}
+
+impl<R: Read + Seek> DataSource for PagedReader<R> {
+    fn fill(&mut self, length: usize) -> Result<&[u8], BinaryFileReaderError> {
+        let wanted = length.min(self.available_bytes());
+        if wanted == 0 {
+            return Ok(&[]);
+        }
+
+        let within = self.offset % self.page_size;
+        self.load(self.offset / self.page_size)?;
+        if self.pages[0].1.len() - within >= wanted {
+            return Ok(&self.pages[0].1[within..]);
+        }
+
+        self.scratch.clear();
+        let mut position = self.offset;
+        while self.scratch.len() < wanted {
+            self.load(position / self.page_size)?;
+            let page = &self.pages[0].1;
+            let within = position % self.page_size;
+            let take = (wanted - self.scratch.len()).min(page.len() - within);
+            self.scratch.extend_from_slice(&page[within..within + take]);
+            position += take;
+        }
+
+        Ok(&self.scratch)
+    }
+
+    fn consume(&mut self, length: usize) {
+        debug_assert!(length <= self.available_bytes());
+        self.offset += length;
+    }
+
+    #[inline]
+    fn window_size(&self) -> usize {
+        self.page_size
+    }
+
+    #[inline]
+    fn current_offset(&self) -> usize {
+        self.offset
+    }
+
+    #[inline]
+    fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    /// Moves the cursor `length` bytes forward without reading.
+    fn skip(&mut self, length: usize) -> Result<(), BinaryFileReaderError> {
+        if length > self.available_bytes() {
+            return Err(BinaryFileReaderError::BufferUnderflow {
+                requested_bytes: length,
+                current_offset: self.offset,
+                available_bytes: self.available_bytes(),
+            });
+        }
+
+        self.offset += length;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PagedReader;
+    use crate::{error::BinaryFileReaderError, source::DataSource};
+    use std::io::{Cursor, Read, Seek, SeekFrom};
+
+    /// Counts the reads made against the wrapped source.
+    struct CountingReads<R> {
+        inner: R,
+        reads: usize,
+    }
+
+    impl<R: Read> Read for CountingReads<R> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.reads += 1;
+            self.inner.read(buf)
+        }
+    }
+
+    impl<R: Seek> Seek for CountingReads<R> {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    #[test]
+    fn test_paged_reader() -> Result<(), BinaryFileReaderError> {
+        let data = (0..=255).collect::<Vec<u8>>();
+        let source = CountingReads {
+            inner: Cursor::new(data),
+            reads: 0,
+        };
+        let mut reader = PagedReader::with_pages(16, 2, source)?;
+
+        assert_eq!(reader.read_u32()?, 0x00010203);
+        assert_eq!(reader.inner.reads, 1);
+
+        // Spans pages 0 and 1, both of which stay cached.
+        reader.set_offset(14)?;
+        assert_eq!(reader.read_u32()?, 0x0e0f1011);
+        reader.set_offset(2)?;
+        assert_eq!(reader.read_u16()?, 0x0203);
+        assert_eq!(reader.inner.reads, 2);
+
+        // Page 15 evicts page 1, the least recently used.
+        reader.set_offset(250)?;
+        assert_eq!(reader.peek_slice(6)?, &[250, 251, 252, 253, 254, 255]);
+        assert_eq!(reader.follow_offset(0, |r| r.read_u8())?, 0);
+        assert_eq!(reader.inner.reads, 3);
+        assert_eq!(reader.follow_offset(16, |r| r.read_u8())?, 16);
+        assert_eq!(reader.inner.reads, 4);
+
+        assert!(matches!(
+            reader.read_u64(),
+            Err(BinaryFileReaderError::BufferUnderflow {
+                current_offset: 250,
+                available_bytes: 6,
+                ..
+            })
+        ));
+        assert!(matches!(
+            reader.peek_slice(17),
+            Err(BinaryFileReaderError::WindowExceeded {
+                requested_bytes: 17,
+                window_size: 16
+            })
+        ));
+        reader.skip(6)?;
+        assert!(reader.is_at_end()?);
+
+        Ok(())
+    }
+}
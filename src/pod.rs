@@ -0,0 +1,115 @@
+use alloc::borrow::Cow;
+use core::mem;
+
+use crate::{error::BinaryFileReaderError, BinaryFileReader};
+
+/// Types that can be read by reinterpreting their in-memory bytes.
+///
+/// # Safety
+/// Every bit pattern of `size_of::<Self>()` bytes must be a valid value of
+/// `Self`, and `Self` must not contain padding bytes or pointers. `#[repr(C)]`
+/// structs made only of `Pod` fields without padding satisfy this.
+pub unsafe trait Pod: Copy + 'static {}
+
+unsafe impl Pod for u8 {}
+unsafe impl Pod for u16 {}
+unsafe impl Pod for u32 {}
+unsafe impl Pod for u64 {}
+unsafe impl Pod for u128 {}
+unsafe impl Pod for i8 {}
+unsafe impl Pod for i16 {}
+unsafe impl Pod for i32 {}
+unsafe impl Pod for i64 {}
+unsafe impl Pod for i128 {}
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
+
+impl<'a> BinaryFileReader<'a> {
+    /// Values are interpreted in the target's native byte order, regardless of
+    /// the reader's [`Endian`](crate::endian::Endian).
+    ///
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::BinaryFileReader;
+    /// # use binary_file_reader::pod::Pod;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// #[derive(Debug, Clone, Copy, PartialEq)]
+    /// #[repr(C)]
+    /// struct Entry {
+    ///     offset: [u8; 4],
+    ///     size: [u8; 4],
+    /// }
+    ///
+    /// unsafe impl Pod for Entry {}
+    ///
+    /// let buffer = vec![0, 0, 0, 8, 0, 0, 1, 0, 0xff];
+    /// let mut reader = BinaryFileReader::new(&buffer);
+    /// let entry = reader.read_pod::<Entry>()?;
+    /// assert_eq!(u32::from_be_bytes(entry.offset), 8);
+    /// assert_eq!(u32::from_be_bytes(entry.size), 256);
+    /// assert!(reader.read_pod::<Entry>().is_err());
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn read_pod<T: Pod>(&mut self) -> Result<Cow<'a, T>, BinaryFileReaderError> {
+        let slice = self.read_slice(mem::size_of::<T>())?;
+        let ptr = slice.as_ptr();
+
+        if ptr.align_offset(mem::align_of::<T>()) == 0 {
+            // SAFETY: `slice` is exactly `size_of::<T>()` bytes borrowed for `'a`,
+            // the pointer is aligned for `T`, and `T: Pod` accepts any bit pattern.
+            let value = unsafe { &*(ptr as *const T) };
+            Ok(Cow::Borrowed(value))
+        } else {
+            // SAFETY: as above, but the unaligned read copies the bytes out.
+            let value = unsafe { (ptr as *const T).read_unaligned() };
+            Ok(Cow::Owned(value))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::{error::BinaryFileReaderError, BinaryFileReader};
+
+    #[test]
+    fn test_read_pod() -> Result<(), BinaryFileReaderError> {
+        let words: Vec<u32> = vec![0x01020304, 0x05060708, 0x090a0b0c];
+        let buffer = words
+            .iter()
+            .flat_map(|w| w.to_ne_bytes())
+            .collect::<Vec<u8>>();
+
+        let aligned = buffer.as_ptr().align_offset(4) == 0;
+        let mut reader = BinaryFileReader::new(&buffer);
+        let value = reader.read_pod::<u32>()?;
+        assert_eq!(*value, 0x01020304);
+        assert_eq!(matches!(value, Cow::Borrowed(_)), aligned);
+
+        reader.read_u8()?;
+        let value = reader.read_pod::<[u8; 3]>()?;
+        assert_eq!(*value, 0x05060708u32.to_ne_bytes()[1..]);
+
+        let value = reader.read_pod::<u32>()?;
+        assert_eq!(*value, 0x090a0b0c);
+
+        let mut reader = BinaryFileReader::new(&buffer[1..]);
+        let value = reader.read_pod::<u32>()?;
+        assert!(matches!(value, Cow::Owned(_)) || !aligned);
+        assert!(matches!(
+            reader.read_pod::<[u32; 2]>(),
+            Err(BinaryFileReaderError::BufferUnderflow {
+                requested_bytes: 8,
+                current_offset: 4,
+                available_bytes: 7
+            })
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,34 @@
+use crate::endian::Endian;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Fixed-width integers that can be decoded from bytes in either byte order.
+///
+/// This trait is sealed; every bit pattern of an implementing type is valid,
+/// which lets readers reinterpret suitably aligned buffers in place.
+pub trait Primitive: sealed::Sealed + Copy + 'static {
+    fn from_bytes(bytes: &[u8], endian: Endian) -> Self;
+}
+
+macro_rules! impl_primitive {
+    ($($ty:ty),*) => {
+        $(
+            impl sealed::Sealed for $ty {}
+
+            impl Primitive for $ty {
+                fn from_bytes(bytes: &[u8], endian: Endian) -> Self {
+                    let mut buffer = [0; core::mem::size_of::<$ty>()];
+                    buffer.copy_from_slice(bytes);
+                    match endian {
+                        Endian::Big => <$ty>::from_be_bytes(buffer),
+                        Endian::Little => <$ty>::from_le_bytes(buffer),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_primitive!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
@@ -0,0 +1,414 @@
+use crate::{error::BinaryFileReaderError, fourcc::FourCC, BinaryFileReader};
+use alloc::vec::Vec;
+use core::ops::{Deref, Range};
+
+/// # Examples
+/// ```
+/// # use binary_file_reader::BinaryFileReader;
+/// # use binary_file_reader::error::BinaryFileReaderError;
+/// # use binary_file_reader::read_from::ReadFrom;
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// #[derive(Debug, PartialEq)]
+/// struct Phys {
+///     px_per_unit: (u32, u32),
+///     unit_specifier: u8,
+/// }
+///
+/// impl ReadFrom for Phys {
+///     fn read_from(reader: &mut BinaryFileReader) -> Result<Self, BinaryFileReaderError> {
+///         Ok(Self {
+///             px_per_unit: reader.read_struct()?,
+///             unit_specifier: reader.read_struct()?,
+///         })
+///     }
+/// }
+///
+/// let buffer = vec![0, 0, 0x2e, 0x23, 0, 0, 0x2e, 0x23, 1];
+/// let mut reader = BinaryFileReader::new(&buffer);
+/// let phys = reader.read_struct::<Phys>()?;
+/// assert_eq!(
+///     phys,
+///     Phys {
+///         px_per_unit: (11811, 11811),
+///         unit_specifier: 1
+///     }
+/// );
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+pub trait ReadFrom: Sized {
+    /// The fewest bytes a value can occupy, used to reject impossible counts
+    /// before reading repeated values.
+    const MIN_SIZE: usize = 0;
+
+    fn read_from(reader: &mut BinaryFileReader) -> Result<Self, BinaryFileReaderError>;
+}
+
+impl ReadFrom for u8 {
+    const MIN_SIZE: usize = 1;
+
+    fn read_from(reader: &mut BinaryFileReader) -> Result<Self, BinaryFileReaderError> {
+        reader.read_u8()
+    }
+}
+
+impl ReadFrom for u16 {
+    const MIN_SIZE: usize = 2;
+
+    fn read_from(reader: &mut BinaryFileReader) -> Result<Self, BinaryFileReaderError> {
+        reader.read_u16()
+    }
+}
+
+impl ReadFrom for u32 {
+    const MIN_SIZE: usize = 4;
+
+    fn read_from(reader: &mut BinaryFileReader) -> Result<Self, BinaryFileReaderError> {
+        reader.read_u32()
+    }
+}
+
+impl ReadFrom for u64 {
+    const MIN_SIZE: usize = 8;
+
+    fn read_from(reader: &mut BinaryFileReader) -> Result<Self, BinaryFileReaderError> {
+        reader.read_u64()
+    }
+}
+
+impl ReadFrom for u128 {
+    const MIN_SIZE: usize = 16;
+
+    fn read_from(reader: &mut BinaryFileReader) -> Result<Self, BinaryFileReaderError> {
+        reader.read_u128()
+    }
+}
+
+impl ReadFrom for i8 {
+    const MIN_SIZE: usize = 1;
+
+    fn read_from(reader: &mut BinaryFileReader) -> Result<Self, BinaryFileReaderError> {
+        Ok(reader.read_u8()? as i8)
+    }
+}
+
+impl ReadFrom for i16 {
+    const MIN_SIZE: usize = 2;
+
+    fn read_from(reader: &mut BinaryFileReader) -> Result<Self, BinaryFileReaderError> {
+        Ok(reader.read_u16()? as i16)
+    }
+}
+
+impl ReadFrom for i32 {
+    const MIN_SIZE: usize = 4;
+
+    fn read_from(reader: &mut BinaryFileReader) -> Result<Self, BinaryFileReaderError> {
+        Ok(reader.read_u32()? as i32)
+    }
+}
+
+impl ReadFrom for i64 {
+    const MIN_SIZE: usize = 8;
+
+    fn read_from(reader: &mut BinaryFileReader) -> Result<Self, BinaryFileReaderError> {
+        Ok(reader.read_u64()? as i64)
+    }
+}
+
+impl ReadFrom for i128 {
+    const MIN_SIZE: usize = 16;
+
+    fn read_from(reader: &mut BinaryFileReader) -> Result<Self, BinaryFileReaderError> {
+        Ok(reader.read_u128()? as i128)
+    }
+}
+
+impl ReadFrom for bool {
+    const MIN_SIZE: usize = 1;
+
+    fn read_from(reader: &mut BinaryFileReader) -> Result<Self, BinaryFileReaderError> {
+        reader.read_bool()
+    }
+}
+
+impl ReadFrom for FourCC {
+    const MIN_SIZE: usize = 4;
+
+    fn read_from(reader: &mut BinaryFileReader) -> Result<Self, BinaryFileReaderError> {
+        reader.read_fourcc()
+    }
+}
+
+impl<T: ReadFrom> ReadFrom for Option<T> {
+    const MIN_SIZE: usize = 1;
+
+    fn read_from(reader: &mut BinaryFileReader) -> Result<Self, BinaryFileReaderError> {
+        reader.read_option()
+    }
+}
+
+impl<T: ReadFrom, const N: usize> ReadFrom for [T; N] {
+    const MIN_SIZE: usize = T::MIN_SIZE * N;
+
+    fn read_from(reader: &mut BinaryFileReader) -> Result<Self, BinaryFileReaderError> {
+        let mut items = Vec::with_capacity(N);
+        for _ in 0..N {
+            items.push(T::read_from(reader)?);
+        }
+
+        match items.try_into() {
+            Ok(array) => Ok(array),
+            Err(_) => unreachable!("exactly N items were read"),
+        }
+    }
+}
+
+/// A value together with the absolute offsets of the bytes it was read from.
+/// Wrapping a field's type in `Spanned` records where the field lives, for
+/// both hand-written and derived [`ReadFrom`] impls.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::BinaryFileReader;
+/// # use binary_file_reader::error::BinaryFileReaderError;
+/// # use binary_file_reader::read_from::{ReadFrom, Spanned};
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// struct Header {
+///     version: u8,
+///     length: Spanned<u32>,
+/// }
+///
+/// impl ReadFrom for Header {
+///     fn read_from(reader: &mut BinaryFileReader) -> Result<Self, BinaryFileReaderError> {
+///         Ok(Self {
+///             version: reader.read_struct()?,
+///             length: reader.read_struct()?,
+///         })
+///     }
+/// }
+///
+/// let buffer = vec![0xff, 0x02, 0x00, 0x00, 0x00, 0x10];
+/// let mut reader = BinaryFileReader::new(&buffer);
+/// reader.read_u8()?;
+/// let header = reader.read_struct::<Header>()?;
+/// assert_eq!(header.version, 2);
+/// assert_eq!(*header.length, 16);
+/// assert_eq!(header.length.span, 2..6);
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Spanned<T> {
+    pub value: T,
+    pub span: Range<usize>,
+}
+
+impl<T> Spanned<T> {
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> Deref for Spanned<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: ReadFrom> ReadFrom for Spanned<T> {
+    const MIN_SIZE: usize = T::MIN_SIZE;
+
+    fn read_from(reader: &mut BinaryFileReader) -> Result<Self, BinaryFileReaderError> {
+        let (value, span) = reader.spanned(T::read_from)?;
+        Ok(Self { value, span })
+    }
+}
+
+#[doc(hidden)]
+pub trait ReadWith<'a> {
+    fn read_with<T, F>(&mut self, f: F) -> Result<T, BinaryFileReaderError>
+    where
+        F: FnOnce(&mut BinaryFileReader<'a>) -> Result<T, BinaryFileReaderError>;
+}
+
+impl<'a> ReadWith<'a> for BinaryFileReader<'a> {
+    fn read_with<T, F>(&mut self, f: F) -> Result<T, BinaryFileReaderError>
+    where
+        F: FnOnce(&mut BinaryFileReader<'a>) -> Result<T, BinaryFileReaderError>,
+    {
+        f(self)
+    }
+}
+
+macro_rules! impl_read_from_for_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: ReadFrom),+> ReadFrom for ($($name,)+) {
+            const MIN_SIZE: usize = 0 $(+ $name::MIN_SIZE)+;
+
+            fn read_from(reader: &mut BinaryFileReader) -> Result<Self, BinaryFileReaderError> {
+                Ok(($($name::read_from(reader)?,)+))
+            }
+        }
+    };
+}
+
+impl_read_from_for_tuple!(A);
+impl_read_from_for_tuple!(A, B);
+impl_read_from_for_tuple!(A, B, C);
+impl_read_from_for_tuple!(A, B, C, D);
+impl_read_from_for_tuple!(A, B, C, D, E);
+impl_read_from_for_tuple!(A, B, C, D, E, F);
+impl_read_from_for_tuple!(A, B, C, D, E, F, G);
+impl_read_from_for_tuple!(A, B, C, D, E, F, G, H);
+
+#[cfg(test)]
+mod tests {
+    use super::{ReadFrom, Spanned};
+    use crate::{error::BinaryFileReaderError, fourcc::FourCC, read_fields, BinaryFileReader};
+    use alloc::vec;
+
+    #[test]
+    fn test_read_struct() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![
+            0xff, 0xff, 0xfe, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05, b'I', b'E', b'N', b'D', 0x01,
+        ];
+        let mut reader = BinaryFileReader::new(&buffer);
+        assert_eq!(reader.read_struct::<i8>()?, -1);
+        assert_eq!(reader.read_struct::<i16>()?, -2);
+        assert_eq!(reader.read_struct::<[u8; 3]>()?, [0x00, 0x01, 0x02]);
+        assert_eq!(
+            reader.read_struct::<(u8, [u8; 2], FourCC, bool)>()?,
+            (0x03, [0x04, 0x05], FourCC(*b"IEND"), true)
+        );
+        assert_eq!(reader.available_bytes(), 0);
+
+        let mut reader = BinaryFileReader::new(&buffer);
+        assert!(matches!(
+            reader.read_struct::<[u32; 4]>(),
+            Err(BinaryFileReaderError::BufferUnderflow {
+                requested_bytes: 4,
+                current_offset: 12,
+                available_bytes: 2
+            })
+        ));
+        assert_eq!(reader.read_struct::<[u16; 0]>()?, []);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_vec_of() -> Result<(), BinaryFileReaderError> {
+        assert_eq!(<(u8, [u16; 3], FourCC) as ReadFrom>::MIN_SIZE, 11);
+        assert_eq!(<Option<u32> as ReadFrom>::MIN_SIZE, 1);
+
+        let buffer = vec![0, 1, 0, 2, 0, 3, 0xff];
+        let mut reader = BinaryFileReader::new(&buffer);
+        assert_eq!(reader.read_vec_of::<u16>(3)?, vec![1, 2, 3]);
+        assert_eq!(reader.read_vec_of::<u16>(0)?, vec![]);
+
+        let mut reader = BinaryFileReader::new(&buffer);
+        assert!(matches!(
+            reader.read_vec_of::<u16>(4),
+            Err(BinaryFileReaderError::BufferUnderflow {
+                requested_bytes: 8,
+                current_offset: 0,
+                available_bytes: 7
+            })
+        ));
+        assert!(matches!(
+            reader.read_vec_of::<u16>(usize::MAX),
+            Err(BinaryFileReaderError::BufferUnderflow {
+                requested_bytes: usize::MAX,
+                ..
+            })
+        ));
+        assert_eq!(reader.current_offset(), 0);
+
+        let mut reader = BinaryFileReader::new(&buffer);
+        match reader.read_vec_of::<bool>(4) {
+            Err(BinaryFileReaderError::Element { index, source }) => {
+                assert_eq!(index, 3);
+                assert!(matches!(
+                    *source,
+                    BinaryFileReaderError::InvalidDiscriminant {
+                        value: 2,
+                        current_offset: 3,
+                        ..
+                    }
+                ));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_fields() -> Result<(), BinaryFileReaderError> {
+        struct Time {
+            y: u16,
+            m: u8,
+            d: u8,
+        }
+
+        let buffer = vec![0x07, 0xe9, 1, 28, 0xff, 0xfe];
+        let mut reader = BinaryFileReader::new(&buffer);
+        let time = read_fields!(
+            reader,
+            Time {
+                y: u16,
+                m: u8,
+                d: u8,
+            }
+        )?;
+        assert_eq!((time.y, time.m, time.d), (2025, 1, 28));
+
+        let by_ref = &mut reader;
+        let (a,) = read_fields!(by_ref, { a: i8 })?;
+        assert_eq!(a, -1);
+        assert_eq!(read_fields!(reader, {})?, ());
+
+        assert!(matches!(
+            read_fields!(reader, { a: u8, b: u16 }),
+            Err(BinaryFileReaderError::BufferUnderflow {
+                requested_bytes: 2,
+                current_offset: 6,
+                available_bytes: 0
+            })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_spanned() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0x01, 0x00, 0x02, 0x03, 0x04];
+        let mut reader = BinaryFileReader::new(&buffer);
+        reader.read_u8()?;
+
+        let (a, b) = reader.read_struct::<(Spanned<u16>, Spanned<[u8; 2]>)>()?;
+        assert_eq!(
+            a,
+            Spanned {
+                value: 2,
+                span: 1..3
+            }
+        );
+        assert_eq!((*b, b.span), ([3, 4], 3..5));
+        assert_eq!(<Spanned<u32> as ReadFrom>::MIN_SIZE, 4);
+        assert!(reader.read_struct::<Spanned<u8>>().is_err());
+
+        Ok(())
+    }
+}
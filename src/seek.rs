@@ -0,0 +1,310 @@
+use crate::{endian::Endian, error::BinaryFileReaderError, source::DataSource};
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// A reader over a [`Read`] + [`Seek`] source, such as a large file on disk.
+/// Only a window around the cursor is buffered; moving the cursor seeks.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::seek::SeekableBinaryReader;
+/// # use binary_file_reader::source::DataSource;
+/// # use std::io::Cursor;
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// // A header holding the offset of a record stored at the end.
+/// let data = vec![0, 0, 0, 6, 0xff, 0xff, 0x12, 0x34];
+/// let mut reader = SeekableBinaryReader::new(Cursor::new(data))?;
+///
+/// let record_offset = reader.read_u32()? as usize;
+/// let record = reader.follow_offset(record_offset, |r| r.read_u16())?;
+/// assert_eq!(record, 0x1234);
+/// assert_eq!(reader.current_offset(), 4);
+///
+/// let mut padding = reader.split_off_front(2)?;
+/// assert_eq!(padding.read_u16()?, 0xffff);
+/// assert!(padding.read_u8().is_err());
+/// assert_eq!(reader.read_u16()?, 0x1234);
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SeekableBinaryReader<R> {
+    inner: R,
+    buf: Vec<u8>,
+    capacity: usize,
+    // Offset of `buf[0]` in the source and the number of valid bytes.
+    window_start: usize,
+    window_len: usize,
+    offset: usize,
+    own_left: usize,
+    endian: Endian,
+}
+
+impl<R: Read + Seek> SeekableBinaryReader<R> {
+    /// The window size used by [`new`](Self::new).
+    pub const DEFAULT_CAPACITY: usize = 8 * 1024;
+
+    pub fn new(inner: R) -> Result<Self, BinaryFileReaderError> {
+        Self::with_capacity(Self::DEFAULT_CAPACITY, inner)
+    }
+
+    /// # Panics
+    /// Panics if `capacity` is 0.
+    pub fn with_capacity(capacity: usize, mut inner: R) -> Result<Self, BinaryFileReaderError> {
+        assert!(capacity != 0, "capacity must be non-zero");
+        let length = inner.seek(SeekFrom::End(0))?;
+        let own_left = usize::try_from(length)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "source is too large"))?;
+
+        Ok(Self {
+            inner,
+            buf: vec![0; capacity],
+            capacity,
+            window_start: 0,
+            window_len: 0,
+            offset: 0,
+            own_left,
+            endian: Endian::Big,
+        })
+    }
+
+    #[inline]
+    pub fn current_offset(&self) -> usize {
+        self.offset
+    }
+
+    #[inline]
+    pub fn available_bytes(&self) -> usize {
+        self.own_left - self.offset
+    }
+
+    #[inline]
+    pub fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    #[inline]
+    pub fn set_endian(&mut self, endian: Endian) {
+        self.endian = endian;
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Moves the cursor to `offset`, which may be anywhere up to the end of
+    /// this reader.
+    pub fn set_offset(&mut self, offset: usize) -> Result<(), BinaryFileReaderError> {
+        if offset > self.own_left {
+            return Err(BinaryFileReaderError::OutOfRange {
+                buffer_size: self.own_left,
+                got: offset,
+            });
+        }
+
+        self.offset = offset;
+        Ok(())
+    }
+
+    /// Runs `f` with the cursor at `offset` and restores the cursor
+    /// afterwards, whether or not `f` succeeds.
+    pub fn follow_offset<T, F>(&mut self, offset: usize, f: F) -> Result<T, BinaryFileReaderError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, BinaryFileReaderError>,
+    {
+        let saved = self.offset;
+        self.set_offset(offset)?;
+        let result = f(self);
+        self.offset = saved;
+        result
+    }
+
+    /// Splits off a reader over the next `size` bytes and moves this reader
+    /// past them. The sub-reader borrows the source and keeps absolute
+    /// offsets.
+    pub fn split_off_front(
+        &mut self,
+        size: usize,
+    ) -> Result<SeekableBinaryReader<&mut R>, BinaryFileReaderError> {
+        if size > self.available_bytes() {
+            return Err(BinaryFileReaderError::BufferUnderflow {
+                requested_bytes: size,
+                current_offset: self.offset,
+                available_bytes: self.available_bytes(),
+            });
+        }
+
+        let start = self.offset;
+        self.offset += size;
+        Ok(SeekableBinaryReader {
+            inner: &mut self.inner,
+            buf: vec![0; self.capacity],
+            capacity: self.capacity,
+            window_start: 0,
+            window_len: 0,
+            offset: start,
+            own_left: start + size,
+            endian: self.endian,
+        })
+    }
+}
+
+impl<R: Read + Seek> DataSource for SeekableBinaryReader<R> {
+    fn fill(&mut self, length: usize) -> Result<&[u8], BinaryFileReaderError> {
+        let window_end = self.window_start + self.window_len;
+        let in_window = self.window_start <= self.offset && self.offset <= window_end;
+        let wanted = length.min(self.available_bytes());
+
+        if !in_window || window_end - self.offset < wanted {
+            let size = self.capacity.max(length).min(self.available_bytes());
+            if self.buf.len() < size {
+                self.buf.resize(size, 0);
+            }
+
+            self.inner.seek(SeekFrom::Start(self.offset as u64))?;
+            self.window_start = self.offset;
+            self.window_len = 0;
+            while self.window_len < size {
+                match self.inner.read(&mut self.buf[self.window_len..size]) {
+                    Ok(0) => break,
+                    Ok(read) => self.window_len += read,
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                    Err(err) => return Err(err.into()),
+                }
+            }
+        }
+
+        let start = self.offset - self.window_start;
+        let end = self.window_len.min(start + self.available_bytes());
+        Ok(&self.buf[start..end])
+    }
+
+    fn consume(&mut self, length: usize) {
+        debug_assert!(length <= self.available_bytes());
+        self.offset += length;
+    }
+
+    #[inline]
+    fn window_size(&self) -> usize {
+        self.capacity
+    }
+
+    #[inline]
+    fn current_offset(&self) -> usize {
+        self.offset
+    }
+
+    #[inline]
+    fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    /// Moves the cursor `length` bytes forward without reading.
+    fn skip(&mut self, length: usize) -> Result<(), BinaryFileReaderError> {
+        if length > self.available_bytes() {
+            return Err(BinaryFileReaderError::BufferUnderflow {
+                requested_bytes: length,
+                current_offset: self.offset,
+                available_bytes: self.available_bytes(),
+            });
+        }
+
+        self.offset += length;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SeekableBinaryReader;
+    use crate::{endian::Endian, error::BinaryFileReaderError, source::DataSource};
+    use std::io::{Cursor, Read, Seek, SeekFrom};
+
+    /// Counts the seeks made against the wrapped source.
+    struct CountingSeeks<R> {
+        inner: R,
+        seeks: usize,
+    }
+
+    impl<R: Read> Read for CountingSeeks<R> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.inner.read(buf)
+        }
+    }
+
+    impl<R: Seek> Seek for CountingSeeks<R> {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.seeks += 1;
+            self.inner.seek(pos)
+        }
+    }
+
+    #[test]
+    fn test_seekable_reader() -> Result<(), BinaryFileReaderError> {
+        let data = (0..=255).collect::<Vec<u8>>();
+        let source = CountingSeeks {
+            inner: Cursor::new(data),
+            seeks: 0,
+        };
+        let mut reader = SeekableBinaryReader::with_capacity(8, source)?;
+        assert_eq!(reader.available_bytes(), 256);
+
+        assert_eq!(reader.read_u32()?, 0x00010203);
+        assert_eq!(reader.read_u32()?, 0x04050607);
+        assert_eq!(reader.inner.seeks, 2);
+
+        reader.set_offset(250)?;
+        reader.set_endian(Endian::Little);
+        assert_eq!(reader.peek_u16()?, 0xfbfa);
+        assert!(matches!(
+            reader.read_u64(),
+            Err(BinaryFileReaderError::BufferUnderflow {
+                requested_bytes: 8,
+                current_offset: 250,
+                available_bytes: 6
+            })
+        ));
+        assert!(matches!(
+            reader.set_offset(257),
+            Err(BinaryFileReaderError::OutOfRange {
+                buffer_size: 256,
+                got: 257
+            })
+        ));
+        reader.set_endian(Endian::Big);
+
+        reader.set_offset(16)?;
+        let mut sub = reader.split_off_front(4)?;
+        assert_eq!(sub.available_bytes(), 4);
+        sub.expect(&[16, 17])?;
+        assert!(matches!(
+            sub.read_u32(),
+            Err(BinaryFileReaderError::BufferUnderflow {
+                current_offset: 18,
+                available_bytes: 2,
+                ..
+            })
+        ));
+        let mut rest = [0; 2];
+        sub.read_bytes(&mut rest)?;
+        assert_eq!(rest, [18, 19]);
+        assert_eq!(reader.current_offset(), 20);
+
+        let value = reader.follow_offset(100, |r| {
+            r.skip(100)?;
+            r.read_u16()
+        })?;
+        assert_eq!(value, 0xc8c9);
+        assert_eq!(reader.current_offset(), 20);
+        assert!(reader.follow_offset(300, |r| r.read_u8()).is_err());
+
+        let mut block = [0; 20];
+        reader.read_bytes(&mut block)?;
+        assert_eq!(block[19], 39);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,406 @@
+use crate::{
+    endian::Endian,
+    error::{BinaryFileReaderError, ErrorBytes},
+    iter::Bytes,
+    primitive::Primitive,
+    BinaryFileReader,
+};
+use alloc::vec::Vec;
+
+macro_rules! primitive_reads {
+    ($($read:ident, $peek:ident => $ty:ty;)*) => {
+        $(
+            #[doc = concat!("Reads a `", stringify!($ty), "` in the source's byte order.")]
+            fn $read(&mut self) -> Result<$ty, BinaryFileReaderError> {
+                let value = self.$peek()?;
+                self.consume(core::mem::size_of::<$ty>());
+                Ok(value)
+            }
+
+            #[doc = concat!("Peeks a `", stringify!($ty), "` in the source's byte order.")]
+            fn $peek(&mut self) -> Result<$ty, BinaryFileReaderError> {
+                peek_primitive(self)
+            }
+        )*
+    };
+}
+
+/// A byte source read through a cursor. Implementors provide
+/// [`fill`](Self::fill), [`consume`](Self::consume), [`current_offset`](Self::current_offset)
+/// and [`endian`](Self::endian); the read, peek and expect methods are
+/// provided on top of them.
+///
+/// [`BinaryFileReader`] implements this trait, but its inherent methods stay
+/// the zero-cost way to read a slice.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::BinaryFileReader;
+/// # use binary_file_reader::endian::Endian;
+/// # use binary_file_reader::error::BinaryFileReaderError;
+/// # use binary_file_reader::source::DataSource;
+/// # use binary_file_reader::stream::StreamingBinaryReader;
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// /// Decrypts its input by XOR-ing every byte with a key.
+/// struct Xored<'a> {
+///     data: &'a [u8],
+///     key: u8,
+///     offset: usize,
+///     scratch: Vec<u8>,
+/// }
+///
+/// impl DataSource for Xored<'_> {
+///     fn fill(&mut self, length: usize) -> Result<&[u8], BinaryFileReaderError> {
+///         let end = (self.offset + length).min(self.data.len());
+///         self.scratch = self.data[self.offset..end].iter().map(|b| b ^ self.key).collect();
+///         Ok(&self.scratch)
+///     }
+///
+///     fn consume(&mut self, length: usize) {
+///         self.offset += length;
+///     }
+///
+///     fn current_offset(&self) -> usize {
+///         self.offset
+///     }
+///
+///     fn endian(&self) -> Endian {
+///         Endian::Big
+///     }
+/// }
+///
+/// fn read_header<S: DataSource>(source: &mut S) -> Result<u16, BinaryFileReaderError> {
+///     source.expect(b"HD")?;
+///     source.read_u16()
+/// }
+///
+/// let plain = [b'H', b'D', 0x01, 0x02];
+/// let xored = plain.iter().map(|b| b ^ 0x5a).collect::<Vec<_>>();
+/// let mut source = Xored { data: &xored, key: 0x5a, offset: 0, scratch: Vec::new() };
+/// assert_eq!(read_header(&mut source)?, 0x0102);
+///
+/// assert_eq!(read_header(&mut BinaryFileReader::new(&plain))?, 0x0102);
+/// assert_eq!(read_header(&mut StreamingBinaryReader::new(&plain[..]))?, 0x0102);
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+pub trait DataSource {
+    /// Makes at least `length` bytes available at the cursor if the source
+    /// has them, and returns the available bytes. Fewer bytes are returned
+    /// only at the end of the source.
+    fn fill(&mut self, length: usize) -> Result<&[u8], BinaryFileReaderError>;
+
+    /// Advances the cursor by `length` bytes, which must not exceed the
+    /// bytes returned by the last [`fill`](Self::fill).
+    fn consume(&mut self, length: usize);
+
+    fn current_offset(&self) -> usize;
+
+    fn endian(&self) -> Endian;
+
+    /// The largest slice [`peek_slice`](Self::peek_slice) can return.
+    fn window_size(&self) -> usize {
+        usize::MAX
+    }
+
+    /// Iterates over the remaining bytes, consuming each one as it is
+    /// yielded. A failed fill is yielded as an error and ends the iteration.
+    ///
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::source::DataSource;
+    /// # use binary_file_reader::stream::StreamingBinaryReader;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let input: &[u8] = b"key=value";
+    /// let mut reader = StreamingBinaryReader::with_capacity(2, input);
+    ///
+    /// let key = reader
+    ///     .bytes()
+    ///     .map_while(|b| b.ok().filter(|b| *b != b'='))
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(key, b"key");
+    /// assert_eq!(reader.bytes().count(), 5);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    fn bytes(&mut self) -> Bytes<'_, Self> {
+        Bytes::new(self)
+    }
+
+    /// Returns `true` once every byte of the source has been consumed.
+    fn is_at_end(&mut self) -> Result<bool, BinaryFileReaderError> {
+        Ok(self.fill(1)?.is_empty())
+    }
+
+    primitive_reads! {
+        read_u8, peek_u8 => u8;
+        read_u16, peek_u16 => u16;
+        read_u32, peek_u32 => u32;
+        read_u64, peek_u64 => u64;
+        read_u128, peek_u128 => u128;
+    }
+
+    /// Fills `buffer`, which may be larger than the window. If the source
+    /// ends first, the bytes read so far are consumed.
+    fn read_bytes(&mut self, buffer: &mut [u8]) -> Result<(), BinaryFileReaderError> {
+        let window = self.window_size();
+        let mut copied = 0;
+        while copied < buffer.len() {
+            let length = (buffer.len() - copied).min(window);
+            let offset = self.current_offset();
+            let buffered = self.fill(length)?;
+            if buffered.is_empty() {
+                return Err(BinaryFileReaderError::BufferUnderflow {
+                    requested_bytes: buffer.len() - copied,
+                    current_offset: offset,
+                    available_bytes: 0,
+                });
+            }
+
+            let length = length.min(buffered.len());
+            buffer[copied..copied + length].copy_from_slice(&buffered[..length]);
+            self.consume(length);
+            copied += length;
+        }
+
+        Ok(())
+    }
+
+    /// Peeks `length` bytes, which must fit in the window.
+    fn peek_slice(&mut self, length: usize) -> Result<&[u8], BinaryFileReaderError> {
+        if length > self.window_size() {
+            return Err(BinaryFileReaderError::WindowExceeded {
+                requested_bytes: length,
+                window_size: self.window_size(),
+            });
+        }
+
+        fill_exact(self, length)
+    }
+
+    /// Discards `length` bytes. If the source ends first, the bytes read so
+    /// far are consumed.
+    fn skip(&mut self, length: usize) -> Result<(), BinaryFileReaderError> {
+        let window = self.window_size();
+        let mut remaining = length;
+        while remaining > 0 {
+            let offset = self.current_offset();
+            let buffered = self.fill(remaining.min(window))?.len();
+            if buffered == 0 {
+                return Err(BinaryFileReaderError::BufferUnderflow {
+                    requested_bytes: remaining,
+                    current_offset: offset,
+                    available_bytes: 0,
+                });
+            }
+
+            let step = remaining.min(buffered);
+            self.consume(step);
+            remaining -= step;
+        }
+
+        Ok(())
+    }
+
+    fn expect(&mut self, expect_bytes: &[u8]) -> Result<(), BinaryFileReaderError> {
+        self.expect_peek(expect_bytes)?;
+        self.consume(expect_bytes.len());
+        Ok(())
+    }
+
+    fn expect_peek(&mut self, expect_bytes: &[u8]) -> Result<(), BinaryFileReaderError> {
+        let offset = self.current_offset();
+        let buffered = self.fill(expect_bytes.len())?;
+        if buffered.len() < expect_bytes.len() {
+            return Err(BinaryFileReaderError::ExpectInsufficientBytes {
+                require: ErrorBytes::new(expect_bytes),
+                available_bytes: buffered.len(),
+                current_offset: offset,
+            });
+        }
+
+        let got = &buffered[..expect_bytes.len()];
+        if got != expect_bytes {
+            return Err(BinaryFileReaderError::Expect {
+                require: ErrorBytes::new(expect_bytes),
+                got: ErrorBytes::new(got),
+                available_bytes: buffered.len(),
+                current_offset: offset,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Reads `length` bytes and parses them with a [`BinaryFileReader`], which
+    /// gives access to the full slice API for one record. Offsets seen by `f`
+    /// are relative to the start of the block.
+    ///
+    /// The block is read one window at a time, so a length larger than the
+    /// source only allocates for the bytes that are there. If the source ends
+    /// first, the bytes read so far are consumed.
+    fn read_block<T, F>(&mut self, length: usize, f: F) -> Result<T, BinaryFileReaderError>
+    where
+        F: FnOnce(&mut BinaryFileReader) -> Result<T, BinaryFileReaderError>,
+    {
+        let window = self.window_size();
+        let mut block = Vec::new();
+        while block.len() < length {
+            let offset = self.current_offset();
+            let buffered = self.fill((length - block.len()).min(window))?;
+            if buffered.is_empty() {
+                return Err(BinaryFileReaderError::BufferUnderflow {
+                    requested_bytes: length - block.len(),
+                    current_offset: offset,
+                    available_bytes: 0,
+                });
+            }
+
+            let step = (length - block.len()).min(buffered.len());
+            block.extend_from_slice(&buffered[..step]);
+            self.consume(step);
+        }
+
+        let mut reader = BinaryFileReader::new(&block);
+        reader.set_endian(self.endian());
+        f(&mut reader)
+    }
+}
+
+fn fill_exact<S: DataSource + ?Sized>(
+    source: &mut S,
+    length: usize,
+) -> Result<&[u8], BinaryFileReaderError> {
+    let offset = source.current_offset();
+    let buffered = source.fill(length)?;
+    if buffered.len() < length {
+        return Err(BinaryFileReaderError::BufferUnderflow {
+            requested_bytes: length,
+            current_offset: offset,
+            available_bytes: buffered.len(),
+        });
+    }
+
+    Ok(&buffered[..length])
+}
+
+fn peek_primitive<S: DataSource + ?Sized, T: Primitive>(
+    source: &mut S,
+) -> Result<T, BinaryFileReaderError> {
+    let endian = source.endian();
+    let bytes = fill_exact(source, core::mem::size_of::<T>())?;
+    Ok(T::from_bytes(bytes, endian))
+}
+
+impl DataSource for BinaryFileReader<'_> {
+    fn fill(&mut self, _length: usize) -> Result<&[u8], BinaryFileReaderError> {
+        Ok(self.remaining())
+    }
+
+    fn consume(&mut self, length: usize) {
+        self.advance(length)
+            .expect("consumed more bytes than are available");
+    }
+
+    fn current_offset(&self) -> usize {
+        self.current_offset
+    }
+
+    fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    fn skip(&mut self, length: usize) -> Result<(), BinaryFileReaderError> {
+        self.current_offset = self.end_offset(length)?;
+        Ok(())
+    }
+}
+
+impl<S: DataSource + ?Sized> DataSource for &mut S {
+    fn fill(&mut self, length: usize) -> Result<&[u8], BinaryFileReaderError> {
+        (**self).fill(length)
+    }
+
+    fn consume(&mut self, length: usize) {
+        (**self).consume(length)
+    }
+
+    fn current_offset(&self) -> usize {
+        (**self).current_offset()
+    }
+
+    fn endian(&self) -> Endian {
+        (**self).endian()
+    }
+
+    fn window_size(&self) -> usize {
+        (**self).window_size()
+    }
+
+    fn skip(&mut self, length: usize) -> Result<(), BinaryFileReaderError> {
+        (**self).skip(length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DataSource;
+    use crate::{endian::Endian, error::BinaryFileReaderError, BinaryFileReader};
+    use alloc::vec;
+
+    #[test]
+    fn test_slice_source() -> Result<(), BinaryFileReaderError> {
+        fn read_generic<S: DataSource>(mut source: S) -> Result<(u16, u8), BinaryFileReaderError> {
+            source.expect(&[0xca, 0xfe])?;
+            let value = source.read_u16()?;
+            source.skip(1)?;
+            Ok((value, source.read_u8()?))
+        }
+
+        let buffer = vec![0xca, 0xfe, 0x01, 0x02, 0xff, 0x03];
+        let mut reader = BinaryFileReader::new(&buffer);
+        assert_eq!(read_generic(&mut reader)?, (0x0102, 3));
+        assert!(DataSource::is_at_end(&mut reader)?);
+
+        let mut reader = BinaryFileReader::new(&buffer);
+        reader.set_endian(Endian::Little);
+        assert_eq!(DataSource::peek_u16(&mut reader)?, 0xfeca);
+        assert_eq!(DataSource::peek_slice(&mut reader, 6)?, &buffer[..]);
+        assert!(matches!(
+            DataSource::skip(&mut reader, 7),
+            Err(BinaryFileReaderError::BufferUnderflow {
+                requested_bytes: 7,
+                current_offset: 0,
+                available_bytes: 6
+            })
+        ));
+        assert_eq!(reader.current_offset(), 0);
+        // Through a reference, the reader's own skip still leaves it in place.
+        assert!(DataSource::skip(&mut &mut reader, 7).is_err());
+        assert_eq!(reader.current_offset(), 0);
+
+        // A block longer than the source fails without allocating for it.
+        let mut reader = BinaryFileReader::new(&buffer);
+        assert!(matches!(
+            DataSource::read_block(&mut reader, usize::MAX, |_| Ok(())),
+            Err(BinaryFileReaderError::BufferUnderflow {
+                current_offset: 6,
+                available_bytes: 0,
+                ..
+            })
+        ));
+        let mut reader = BinaryFileReader::new(&buffer);
+        let value = DataSource::read_block(&mut reader, 4, |block| block.read_u32())?;
+        assert_eq!((value, reader.current_offset()), (0xcafe0102, 4));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,195 @@
+use crate::{endian::Endian, error::BinaryFileReaderError, BinaryFileReader};
+
+/// A snapshot of a reader's position and settings, taken with
+/// [`BinaryFileReader::save_state`] and restored with
+/// [`BinaryFileReader::resume`]. It does not borrow the buffer, so it can be
+/// stored as a checkpoint with [`to_bytes`](Self::to_bytes) and the parse
+/// continued after a restart.
+///
+/// The [source name](BinaryFileReader::with_source_name), cancel token,
+/// event sink, trace, layout and coverage are not saved; set them again on
+/// the resumed reader if they are needed.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::BinaryFileReader;
+/// # use binary_file_reader::endian::Endian;
+/// # use binary_file_reader::state::ReaderState;
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// let buffer = vec![0x00, 0x01, 0x02, 0x00, 0x03, 0x00];
+/// let mut reader = BinaryFileReader::new(&buffer);
+/// reader.read_u16()?;
+/// reader.set_endian(Endian::Little);
+/// let checkpoint = reader.save_state().to_bytes();
+///
+/// // After a restart.
+/// let state = ReaderState::from_bytes(&checkpoint)?;
+/// let mut reader = BinaryFileReader::resume(&buffer, state)?;
+/// assert_eq!(reader.read_u16()?, 2);
+/// assert_eq!(reader.read_u16()?, 3);
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ReaderState {
+    pub current_offset: usize,
+    /// The offset of the first byte the reader may move back to.
+    pub start: usize,
+    /// The offset one past the last byte the reader may read.
+    pub end: usize,
+    pub endian: Endian,
+    pub max_depth: usize,
+    pub max_alloc: usize,
+    pub max_error_bytes: usize,
+}
+
+impl ReaderState {
+    const MAGIC: &'static [u8; 4] = b"BFRS";
+    const VERSION: u8 = 3;
+
+    /// The length of [`to_bytes`](Self::to_bytes).
+    pub const ENCODED_LEN: usize = 4 + 1 + 1 + 6 * 8;
+
+    /// Encodes the state in a fixed little-endian layout, so a checkpoint
+    /// written on one platform can be read on another.
+    pub fn to_bytes(&self) -> [u8; Self::ENCODED_LEN] {
+        let mut bytes = [0; Self::ENCODED_LEN];
+        bytes[..4].copy_from_slice(Self::MAGIC);
+        bytes[4] = Self::VERSION;
+        bytes[5] = match self.endian {
+            Endian::Big => 0,
+            Endian::Little => 1,
+        };
+        let fields = [
+            self.current_offset,
+            self.start,
+            self.end,
+            self.max_depth,
+            self.max_alloc,
+            self.max_error_bytes,
+        ];
+        for (chunk, field) in bytes[6..].chunks_exact_mut(8).zip(fields) {
+            chunk.copy_from_slice(&(field as u64).to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Decodes a state written by [`to_bytes`](Self::to_bytes). Values that do
+    /// not fit in `usize` saturate, so [`BinaryFileReader::resume`] rejects
+    /// the offsets instead of wrapping them.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BinaryFileReaderError> {
+        let mut reader = BinaryFileReader::new(bytes);
+        reader.set_endian(Endian::Little);
+        reader.expect(Self::MAGIC)?;
+        let state = reader.read_versioned::<u8, _, _>(|version, r| {
+            if version != Self::VERSION {
+                return Ok(None);
+            }
+
+            let endian = match r.read_u8()? {
+                0 => Endian::Big,
+                1 => Endian::Little,
+                value => {
+                    return Err(BinaryFileReaderError::InvalidDiscriminant {
+                        type_name: "Endian",
+                        value: value.into(),
+                        current_offset: r.current_offset() - 1,
+                    })
+                }
+            };
+            let mut field = || -> Result<usize, BinaryFileReaderError> {
+                Ok(usize::try_from(r.read_u64()?).unwrap_or(usize::MAX))
+            };
+            Ok(Some(Self {
+                current_offset: field()?,
+                start: field()?,
+                end: field()?,
+                max_depth: field()?,
+                max_alloc: field()?,
+                max_error_bytes: field()?,
+                endian,
+            }))
+        })?;
+        reader.finish()?;
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReaderState;
+    use crate::{endian::Endian, error::BinaryFileReaderError, BinaryFileReader};
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_reader_state() -> Result<(), BinaryFileReaderError> {
+        let buffer = (0..32).collect::<Vec<u8>>();
+        let mut reader = BinaryFileReader::new(&buffer);
+        reader.read_u32()?;
+        reader.set_max_depth(3);
+        reader.set_max_alloc(100);
+        reader.set_max_error_bytes(4);
+        let mut sub = reader.split_off_front(8)?;
+        sub.read_u8()?;
+
+        let state = sub.save_state();
+        assert_eq!(
+            state,
+            ReaderState {
+                current_offset: 5,
+                start: 4,
+                end: 12,
+                endian: Endian::Big,
+                max_depth: 3,
+                max_alloc: 100,
+                max_error_bytes: 4,
+            }
+        );
+        assert_eq!(ReaderState::from_bytes(&state.to_bytes())?, state);
+
+        let mut resumed = BinaryFileReader::resume(&buffer, state)?;
+        assert_eq!(resumed.max_alloc(), 100);
+        assert_eq!(resumed.max_error_bytes(), 4);
+        assert_eq!(resumed.read_slice(7)?, &buffer[5..12]);
+        assert!(resumed.read_u8().is_err());
+
+        assert!(matches!(
+            BinaryFileReader::resume(&buffer[..10], state),
+            Err(BinaryFileReaderError::OutOfRange {
+                buffer_size: 10,
+                got: 12
+            })
+        ));
+        for current_offset in [3, 13] {
+            let outside = ReaderState {
+                current_offset,
+                ..state
+            };
+            assert!(BinaryFileReader::resume(&buffer, outside).is_err());
+        }
+
+        let mut bytes = state.to_bytes();
+        bytes[4] = 2;
+        assert!(matches!(
+            ReaderState::from_bytes(&bytes),
+            Err(BinaryFileReaderError::UnsupportedVersion { version: 2, .. })
+        ));
+        bytes[4] = 3;
+        bytes[5] = 9;
+        assert!(matches!(
+            ReaderState::from_bytes(&bytes),
+            Err(BinaryFileReaderError::InvalidDiscriminant {
+                value: 9,
+                current_offset: 5,
+                ..
+            })
+        ));
+        assert!(ReaderState::from_bytes(&bytes[..20]).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,234 @@
+use crate::{endian::Endian, error::BinaryFileReaderError, source::DataSource};
+use std::io::{self, Read};
+
+/// A reader over any [`Read`] source, such as a pipe or a socket. Data is
+/// pulled into an internal window as needed, so peeks are limited to the
+/// window size.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::source::DataSource;
+/// # use binary_file_reader::stream::StreamingBinaryReader;
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// let input: &[u8] = &[0x89, b'P', b'N', b'G', 0x00, 0x00, 0x00, 0x0d];
+/// let mut reader = StreamingBinaryReader::new(input);
+///
+/// reader.expect(b"\x89PNG")?;
+/// assert_eq!(reader.peek_slice(2)?, &[0, 0]);
+/// assert_eq!(reader.read_u32()?, 13);
+/// assert_eq!(reader.current_offset(), 8);
+/// assert!(reader.is_at_end()?);
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct StreamingBinaryReader<R> {
+    inner: R,
+    buf: Vec<u8>,
+    capacity: usize,
+    start: usize,
+    end: usize,
+    offset: usize,
+    endian: Endian,
+}
+
+impl<R: Read> StreamingBinaryReader<R> {
+    /// The window size used by [`new`](Self::new).
+    pub const DEFAULT_CAPACITY: usize = 8 * 1024;
+
+    pub fn new(inner: R) -> Self {
+        Self::with_capacity(Self::DEFAULT_CAPACITY, inner)
+    }
+
+    /// # Panics
+    /// Panics if `capacity` is 0.
+    pub fn with_capacity(capacity: usize, inner: R) -> Self {
+        assert!(capacity != 0, "capacity must be non-zero");
+        Self {
+            inner,
+            buf: vec![0; capacity],
+            capacity,
+            start: 0,
+            end: 0,
+            offset: 0,
+            endian: Endian::Big,
+        }
+    }
+
+    /// The number of bytes consumed from the source so far.
+    #[inline]
+    pub fn current_offset(&self) -> usize {
+        self.offset
+    }
+
+    #[inline]
+    pub fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    #[inline]
+    pub fn set_endian(&mut self, endian: Endian) {
+        self.endian = endian;
+    }
+
+    /// The bytes pulled from the source but not consumed yet.
+    #[inline]
+    pub fn buffered(&self) -> &[u8] {
+        &self.buf[self.start..self.end]
+    }
+
+    /// Returns the source. Buffered bytes that were not consumed are lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> DataSource for StreamingBinaryReader<R> {
+    fn fill(&mut self, length: usize) -> Result<&[u8], BinaryFileReaderError> {
+        if self.end - self.start < length {
+            self.buf.copy_within(self.start..self.end, 0);
+            self.end -= self.start;
+            self.start = 0;
+            if self.buf.len() < length {
+                self.buf.resize(length, 0);
+            }
+
+            while self.end < length {
+                match self.inner.read(&mut self.buf[self.end..]) {
+                    Ok(0) => break,
+                    Ok(read) => self.end += read,
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                    Err(err) => return Err(err.into()),
+                }
+            }
+        }
+
+        Ok(&self.buf[self.start..self.end])
+    }
+
+    fn consume(&mut self, length: usize) {
+        debug_assert!(length <= self.end - self.start);
+        self.start += length;
+        self.offset += length;
+    }
+
+    #[inline]
+    fn window_size(&self) -> usize {
+        self.capacity
+    }
+
+    #[inline]
+    fn current_offset(&self) -> usize {
+        self.offset
+    }
+
+    #[inline]
+    fn endian(&self) -> Endian {
+        self.endian
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StreamingBinaryReader;
+    use crate::{endian::Endian, error::BinaryFileReaderError, source::DataSource};
+    use std::io::{self, Read};
+
+    /// Hands out one byte per call, interrupting every other call.
+    struct Trickle<'a> {
+        data: &'a [u8],
+        interrupt: bool,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.interrupt = !self.interrupt;
+            if self.interrupt {
+                return Err(io::ErrorKind::Interrupted.into());
+            }
+            match self.data.split_first() {
+                Some((byte, rest)) if !buf.is_empty() => {
+                    buf[0] = *byte;
+                    self.data = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    fn test_streaming_reader() -> Result<(), BinaryFileReaderError> {
+        let data = (0..=255).collect::<Vec<u8>>();
+        let source = Trickle {
+            data: &data,
+            interrupt: false,
+        };
+        let mut reader = StreamingBinaryReader::with_capacity(3, source);
+
+        assert_eq!(reader.read_u8()?, 0);
+        assert_eq!(reader.read_u32()?, 0x01020304);
+        reader.set_endian(Endian::Little);
+        assert_eq!(reader.peek_u16()?, 0x0605);
+        assert_eq!(
+            reader.read_u128()?,
+            u128::from_le_bytes(core::array::from_fn(|i| i as u8 + 5))
+        );
+        assert_eq!(reader.current_offset(), 21);
+
+        reader.skip(200)?;
+        reader.expect(&[221, 222, 223, 224])?;
+        assert!(matches!(
+            reader.expect(&[0]),
+            Err(BinaryFileReaderError::Expect {
+                current_offset: 225,
+                ..
+            })
+        ));
+
+        assert!(matches!(
+            reader.peek_slice(4),
+            Err(BinaryFileReaderError::WindowExceeded {
+                requested_bytes: 4,
+                ..
+            })
+        ));
+
+        let mut rest = [0; 30];
+        reader.read_bytes(&mut rest)?;
+        assert_eq!(rest[29], 254);
+        assert!(matches!(
+            reader.read_u16(),
+            Err(BinaryFileReaderError::BufferUnderflow {
+                requested_bytes: 2,
+                current_offset: 255,
+                available_bytes: 1
+            })
+        ));
+        assert_eq!(reader.read_u8()?, 255);
+        assert!(reader.is_at_end()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_io_error() {
+        struct Broken;
+
+        impl Read for Broken {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::ErrorKind::ConnectionReset.into())
+            }
+        }
+
+        let mut reader = StreamingBinaryReader::new(Broken);
+        assert!(matches!(
+            reader.read_u8(),
+            Err(BinaryFileReaderError::IO(err)) if err.kind() == io::ErrorKind::ConnectionReset
+        ));
+    }
+}
@@ -0,0 +1,358 @@
+use crate::{endian::Endian, error::BinaryFileReaderError, source::DataSource};
+use alloc::vec::Vec;
+
+/// A text encoding of binary data, decoded by a [`TextDecoder`] and by
+/// [`hex_sub_reader`](crate::BinaryFileReader::hex_sub_reader) and
+/// [`base64_sub_reader`](crate::BinaryFileReader::base64_sub_reader).
+///
+/// ASCII whitespace between characters, such as the line breaks of PEM
+/// bodies, is ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextEncoding {
+    /// Two hex digits per byte, in either case.
+    Hex,
+    /// The standard RFC 4648 alphabet, with optional `=` padding.
+    Base64,
+}
+
+impl TextEncoding {
+    fn name(self) -> &'static str {
+        match self {
+            TextEncoding::Hex => "hex",
+            TextEncoding::Base64 => "base64",
+        }
+    }
+
+    /// The number of bits one character carries.
+    fn bits(self) -> u32 {
+        match self {
+            TextEncoding::Hex => 4,
+            TextEncoding::Base64 => 6,
+        }
+    }
+
+    fn value(self, byte: u8) -> Option<u32> {
+        let value = match (self, byte) {
+            (TextEncoding::Hex, b'0'..=b'9') => byte - b'0',
+            (TextEncoding::Hex, b'a'..=b'f') => byte - b'a' + 10,
+            (TextEncoding::Hex, b'A'..=b'F') => byte - b'A' + 10,
+            (TextEncoding::Base64, b'A'..=b'Z') => byte - b'A',
+            (TextEncoding::Base64, b'a'..=b'z') => byte - b'a' + 26,
+            (TextEncoding::Base64, b'0'..=b'9') => byte - b'0' + 52,
+            (TextEncoding::Base64, b'+') => 62,
+            (TextEncoding::Base64, b'/') => 63,
+            _ => return None,
+        };
+        Some(value as u32)
+    }
+}
+
+/// Decodes characters one at a time, keeping the bits that do not make up a
+/// whole byte yet.
+#[derive(Debug, Clone)]
+struct Decoder {
+    encoding: TextEncoding,
+    accumulator: u32,
+    bits: u32,
+    padded: bool,
+}
+
+impl Decoder {
+    fn new(encoding: TextEncoding) -> Self {
+        Self {
+            encoding,
+            accumulator: 0,
+            bits: 0,
+            padded: false,
+        }
+    }
+
+    fn error(&self, reason: &'static str, current_offset: usize) -> BinaryFileReaderError {
+        BinaryFileReaderError::InvalidEncoding {
+            encoding: self.encoding.name(),
+            reason,
+            current_offset,
+        }
+    }
+
+    /// Decodes the character at absolute offset `offset`, returning the byte
+    /// it completes, if any.
+    fn push(&mut self, byte: u8, offset: usize) -> Result<Option<u8>, BinaryFileReaderError> {
+        if byte.is_ascii_whitespace() {
+            return Ok(None);
+        }
+        if byte == b'=' && self.encoding == TextEncoding::Base64 {
+            if self.bits == 6 {
+                return Err(self.error("padding cuts a byte short", offset));
+            }
+            self.padded = true;
+            return Ok(None);
+        }
+        if self.padded {
+            return Err(self.error("data after padding", offset));
+        }
+
+        let value = self
+            .encoding
+            .value(byte)
+            .ok_or_else(|| self.error("invalid character", offset))?;
+        self.accumulator = (self.accumulator << self.encoding.bits()) | value;
+        self.bits += self.encoding.bits();
+        if self.bits < 8 {
+            return Ok(None);
+        }
+        self.bits -= 8;
+        let byte = (self.accumulator >> self.bits) as u8;
+        self.accumulator &= (1 << self.bits) - 1;
+        Ok(Some(byte))
+    }
+
+    /// The number of characters needed to complete `length` more bytes.
+    fn characters(&self, length: usize) -> usize {
+        let bits = length.saturating_mul(8).saturating_sub(self.bits as usize);
+        bits.div_ceil(self.encoding.bits() as usize)
+    }
+
+    /// Fails if the text ended inside a byte. `offset` is the end of the
+    /// text.
+    fn finish(&self, offset: usize) -> Result<(), BinaryFileReaderError> {
+        if self.bits >= self.encoding.bits() {
+            return Err(self.error("the text ends inside a byte", offset));
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn decode_at(
+    text: &[u8],
+    encoding: TextEncoding,
+    max_output: usize,
+    base_offset: usize,
+) -> Result<Vec<u8>, BinaryFileReaderError> {
+    let mut decoder = Decoder::new(encoding);
+    let mut output = Vec::new();
+    for (index, &byte) in text.iter().enumerate() {
+        if let Some(byte) = decoder.push(byte, base_offset + index)? {
+            if output.len() == max_output {
+                return Err(BinaryFileReaderError::AllocationLimit {
+                    requested_bytes: output.len() + 1,
+                    max_alloc: max_output,
+                    current_offset: base_offset + index,
+                });
+            }
+            output.push(byte);
+        }
+    }
+    decoder.finish(base_offset + text.len())?;
+    Ok(output)
+}
+
+/// A [`DataSource`] over the bytes encoded as text in another source. The
+/// text is decoded as it is read, so only the characters a read needs are
+/// pulled from the inner source.
+///
+/// Offsets are those of the decoded bytes, except in
+/// [`InvalidEncoding`](BinaryFileReaderError::InvalidEncoding) errors,
+/// which give the offset of the offending character in the inner source.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::BinaryFileReader;
+/// # use binary_file_reader::source::DataSource;
+/// # use binary_file_reader::text::{TextDecoder, TextEncoding};
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// let pem = b"iVBORw0K\nGgoAAAAN\n";
+/// let mut reader = BinaryFileReader::new(pem);
+/// let mut png = TextDecoder::new(&mut reader, TextEncoding::Base64);
+///
+/// png.expect(b"\x89PNG\r\n\x1a\n")?;
+/// assert_eq!(png.read_u32()?, 13);
+/// assert!(png.is_at_end()?);
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TextDecoder<S> {
+    inner: S,
+    decoder: Decoder,
+    // The decoded bytes at the cursor, not consumed yet.
+    decoded: Vec<u8>,
+    offset: usize,
+    endian: Endian,
+}
+
+impl<S: DataSource> TextDecoder<S> {
+    /// A decoder reading in the byte order of `inner`.
+    pub fn new(inner: S, encoding: TextEncoding) -> Self {
+        let endian = inner.endian();
+        Self {
+            inner,
+            decoder: Decoder::new(encoding),
+            decoded: Vec::new(),
+            offset: 0,
+            endian,
+        }
+    }
+
+    #[inline]
+    pub fn encoding(&self) -> TextEncoding {
+        self.decoder.encoding
+    }
+
+    #[inline]
+    pub fn set_endian(&mut self, endian: Endian) {
+        self.endian = endian;
+    }
+
+    /// Returns the inner source, positioned after the characters decoded so
+    /// far.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: DataSource> DataSource for TextDecoder<S> {
+    fn fill(&mut self, length: usize) -> Result<&[u8], BinaryFileReaderError> {
+        while self.decoded.len() < length {
+            let characters = self.decoder.characters(length - self.decoded.len());
+            let start = self.inner.current_offset();
+            let text = self.inner.fill(characters.min(self.inner.window_size()))?;
+            if text.is_empty() {
+                self.decoder.finish(start)?;
+                break;
+            }
+
+            let used = characters.min(text.len());
+            let mut failed = None;
+            for (index, &byte) in text[..used].iter().enumerate() {
+                match self.decoder.push(byte, start + index) {
+                    Ok(Some(byte)) => self.decoded.push(byte),
+                    Ok(None) => {}
+                    Err(err) => {
+                        failed = Some((index, err));
+                        break;
+                    }
+                }
+            }
+            // The offending character is left unconsumed.
+            if let Some((index, err)) = failed {
+                self.inner.consume(index);
+                return Err(err);
+            }
+            self.inner.consume(used);
+        }
+
+        Ok(&self.decoded)
+    }
+
+    fn consume(&mut self, length: usize) {
+        self.decoded.drain(..length);
+        self.offset += length;
+    }
+
+    #[inline]
+    fn current_offset(&self) -> usize {
+        self.offset
+    }
+
+    #[inline]
+    fn endian(&self) -> Endian {
+        self.endian
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_at, TextDecoder, TextEncoding};
+    use crate::{error::BinaryFileReaderError, source::DataSource, BinaryFileReader};
+
+    #[test]
+    fn test_decode() -> Result<(), BinaryFileReaderError> {
+        let base64 = TextEncoding::Base64;
+        for (text, bytes) in [
+            (&b""[..], &b""[..]),
+            (b"Zg==", b"f"),
+            (b"Zm8=", b"fo"),
+            (b"Zm9v", b"foo"),
+            (b"Zm9vYg", b"foob"),
+            (b" Zm9v\r\nYmE= \n", b"fooba"),
+            (b"+/+/", b"\xfb\xff\xbf"),
+        ] {
+            assert_eq!(decode_at(text, base64, usize::MAX, 0)?, bytes);
+        }
+        assert_eq!(
+            decode_at(b"00 7f\nFFab", TextEncoding::Hex, usize::MAX, 0)?,
+            [0x00, 0x7f, 0xff, 0xab]
+        );
+
+        for (text, encoding, reason, offset) in [
+            (&b"Zm9v*"[..], base64, "invalid character", 14),
+            (b"Zg==Zg==", base64, "data after padding", 14),
+            (b"Z===", base64, "padding cuts a byte short", 11),
+            (b"Zm9vY", base64, "the text ends inside a byte", 15),
+            (b"0x12", TextEncoding::Hex, "invalid character", 11),
+            (b"123", TextEncoding::Hex, "the text ends inside a byte", 13),
+        ] {
+            match decode_at(text, encoding, usize::MAX, 10) {
+                Err(BinaryFileReaderError::InvalidEncoding {
+                    reason: got,
+                    current_offset,
+                    ..
+                }) => assert_eq!((got, current_offset), (reason, offset)),
+                other => panic!("{:?}", other),
+            }
+        }
+        assert!(matches!(
+            decode_at(b"010203", TextEncoding::Hex, 2, 0),
+            Err(BinaryFileReaderError::AllocationLimit {
+                requested_bytes: 3,
+                current_offset: 5,
+                ..
+            })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_text_decoder() -> Result<(), BinaryFileReaderError> {
+        let text = b"ca fe\n00 2a\n01 ?";
+        let mut reader = BinaryFileReader::new(text);
+        let mut source = TextDecoder::new(&mut reader, TextEncoding::Hex);
+
+        assert_eq!(source.peek_u8()?, 0xca);
+        source.expect(&[0xca, 0xfe])?;
+        assert_eq!(source.read_u16()?, 42);
+        assert_eq!(source.current_offset(), 4);
+        assert_eq!(source.read_u8()?, 0x01);
+        assert!(matches!(
+            source.read_u8(),
+            Err(BinaryFileReaderError::InvalidEncoding {
+                encoding: "hex",
+                current_offset: 15,
+                ..
+            })
+        ));
+        drop(source);
+        assert_eq!(reader.current_offset(), 15);
+
+        let mut reader = BinaryFileReader::new(b"AAE=");
+        let mut source = TextDecoder::new(&mut reader, TextEncoding::Base64);
+        assert!(matches!(
+            source.read_u32(),
+            Err(BinaryFileReaderError::BufferUnderflow {
+                requested_bytes: 4,
+                current_offset: 0,
+                available_bytes: 2,
+            })
+        ));
+        assert_eq!(source.read_u16()?, 1);
+        assert!(source.is_at_end()?);
+
+        Ok(())
+    }
+}
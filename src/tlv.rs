@@ -0,0 +1,260 @@
+use crate::{endian::Endian, error::BinaryFileReaderError, BinaryFileReader};
+use core::iter::FusedIterator;
+
+/// The shape of tag-length-value records: the widths of the tag and length
+/// fields, their byte order, and whether the length counts the header as
+/// well as the value.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::BinaryFileReader;
+/// # use binary_file_reader::endian::Endian;
+/// # use binary_file_reader::tlv::TlvLayout;
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// // A 1-byte tag, then a little-endian 2-byte length that includes the
+/// // three header bytes.
+/// let buffer = vec![0x01, 0x05, 0x00, 0x2a, 0x00, 0x02, 0x03, 0x00];
+/// let layout = TlvLayout::new(1, 2)
+///     .endian(Endian::Little)
+///     .length_includes_header(true);
+///
+/// let mut records = layout.iter(BinaryFileReader::new(&buffer));
+/// let mut first = records.next().unwrap()?;
+/// assert_eq!(first.tag, 1);
+/// assert_eq!(first.value.read_u16()?, 42);
+///
+/// let second = records.next().unwrap()?;
+/// assert_eq!((second.tag, second.offset), (2, 5));
+/// assert_eq!(second.value.available_bytes(), 0);
+/// assert!(records.next().is_none());
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TlvLayout {
+    tag_width: usize,
+    length_width: usize,
+    endian: Endian,
+    length_includes_header: bool,
+}
+
+impl TlvLayout {
+    /// A layout with big-endian fields of `tag_width` and `length_width`
+    /// bytes, whose length counts only the value.
+    ///
+    /// # Panics
+    /// Panics if either width is not between 1 and 8.
+    pub fn new(tag_width: usize, length_width: usize) -> Self {
+        assert!(
+            (1..=8).contains(&tag_width),
+            "tag width must be 1 to 8 bytes"
+        );
+        assert!(
+            (1..=8).contains(&length_width),
+            "length width must be 1 to 8 bytes"
+        );
+        Self {
+            tag_width,
+            length_width,
+            endian: Endian::Big,
+            length_includes_header: false,
+        }
+    }
+
+    /// The byte order of the tag and length, which the value readers also
+    /// use.
+    pub fn endian(mut self, endian: Endian) -> Self {
+        self.endian = endian;
+        self
+    }
+
+    /// Whether the length counts the tag and length fields too.
+    pub fn length_includes_header(mut self, includes: bool) -> Self {
+        self.length_includes_header = includes;
+        self
+    }
+
+    /// The combined width of the tag and length fields.
+    #[inline]
+    pub fn header_len(&self) -> usize {
+        self.tag_width + self.length_width
+    }
+
+    /// Reads the record at the cursor. On failure the reader is left at the
+    /// start of the record.
+    pub fn read<'a>(
+        &self,
+        reader: &mut BinaryFileReader<'a>,
+    ) -> Result<Tlv<'a>, BinaryFileReaderError> {
+        let offset = reader.current_offset();
+        let result = (|| {
+            let tag = self.read_field(reader, self.tag_width)?;
+            let mut length = self.read_field(reader, self.length_width)?;
+            if self.length_includes_header {
+                length = length.checked_sub(self.header_len() as u64).ok_or(
+                    BinaryFileReaderError::malformed(
+                        "length is smaller than the header it includes",
+                        offset,
+                    ),
+                )?;
+            }
+            let length = usize::try_from(length).unwrap_or(usize::MAX);
+            let mut value = reader.split_off_front(length)?;
+            value.set_endian(self.endian);
+            Ok(Tlv { tag, offset, value })
+        })();
+        if result.is_err() {
+            reader.set_offset(offset)?;
+        }
+        result
+    }
+
+    /// Iterates over the records at the cursor of `reader` until it is
+    /// exhausted. A record that does not fit is yielded as an error and ends
+    /// the iteration.
+    pub fn iter(self, reader: BinaryFileReader<'_>) -> TlvIter<'_> {
+        TlvIter {
+            layout: self,
+            reader,
+            done: false,
+        }
+    }
+
+    fn read_field(
+        &self,
+        reader: &mut BinaryFileReader<'_>,
+        width: usize,
+    ) -> Result<u64, BinaryFileReaderError> {
+        let bytes = reader.read_slice(width)?;
+        Ok(match self.endian {
+            Endian::Big => bytes
+                .iter()
+                .fold(0, |value, &byte| (value << 8) | byte as u64),
+            Endian::Little => bytes
+                .iter()
+                .rev()
+                .fold(0, |value, &byte| (value << 8) | byte as u64),
+        })
+    }
+}
+
+/// A tag-length-value record read with a [`TlvLayout`].
+#[derive(Debug, Clone)]
+pub struct Tlv<'a> {
+    pub tag: u64,
+    /// The absolute offset of the tag.
+    pub offset: usize,
+    /// A reader over the value, in the layout's byte order.
+    pub value: BinaryFileReader<'a>,
+}
+
+/// Iterates over tag-length-value records. Created with [`TlvLayout::iter`].
+#[derive(Debug, Clone)]
+pub struct TlvIter<'a> {
+    layout: TlvLayout,
+    reader: BinaryFileReader<'a>,
+    done: bool,
+}
+
+impl<'a> TlvIter<'a> {
+    /// The reader after the records yielded so far.
+    pub fn into_inner(self) -> BinaryFileReader<'a> {
+        self.reader
+    }
+}
+
+impl<'a> Iterator for TlvIter<'a> {
+    type Item = Result<Tlv<'a>, BinaryFileReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.reader.available_bytes() == 0 {
+            return None;
+        }
+
+        let record = self.layout.read(&mut self.reader);
+        self.done = record.is_err();
+        Some(record)
+    }
+}
+
+impl FusedIterator for TlvIter<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::TlvLayout;
+    use crate::{
+        endian::Endian,
+        error::{BinaryFileReaderError, ErrorCode},
+        BinaryFileReader,
+    };
+    use alloc::vec;
+
+    #[test]
+    fn test_tlv_layouts() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![
+            0x12, 0x34, 0x00, 0x00, 0x02, 0xab, 0xcd, 0x56, 0x78, 0, 0, 0,
+        ];
+        let mut records = TlvLayout::new(2, 3).iter(BinaryFileReader::new(&buffer));
+        let mut first = records.next().unwrap()?;
+        assert_eq!(first.tag, 0x1234);
+        assert_eq!(first.value.read_u16()?, 0xabcd);
+        let second = records.next().unwrap()?;
+        assert_eq!((second.tag, second.offset), (0x5678, 7));
+        assert_eq!(second.value.current_offset(), 12);
+        assert!(records.next().is_none());
+
+        let layout = TlvLayout::new(2, 3).endian(Endian::Little);
+        let first = layout.read(&mut BinaryFileReader::new(&buffer[..7]));
+        assert_eq!(first.unwrap_err().code(), ErrorCode::BufferUnderflow);
+        let mut reader = BinaryFileReader::new(&[0x01, 0x00, 0x01, 0x00, 0x00, 0xff]);
+        let mut record = layout.read(&mut reader)?;
+        assert_eq!(record.tag, 1);
+        assert_eq!(record.value.endian(), Endian::Little);
+        assert_eq!(record.value.read_u8()?, 0xff);
+
+        let layout = TlvLayout::new(8, 8);
+        let mut buffer = vec![0xff; 8];
+        buffer.extend(0x0100_0000_0000_0000_u64.to_be_bytes());
+        let mut reader = BinaryFileReader::new(&buffer);
+        assert_eq!(
+            layout.read(&mut reader).unwrap_err().code(),
+            ErrorCode::BufferUnderflow
+        );
+        assert_eq!(reader.current_offset(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_length_includes_header() -> Result<(), BinaryFileReaderError> {
+        let layout = TlvLayout::new(1, 1).length_includes_header(true);
+        assert_eq!(layout.header_len(), 2);
+
+        let buffer = vec![0x07, 0x03, 0x2a, 0x08, 0x02, 0x09, 0x01];
+        let mut records = layout.iter(BinaryFileReader::new(&buffer));
+        assert_eq!(records.next().unwrap()?.value.available_bytes(), 1);
+        assert_eq!(records.next().unwrap()?.value.available_bytes(), 0);
+        let err = records.next().unwrap().unwrap_err();
+        assert!(matches!(
+            err,
+            BinaryFileReaderError::Malformed {
+                current_offset: 5,
+                ..
+            }
+        ));
+        assert!(records.next().is_none());
+        assert_eq!(records.into_inner().current_offset(), 5);
+
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic(expected = "tag width must be 1 to 8 bytes")]
+    fn test_tlv_layout_width() {
+        TlvLayout::new(0, 2);
+    }
+}
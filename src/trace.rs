@@ -0,0 +1,212 @@
+use crate::{error::ErrorBytes, fourcc::FourCC, BinaryFileReader};
+use alloc::borrow::Cow;
+use core::{fmt, str::FromStr};
+use std::sync::{Arc, Mutex};
+
+pub(crate) type SharedTrace = Arc<Mutex<Trace>>;
+
+/// Formats a traced value. Byte slices are shown like error bytes, so large
+/// reads do not bloat the trace.
+pub(crate) trait TraceValue {
+    fn trace_value(&self) -> String;
+}
+
+macro_rules! debug_trace_value {
+    ($($ty:ty),*) => {
+        $(
+            impl TraceValue for $ty {
+                fn trace_value(&self) -> String {
+                    format!("{:?}", self)
+                }
+            }
+        )*
+    };
+}
+
+debug_trace_value!(u8, u16, u32, u64, u128, bool, (u8, u8), &str);
+
+impl TraceValue for () {
+    fn trace_value(&self) -> String {
+        String::new()
+    }
+}
+
+impl TraceValue for &[u8] {
+    fn trace_value(&self) -> String {
+        ErrorBytes::new(self).to_string()
+    }
+}
+
+impl TraceValue for FourCC {
+    fn trace_value(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl TraceValue for BinaryFileReader<'_> {
+    fn trace_value(&self) -> String {
+        format!("{}..{}", self.own_start, self.own_left)
+    }
+}
+
+/// One traced operation: the method, the absolute offset it started at, the
+/// number of bytes it consumed and its formatted result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEvent {
+    pub method: Cow<'static, str>,
+    pub offset: usize,
+    pub length: usize,
+    /// The value read, or `Err(` and the [`ErrorCode`](crate::error::ErrorCode)
+    /// name followed by `)` if the operation failed.
+    pub value: String,
+}
+
+/// The operations recorded since
+/// [`enable_trace`](crate::BinaryFileReader::enable_trace), returned by
+/// [`trace`](crate::BinaryFileReader::trace).
+///
+/// `Display` writes one event per line and [`FromStr`] reads that back, so a
+/// trace can be stored as a golden file and compared with
+/// [`first_difference`](Self::first_difference) after a refactor.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::BinaryFileReader;
+/// # use binary_file_reader::trace::Trace;
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// let buffer = b"\x00\x04IHDR\x01".to_vec();
+/// let mut reader = BinaryFileReader::new(&buffer);
+/// reader.enable_trace();
+///
+/// let length = reader.read_u16()? as usize;
+/// reader.read_slice(length)?;
+/// reader.expect(b"\x02").unwrap_err();
+///
+/// let trace = reader.trace().unwrap();
+/// let golden = "\
+/// 0x00000000 2 read_u16 4
+/// 0x00000002 4 read_slice 49 48 44 52
+/// 0x00000006 0 expect Err(Expect)
+/// ";
+/// assert_eq!(trace.to_string(), golden);
+/// assert_eq!(trace.first_difference(&golden.parse::<Trace>()?), None);
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Trace {
+    pub events: Vec<TraceEvent>,
+}
+
+impl Trace {
+    /// The index of the first event that differs between the traces, or
+    /// `None` if they are the same. If one trace is a prefix of the other, this
+    /// is the length of the shorter one.
+    pub fn first_difference(&self, other: &Trace) -> Option<usize> {
+        let common = self
+            .events
+            .iter()
+            .zip(&other.events)
+            .position(|(a, b)| a != b);
+        match common {
+            Some(index) => Some(index),
+            None if self.events.len() != other.events.len() => {
+                Some(self.events.len().min(other.events.len()))
+            }
+            None => None,
+        }
+    }
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for event in &self.events {
+            write!(
+                f,
+                "{:#010x} {} {}",
+                event.offset, event.length, event.method
+            )?;
+            if !event.value.is_empty() {
+                write!(f, " {}", event.value)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// The error returned when a line of a stored [`Trace`] is malformed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseTraceError {
+    /// The 1-based line number.
+    pub line: usize,
+}
+
+impl fmt::Display for ParseTraceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Malformed trace event on line {}", self.line)
+    }
+}
+
+impl core::error::Error for ParseTraceError {}
+
+impl FromStr for Trace {
+    type Err = ParseTraceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let events = s
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.is_empty())
+            .map(|(index, line)| parse_event(line).ok_or(ParseTraceError { line: index + 1 }))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { events })
+    }
+}
+
+fn parse_event(line: &str) -> Option<TraceEvent> {
+    let mut fields = line.splitn(4, ' ');
+    let offset = fields.next()?.strip_prefix("0x")?;
+    let offset = usize::from_str_radix(offset, 16).ok()?;
+    let length = fields.next()?.parse().ok()?;
+    let method = fields.next().filter(|method| !method.is_empty())?;
+    Some(TraceEvent {
+        method: Cow::Owned(method.into()),
+        offset,
+        length,
+        value: fields.next().unwrap_or_default().into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ParseTraceError, Trace};
+
+    #[test]
+    fn test_trace_round_trip() -> Result<(), ParseTraceError> {
+        let text = "0x00000000 4 read_u32 7\n0x00000004 0 read_u4 Err(BufferUnderflow)\n0x00000004 0 expect\n";
+        let trace = text.parse::<Trace>()?;
+        assert_eq!(trace.events.len(), 3);
+        assert_eq!(trace.events[1].value, "Err(BufferUnderflow)");
+        assert_eq!(trace.events[2].value, "");
+        assert_eq!(trace.to_string(), text);
+
+        let mut changed = trace.clone();
+        changed.events[1].length = 1;
+        assert_eq!(trace.first_difference(&changed), Some(1));
+        changed.events.truncate(1);
+        assert_eq!(trace.first_difference(&changed), Some(1));
+        assert_eq!(trace.first_difference(&trace), None);
+
+        assert_eq!(
+            "0x0 1 read_u8 1\nread_u8 1 0".parse::<Trace>(),
+            Err(ParseTraceError { line: 2 })
+        );
+
+        Ok(())
+    }
+}
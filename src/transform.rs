@@ -0,0 +1,269 @@
+use crate::{endian::Endian, error::BinaryFileReaderError, source::DataSource};
+use alloc::vec::Vec;
+
+/// A byte-wise transformation applied by a [`TransformReader`].
+///
+/// Each byte of the source is transformed exactly once, in order, so a
+/// transform may keep state that depends on the bytes before it. Closures
+/// taking the position and the byte implement this trait.
+pub trait Transform {
+    /// Transforms `bytes` in place. `position` is the number of bytes before
+    /// `bytes` since the reader was created.
+    fn apply(&mut self, position: usize, bytes: &mut [u8]);
+}
+
+impl<F: FnMut(usize, u8) -> u8> Transform for F {
+    fn apply(&mut self, position: usize, bytes: &mut [u8]) {
+        for (index, byte) in bytes.iter_mut().enumerate() {
+            *byte = self(position + index, *byte);
+        }
+    }
+}
+
+/// XORs every byte with the same key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Xor(pub u8);
+
+impl Transform for Xor {
+    fn apply(&mut self, _position: usize, bytes: &mut [u8]) {
+        for byte in bytes {
+            *byte ^= self.0;
+        }
+    }
+}
+
+/// XORs the bytes with a key that repeats every `key.len()` bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepeatingXor {
+    key: Vec<u8>,
+}
+
+impl RepeatingXor {
+    /// # Panics
+    /// Panics if `key` is empty.
+    pub fn new(key: impl Into<Vec<u8>>) -> Self {
+        let key = key.into();
+        assert!(!key.is_empty(), "key must be non-empty");
+        Self { key }
+    }
+}
+
+impl Transform for RepeatingXor {
+    fn apply(&mut self, position: usize, bytes: &mut [u8]) {
+        for (index, byte) in bytes.iter_mut().enumerate() {
+            *byte ^= self.key[(position + index) % self.key.len()];
+        }
+    }
+}
+
+/// XORs each byte with a key that starts at `key` and grows by `step`
+/// after every byte, wrapping around.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RollingXor {
+    pub key: u8,
+    pub step: u8,
+}
+
+impl Transform for RollingXor {
+    fn apply(&mut self, position: usize, bytes: &mut [u8]) {
+        let mut key = self
+            .key
+            .wrapping_add(self.step.wrapping_mul(position as u8));
+        for byte in bytes {
+            *byte ^= key;
+            key = key.wrapping_add(self.step);
+        }
+    }
+}
+
+/// A [`DataSource`] that transforms the bytes of another source as they are
+/// read, such as to remove a light obfuscation. Only the bytes a read needs
+/// are transformed, and skipped bytes are not transformed at all. Offsets,
+/// including those in errors, are those of the inner source.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::BinaryFileReader;
+/// # use binary_file_reader::source::DataSource;
+/// # use binary_file_reader::transform::{RepeatingXor, TransformReader};
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// let plain = b"SAVE\x00\x2a";
+/// let key = b"k3y";
+/// let hidden = plain
+///     .iter()
+///     .enumerate()
+///     .map(|(index, byte)| byte ^ key[index % key.len()])
+///     .collect::<Vec<_>>();
+///
+/// let mut reader = BinaryFileReader::new(&hidden);
+/// let mut save = TransformReader::new(&mut reader, RepeatingXor::new(&key[..]));
+/// save.expect(b"SAVE")?;
+/// assert_eq!(save.read_u16()?, 42);
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TransformReader<S, T> {
+    inner: S,
+    transform: T,
+    // The transformed bytes at the cursor, not consumed yet.
+    decoded: Vec<u8>,
+    start: usize,
+}
+
+impl<S: DataSource, T: Transform> TransformReader<S, T> {
+    pub fn new(inner: S, transform: T) -> Self {
+        let start = inner.current_offset();
+        Self {
+            inner,
+            transform,
+            decoded: Vec::new(),
+            start,
+        }
+    }
+
+    /// The number of bytes read through this reader, which is the position
+    /// passed to the transform for the next byte.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.inner.current_offset() - self.start
+    }
+
+    #[inline]
+    pub fn transform(&self) -> &T {
+        &self.transform
+    }
+
+    /// Returns the inner source, positioned after the bytes consumed through
+    /// this reader.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: DataSource, T: Transform> DataSource for TransformReader<S, T> {
+    fn fill(&mut self, length: usize) -> Result<&[u8], BinaryFileReaderError> {
+        let decoded = self.decoded.len();
+        if decoded < length {
+            let position = self.position() + decoded;
+            let raw = self.inner.fill(length)?;
+            if raw.len() > decoded {
+                self.decoded
+                    .extend_from_slice(&raw[decoded..length.min(raw.len())]);
+                self.transform.apply(position, &mut self.decoded[decoded..]);
+            }
+        }
+
+        Ok(&self.decoded)
+    }
+
+    fn consume(&mut self, length: usize) {
+        self.inner.consume(length);
+        self.decoded.drain(..length);
+    }
+
+    #[inline]
+    fn current_offset(&self) -> usize {
+        self.inner.current_offset()
+    }
+
+    #[inline]
+    fn endian(&self) -> Endian {
+        self.inner.endian()
+    }
+
+    #[inline]
+    fn window_size(&self) -> usize {
+        self.inner.window_size()
+    }
+
+    fn skip(&mut self, length: usize) -> Result<(), BinaryFileReaderError> {
+        let decoded = length.min(self.decoded.len());
+        self.consume(decoded);
+        self.inner.skip(length - decoded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RepeatingXor, RollingXor, TransformReader, Xor};
+    use crate::{
+        endian::Endian, error::BinaryFileReaderError, source::DataSource, BinaryFileReader,
+    };
+    use alloc::{vec, vec::Vec};
+
+    #[test]
+    fn test_transforms() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0x5a, 0x5b, 0x5e, 0x5f];
+        let mut reader = BinaryFileReader::new(&buffer);
+        let mut source = TransformReader::new(&mut reader, Xor(0x5a));
+        assert_eq!(source.read_u32()?, 0x00010405);
+
+        let buffer = vec![0x10, 0x21, 0x12, 0x23];
+        let mut reader = BinaryFileReader::new(&buffer);
+        let mut source = TransformReader::new(&mut reader, RepeatingXor::new([0x10, 0x20]));
+        assert_eq!(source.read_u16()?, 0x0001);
+        assert_eq!(source.read_u16()?, 0x0203);
+
+        let buffer = vec![0x01, 0x05, 0x0b, 0xfd];
+        let mut reader = BinaryFileReader::new(&buffer);
+        let mut source = TransformReader::new(&mut reader, RollingXor { key: 1, step: 4 });
+        assert_eq!(source.read_u8()?, 0x00);
+        assert_eq!(source.read_u8()?, 0x00);
+        assert_eq!(source.read_u16()?, 0x02f0);
+
+        // A closure that XORs each byte with the previous ciphertext byte.
+        let buffer = vec![0x03, 0x07, 0x07, 0x0f];
+        let mut reader = BinaryFileReader::new(&buffer);
+        let mut previous = 0;
+        let chained = move |_: usize, byte: u8| {
+            let plain = byte ^ previous;
+            previous = byte;
+            plain
+        };
+        let mut source = TransformReader::new(&mut reader, chained);
+        assert_eq!(source.peek_u8()?, 0x03);
+        assert_eq!(source.read_u32()?, 0x03040008);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_transform_reader() -> Result<(), BinaryFileReaderError> {
+        let buffer = vec![0xff, 0x00, 0x01, 0x02, 0x03, 0x04];
+        let mut reader = BinaryFileReader::new(&buffer);
+        reader.advance(1)?;
+        reader.set_endian(Endian::Little);
+
+        let mut positions = Vec::new();
+        let mut source = TransformReader::new(&mut reader, |position: usize, byte: u8| {
+            positions.push(position);
+            byte ^ 0x80
+        });
+        assert_eq!(source.peek_u8()?, 0x80);
+        assert_eq!(source.read_u16()?, 0x8180);
+        assert_eq!(source.position(), 2);
+        source.skip(2)?;
+        assert_eq!(source.current_offset(), 5);
+        assert!(matches!(
+            source.read_u16(),
+            Err(BinaryFileReaderError::BufferUnderflow {
+                current_offset: 5,
+                available_bytes: 1,
+                ..
+            })
+        ));
+        assert_eq!(source.read_u8()?, 0x84);
+        assert!(source.is_at_end()?);
+        drop(source);
+
+        assert_eq!(positions, [0, 1, 4]);
+        assert_eq!(reader.current_offset(), 6);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,351 @@
+use alloc::{format, string::String, vec::Vec};
+use core::{fmt, ops::Range};
+
+/// A named byte range of a parsed file, with the nodes nested in it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseNode {
+    pub name: String,
+    /// The absolute offsets the node covers.
+    pub range: Range<usize>,
+    /// The decoded value, if the node is a field rather than a section.
+    pub value: Option<String>,
+    pub children: Vec<ParseNode>,
+}
+
+impl ParseNode {
+    fn descendants<'t>(&'t self, depth: usize, out: &mut Vec<(usize, &'t ParseNode)>) {
+        out.push((depth, self));
+        for child in &self.children {
+            child.descendants(depth + 1, out);
+        }
+    }
+}
+
+/// The nodes a format parser recorded with a [`ParseTreeBuilder`], in the
+/// order they were added.
+///
+/// `Display` renders a dissection: one node per line with its inclusive hex
+/// offset range, indented by depth and named by its full path, followed by
+/// its value for fields.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::tree::ParseTreeBuilder;
+/// let mut builder = ParseTreeBuilder::new();
+/// builder.start("IHDR", 8);
+/// builder.field("width", 16..20, "256");
+/// builder.field("height", 20..24, "128");
+/// builder.end(33);
+/// let tree = builder.finish();
+///
+/// let height = tree.find("IHDR.height").unwrap();
+/// assert_eq!(height.range, 20..24);
+/// assert_eq!(tree.node_at(21).unwrap().name, "height");
+/// assert_eq!(tree.node_at(30).unwrap().name, "IHDR");
+/// assert_eq!(tree.iter().count(), 3);
+///
+/// assert_eq!(
+///     tree.to_string(),
+///     "\
+/// 0008-0020  IHDR
+/// 0010-0013    IHDR.width = 256
+/// 0014-0017    IHDR.height = 128
+/// "
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseTree {
+    pub roots: Vec<ParseNode>,
+}
+
+impl ParseTree {
+    /// All nodes depth-first, parents before their children, with the number
+    /// of nodes each is nested in.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &ParseNode)> {
+        let mut nodes = Vec::new();
+        for root in &self.roots {
+            root.descendants(0, &mut nodes);
+        }
+        nodes.into_iter()
+    }
+
+    /// The first node at the dot-separated `path` of names, such as
+    /// `"IHDR.width"`.
+    pub fn find(&self, path: &str) -> Option<&ParseNode> {
+        let mut names = path.split('.');
+        let first = names.next()?;
+        let mut node = self.roots.iter().find(|node| node.name == first)?;
+        for name in names {
+            node = node.children.iter().find(|node| node.name == name)?;
+        }
+        Some(node)
+    }
+
+    /// Every node named `name`, at any depth, in depth-first order.
+    pub fn find_all<'t>(&'t self, name: &'t str) -> impl Iterator<Item = &'t ParseNode> + 't {
+        self.iter()
+            .map(|(_, node)| node)
+            .filter(move |node| node.name == name)
+    }
+
+    /// The innermost node covering `offset`, or `None` if no node does.
+    pub fn node_at(&self, offset: usize) -> Option<&ParseNode> {
+        let mut nodes = &self.roots;
+        let mut found = None;
+        while let Some(node) = nodes.iter().find(|node| node.range.contains(&offset)) {
+            found = Some(node);
+            nodes = &node.children;
+        }
+        found
+    }
+
+    /// The path of names from a root to the innermost node covering `offset`,
+    /// joined with dots.
+    pub fn path_at(&self, offset: usize) -> Option<String> {
+        let mut nodes = &self.roots;
+        let mut path: Option<String> = None;
+        while let Some(node) = nodes.iter().find(|node| node.range.contains(&offset)) {
+            match &mut path {
+                Some(path) => {
+                    path.push('.');
+                    path.push_str(&node.name);
+                }
+                None => path = Some(node.name.clone()),
+            }
+            nodes = &node.children;
+        }
+        path
+    }
+}
+
+impl fmt::Display for ParseTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let last = self
+            .iter()
+            .map(|(_, node)| node.range.end)
+            .max()
+            .unwrap_or(0);
+        let width = format!("{:x}", last).len().max(4);
+        let mut path = Vec::new();
+        for (depth, node) in self.iter() {
+            path.truncate(depth);
+            path.push(node.name.as_str());
+            if node.range.is_empty() {
+                write!(f, "{:0width$x}{:width$} ", node.range.start, "")?;
+            } else {
+                write!(
+                    f,
+                    "{:0width$x}-{:0width$x}",
+                    node.range.start,
+                    node.range.end - 1
+                )?;
+            }
+            write!(f, "  {:indent$}{}", "", path.join("."), indent = depth * 2)?;
+            if let Some(value) = &node.value {
+                write!(f, " = {}", value)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Builds a [`ParseTree`] as a parser runs: sections are opened with
+/// [`start`](Self::start) and closed with [`end`](Self::end), and fields are
+/// added to the innermost open section.
+///
+/// With the `std` feature, [`event`](Self::event) adds the
+/// [`ParseEvent`](crate::events::ParseEvent)s of a reader's event sink.
+#[derive(Debug, Clone, Default)]
+pub struct ParseTreeBuilder {
+    tree: ParseTree,
+    open: Vec<ParseNode>,
+}
+
+impl ParseTreeBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Opens a section at absolute offset `offset`.
+    pub fn start(&mut self, name: impl Into<String>, offset: usize) {
+        self.open.push(ParseNode {
+            name: name.into(),
+            range: offset..offset,
+            value: None,
+            children: Vec::new(),
+        });
+    }
+
+    /// Adds a field with a decoded value to the innermost open section.
+    pub fn field(
+        &mut self,
+        name: impl Into<String>,
+        range: Range<usize>,
+        value: impl Into<String>,
+    ) {
+        self.push(ParseNode {
+            name: name.into(),
+            range,
+            value: Some(value.into()),
+            children: Vec::new(),
+        });
+    }
+
+    /// Closes the innermost open section at absolute offset `end`.
+    ///
+    /// # Panics
+    /// Panics if no section is open.
+    pub fn end(&mut self, end: usize) {
+        let mut node = self.open.pop().expect("no section is open");
+        node.range.end = end.max(node.range.start);
+        self.push(node);
+    }
+
+    /// Adds a reader event: `SectionStart` and `SectionEnd` open and close
+    /// sections, and `Field` adds a field with its `Debug` output as the
+    /// value. A `SectionEnd` with no section open, as when the sink was set
+    /// inside a section, is ignored.
+    ///
+    /// Only available with the `std` feature.
+    #[cfg(feature = "std")]
+    pub fn event(&mut self, event: &crate::events::ParseEvent<'_>) {
+        use crate::events::ParseEvent;
+
+        match *event {
+            ParseEvent::SectionStart { name, offset } => self.start(name, offset),
+            ParseEvent::Field {
+                name,
+                offset,
+                length,
+                value,
+            } => self.field(name, offset..offset + length, alloc::format!("{:?}", value)),
+            ParseEvent::SectionEnd { .. } if self.open.is_empty() => {}
+            ParseEvent::SectionEnd { offset, length, .. } => self.end(offset + length),
+        }
+    }
+
+    /// Returns the tree. Sections still open, as after a failed parse, are
+    /// closed at the end of their last child.
+    pub fn finish(mut self) -> ParseTree {
+        while let Some(node) = self.open.last() {
+            let end = node
+                .children
+                .last()
+                .map_or(node.range.start, |child| child.range.end);
+            self.end(end);
+        }
+        self.tree
+    }
+
+    fn push(&mut self, node: ParseNode) {
+        match self.open.last_mut() {
+            Some(parent) => parent.children.push(node),
+            None => self.tree.roots.push(node),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ParseTree, ParseTreeBuilder};
+    use alloc::{string::ToString, vec::Vec};
+
+    #[test]
+    fn test_parse_tree() {
+        let mut builder = ParseTreeBuilder::new();
+        builder.field("signature", 0..8, "PNG");
+        builder.start("chunk", 8);
+        builder.field("length", 8..12, "4");
+        builder.start("data", 16);
+        builder.field("gamma", 16..20, "45455");
+        builder.end(20);
+        builder.end(24);
+        builder.start("chunk", 24);
+        builder.field("length", 24..28, "0");
+        builder.start("data", 32);
+        let tree = builder.finish();
+
+        assert_eq!(tree.roots.len(), 3);
+        assert_eq!(tree.roots[2].range, 24..32);
+        assert_eq!(tree.roots[2].children[1].range, 32..32);
+        assert_eq!(
+            tree.find("chunk.data.gamma").unwrap().value.as_deref(),
+            Some("45455")
+        );
+        assert!(tree.find("chunk.gamma").is_none());
+        assert!(tree.find("").is_none());
+        assert_eq!(tree.find_all("length").count(), 2);
+        assert_eq!(
+            tree.iter().map(|(depth, _)| depth).collect::<Vec<_>>(),
+            [0, 0, 1, 1, 2, 0, 1, 1]
+        );
+
+        assert_eq!(tree.node_at(17).unwrap().name, "gamma");
+        assert_eq!(tree.path_at(17).unwrap(), "chunk.data.gamma");
+        assert_eq!(tree.path_at(12).unwrap(), "chunk");
+        assert!(tree.node_at(32).is_none());
+        assert!(tree.path_at(100).is_none());
+
+        assert_eq!(
+            tree.to_string(),
+            "\
+0000-0007  signature = PNG
+0008-0017  chunk
+0008-000b    chunk.length = 4
+0010-0013    chunk.data
+0010-0013      chunk.data.gamma = 45455
+0018-001f  chunk
+0018-001b    chunk.length = 0
+0020         chunk.data
+"
+        );
+        assert_eq!(ParseTree::default().to_string(), "");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_build_from_events() -> Result<(), crate::error::BinaryFileReaderError> {
+        use crate::BinaryFileReader;
+        use std::sync::{Arc, Mutex};
+
+        let builder = Arc::new(Mutex::new(ParseTreeBuilder::new()));
+        let sink = builder.clone();
+        let buffer = vec![0x00, 0x10, 0x01];
+        let mut reader = BinaryFileReader::new(&buffer);
+        reader.set_event_sink(move |event| sink.lock().unwrap().event(event));
+
+        reader.section("header", |r| {
+            r.field("size", |r| r.read_u16())?;
+            r.field("flag", |r| r.read_bool())
+        })?;
+        drop(reader);
+
+        let tree = Arc::try_unwrap(builder)
+            .unwrap()
+            .into_inner()
+            .unwrap()
+            .finish();
+        assert_eq!(tree.roots[0].range, 0..3);
+        assert_eq!(
+            tree.find("header.flag").unwrap().value.as_deref(),
+            Some("true")
+        );
+
+        // The sink replaces another inside a section, so its end has no start.
+        let builder = Arc::new(Mutex::new(ParseTreeBuilder::new()));
+        let sink = builder.clone();
+        let mut reader = BinaryFileReader::new(&buffer);
+        reader.set_event_sink(|_| {});
+        reader.section("outer", |r| {
+            r.set_event_sink(move |event| sink.lock().unwrap().event(event));
+            r.field("size", |r| r.read_u16())
+        })?;
+        drop(reader);
+        let tree = builder.lock().unwrap().clone().finish();
+        assert_eq!(tree.roots.len(), 1);
+        assert_eq!(tree.roots[0].name, "size");
+
+        Ok(())
+    }
+}
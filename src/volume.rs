@@ -0,0 +1,331 @@
+use crate::{endian::Endian, error::BinaryFileReaderError, source::DataSource};
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+/// A reader over several [`Read`] + [`Seek`] volumes read back to back, such
+/// as the parts of a split archive. Offsets count from the start of the first
+/// volume; [`locate`](Self::locate) maps them back to a volume.
+///
+/// # Examples
+/// ```
+/// # use binary_file_reader::error::BinaryFileReaderError;
+/// # use binary_file_reader::source::DataSource;
+/// # use binary_file_reader::volume::MultiVolumeReader;
+/// # use std::io::Cursor;
+/// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+/// let volumes = vec![Cursor::new(vec![0x00, 0x01]), Cursor::new(vec![0x02, 0x03, 0x04])];
+/// let mut reader = MultiVolumeReader::new(volumes)?;
+///
+/// reader.read_u8()?;
+/// assert_eq!(reader.read_u16()?, 0x0102);
+///
+/// let err = reader.read_u32().unwrap_err();
+/// assert!(matches!(err, BinaryFileReaderError::BufferUnderflow { current_offset: 3, .. }));
+/// assert_eq!(reader.locate(3), Some((1, 1)));
+/// #
+/// # Ok(())
+/// # }
+/// # fn main() {
+/// #    try_main().unwrap();
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MultiVolumeReader<R> {
+    volumes: Vec<R>,
+    // The offset of the first byte of each volume, followed by the total
+    // length.
+    starts: Vec<usize>,
+    paths: Vec<PathBuf>,
+    buf: Vec<u8>,
+    capacity: usize,
+    window_start: usize,
+    window_len: usize,
+    offset: usize,
+    endian: Endian,
+}
+
+impl MultiVolumeReader<File> {
+    /// Opens the files at `paths` as consecutive volumes. The paths are kept
+    /// for [`locate_path`](Self::locate_path) and included in the error if a
+    /// file cannot be opened.
+    ///
+    /// # Examples
+    /// ```
+    /// # use binary_file_reader::source::DataSource;
+    /// # use binary_file_reader::volume::MultiVolumeReader;
+    /// # use std::path::Path;
+    /// # fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let path = "./sample-files/1.png";
+    /// let mut reader = MultiVolumeReader::open([path, path])?;
+    /// reader.expect(&[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A])?;
+    ///
+    /// let length = std::fs::metadata(path)?.len() as usize;
+    /// assert_eq!(reader.locate_path(length + 1), Some((Path::new(path), 1)));
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # fn main() {
+    /// #    try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn open<P: AsRef<Path>>(
+        paths: impl IntoIterator<Item = P>,
+    ) -> Result<Self, BinaryFileReaderError> {
+        let mut volumes = Vec::new();
+        let mut opened = Vec::new();
+        for path in paths {
+            let path = path.as_ref();
+            let file = File::open(path).map_err(|err| {
+                io::Error::new(err.kind(), format!("{}: {}", path.display(), err))
+            })?;
+            volumes.push(file);
+            opened.push(path.to_path_buf());
+        }
+
+        Ok(Self {
+            paths: opened,
+            ..Self::new(volumes)?
+        })
+    }
+}
+
+impl<R: Read + Seek> MultiVolumeReader<R> {
+    /// The window size used by [`new`](Self::new).
+    pub const DEFAULT_CAPACITY: usize = 8 * 1024;
+
+    pub fn new(volumes: Vec<R>) -> Result<Self, BinaryFileReaderError> {
+        Self::with_capacity(Self::DEFAULT_CAPACITY, volumes)
+    }
+
+    /// # Panics
+    /// Panics if `capacity` is 0.
+    pub fn with_capacity(
+        capacity: usize,
+        mut volumes: Vec<R>,
+    ) -> Result<Self, BinaryFileReaderError> {
+        assert!(capacity != 0, "capacity must be non-zero");
+        let mut starts = vec![0];
+        let mut total = 0usize;
+        for volume in &mut volumes {
+            let length = usize::try_from(volume.seek(SeekFrom::End(0))?).ok();
+            total = length
+                .and_then(|length| total.checked_add(length))
+                .ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "source is too large")
+                })?;
+            starts.push(total);
+        }
+
+        Ok(Self {
+            volumes,
+            starts,
+            paths: Vec::new(),
+            buf: vec![0; capacity],
+            capacity,
+            window_start: 0,
+            window_len: 0,
+            offset: 0,
+            endian: Endian::Big,
+        })
+    }
+
+    #[inline]
+    pub fn current_offset(&self) -> usize {
+        self.offset
+    }
+
+    #[inline]
+    pub fn available_bytes(&self) -> usize {
+        self.len() - self.offset
+    }
+
+    #[inline]
+    pub fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    #[inline]
+    pub fn set_endian(&mut self, endian: Endian) {
+        self.endian = endian;
+    }
+
+    /// Moves the cursor to `offset`, which may be anywhere up to the end of
+    /// the last volume.
+    pub fn set_offset(&mut self, offset: usize) -> Result<(), BinaryFileReaderError> {
+        if offset > self.len() {
+            return Err(BinaryFileReaderError::OutOfRange {
+                buffer_size: self.len(),
+                got: offset,
+            });
+        }
+
+        self.offset = offset;
+        Ok(())
+    }
+
+    /// Maps `offset` to the index of the volume holding it and the offset
+    /// inside that volume. Returns `None` past the end of the last volume.
+    pub fn locate(&self, offset: usize) -> Option<(usize, usize)> {
+        if offset >= self.len() {
+            return None;
+        }
+
+        let volume = self.starts.partition_point(|&start| start <= offset) - 1;
+        Some((volume, offset - self.starts[volume]))
+    }
+
+    /// Like [`locate`](Self::locate), but names the volume by the path it was
+    /// opened from. Returns `None` for volumes not opened with
+    /// [`open`](MultiVolumeReader::open).
+    pub fn locate_path(&self, offset: usize) -> Option<(&Path, usize)> {
+        let (volume, offset) = self.locate(offset)?;
+        Some((self.paths.get(volume)?, offset))
+    }
+
+    pub fn into_inner(self) -> Vec<R> {
+        self.volumes
+    }
+
+    fn len(&self) -> usize {
+        self.starts[self.starts.len() - 1]
+    }
+}
+
+impl<R: Read + Seek> DataSource for MultiVolumeReader<R> {
+    fn fill(&mut self, length: usize) -> Result<&[u8], BinaryFileReaderError> {
+        let window_end = self.window_start + self.window_len;
+        let in_window = self.window_start <= self.offset && self.offset <= window_end;
+        let wanted = length.min(self.available_bytes());
+
+        if !in_window || window_end - self.offset < wanted {
+            let size = self.capacity.max(length).min(self.available_bytes());
+            if self.buf.len() < size {
+                self.buf.resize(size, 0);
+            }
+
+            self.window_start = self.offset;
+            self.window_len = 0;
+            while self.window_len < size {
+                let position = self.window_start + self.window_len;
+                let Some((volume, inner_offset)) = self.locate(position) else {
+                    break;
+                };
+                let end = size.min(self.window_len + self.starts[volume + 1] - position);
+
+                let source = &mut self.volumes[volume];
+                source.seek(SeekFrom::Start(inner_offset as u64))?;
+                while self.window_len < end {
+                    match source.read(&mut self.buf[self.window_len..end]) {
+                        Ok(0) => {
+                            return Err(io::Error::new(
+                                io::ErrorKind::UnexpectedEof,
+                                format!("volume {} is shorter than when it was opened", volume),
+                            )
+                            .into())
+                        }
+                        Ok(read) => self.window_len += read,
+                        Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                        Err(err) => return Err(err.into()),
+                    }
+                }
+            }
+        }
+
+        let start = self.offset - self.window_start;
+        let end = self.window_len.min(start + self.available_bytes());
+        Ok(&self.buf[start..end])
+    }
+
+    fn consume(&mut self, length: usize) {
+        debug_assert!(length <= self.available_bytes());
+        self.offset += length;
+    }
+
+    #[inline]
+    fn window_size(&self) -> usize {
+        self.capacity
+    }
+
+    #[inline]
+    fn current_offset(&self) -> usize {
+        self.offset
+    }
+
+    #[inline]
+    fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    /// Moves the cursor `length` bytes forward without reading.
+    fn skip(&mut self, length: usize) -> Result<(), BinaryFileReaderError> {
+        if length > self.available_bytes() {
+            return Err(BinaryFileReaderError::BufferUnderflow {
+                requested_bytes: length,
+                current_offset: self.offset,
+                available_bytes: self.available_bytes(),
+            });
+        }
+
+        self.offset += length;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MultiVolumeReader;
+    use crate::{error::BinaryFileReaderError, source::DataSource};
+    use std::io::{self, Cursor};
+
+    #[test]
+    fn test_multi_volume_reader() -> Result<(), BinaryFileReaderError> {
+        let volumes = vec![
+            Cursor::new(vec![0, 1, 2]),
+            Cursor::new(vec![]),
+            Cursor::new(vec![3]),
+            Cursor::new(vec![4, 5, 6, 7, 8]),
+        ];
+        let mut reader = MultiVolumeReader::with_capacity(2, volumes)?;
+        assert_eq!(reader.available_bytes(), 9);
+
+        assert_eq!(reader.read_u32()?, 0x00010203);
+        assert_eq!(reader.read_u16()?, 0x0405);
+        reader.set_offset(2)?;
+        assert_eq!(reader.peek_slice(2)?, &[2, 3]);
+        reader.skip(4)?;
+        assert!(matches!(
+            reader.read_u32(),
+            Err(BinaryFileReaderError::BufferUnderflow {
+                current_offset: 6,
+                available_bytes: 3,
+                ..
+            })
+        ));
+
+        assert_eq!(reader.locate(0), Some((0, 0)));
+        assert_eq!(reader.locate(3), Some((2, 0)));
+        assert_eq!(reader.locate(8), Some((3, 4)));
+        assert_eq!(reader.locate(9), None);
+        assert_eq!(reader.locate_path(0), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_truncated_volume() -> Result<(), BinaryFileReaderError> {
+        // The volume shrinks after its length was measured.
+        let mut reader = MultiVolumeReader {
+            volumes: vec![Cursor::new(vec![0, 1])],
+            ..MultiVolumeReader::new(vec![Cursor::new(vec![0, 1, 2, 3])])?
+        };
+        assert!(matches!(
+            reader.read_u32(),
+            Err(BinaryFileReaderError::IO(err)) if err.kind() == io::ErrorKind::UnexpectedEof
+        ));
+
+        Ok(())
+    }
+}
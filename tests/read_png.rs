@@ -1,6 +1,10 @@
 use std::fs;
 
-use binary_file_reader::{error::BinaryFileReaderError, BinaryFileReader};
+use binary_file_reader::{
+    chunk::{ChunkDispatcher, ChunkLayout},
+    error::BinaryFileReaderError,
+    BinaryFileReader,
+};
 
 #[test]
 fn test() -> Result<(), BinaryFileReaderError> {
@@ -34,6 +38,88 @@ fn test() -> Result<(), BinaryFileReaderError> {
     Ok(())
 }
 
+#[test]
+fn test_dispatcher() -> Result<(), BinaryFileReaderError> {
+    let expect = read_png("sample-files/1.png")?;
+
+    let buffer = fs::read("sample-files/1.png").expect("fault to read file");
+    let mut reader = BinaryFileReader::new(&buffer);
+    reader.expect(&[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A])?;
+
+    let mut dispatcher = ChunkDispatcher::new(ChunkLayout::LengthTag)
+        .trailer(4)
+        .on(b"IHDR", |r| {
+            Ok(Some(Chunk::Ihdr {
+                width: r.read_u32()?,
+                height: r.read_u32()?,
+                bit_depth: r.read_u8()?,
+                color_type: r.read_u8()?,
+                compression_method: r.read_u8()?,
+                fileter_method: r.read_u8()?,
+                interlace_method: r.read_u8()?,
+            }))
+        })
+        .on(b"tEXt", |r| {
+            Ok(Some(Chunk::Text(
+                r.read_utf8(r.available_bytes())?.to_string(),
+            )))
+        })
+        .on(b"pHYs", |r| {
+            Ok(Some(Chunk::Phys {
+                px_per_unit_x: r.read_u32()?,
+                px_per_unit_y: r.read_u32()?,
+                unit_specifier: r.read_u8()?,
+            }))
+        })
+        .on(b"tIME", |r| {
+            Ok(Some(Chunk::Time {
+                y: r.read_u16()?,
+                m: r.read_u8()?,
+                d: r.read_u8()?,
+            }))
+        })
+        .on(b"IEND", |_| Ok(None))
+        .on_unknown(|_, _| Ok(Some(Chunk::Unknown)));
+
+    let mut chunks = Vec::new();
+    while let Some(Some(chunk)) = dispatcher.dispatch(&mut reader)? {
+        chunks.push(chunk);
+    }
+    assert_eq!(chunks, expect);
+
+    Ok(())
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn test_formats_png() -> Result<(), BinaryFileReaderError> {
+    use binary_file_reader::formats::png::{ChunkIter, Ihdr, Phys, Text, Time};
+
+    let buffer = fs::read("sample-files/1.png").expect("fault to read file");
+    let mut tags = Vec::new();
+    for chunk in ChunkIter::new(BinaryFileReader::new(&buffer))? {
+        let mut chunk = chunk?;
+        assert!(chunk.crc_valid);
+        tags.push(chunk.tag.to_string());
+        match chunk.tag.as_bytes() {
+            b"IHDR" => {
+                let ihdr = chunk.data.read_struct::<Ihdr>()?;
+                assert_eq!((ihdr.width, ihdr.height, ihdr.color_type), (100, 100, 2));
+            }
+            b"pHYs" => assert_eq!(chunk.data.read_struct::<Phys>()?.pixels_per_unit_x, 11811),
+            b"tIME" => assert_eq!(chunk.data.read_struct::<Time>()?.year, 2025),
+            b"tEXt" => assert_eq!(chunk.data.read_struct::<Text>()?.keyword, "Comment"),
+            _ => {}
+        }
+    }
+    assert_eq!(
+        tags,
+        ["IHDR", "iCCP", "pHYs", "tIME", "tEXt", "IDAT", "IEND"]
+    );
+
+    Ok(())
+}
+
 fn read_png(path: &str) -> Result<Vec<Chunk>, BinaryFileReaderError> {
     let buffer = fs::read(path).expect("fault to read file");
     let mut reader = BinaryFileReader::new(&buffer);
@@ -43,13 +129,10 @@ fn read_png(path: &str) -> Result<Vec<Chunk>, BinaryFileReaderError> {
     let mut chunks = Vec::new();
 
     loop {
-        let chunk_length = reader.read_u32()?;
-        let chunk_name = reader.read_utf8(4)?;
-        let mut chunk_reader = reader.split_off_front(chunk_length as usize)?;
-        let _crc = reader.read_u32()?;
+        let (chunk_name, mut chunk_reader) = reader.read_crc32_chunk()?;
 
-        let chunk = match chunk_name {
-            "IHDR" => Chunk::Ihdr {
+        let chunk = match &chunk_name.0 {
+            b"IHDR" => Chunk::Ihdr {
                 width: chunk_reader.read_u32()?,
                 height: chunk_reader.read_u32()?,
                 bit_depth: chunk_reader.read_u8()?,
@@ -58,22 +141,22 @@ fn read_png(path: &str) -> Result<Vec<Chunk>, BinaryFileReaderError> {
                 fileter_method: chunk_reader.read_u8()?,
                 interlace_method: chunk_reader.read_u8()?,
             },
-            "tEXt" => Chunk::Text(
+            b"tEXt" => Chunk::Text(
                 chunk_reader
                     .read_utf8(chunk_reader.available_bytes())?
                     .to_string(),
             ),
-            "pHYs" => Chunk::Phys {
+            b"pHYs" => Chunk::Phys {
                 px_per_unit_x: chunk_reader.read_u32()?,
                 px_per_unit_y: chunk_reader.read_u32()?,
                 unit_specifier: chunk_reader.read_u8()?,
             },
-            "tIME" => Chunk::Time {
+            b"tIME" => Chunk::Time {
                 y: chunk_reader.read_u16()?,
                 m: chunk_reader.read_u8()?,
                 d: chunk_reader.read_u8()?,
             },
-            "IEND" => {
+            b"IEND" => {
                 break;
             }
             _ => Chunk::Unknown,